@@ -0,0 +1,149 @@
+//! `bin diff`: compare two binary files and report differing byte ranges
+//! with hexdump context. `--quick` compares SHA-256 digests only.
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use crate::helpers::{hex, style};
+
+/// A run of differing bytes: `[start, end)` in file offsets.
+#[derive(Debug, PartialEq, Eq)]
+struct Range {
+    start: u64,
+    end: u64,
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut reader = BufReader::new(
+        File::open(path).with_context(|| format!("Failed to open: {}", path.display()))?,
+    );
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(&hasher.finalize()))
+}
+
+/// Collect differing ranges of two byte slices. Ranges separated by fewer than
+/// `merge_gap` equal bytes are merged so output stays readable.
+fn diff_ranges(a: &[u8], b: &[u8], merge_gap: u64) -> Vec<Range> {
+    let common = a.len().min(b.len());
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut i = 0;
+    while i < common {
+        if a[i] == b[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < common && a[i] != b[i] {
+            i += 1;
+        }
+        let (start, end) = (start as u64, i as u64);
+        match ranges.last_mut() {
+            Some(last) if start - last.end < merge_gap => last.end = end,
+            _ => ranges.push(Range { start, end }),
+        }
+    }
+    ranges
+}
+
+/// Compare two files; exits with an error when they differ.
+pub fn run(a: &Path, b: &Path, quick: bool, context: usize, max_ranges: usize) -> Result<()> {
+    let (meta_a, meta_b) = (
+        a.metadata()
+            .with_context(|| format!("Failed to stat: {}", a.display()))?,
+        b.metadata()
+            .with_context(|| format!("Failed to stat: {}", b.display()))?,
+    );
+
+    if quick {
+        let (ha, hb) = (sha256_file(a)?, sha256_file(b)?);
+        println!("{}  {}", ha, a.display());
+        println!("{}  {}", hb, b.display());
+        if ha == hb {
+            style::ok("Files are identical");
+            return Ok(());
+        }
+        anyhow::bail!("Files differ (SHA-256 mismatch)");
+    }
+
+    let da = std::fs::read(a).with_context(|| format!("Failed to read: {}", a.display()))?;
+    let db = std::fs::read(b).with_context(|| format!("Failed to read: {}", b.display()))?;
+    let ranges = diff_ranges(&da, &db, 16);
+
+    if ranges.is_empty() && da.len() == db.len() {
+        style::ok("Files are identical");
+        return Ok(());
+    }
+
+    let differing: u64 = ranges.iter().map(|r| r.end - r.start).sum();
+    style::title(&format!(
+        "{} differing range(s), {} byte(s) within ranges",
+        ranges.len(),
+        differing
+    ));
+    for r in ranges.iter().take(max_ranges) {
+        println!();
+        println!(
+            "{} 0x{:08x}..0x{:08x} ({} bytes)",
+            "Range".bold(),
+            r.start,
+            r.end,
+            r.end - r.start
+        );
+        let from = (r.start as usize).saturating_sub(context);
+        for (label, data) in [(a, &da), (b, &db)] {
+            let to = (r.end as usize + context).min(data.len());
+            println!("{}", label.display().to_string().cyan());
+            let in_range = |pos: usize| (r.start..r.end).contains(&(pos as u64));
+            for line in hex::dump(data, from, to, in_range) {
+                println!("  {line}");
+            }
+        }
+    }
+    if ranges.len() > max_ranges {
+        println!("\n… {} more range(s) not shown", ranges.len() - max_ranges);
+    }
+    if meta_a.len() != meta_b.len() {
+        println!();
+        style::warn_line(&format!(
+            "Sizes differ: {} is {} bytes, {} is {} bytes",
+            a.display(),
+            meta_a.len(),
+            b.display(),
+            meta_b.len()
+        ));
+    }
+    anyhow::bail!("Files differ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_ranges_merges_close_runs() {
+        let a = b"0123456789abcdef0123";
+        let b = b"0X23456Y89abcdefZ123";
+        let ranges = diff_ranges(a, b, 8);
+        assert_eq!(
+            ranges,
+            vec![Range { start: 1, end: 8 }, Range { start: 16, end: 17 }]
+        );
+    }
+
+    #[test]
+    fn test_diff_ranges_identical_prefix() {
+        assert!(diff_ranges(b"abc", b"abcdef", 16).is_empty());
+    }
+}
@@ -0,0 +1,301 @@
+//! `cert convert`: move certificates between PEM, DER, and bare Base64 (DER
+//! without PEM armor, as pasted into web consoles), and extract the chain and
+//! private key from PKCS#12 (.p12/.pfx) bundles. Input formats are detected;
+//! the output format comes from `--to` or the `--out` extension.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use owo_colors::OwoColorize;
+use p12_keystore::{KeyStore, KeyStoreEntry};
+use std::path::Path;
+
+use crate::cli::CertFormat;
+use crate::commands::cert_gen::{refuse_existing, write_output};
+use crate::commands::cert_inspect::cert_ders;
+use crate::commands::rand_password::read_password;
+
+/// Certificates (and, from PKCS#12, the private key) read from the input.
+struct Decoded {
+    format: &'static str,
+    certs: Vec<Vec<u8>>,
+    /// PKCS#8 DER.
+    key: Option<Vec<u8>>,
+}
+
+/// PEM armor for `der` with 64-column Base64 lines.
+pub(crate) fn pem_block(label: &str, der: &[u8]) -> String {
+    let b64 = base64::engine::general_purpose::STANDARD.encode(der);
+    let mut out = format!("-----BEGIN {label}-----\n");
+    for line in b64.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).unwrap_or_default());
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {label}-----\n"));
+    out
+}
+
+/// A `.p12`/`.pfx` name, or a DER SEQUENCE opening with `INTEGER 3` (the PFX version).
+fn is_pkcs12(path: &Path, data: &[u8]) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if ext == "p12" || ext == "pfx" {
+        return true;
+    }
+    let body = match data {
+        [0x30, 0x80, rest @ ..] => rest,
+        [0x30, n, rest @ ..] if *n < 0x80 => rest,
+        [0x30, n, rest @ ..] if (0x81..=0x84).contains(n) => {
+            rest.get(usize::from(n - 0x80)..).unwrap_or_default()
+        }
+        _ => return false,
+    };
+    body.starts_with(&[0x02, 0x01, 0x03])
+}
+
+/// Bare Base64 text (line breaks allowed) decoded to bytes.
+fn decode_base64(data: &[u8]) -> Option<Vec<u8>> {
+    let text: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|b| !b.is_ascii_whitespace())
+        .collect();
+    let alphabet = |b: &u8| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=');
+    if text.is_empty() || !text.iter().all(alphabet) {
+        return None;
+    }
+    base64::engine::general_purpose::STANDARD.decode(&text).ok()
+}
+
+fn decode_pkcs12(data: &[u8], password: &str) -> Result<Decoded> {
+    let store = KeyStore::from_pkcs12(data, password).map_err(|e| match e {
+        p12_keystore::error::Error::MacError(_) => anyhow::anyhow!("Wrong PKCS#12 password"),
+        e => anyhow::anyhow!("Invalid PKCS#12 file: {e}"),
+    })?;
+    let mut decoded = Decoded {
+        format: "PKCS#12",
+        certs: Vec::new(),
+        key: None,
+    };
+    // The key's chain comes first (leaf to root), then any loose certificates.
+    if let Some((_, chain)) = store.private_key_chain() {
+        decoded.key = Some(chain.key().to_vec());
+        decoded
+            .certs
+            .extend(chain.chain().iter().map(|c| c.as_der().to_vec()));
+    }
+    for (_, entry) in store.entries() {
+        if let KeyStoreEntry::Certificate(cert) = entry {
+            if !decoded.certs.iter().any(|c| c == cert.as_der()) {
+                decoded.certs.push(cert.as_der().to_vec());
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+/// Detect the input format and read its certificates; `password` is asked for only for PKCS#12.
+fn decode(path: &Path, data: &[u8], password: impl FnOnce() -> Result<String>) -> Result<Decoded> {
+    let (format, certs) = if data.windows(11).any(|w| w == b"-----BEGIN ") {
+        ("PEM", cert_ders(data)?)
+    } else if is_pkcs12(path, data) {
+        return decode_pkcs12(data, &password()?);
+    } else if let Some(der) = decode_base64(data) {
+        ("Base64", vec![der])
+    } else {
+        ("DER", vec![data.to_vec()])
+    };
+    Ok(Decoded {
+        format,
+        certs,
+        key: None,
+    })
+}
+
+/// `--to`, else the `--out` extension.
+fn output_format(out: &Path, to: Option<CertFormat>) -> Result<CertFormat> {
+    if let Some(to) = to {
+        return Ok(to);
+    }
+    let ext = out
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    Ok(match ext.as_str() {
+        "pem" | "crt" => CertFormat::Pem,
+        "der" | "cer" => CertFormat::Der,
+        "b64" | "base64" | "txt" => CertFormat::Base64,
+        _ => bail!(
+            "Cannot tell the output format from {}; pass --to pem|der|base64",
+            out.display()
+        ),
+    })
+}
+
+fn encode(format: CertFormat, certs: &[Vec<u8>]) -> Result<Vec<u8>> {
+    if format != CertFormat::Pem && certs.len() > 1 {
+        bail!(
+            "{} certificates found; DER and Base64 hold only one (convert to PEM instead)",
+            certs.len()
+        );
+    }
+    Ok(match format {
+        CertFormat::Pem => certs
+            .iter()
+            .map(|der| pem_block("CERTIFICATE", der))
+            .collect::<String>()
+            .into_bytes(),
+        CertFormat::Der => certs[0].clone(),
+        CertFormat::Base64 => {
+            let mut b64 = base64::engine::general_purpose::STANDARD.encode(&certs[0]);
+            b64.push('\n');
+            b64.into_bytes()
+        }
+    })
+}
+
+/// Convert `input` to `out`; a PKCS#12 private key is written to `key_out` as PEM.
+pub fn run(
+    input: &Path,
+    out: &Path,
+    to: Option<CertFormat>,
+    key_out: Option<&Path>,
+    force: bool,
+) -> Result<()> {
+    let format = output_format(out, to)?;
+    if key_out == Some(out) {
+        bail!("--out and --key-out must be different files");
+    }
+    refuse_existing(
+        &[Some(out), key_out]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>(),
+        force,
+    )?;
+    let data = std::fs::read(input)
+        .with_context(|| format!("Failed to read file: {}", input.display()))?;
+    let decoded = decode(input, &data, || read_password(None))?;
+    if decoded.certs.is_empty() {
+        bail!("No certificates found in {}", input.display());
+    }
+    let mut subjects = Vec::new();
+    for (i, der) in decoded.certs.iter().enumerate() {
+        let (_, cert) = x509_parser::parse_x509_certificate(der).map_err(|e| {
+            anyhow::anyhow!(
+                "{} certificate {} is not valid X.509: {e}",
+                decoded.format,
+                i + 1
+            )
+        })?;
+        subjects.push(cert.subject().to_string());
+    }
+
+    write_output(out, encode(format, &decoded.certs)?, false, force)?;
+
+    let label = match format {
+        CertFormat::Pem => "PEM",
+        CertFormat::Der => "DER",
+        CertFormat::Base64 => "Base64",
+    };
+    println!(
+        "{} {} → {label} {}",
+        "Converted:".bold(),
+        decoded.format,
+        out.display()
+    );
+    for subject in &subjects {
+        println!("  {}", subject.dimmed());
+    }
+    match (&decoded.key, key_out) {
+        (Some(key), Some(path)) => {
+            write_output(path, pem_block("PRIVATE KEY", key), true, force)?;
+            println!("{} {}", "Key:".bold(), path.display());
+        }
+        (Some(_), None) => println!(
+            "{}",
+            "The bundle also holds a private key; pass --key-out to extract it.".yellow()
+        ),
+        (None, Some(_)) => println!(
+            "{}",
+            "No private key in the input; --key-out ignored.".dimmed()
+        ),
+        (None, None) => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{CertificateParams, KeyPair};
+
+    fn cert_der() -> (Vec<u8>, KeyPair) {
+        let key = KeyPair::generate().unwrap();
+        let cert = CertificateParams::new(vec!["example.test".to_string()])
+            .unwrap()
+            .self_signed(&key)
+            .unwrap();
+        (cert.der().to_vec(), key)
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let (der, _) = cert_der();
+        let pem = encode(CertFormat::Pem, std::slice::from_ref(&der)).unwrap();
+        assert!(pem.split(|b| *b == b'\n').all(|line| line.len() <= 64));
+        let back = decode(Path::new("x.pem"), &pem, || unreachable!()).unwrap();
+        assert_eq!((back.format, back.certs), ("PEM", vec![der.clone()]));
+
+        let b64 = encode(CertFormat::Base64, std::slice::from_ref(&der)).unwrap();
+        let back = decode(Path::new("x.txt"), &b64, || unreachable!()).unwrap();
+        assert_eq!((back.format, back.certs), ("Base64", vec![der.clone()]));
+
+        let back = decode(Path::new("x.cer"), &der, || unreachable!()).unwrap();
+        assert_eq!((back.format, back.certs), ("DER", vec![der.clone()]));
+        assert!(encode(CertFormat::Der, &[der.clone(), der]).is_err());
+    }
+
+    #[test]
+    fn test_pkcs12() {
+        let (der, key) = cert_der();
+        let mut store = KeyStore::new();
+        let chain = p12_keystore::PrivateKeyChain::new(
+            key.serialize_der(),
+            [1u8; 4],
+            [p12_keystore::Certificate::from_der(&der).unwrap()],
+        );
+        store.add_entry("dev", KeyStoreEntry::PrivateKeyChain(chain));
+        let p12 = store.writer("s3cret").write().unwrap();
+
+        // Detected by content, even without a .p12 name.
+        assert!(is_pkcs12(Path::new("bundle.bin"), &p12));
+        assert!(!is_pkcs12(Path::new("cert.der"), &der));
+        let decoded = decode(Path::new("bundle.bin"), &p12, || Ok("s3cret".into())).unwrap();
+        assert_eq!(decoded.format, "PKCS#12");
+        assert_eq!(decoded.certs, [der]);
+        assert_eq!(decoded.key.unwrap(), key.serialize_der());
+        let wrong = decode(Path::new("b.p12"), &p12, || Ok("nope".into()));
+        assert_eq!(wrong.err().unwrap().to_string(), "Wrong PKCS#12 password");
+    }
+
+    #[test]
+    fn test_output_format() {
+        assert_eq!(
+            output_format(Path::new("a.PEM"), None).unwrap(),
+            CertFormat::Pem
+        );
+        assert_eq!(
+            output_format(Path::new("a.cer"), None).unwrap(),
+            CertFormat::Der
+        );
+        assert_eq!(
+            output_format(Path::new("a.bin"), Some(CertFormat::Base64)).unwrap(),
+            CertFormat::Base64
+        );
+        assert!(output_format(Path::new("a.bin"), None).is_err());
+    }
+}
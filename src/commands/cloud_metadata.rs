@@ -0,0 +1,212 @@
+//! `cloud metadata`: query the instance metadata service of the VM we run on
+//! and print the answer as JSON. Knows each provider's endpoint and headers:
+//! AWS IMDSv2 (session token first, IMDSv1 if the token call is refused), GCP
+//! (`Metadata-Flavor: Google`), and Azure IMDS (`Metadata: true`).
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use reqwest::{Client, Method, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+use url::Url;
+
+use crate::cli::CloudProvider;
+
+const AWS_ENDPOINT: &str = "http://169.254.169.254";
+const GCP_ENDPOINT: &str = "http://metadata.google.internal";
+const AZURE_ENDPOINT: &str = "http://169.254.169.254";
+const AZURE_API_VERSION: &str = "2021-02-01";
+
+/// URL for `path` under the provider's metadata tree. AWS paths are relative to
+/// `latest/meta-data/` unless they start with `dynamic/` or `user-data`; GCP paths
+/// are relative to `computeMetadata/v1/`, with directories (trailing `/`) fetched
+/// recursively as JSON. Azure always fetches the instance document.
+fn metadata_url(provider: CloudProvider, endpoint: &str, path: &str) -> Result<Url> {
+    let base = Url::parse(endpoint).with_context(|| format!("Invalid endpoint: {endpoint}"))?;
+    let path = path.trim_start_matches('/');
+    let url = match provider {
+        CloudProvider::Aws => {
+            let rel = if path.starts_with("dynamic/")
+                || path.starts_with("user-data")
+                || path.starts_with("meta-data")
+            {
+                path.to_string()
+            } else {
+                format!("meta-data/{path}")
+            };
+            base.join(&format!("latest/{rel}"))?
+        }
+        CloudProvider::Gcp => {
+            let mut url = base.join(&format!("computeMetadata/v1/{path}"))?;
+            if path.is_empty() || path.ends_with('/') {
+                url.set_query(Some("recursive=true&alt=json"));
+            }
+            url
+        }
+        CloudProvider::Azure => {
+            let mut url = base.join("metadata/instance")?;
+            url.query_pairs_mut()
+                .append_pair("api-version", AZURE_API_VERSION);
+            url
+        }
+    };
+    Ok(url)
+}
+
+/// A response body as JSON: documents as they are, AWS directory listings as
+/// arrays of entries, and anything else as a string.
+fn to_json(body: &str, listing: bool) -> Value {
+    match serde_json::from_str::<Value>(body) {
+        Ok(v @ (Value::Object(_) | Value::Array(_))) => v,
+        _ if listing => Value::Array(
+            body.lines()
+                .filter(|l| !l.is_empty())
+                .map(|l| Value::String(l.to_string()))
+                .collect(),
+        ),
+        _ => Value::String(body.trim_end_matches('\n').to_string()),
+    }
+}
+
+/// The node of `doc` at a `/`-separated path (object keys or array indices).
+fn descend<'a>(doc: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('/')
+        .filter(|s| !s.is_empty())
+        .try_fold(doc, |node, key| match node {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+/// An IMDSv2 session token, or `None` when the instance only speaks IMDSv1.
+async fn aws_token(client: &Client, endpoint: &str) -> Result<Option<String>> {
+    let url = Url::parse(endpoint)?.join("latest/api/token")?;
+    let resp = client
+        .put(url)
+        .header("X-aws-ec2-metadata-token-ttl-seconds", "60")
+        .send()
+        .await
+        .context(
+            "No AWS metadata service reachable (not an EC2 instance, or hop limit too low?)",
+        )?;
+    match resp.status() {
+        s if s.is_success() => Ok(Some(resp.text().await?)),
+        StatusCode::FORBIDDEN => {
+            bail!("IMDS token request refused (403): metadata access is disabled")
+        }
+        s => {
+            info!("IMDSv2 token request returned {s}; falling back to IMDSv1");
+            Ok(None)
+        }
+    }
+}
+
+/// Fetch `path` from `provider`'s metadata service and print it as JSON.
+pub async fn run(
+    provider: CloudProvider,
+    path: Option<&str>,
+    endpoint: Option<&str>,
+    timeout: Option<u64>,
+) -> Result<()> {
+    let path = path.unwrap_or_default();
+    let endpoint = endpoint.unwrap_or(match provider {
+        CloudProvider::Aws => AWS_ENDPOINT,
+        CloudProvider::Gcp => GCP_ENDPOINT,
+        CloudProvider::Azure => AZURE_ENDPOINT,
+    });
+    // Metadata services are link-local: never through a proxy, and fail fast off-cloud.
+    let client = Client::builder()
+        .no_proxy()
+        .timeout(Duration::from_secs(timeout.unwrap_or(5)))
+        .user_agent(concat!("swiftline/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let url = metadata_url(provider, endpoint, path)?;
+    let mut req = client.request(Method::GET, url.clone());
+    req = match provider {
+        CloudProvider::Aws => match aws_token(&client, endpoint).await? {
+            Some(token) => req.header("X-aws-ec2-metadata-token", token),
+            None => req,
+        },
+        CloudProvider::Gcp => req.header("Metadata-Flavor", "Google"),
+        CloudProvider::Azure => req.header("Metadata", "true"),
+    };
+
+    info!("GET {url}");
+    let resp = req
+        .send()
+        .await
+        .with_context(|| format!("No metadata service reachable at {endpoint}"))?;
+    let status = resp.status();
+    let body = resp.text().await?;
+    if status == StatusCode::NOT_FOUND {
+        bail!("No metadata at {path:?} (404)");
+    }
+    if !status.is_success() {
+        bail!("Metadata service returned {status}: {}", body.trim());
+    }
+
+    let listing =
+        matches!(provider, CloudProvider::Aws) && (path.is_empty() || path.ends_with('/'));
+    let mut value = to_json(&body, listing);
+    if matches!(provider, CloudProvider::Azure) {
+        value = descend(&value, path)
+            .cloned()
+            .with_context(|| format!("No metadata at {path:?}"))?;
+    }
+    println!("{}", colored_json::to_colored_json_auto(&value)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_metadata_url() {
+        let url = |p, path| {
+            metadata_url(p, "http://169.254.169.254", path)
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            url(CloudProvider::Aws, "instance-id"),
+            "http://169.254.169.254/latest/meta-data/instance-id"
+        );
+        assert_eq!(
+            url(CloudProvider::Aws, "dynamic/instance-identity/document"),
+            "http://169.254.169.254/latest/dynamic/instance-identity/document"
+        );
+        assert_eq!(
+            url(CloudProvider::Gcp, "instance/"),
+            "http://169.254.169.254/computeMetadata/v1/instance/?recursive=true&alt=json"
+        );
+        assert_eq!(
+            url(CloudProvider::Gcp, "instance/zone"),
+            "http://169.254.169.254/computeMetadata/v1/instance/zone"
+        );
+        assert_eq!(
+            url(CloudProvider::Azure, "compute/vmSize"),
+            "http://169.254.169.254/metadata/instance?api-version=2021-02-01"
+        );
+    }
+
+    #[test]
+    fn test_to_json_and_descend() {
+        assert_eq!(
+            to_json("ami-id\nplacement/\n", true),
+            json!(["ami-id", "placement/"])
+        );
+        assert_eq!(to_json("i-0abc\n", false), json!("i-0abc"));
+        assert_eq!(to_json("12345", false), json!("12345"));
+        let doc = json!({"compute": {"vmSize": "Standard_B2s", "tags": [{"name": "env"}]}});
+        assert_eq!(
+            descend(&doc, "compute/vmSize"),
+            Some(&json!("Standard_B2s"))
+        );
+        assert_eq!(descend(&doc, "/compute/tags/0/name"), Some(&json!("env")));
+        assert_eq!(descend(&doc, ""), Some(&doc));
+        assert!(descend(&doc, "compute/nope").is_none());
+    }
+}
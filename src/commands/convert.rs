@@ -0,0 +1,347 @@
+//! `convert`: one command for every pairing of JSON, YAML, TOML, CSV,
+//! MessagePack, and INI. Formats come from the file extensions (or --from/--to),
+//! and every input is read into a JSON value first, so each format has exactly
+//! one reader and one writer.
+
+use anyhow::{bail, Context, Result};
+use ini::Ini;
+use serde_json::{Map, Number, Value};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use crate::cli::DataFormat;
+use crate::helpers::style;
+
+/// Format implied by a file extension.
+fn from_extension(path: &Path) -> Option<DataFormat> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "json" => DataFormat::Json,
+        "yaml" | "yml" => DataFormat::Yaml,
+        "toml" => DataFormat::Toml,
+        "csv" => DataFormat::Csv,
+        "msgpack" | "mpk" | "mp" => DataFormat::Msgpack,
+        "ini" | "cfg" => DataFormat::Ini,
+        _ => return None,
+    })
+}
+
+/// A CSV field as a number or boolean when it prints back identically, else a string,
+/// so zip codes like `01234` and over-long ids survive the trip.
+fn infer_field(field: &str) -> Value {
+    match field {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match field.parse::<Number>() {
+            Ok(n) if n.to_string() == field => Value::Number(n),
+            _ => Value::String(field.to_string()),
+        },
+    }
+}
+
+fn read_csv(data: &[u8]) -> Result<Value> {
+    let mut reader = csv::Reader::from_reader(data);
+    let headers = reader.headers().context("Invalid CSV header")?.clone();
+    let mut rows = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.with_context(|| format!("Invalid CSV record {}", i + 1))?;
+        let row: Map<String, Value> = headers
+            .iter()
+            .zip(record.iter())
+            .map(|(k, v)| (k.to_string(), infer_field(v)))
+            .collect();
+        rows.push(Value::Object(row));
+    }
+    Ok(Value::Array(rows))
+}
+
+/// Rows of objects (or one object) as CSV; columns are every key in first-seen order.
+/// Nested values are written as JSON text and null as an empty field.
+fn write_csv(value: &Value) -> Result<Vec<u8>> {
+    let rows: Vec<&Map<String, Value>> = match value {
+        Value::Object(o) => vec![o],
+        Value::Array(items) => items
+            .iter()
+            .map(|v| {
+                v.as_object()
+                    .context("CSV output needs an array of objects")
+            })
+            .collect::<Result<_>>()?,
+        _ => bail!("CSV output needs an array of objects"),
+    };
+    let mut columns: Vec<&str> = Vec::new();
+    for row in &rows {
+        for k in row.keys() {
+            if !columns.contains(&k.as_str()) {
+                columns.push(k);
+            }
+        }
+    }
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns)?;
+    for row in rows {
+        writer.write_record(columns.iter().map(|c| match row.get(*c) {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => other.to_string(),
+        }))?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+/// Move the leaves of `value` into `out`, keyed by their path from `prefix`
+/// joined with `sep`. Arrays and empty objects stay whole.
+fn flatten_into(prefix: &str, value: Value, sep: &str, out: &mut Map<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                let key = if prefix.is_empty() {
+                    k
+                } else {
+                    format!("{prefix}{sep}{k}")
+                };
+                flatten_into(&key, v, sep, out);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other);
+        }
+    }
+}
+
+/// Rows (an array of objects, or one object) with nested objects flattened into
+/// columns like `address.city`, so CSV gets one column per leaf.
+pub(crate) fn flatten_rows(value: Value, sep: &str) -> Value {
+    let flatten = |row: Value| match row {
+        Value::Object(map) => {
+            let mut out = Map::new();
+            for (k, v) in map {
+                flatten_into(&k, v, sep, &mut out);
+            }
+            Value::Object(out)
+        }
+        other => other,
+    };
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(flatten).collect()),
+        other => flatten(other),
+    }
+}
+
+/// Sections become objects; keys before the first section stay at the top level.
+fn read_ini(text: &str) -> Result<Value> {
+    let ini = Ini::load_from_str(text).context("Invalid INI")?;
+    let mut out = Map::new();
+    for (section, props) in ini.iter() {
+        let pairs = props
+            .iter()
+            .map(|(k, v)| (k.to_string(), Value::String(v.to_string())));
+        match section {
+            None => out.extend(pairs),
+            Some(name) => {
+                let entry = out
+                    .entry(name.to_string())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                if let Value::Object(map) = entry {
+                    map.extend(pairs);
+                }
+            }
+        }
+    }
+    Ok(Value::Object(out))
+}
+
+fn ini_scalar(key: &str, value: &Value) -> Result<String> {
+    Ok(match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        Value::Bool(_) | Value::Number(_) => value.to_string(),
+        _ => bail!("INI cannot hold the nested value at {key}"),
+    })
+}
+
+/// Top-level scalars go in the general section and objects of scalars become sections.
+fn write_ini(value: &Value) -> Result<Vec<u8>> {
+    let top = value.as_object().context("INI output needs an object")?;
+    let mut ini = Ini::new();
+    for (k, v) in top.iter().filter(|(_, v)| !v.is_object()) {
+        ini.with_general_section().set(k, ini_scalar(k, v)?);
+    }
+    for (section, v) in top {
+        let Value::Object(props) = v else { continue };
+        for (k, v) in props {
+            ini.with_section(Some(section))
+                .set(k, ini_scalar(&format!("{section}.{k}"), v)?);
+        }
+    }
+    let mut out = Vec::new();
+    ini.write_to(&mut out)?;
+    Ok(out)
+}
+
+/// Read `data` in `format` into the common value model.
+pub(crate) fn read(format: DataFormat, data: &[u8]) -> Result<Value> {
+    let text = || std::str::from_utf8(data).context("Input is not UTF-8 text");
+    match format {
+        DataFormat::Json => serde_json::from_slice(data).context("Invalid JSON"),
+        DataFormat::Yaml => serde_yaml::from_slice(data).context("Invalid YAML"),
+        DataFormat::Toml => {
+            let table: toml::Table = toml::from_str(text()?).context("Invalid TOML")?;
+            Ok(serde_json::to_value(table)?)
+        }
+        DataFormat::Csv => read_csv(data),
+        DataFormat::Msgpack => rmp_serde::from_slice(data).context("Invalid MessagePack"),
+        DataFormat::Ini => read_ini(text()?),
+    }
+}
+
+/// Render `value` in `format`.
+pub(crate) fn write(format: DataFormat, value: &Value) -> Result<Vec<u8>> {
+    Ok(match format {
+        DataFormat::Json => {
+            let mut out = serde_json::to_vec_pretty(value)?;
+            out.push(b'\n');
+            out
+        }
+        DataFormat::Yaml => serde_yaml::to_string(value)?.into_bytes(),
+        DataFormat::Toml => {
+            if !value.is_object() {
+                bail!("TOML output needs an object at the top level");
+            }
+            toml::to_string(value)
+                .context("Value cannot be represented as TOML (it has no null)")?
+                .into_bytes()
+        }
+        DataFormat::Csv => write_csv(value)?,
+        DataFormat::Msgpack => rmp_serde::to_vec_named(value)?,
+        DataFormat::Ini => write_ini(value)?,
+    })
+}
+
+/// Convert `input` ("-" for stdin) to `output`, or to stdout (JSON unless `to` says otherwise).
+pub fn run(
+    input: &Path,
+    output: Option<&Path>,
+    from: Option<DataFormat>,
+    to: Option<DataFormat>,
+) -> Result<()> {
+    let stdin = input == Path::new("-");
+    let from = match from.or_else(|| from_extension(input)) {
+        Some(f) => f,
+        None if stdin => bail!("Pass --from when reading stdin"),
+        None => bail!("Cannot tell the format of {}; pass --from", input.display()),
+    };
+    let to = match (to, output) {
+        (Some(t), _) => t,
+        (None, Some(path)) => from_extension(path)
+            .with_context(|| format!("Cannot tell the format of {}; pass --to", path.display()))?,
+        (None, None) => DataFormat::Json,
+    };
+
+    let data = if stdin {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        buf
+    } else {
+        fs::read(input).with_context(|| format!("Failed to read {}", input.display()))?
+    };
+    let value = read(from, &data)?;
+    let bytes = write(to, &value)?;
+
+    match output {
+        Some(path) if path != Path::new("-") => {
+            fs::write(path, &bytes)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            style::ok(&format!(
+                "{} → {} ({} bytes)",
+                input.display(),
+                path.display(),
+                bytes.len()
+            ));
+        }
+        _ => {
+            if matches!(to, DataFormat::Msgpack) && style::is_tty() {
+                bail!("Refusing to write binary MessagePack to a terminal; give an output file");
+            }
+            io::stdout().write_all(&bytes)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trips() {
+        let value = json!({"name": "swiftline", "port": 8080, "debug": false,
+                           "server": {"host": "::1", "tags": ["a", "b"]}});
+        for format in [
+            DataFormat::Json,
+            DataFormat::Yaml,
+            DataFormat::Toml,
+            DataFormat::Msgpack,
+        ] {
+            let bytes = write(format, &value).unwrap();
+            assert_eq!(read(format, &bytes).unwrap(), value, "{format:?}");
+        }
+        assert!(write(DataFormat::Toml, &json!({"a": null})).is_err());
+    }
+
+    #[test]
+    fn test_csv() {
+        let value = read(
+            DataFormat::Csv,
+            b"id,zip,score,ok,big\n7,01234,2.5,true,12345678901234567890123\n",
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            json!([{"id": 7, "zip": "01234", "score": 2.5, "ok": true, "big": "12345678901234567890123"}])
+        );
+
+        let rows = json!([{"a": 1, "b": {"x": 1}}, {"c": null, "a": "q,r"}]);
+        let out = String::from_utf8(write(DataFormat::Csv, &rows).unwrap()).unwrap();
+        assert_eq!(out, "a,b,c\n1,\"{\"\"x\"\":1}\",\n\"q,r\",,\n");
+    }
+
+    #[test]
+    fn test_flatten_rows() {
+        let rows =
+            json!([{"id": 1, "user": {"name": "a", "geo": {"lat": 1.5}}, "tags": [1], "meta": {}}]);
+        assert_eq!(
+            flatten_rows(rows, "_"),
+            json!([{"id": 1, "user_name": "a", "user_geo_lat": 1.5, "tags": [1], "meta": {}}])
+        );
+        assert_eq!(flatten_rows(json!({"a": {"b": 1}}), "."), json!({"a.b": 1}));
+    }
+
+    #[test]
+    fn test_ini() {
+        let value = read(
+            DataFormat::Ini,
+            b"root = yes\n[db]\nhost = localhost\nport = 5432\n",
+        )
+        .unwrap();
+        assert_eq!(
+            value,
+            json!({"root": "yes", "db": {"host": "localhost", "port": "5432"}})
+        );
+        let back = String::from_utf8(
+            write(DataFormat::Ini, &json!({"db": {"port": 5432}, "x": 1})).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            read(DataFormat::Ini, back.as_bytes()).unwrap(),
+            json!({"x": "1", "db": {"port": "5432"}})
+        );
+        assert!(write(DataFormat::Ini, &json!({"db": {"nested": {"a": 1}}})).is_err());
+        assert_eq!(
+            from_extension(Path::new("a/b.YML")).map(|f| format!("{f:?}")),
+            Some("Yaml".into())
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! `db query`: one-shot Postgres or MySQL/MariaDB queries from a connection
+//! URL, printed with the same table and JSON renderers as `db sqlite query`.
+//! The SQL is sent over the text protocol (several `;`-separated statements are
+//! fine), so every column arrives as text and is typed for JSON output by its
+//! column type: numbers, booleans, and JSON documents stay what they are.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use futures::{Stream, StreamExt};
+use log::info;
+use owo_colors::OwoColorize;
+use serde_json::{Map, Number, Value};
+use sqlx::mysql::{MySqlConnectOptions, MySqlConnection, MySqlSslMode};
+use sqlx::postgres::{PgConnectOptions, PgConnection, PgSslMode};
+use sqlx::{Column, ConnectOptions, Database, Decode, Either, Row, TypeInfo};
+use std::time::Duration;
+use url::Url;
+
+use crate::cli::{DbTls, OutputFormat};
+use crate::commands::rand_password::read_password;
+use crate::helpers::table;
+
+/// Result of running the SQL: column names and JSON cells of every returned
+/// row, and the rows changed by statements that return none.
+#[derive(Debug, Default)]
+struct Outcome {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    changed: u64,
+}
+
+/// A text-protocol value as JSON, by its column type (Postgres or MySQL name).
+fn typed(type_name: &str, text: &str) -> Value {
+    let upper = type_name.to_ascii_uppercase();
+    let base = upper.split([' ', '(']).next().unwrap_or_default();
+    match base {
+        "BOOL" | "BOOLEAN" => match text {
+            "t" | "true" | "1" => Value::Bool(true),
+            "f" | "false" | "0" => Value::Bool(false),
+            _ => Value::String(text.to_string()),
+        },
+        "JSON" | "JSONB" => {
+            serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+        }
+        "FLOAT4" | "FLOAT8" | "FLOAT" | "DOUBLE" | "REAL" => match text.parse::<f64>() {
+            Ok(f) => {
+                Number::from_f64(f).map_or_else(|| Value::String(text.to_string()), Value::Number)
+            }
+            Err(_) => Value::String(text.to_string()),
+        },
+        // Exact types: a number only when it prints back identically, so
+        // NUMERIC(30) ids and 1.10 keep every digit as strings.
+        "INT2" | "INT4" | "INT8" | "OID" | "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT"
+        | "BIGINT" | "YEAR" | "NUMERIC" | "DECIMAL" => match text.parse::<Number>() {
+            Ok(n) if n.to_string() == text => Value::Number(n),
+            _ => Value::String(text.to_string()),
+        },
+        _ => Value::String(text.to_string()),
+    }
+}
+
+/// JSON cells of a row; bytes that are not UTF-8 (MySQL blobs) become base64.
+fn row_values<R>(row: &R) -> Result<Vec<Value>>
+where
+    R: Row,
+    usize: sqlx::ColumnIndex<R>,
+    for<'r> Option<&'r str>: Decode<'r, R::Database>,
+    for<'r> Option<Vec<u8>>: Decode<'r, R::Database>,
+{
+    let mut cells = Vec::with_capacity(row.len());
+    for (i, column) in row.columns().iter().enumerate() {
+        let cell = match row.try_get_unchecked::<Option<&str>, _>(i) {
+            Ok(Some(text)) => typed(column.type_info().name(), text),
+            Ok(None) => Value::Null,
+            Err(_) => match row.try_get_unchecked::<Option<Vec<u8>>, _>(i)? {
+                Some(bytes) => {
+                    Value::String(base64::engine::general_purpose::STANDARD.encode(bytes))
+                }
+                None => Value::Null,
+            },
+        };
+        cells.push(cell);
+    }
+    Ok(cells)
+}
+
+/// Drain a `fetch_many` stream: rows of every statement, and affected-row counts.
+async fn collect<DB, S>(
+    mut stream: S,
+    affected: impl Fn(&DB::QueryResult) -> u64,
+) -> Result<Outcome>
+where
+    DB: Database,
+    S: Stream<Item = sqlx::Result<Either<DB::QueryResult, DB::Row>>> + Unpin,
+    usize: sqlx::ColumnIndex<DB::Row>,
+    for<'r> Option<&'r str>: Decode<'r, DB>,
+    for<'r> Option<Vec<u8>>: Decode<'r, DB>,
+{
+    let mut out = Outcome::default();
+    while let Some(item) = stream.next().await {
+        match item? {
+            Either::Left(result) => out.changed += affected(&result),
+            Either::Right(row) => {
+                if out.columns.is_empty() {
+                    out.columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+                }
+                out.rows.push(row_values(&row)?);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// Connection options shared by both databases.
+pub struct QueryOptions {
+    pub password: bool,
+    pub tls: Option<DbTls>,
+    pub cacert: Option<std::path::PathBuf>,
+    pub timeout: Option<u64>,
+    pub output: OutputFormat,
+}
+
+async fn query_postgres(
+    url: &str,
+    sql: &str,
+    password: Option<&str>,
+    opts: &QueryOptions,
+) -> Result<Outcome> {
+    let mut connect: PgConnectOptions = url.parse().context("Invalid Postgres URL")?;
+    if let Some(password) = password {
+        connect = connect.password(password);
+    }
+    if let Some(tls) = opts.tls {
+        connect = connect.ssl_mode(match tls {
+            DbTls::Disable => PgSslMode::Disable,
+            DbTls::Prefer => PgSslMode::Prefer,
+            DbTls::Require => PgSslMode::Require,
+            DbTls::VerifyCa => PgSslMode::VerifyCa,
+            DbTls::VerifyFull => PgSslMode::VerifyFull,
+        });
+    }
+    if let Some(ca) = &opts.cacert {
+        connect = connect.ssl_root_cert(ca);
+    }
+    let mut conn: PgConnection = connect
+        .connect()
+        .await
+        .context("Failed to connect to Postgres")?;
+    collect::<sqlx::Postgres, _>(sqlx::raw_sql(sql).fetch_many(&mut conn), |r| {
+        r.rows_affected()
+    })
+    .await
+}
+
+async fn query_mysql(
+    url: &str,
+    sql: &str,
+    password: Option<&str>,
+    opts: &QueryOptions,
+) -> Result<Outcome> {
+    let mut connect: MySqlConnectOptions = url.parse().context("Invalid MySQL URL")?;
+    // sqlx reads PGPASSWORD for Postgres but not the MySQL client's MYSQL_PWD.
+    let env_password = std::env::var("MYSQL_PWD")
+        .ok()
+        .filter(|_| Url::parse(url).is_ok_and(|u| u.password().is_none()));
+    if let Some(password) = password.or(env_password.as_deref()) {
+        connect = connect.password(password);
+    }
+    if let Some(tls) = opts.tls {
+        connect = connect.ssl_mode(match tls {
+            DbTls::Disable => MySqlSslMode::Disabled,
+            DbTls::Prefer => MySqlSslMode::Preferred,
+            DbTls::Require => MySqlSslMode::Required,
+            DbTls::VerifyCa => MySqlSslMode::VerifyCa,
+            DbTls::VerifyFull => MySqlSslMode::VerifyIdentity,
+        });
+    }
+    if let Some(ca) = &opts.cacert {
+        connect = connect.ssl_ca(ca);
+    }
+    let mut conn: MySqlConnection = connect
+        .connect()
+        .await
+        .context("Failed to connect to MySQL")?;
+    collect::<sqlx::MySql, _>(sqlx::raw_sql(sql).fetch_many(&mut conn), |r| {
+        r.rows_affected()
+    })
+    .await
+}
+
+/// The URL with the mariadb:// scheme mapped to mysql://, and its database kind.
+fn classify(url: &str) -> Result<(String, bool)> {
+    let parsed = Url::parse(url).context("Invalid connection URL")?;
+    match parsed.scheme() {
+        "postgres" | "postgresql" => Ok((url.to_string(), true)),
+        "mysql" => Ok((url.to_string(), false)),
+        "mariadb" => Ok((format!("mysql{}", &url["mariadb".len()..]), false)),
+        other => bail!("Unsupported database URL scheme {other}://; use postgres:// or mysql://"),
+    }
+}
+
+/// Run `sql` against the database at `url` and print its rows.
+pub async fn run_query(url: &str, sql: &str, opts: QueryOptions) -> Result<()> {
+    if let Some(ca) = &opts.cacert {
+        if !ca.is_file() {
+            bail!("CA bundle not found: {}", ca.display());
+        }
+    }
+    let (url, postgres) = classify(url)?;
+    // Prompt before the timeout starts.
+    let password = opts.password.then(|| read_password(None)).transpose()?;
+    if let Ok(u) = Url::parse(&url) {
+        info!("Connecting to {}", u.host_str().unwrap_or("localhost"));
+    }
+    let work = async {
+        if postgres {
+            query_postgres(&url, sql, password.as_deref(), &opts).await
+        } else {
+            query_mysql(&url, sql, password.as_deref(), &opts).await
+        }
+    };
+    let secs = opts.timeout.unwrap_or(30);
+    let out = tokio::time::timeout(Duration::from_secs(secs), work)
+        .await
+        .with_context(|| format!("Query timed out after {secs}s"))??;
+
+    if out.rows.is_empty() && out.changed > 0 {
+        eprintln!("{}", format!("{} row(s) changed", out.changed).dimmed());
+        return Ok(());
+    }
+    match opts.output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = out
+                .rows
+                .into_iter()
+                .map(|row| {
+                    Value::Object(out.columns.iter().cloned().zip(row).collect::<Map<_, _>>())
+                })
+                .collect();
+            println!(
+                "{}",
+                colored_json::to_colored_json_auto(&Value::Array(items))?
+            );
+        }
+        OutputFormat::Table => {
+            let header: Vec<&str> = out.columns.iter().map(String::as_str).collect();
+            let cells: Vec<Vec<String>> = out
+                .rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|v| match v {
+                            Value::Null => "NULL".dimmed().to_string(),
+                            Value::String(s) => s.clone(),
+                            other => other.to_string(),
+                        })
+                        .collect()
+                })
+                .collect();
+            if !cells.is_empty() {
+                table::print(&header, &cells);
+            }
+            eprintln!("{}", format!("{} row(s)", cells.len()).dimmed());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typed() {
+        assert_eq!(typed("INT8", "42"), json!(42));
+        assert_eq!(
+            typed("BIGINT UNSIGNED", "18446744073709551615"),
+            json!(18446744073709551615u64)
+        );
+        assert_eq!(typed("NUMERIC", "1.10"), json!("1.10"));
+        assert_eq!(typed("NUMERIC", "2.5"), json!(2.5));
+        assert_eq!(typed("FLOAT8", "NaN"), json!("NaN"));
+        assert_eq!(typed("FLOAT8", "1e+30"), json!(1e30));
+        assert_eq!(typed("BOOL", "t"), json!(true));
+        assert_eq!(typed("BOOLEAN", "0"), json!(false));
+        assert_eq!(typed("JSONB", r#"{"a": [1]}"#), json!({"a": [1]}));
+        assert_eq!(
+            typed("TIMESTAMPTZ", "2024-05-01 10:00:00+00"),
+            json!("2024-05-01 10:00:00+00")
+        );
+        assert_eq!(typed("VARCHAR", "007"), json!("007"));
+    }
+
+    #[test]
+    fn test_classify() {
+        assert!(classify("postgresql://u@db/app").unwrap().1);
+        assert_eq!(
+            classify("mariadb://u:p@db:3306/app").unwrap(),
+            ("mysql://u:p@db:3306/app".to_string(), false)
+        );
+        assert!(classify("sqlite://app.db").is_err());
+        assert!(classify("not a url").is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! `db redis`: one-shot Redis operations (PING, GET, SET, DEL, TTL, and key
+//! listing) against a redis:// or rediss:// URL, taken from --url, else
+//! $REDIS_URL, else the local default. Values that parse as JSON are
+//! pretty-printed; binary values are escaped the way redis-cli shows them.
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use owo_colors::OwoColorize;
+use redis::aio::MultiplexedConnection;
+use redis::{cmd, Client};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::time::{Duration, Instant};
+
+use crate::cli::RedisCommands;
+use crate::helpers::style;
+
+const DEFAULT_URL: &str = "redis://127.0.0.1:6379";
+
+/// How a stored value is printed.
+#[derive(Debug, PartialEq)]
+enum Shown {
+    Json(Value),
+    Text(String),
+    /// Non-UTF-8 bytes with `\xNN` escapes.
+    Binary(String),
+}
+
+fn show(bytes: &[u8]) -> Shown {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => match serde_json::from_str::<Value>(text) {
+            // Bare numbers and strings are valid JSON too; only documents are re-rendered.
+            Ok(v @ (Value::Object(_) | Value::Array(_))) => Shown::Json(v),
+            _ => Shown::Text(text.to_string()),
+        },
+        Err(_) => Shown::Binary(bytes.escape_ascii().to_string()),
+    }
+}
+
+/// The URL to use: --url, else $REDIS_URL, else the local default.
+fn resolve_url(url: Option<String>, env: Option<String>) -> String {
+    url.or(env.filter(|u| !u.trim().is_empty()))
+        .unwrap_or_else(|| DEFAULT_URL.to_string())
+}
+
+/// Every key matching `pattern`, via SCAN so large production keyspaces are not blocked.
+async fn scan(con: &mut MultiplexedConnection, pattern: &str) -> Result<BTreeSet<String>> {
+    let mut keys = BTreeSet::new();
+    let mut cursor = 0u64;
+    loop {
+        let (next, batch): (u64, Vec<String>) = cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(1000)
+            .query_async(con)
+            .await?;
+        // SCAN may return a key more than once; the set removes repeats.
+        keys.extend(batch);
+        if next == 0 {
+            return Ok(keys);
+        }
+        cursor = next;
+    }
+}
+
+async fn execute(con: &mut MultiplexedConnection, action: RedisCommands) -> Result<()> {
+    match action {
+        RedisCommands::Ping => {
+            let start = Instant::now();
+            let reply: String = cmd("PING").query_async(con).await?;
+            println!(
+                "{reply} {}",
+                format!("({:.1} ms)", start.elapsed().as_secs_f64() * 1000.0).dimmed()
+            );
+        }
+        RedisCommands::Get { key } => {
+            let value: Option<Vec<u8>> = cmd("GET").arg(&key).query_async(con).await?;
+            match show(&value.with_context(|| format!("Key not found: {key}"))?) {
+                Shown::Json(v) => println!("{}", colored_json::to_colored_json_auto(&v)?),
+                Shown::Text(s) | Shown::Binary(s) => println!("{s}"),
+            }
+        }
+        RedisCommands::Set { key, value, ex, nx } => {
+            let mut c = cmd("SET");
+            c.arg(&key).arg(&value);
+            if let Some(secs) = ex {
+                c.arg("EX").arg(secs);
+            }
+            if nx {
+                c.arg("NX");
+            }
+            let reply: Option<String> = c.query_async(con).await?;
+            if reply.is_none() {
+                bail!("Key {key} already exists; not set (--nx)");
+            }
+            style::ok("OK");
+        }
+        RedisCommands::Del { keys } => {
+            let n: u64 = cmd("DEL").arg(&keys).query_async(con).await?;
+            println!("{n}");
+            eprintln!(
+                "{}",
+                format!("{n} of {} key(s) deleted", keys.len()).dimmed()
+            );
+        }
+        RedisCommands::Ttl { key } => {
+            let ttl: i64 = cmd("TTL").arg(&key).query_async(con).await?;
+            match ttl {
+                -2 => bail!("Key not found: {key}"),
+                -1 => {
+                    println!("-1");
+                    eprintln!("{}", "(no expiry)".dimmed());
+                }
+                secs => println!("{secs}"),
+            }
+        }
+        RedisCommands::Keys { pattern } => {
+            let keys = scan(con, &pattern).await?;
+            for k in &keys {
+                println!("{k}");
+            }
+            eprintln!("{}", format!("{} key(s)", keys.len()).dimmed());
+        }
+    }
+    Ok(())
+}
+
+/// Connect to `url` (see `resolve_url`) and run one operation.
+pub async fn run(url: Option<String>, timeout: Option<u64>, action: RedisCommands) -> Result<()> {
+    let url = resolve_url(url, std::env::var("REDIS_URL").ok());
+    let client = Client::open(url.as_str()).context("Invalid Redis URL")?;
+    info!("Connecting to {}", client.get_connection_info().addr);
+    let secs = timeout.unwrap_or(30);
+    let work = async {
+        let mut con = client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis")?;
+        execute(&mut con, action).await
+    };
+    tokio::time::timeout(Duration::from_secs(secs), work)
+        .await
+        .with_context(|| format!("Redis timed out after {secs}s"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_show() {
+        assert_eq!(show(br#"{"a": [1]}"#), Shown::Json(json!({"a": [1]})));
+        assert_eq!(show(b"42"), Shown::Text("42".into()));
+        assert_eq!(show(b"hello"), Shown::Text("hello".into()));
+        assert_eq!(
+            show(b"\x00\xffok\n"),
+            Shown::Binary("\\x00\\xffok\\n".into())
+        );
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        assert_eq!(
+            resolve_url(Some("redis://a".into()), Some("redis://b".into())),
+            "redis://a"
+        );
+        assert_eq!(resolve_url(None, Some("rediss://b".into())), "rediss://b");
+        assert_eq!(resolve_url(None, Some(" ".into())), DEFAULT_URL);
+        assert_eq!(resolve_url(None, None), DEFAULT_URL);
+    }
+}
@@ -0,0 +1,203 @@
+//! `db sqlite query`: run one SQL statement against a local SQLite file and
+//! print the rows as a table or JSON. Values are bound as parameters rather
+//! than pasted into the SQL: `-p VALUE` fills `?` placeholders in order and
+//! `-p name=VALUE` fills `:name`, `@name`, or `$name`.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use owo_colors::OwoColorize;
+use rusqlite::types::{Value as Sql, ValueRef};
+use rusqlite::{Connection, OpenFlags, Statement};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use crate::cli::OutputFormat;
+use crate::helpers::table;
+
+/// A parameter value as SQLite sees it: integers and reals when the text
+/// prints back identically, so `007` stays text; everything else is text.
+fn infer_param(value: &str) -> Sql {
+    if let Ok(i) = value.parse::<i64>() {
+        if i.to_string() == value {
+            return Sql::Integer(i);
+        }
+    }
+    match value.parse::<f64>() {
+        Ok(f) if f.is_finite() && f.to_string() == value => Sql::Real(f),
+        _ => Sql::Text(value.to_string()),
+    }
+}
+
+/// Bind `params` to the statement's placeholders: `name=value` pairs go to the
+/// named parameter `name`, the rest fill the unnamed ones in order.
+fn bind(stmt: &mut Statement, params: &[String]) -> Result<()> {
+    let count = stmt.parameter_count();
+    let names: Vec<Option<String>> = (1..=count)
+        .map(|i| {
+            stmt.parameter_name(i)
+                .filter(|n| !n.starts_with('?'))
+                .map(|n| n[1..].to_string())
+        })
+        .collect();
+
+    let mut named = Vec::new();
+    let mut positional = Vec::new();
+    for p in params {
+        match p.split_once('=') {
+            Some((k, v)) if names.iter().flatten().any(|n| n == k) => named.push((k, v)),
+            _ => positional.push(p.as_str()),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    for (i, name) in names.iter().enumerate() {
+        let value = match name {
+            Some(name) => named
+                .iter()
+                .rev()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| *v)
+                .with_context(|| format!("Missing -p {name}=VALUE"))?,
+            None => positional.next().with_context(|| {
+                format!(
+                    "The statement has more ? placeholders than -p values (need {})",
+                    names.iter().filter(|n| n.is_none()).count()
+                )
+            })?,
+        };
+        stmt.raw_bind_parameter(i + 1, infer_param(value))?;
+    }
+    if positional.next().is_some() {
+        bail!("More -p values than ? placeholders in the statement");
+    }
+    Ok(())
+}
+
+/// A column value as JSON; blobs become base64 strings.
+fn to_json(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).into_owned()),
+        ValueRef::Blob(b) => Value::String(base64::engine::general_purpose::STANDARD.encode(b)),
+    }
+}
+
+/// A column value as a table cell.
+fn to_cell(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".dimmed().to_string(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()).dimmed().to_string(),
+        v => match to_json(v) {
+            Value::String(s) => s,
+            other => other.to_string(),
+        },
+    }
+}
+
+/// Column names and rows of a query, each row a list of cells built by `cell`.
+fn query<T>(
+    stmt: &mut Statement,
+    cell: impl Fn(ValueRef) -> T,
+) -> Result<(Vec<String>, Vec<Vec<T>>)> {
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = Vec::new();
+    let mut raw = stmt.raw_query();
+    while let Some(row) = raw.next()? {
+        rows.push(
+            (0..columns.len())
+                .map(|i| row.get_ref(i).map(&cell))
+                .collect::<rusqlite::Result<_>>()?,
+        );
+    }
+    Ok((columns, rows))
+}
+
+/// Run `sql` with `params` against the database at `path` and print the result.
+pub fn run_query(path: &Path, sql: &str, params: &[String], output: OutputFormat) -> Result<()> {
+    // No CREATE flag: a mistyped path should fail, not leave an empty database behind.
+    let conn = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_URI,
+    )
+    .with_context(|| format!("Failed to open SQLite database: {}", path.display()))?;
+    let mut stmt = conn.prepare(sql).context("Invalid SQL")?;
+    bind(&mut stmt, params)?;
+
+    if stmt.column_count() == 0 {
+        let changed = stmt.raw_execute()?;
+        eprintln!("{}", format!("{changed} row(s) changed").dimmed());
+        return Ok(());
+    }
+    match output {
+        OutputFormat::Json => {
+            let (columns, rows) = query(&mut stmt, to_json)?;
+            let items: Vec<Value> = rows
+                .into_iter()
+                .map(|row| Value::Object(columns.iter().cloned().zip(row).collect::<Map<_, _>>()))
+                .collect();
+            println!(
+                "{}",
+                colored_json::to_colored_json_auto(&Value::Array(items))?
+            );
+        }
+        OutputFormat::Table => {
+            let (columns, rows) = query(&mut stmt, to_cell)?;
+            let header: Vec<&str> = columns.iter().map(String::as_str).collect();
+            table::print(&header, &rows);
+            eprintln!("{}", format!("{} row(s)", rows.len()).dimmed());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "create table users (id integer, name text, zip text, avatar blob);
+             insert into users values (1, 'ada', '007', x'0102'), (2, 'bob', '12345', null);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_infer_param() {
+        assert_eq!(infer_param("42"), Sql::Integer(42));
+        assert_eq!(infer_param("2.5"), Sql::Real(2.5));
+        assert_eq!(infer_param("007"), Sql::Text("007".into()));
+        assert_eq!(infer_param("1e3"), Sql::Text("1e3".into()));
+    }
+
+    #[test]
+    fn test_bind_and_query() {
+        let conn = db();
+        let mut stmt = conn
+            .prepare("select id, zip, avatar from users where name = :name or id = ?")
+            .unwrap();
+        bind(&mut stmt, &["name=ada".into(), "2".into()]).unwrap();
+        let (columns, rows) = query(&mut stmt, to_json).unwrap();
+        assert_eq!(columns, ["id", "zip", "avatar"]);
+        assert_eq!(
+            rows,
+            [
+                vec![json!(1), json!("007"), json!("AQI=")],
+                vec![json!(2), json!("12345"), Value::Null]
+            ]
+        );
+
+        let mut stmt = conn.prepare("select * from users where id = ?").unwrap();
+        assert!(bind(&mut stmt, &[]).is_err());
+        assert!(bind(&mut stmt, &["1".into(), "2".into()]).is_err());
+        let mut stmt = conn
+            .prepare("select * from users where name = @name")
+            .unwrap();
+        assert!(bind(&mut stmt, &["nme=ada".into()]).is_err());
+    }
+}
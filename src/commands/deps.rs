@@ -0,0 +1,536 @@
+//! `deps list` / `deps why`: read a Cargo.lock or npm package-lock.json
+//! (lockfileVersion 2 and 3) into one package graph, then list what is shipped
+//! or print the inverted tree of dependents that pulls a package in.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::cli::OutputFormat;
+use crate::helpers::table;
+
+/// One locked package.
+#[derive(Debug, Clone, PartialEq)]
+struct Package {
+    name: String,
+    version: String,
+    /// `crates.io`, `npm`, `git`, `registry`, `path`, or `workspace`.
+    source: String,
+    /// npm `"dev": true`: only needed for development.
+    dev: bool,
+    /// Indexes of direct dependencies.
+    deps: Vec<usize>,
+}
+
+/// All packages of a lockfile; `roots` are the project's own packages.
+#[derive(Debug)]
+struct Graph {
+    kind: &'static str,
+    packages: Vec<Package>,
+    roots: Vec<usize>,
+}
+
+fn cargo_source(source: Option<&str>) -> String {
+    match source {
+        None => "path",
+        Some(s)
+            if s.contains("github.com/rust-lang/crates.io-index")
+                || s.contains("index.crates.io") =>
+        {
+            "crates.io"
+        }
+        Some(s) if s.starts_with("git+") => "git",
+        Some(_) => "registry",
+    }
+    .to_string()
+}
+
+/// Cargo.lock: `dependencies` entries are `name`, `name version`, or `name version (source)`.
+fn parse_cargo_lock(text: &str) -> Result<Graph> {
+    let doc: toml::Value = toml::from_str(text).context("Cargo.lock is not valid TOML")?;
+    let entries = doc
+        .get("package")
+        .and_then(|p| p.as_array())
+        .context("Cargo.lock has no [[package]] entries")?;
+    let field = |e: &toml::Value, k: &str| e.get(k).and_then(|v| v.as_str()).map(str::to_string);
+
+    let mut packages: Vec<Package> = entries
+        .iter()
+        .map(|e| Package {
+            name: field(e, "name").unwrap_or_default(),
+            version: field(e, "version").unwrap_or_default(),
+            source: cargo_source(e.get("source").and_then(|v| v.as_str())),
+            dev: false,
+            deps: Vec::new(),
+        })
+        .collect();
+    let sources: Vec<Option<String>> = entries.iter().map(|e| field(e, "source")).collect();
+
+    for (i, e) in entries.iter().enumerate() {
+        let refs = e.get("dependencies").and_then(|d| d.as_array());
+        for r in refs.into_iter().flatten().filter_map(|r| r.as_str()) {
+            let mut parts = r.splitn(3, ' ');
+            let name = parts.next().unwrap_or_default();
+            let version = parts.next();
+            let source = parts
+                .next()
+                .map(|s| s.trim_start_matches('(').trim_end_matches(')'));
+            let found = packages.iter().enumerate().position(|(j, p)| {
+                p.name == name
+                    && version.is_none_or(|v| p.version == v)
+                    && source.is_none_or(|s| sources[j].as_deref() == Some(s))
+            });
+            match found {
+                Some(j) => packages[i].deps.push(j),
+                None => bail!(
+                    "Cargo.lock: {} depends on {r}, which is not locked",
+                    packages[i].name
+                ),
+            }
+        }
+    }
+    let roots = (0..packages.len())
+        .filter(|&i| sources[i].is_none())
+        .collect();
+    Ok(Graph {
+        kind: "Cargo.lock",
+        packages,
+        roots,
+    })
+}
+
+fn npm_source(entry: &Value) -> String {
+    let resolved = entry["resolved"].as_str().unwrap_or_default();
+    if entry["link"] == true {
+        "workspace"
+    } else if resolved.starts_with("git") {
+        "git"
+    } else if resolved.starts_with("file:") {
+        "path"
+    } else if resolved.is_empty() || resolved.contains("registry.npmjs.org") {
+        "npm"
+    } else {
+        "registry"
+    }
+    .to_string()
+}
+
+/// Package name from a `packages` key: the part after the last `node_modules/`.
+fn npm_name(key: &str) -> &str {
+    key.rsplit_once("node_modules/").map_or(key, |(_, n)| n)
+}
+
+/// package-lock.json v2/v3: `packages` keyed by install path. A dependency of
+/// the package at `a/node_modules/b` resolves to the nearest
+/// `.../node_modules/<dep>` walking up, as Node's module lookup does.
+fn parse_package_lock(text: &str) -> Result<Graph> {
+    let doc: Value = serde_json::from_str(text).context("package-lock.json is not valid JSON")?;
+    let Some(entries) = doc["packages"].as_object() else {
+        bail!(
+            "package-lock.json lockfileVersion {} has no \"packages\" map; regenerate it with npm 7 or newer",
+            doc["lockfileVersion"]
+        );
+    };
+    let keys: Vec<&String> = entries.keys().collect();
+    let index: HashMap<&str, usize> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, k)| (k.as_str(), i))
+        .collect();
+    // Workspace links point at the package's own entry.
+    let target = |i: usize| -> usize {
+        let e = &entries[keys[i]];
+        match e["resolved"].as_str() {
+            Some(r) if e["link"] == true => index.get(r).copied().unwrap_or(i),
+            _ => i,
+        }
+    };
+
+    let mut packages: Vec<Package> = keys
+        .iter()
+        .map(|k| {
+            let e = &entries[*k];
+            let name = e["name"].as_str().unwrap_or_else(|| {
+                if k.is_empty() {
+                    "(root)"
+                } else {
+                    npm_name(k)
+                }
+            });
+            Package {
+                name: name.to_string(),
+                version: e["version"].as_str().unwrap_or_default().to_string(),
+                source: if k.is_empty() || !k.contains("node_modules/") {
+                    "workspace".to_string()
+                } else {
+                    npm_source(e)
+                },
+                dev: e["dev"] == true,
+                deps: Vec::new(),
+            }
+        })
+        .collect();
+
+    for (i, key) in keys.iter().enumerate() {
+        let e = &entries[*key];
+        let names = [
+            "dependencies",
+            "devDependencies",
+            "optionalDependencies",
+            "peerDependencies",
+        ]
+        .iter()
+        .flat_map(|section| e[*section].as_object().into_iter().flat_map(|m| m.keys()));
+        for dep in names {
+            let mut base = key.as_str();
+            let found = loop {
+                let candidate = if base.is_empty() {
+                    format!("node_modules/{dep}")
+                } else {
+                    format!("{base}/node_modules/{dep}")
+                };
+                if let Some(&j) = index.get(candidate.as_str()) {
+                    break Some(target(j));
+                }
+                if base.is_empty() {
+                    break None;
+                }
+                base = base.rsplit_once("/node_modules/").map_or("", |(b, _)| b);
+            };
+            // Optional and peer dependencies may legitimately be absent.
+            if let Some(j) = found {
+                if !packages[i].deps.contains(&j) {
+                    packages[i].deps.push(j);
+                }
+            }
+        }
+    }
+    let roots: Vec<usize> = keys
+        .iter()
+        .enumerate()
+        .filter(|(_, k)| !k.contains("node_modules/"))
+        .map(|(i, _)| i)
+        .collect();
+    Ok(Graph {
+        kind: "package-lock.json",
+        packages,
+        roots,
+    })
+}
+
+/// `--lockfile`, else Cargo.lock or package-lock.json in the current directory.
+fn find_lockfile(path: Option<&Path>) -> Result<PathBuf> {
+    if let Some(p) = path {
+        return Ok(p.to_path_buf());
+    }
+    ["Cargo.lock", "package-lock.json", "npm-shrinkwrap.json"]
+        .iter()
+        .map(PathBuf::from)
+        .find(|p| p.exists())
+        .context("No Cargo.lock or package-lock.json here; pass --lockfile")
+}
+
+fn load(path: Option<&Path>) -> Result<Graph> {
+    let path = find_lockfile(path)?;
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    if text.trim_start().starts_with('{') {
+        parse_package_lock(&text)
+    } else {
+        parse_cargo_lock(&text)
+    }
+}
+
+/// Every locked package except the project's own, sorted by name and version.
+fn shipped(graph: &Graph) -> Vec<&Package> {
+    let mut seen = HashSet::new();
+    let mut list: Vec<&Package> = graph
+        .packages
+        .iter()
+        .enumerate()
+        .filter(|(i, p)| !graph.roots.contains(i) && p.source != "workspace")
+        .map(|(_, p)| p)
+        .filter(|p| seen.insert((p.name.clone(), p.version.clone())))
+        .collect();
+    list.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| crate::commands::sbom::cmp_versions(&a.version, &b.version))
+    });
+    list
+}
+
+/// List every locked package with its version and source.
+pub fn run_list(lockfile: Option<&Path>, output: OutputFormat) -> Result<()> {
+    let graph = load(lockfile)?;
+    let list = shipped(&graph);
+    let mut versions: BTreeMap<&str, usize> = BTreeMap::new();
+    for p in &list {
+        *versions.entry(p.name.as_str()).or_default() += 1;
+    }
+    match output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = list
+                .iter()
+                .map(|p| {
+                    json!({
+                        "name": p.name,
+                        "version": p.version,
+                        "source": p.source,
+                        "dev": p.dev,
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                colored_json::to_colored_json_auto(&Value::Array(items))?
+            );
+        }
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = list
+                .iter()
+                .map(|p| {
+                    let version = if versions[p.name.as_str()] > 1 {
+                        p.version.yellow().to_string()
+                    } else {
+                        p.version.clone()
+                    };
+                    let source = if p.dev {
+                        format!("{} (dev)", p.source)
+                    } else {
+                        p.source.clone()
+                    };
+                    vec![p.name.clone(), version, source]
+                })
+                .collect();
+            table::print(&["NAME", "VERSION", "SOURCE"], &rows);
+            let dupes: Vec<&str> = versions
+                .iter()
+                .filter(|(_, n)| **n > 1)
+                .map(|(name, _)| *name)
+                .collect();
+            let mut summary = format!("{}: {} package(s)", graph.kind, list.len());
+            if !dupes.is_empty() {
+                summary.push_str(&format!(
+                    ", {} with several versions: {}",
+                    dupes.len(),
+                    dupes.join(", ")
+                ));
+            }
+            eprintln!("{}", summary.dimmed());
+        }
+    }
+    Ok(())
+}
+
+/// Lines of the inverted tree under `node`: who depends on it, down to the roots.
+/// A package already expanded elsewhere is shown once more with `(*)` and not repeated.
+fn why_lines(
+    graph: &Graph,
+    dependents: &[Vec<usize>],
+    node: usize,
+    prefix: &str,
+    expanded: &mut HashSet<usize>,
+    out: &mut Vec<String>,
+) {
+    let parents = &dependents[node];
+    for (n, &parent) in parents.iter().enumerate() {
+        let last = n + 1 == parents.len();
+        let p = &graph.packages[parent];
+        let mut line = format!(
+            "{prefix}{}{} {}",
+            if last { "└── " } else { "├── " },
+            p.name,
+            p.version
+        );
+        let repeat = !expanded.insert(parent) && !dependents[parent].is_empty();
+        if graph.roots.contains(&parent) {
+            line.push_str(&format!(" {}", "(project)".dimmed()));
+        } else if repeat {
+            line.push_str(" (*)");
+        }
+        out.push(line);
+        if !repeat {
+            let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            why_lines(graph, dependents, parent, &child_prefix, expanded, out);
+        }
+    }
+}
+
+/// Print which packages pull in `query` (`name` or `name@version`).
+pub fn run_why(query: &str, lockfile: Option<&Path>) -> Result<()> {
+    let graph = load(lockfile)?;
+    // npm scopes start with '@', so only an '@' after the first character splits off a version.
+    let (name, version) = match query.get(1..).and_then(|rest| rest.find('@')) {
+        Some(at) => (&query[..at + 1], Some(&query[at + 2..])),
+        None => (query, None),
+    };
+    let mut matches: Vec<usize> = (0..graph.packages.len())
+        .filter(|&i| {
+            let p = &graph.packages[i];
+            p.name == name && version.is_none_or(|v| p.version == v)
+        })
+        .collect();
+    matches.sort_by(|&a, &b| {
+        crate::commands::sbom::cmp_versions(&graph.packages[a].version, &graph.packages[b].version)
+    });
+    if matches.is_empty() {
+        bail!("{query} is not in {}", graph.kind);
+    }
+
+    let mut dependents = vec![Vec::new(); graph.packages.len()];
+    for (i, p) in graph.packages.iter().enumerate() {
+        for &d in &p.deps {
+            if !dependents[d].contains(&i) {
+                dependents[d].push(i);
+            }
+        }
+    }
+    for list in &mut dependents {
+        list.sort_by(|&a, &b| graph.packages[a].name.cmp(&graph.packages[b].name));
+    }
+
+    let mut printed = HashSet::new();
+    for (n, &m) in matches.iter().enumerate() {
+        let p = &graph.packages[m];
+        if !printed.insert((p.name.as_str(), p.version.as_str())) {
+            continue;
+        }
+        if n > 0 {
+            println!();
+        }
+        println!("{} {}", p.name.bold(), p.version.bold());
+        if graph.roots.contains(&m) {
+            println!("{}", "(a package of this project)".dimmed());
+            continue;
+        }
+        let mut lines = Vec::new();
+        why_lines(&graph, &dependents, m, "", &mut HashSet::new(), &mut lines);
+        if lines.is_empty() {
+            println!(
+                "{}",
+                "(nothing depends on it; stale lockfile entry?)".dimmed()
+            );
+        }
+        for line in lines {
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO_LOCK: &str = r#"
+version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["reqwest", "serde 1.0.200"]
+
+[[package]]
+name = "reqwest"
+version = "0.12.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = ["serde 1.0.100 (registry+https://github.com/rust-lang/crates.io-index)"]
+
+[[package]]
+name = "serde"
+version = "1.0.100"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "serde"
+version = "1.0.200"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    const PACKAGE_LOCK: &str = r#"{
+      "name": "web", "lockfileVersion": 3,
+      "packages": {
+        "": {"name": "web", "version": "1.0.0",
+             "dependencies": {"express": "^4"}, "devDependencies": {"jest": "^29"}},
+        "node_modules/express": {"version": "4.18.2", "resolved": "https://registry.npmjs.org/express/-/express-4.18.2.tgz",
+                                 "dependencies": {"debug": "2.6.9", "@types/qs": "*"}},
+        "node_modules/express/node_modules/debug": {"version": "2.6.9"},
+        "node_modules/debug": {"version": "4.3.4", "dev": true},
+        "node_modules/@types/qs": {"version": "6.9.7"},
+        "node_modules/jest": {"version": "29.7.0", "dev": true, "dependencies": {"debug": "^4"}}
+      }
+    }"#;
+
+    fn names(g: &Graph, i: usize) -> Vec<String> {
+        g.packages[i]
+            .deps
+            .iter()
+            .map(|&d| format!("{} {}", g.packages[d].name, g.packages[d].version))
+            .collect()
+    }
+
+    #[test]
+    fn test_cargo_lock() {
+        let g = parse_cargo_lock(CARGO_LOCK).unwrap();
+        assert_eq!(g.roots, [0]);
+        assert_eq!(names(&g, 0), ["reqwest 0.12.0", "serde 1.0.200"]);
+        assert_eq!(names(&g, 1), ["serde 1.0.100"]);
+        let list: Vec<String> = shipped(&g)
+            .iter()
+            .map(|p| format!("{} {} {}", p.name, p.version, p.source))
+            .collect();
+        assert_eq!(
+            list,
+            [
+                "reqwest 0.12.0 crates.io",
+                "serde 1.0.100 crates.io",
+                "serde 1.0.200 crates.io"
+            ]
+        );
+        assert!(parse_cargo_lock(
+            "[[package]]\nname = \"a\"\nversion = \"1\"\ndependencies = [\"b\"]\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_package_lock_resolution() {
+        let g = parse_package_lock(PACKAGE_LOCK).unwrap();
+        let at = |name: &str, version: &str| {
+            g.packages
+                .iter()
+                .position(|p| p.name == name && p.version == version)
+                .unwrap()
+        };
+        // The nested debug 2.6.9 shadows the hoisted 4.3.4 for express only.
+        assert_eq!(
+            names(&g, at("express", "4.18.2")),
+            ["debug 2.6.9", "@types/qs 6.9.7"]
+        );
+        assert_eq!(names(&g, at("jest", "29.7.0")), ["debug 4.3.4"]);
+        assert_eq!(
+            names(&g, at("web", "1.0.0")),
+            ["express 4.18.2", "jest 29.7.0"]
+        );
+        assert!(g.packages[at("debug", "4.3.4")].dev);
+        assert!(parse_package_lock(r#"{"lockfileVersion": 1, "dependencies": {}}"#).is_err());
+    }
+
+    #[test]
+    fn test_why_tree() {
+        let g = parse_cargo_lock(CARGO_LOCK).unwrap();
+        let mut dependents = vec![Vec::new(); g.packages.len()];
+        for (i, p) in g.packages.iter().enumerate() {
+            for &d in &p.deps {
+                dependents[d].push(i);
+            }
+        }
+        let mut lines = Vec::new();
+        why_lines(&g, &dependents, 2, "", &mut HashSet::new(), &mut lines);
+        let plain: Vec<String> = lines.iter().map(|l| table::strip_ansi(l)).collect();
+        assert_eq!(plain, ["└── reqwest 0.12.0", "    └── app 0.1.0 (project)"]);
+    }
+}
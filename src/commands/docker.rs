@@ -0,0 +1,287 @@
+//! `docker ps|images|inspect`: read-only views of the local Docker Engine API over
+//! its Unix socket, without needing the docker CLI. Compose-managed containers
+//! show their project/service from the `com.docker.compose.*` labels.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::cli::OutputFormat;
+use crate::helpers::format::{human_size, print_json};
+use crate::helpers::{table, unix_http};
+
+const DEFAULT_SOCKET: &str = "/var/run/docker.sock";
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Socket priority: --socket > DOCKER_HOST (unix:// only) > /var/run/docker.sock.
+fn socket_path(flag: Option<PathBuf>, docker_host: Option<String>) -> Result<PathBuf> {
+    if let Some(p) = flag {
+        return Ok(p);
+    }
+    match docker_host.filter(|h| !h.is_empty()) {
+        Some(host) => match host.strip_prefix("unix://") {
+            Some(path) => Ok(PathBuf::from(path)),
+            None => bail!("DOCKER_HOST={host} is not a unix:// socket; pass --socket instead"),
+        },
+        None => Ok(PathBuf::from(DEFAULT_SOCKET)),
+    }
+}
+
+/// GET an Engine API path; `Ok(None)` on 404, API error messages surfaced otherwise.
+async fn api(socket: &Path, path: &str) -> Result<Option<Value>> {
+    let resp = unix_http::get(socket, path, TIMEOUT)
+        .await
+        .context("Is the Docker daemon running? (set --socket or DOCKER_HOST)")?;
+    let body: Value = serde_json::from_slice(&resp.body).unwrap_or(Value::Null);
+    match resp.status {
+        200..=299 => Ok(Some(body)),
+        404 => Ok(None),
+        status => bail!(
+            "Docker API error {status}: {}",
+            body["message"].as_str().unwrap_or("no message")
+        ),
+    }
+}
+
+fn short_id(id: &str) -> String {
+    id.trim_start_matches("sha256:").chars().take(12).collect()
+}
+
+/// Ports the way `docker ps` shows them: `0.0.0.0:8080->80/tcp, 443/tcp`.
+fn format_ports(ports: &Value) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for p in ports.as_array().into_iter().flatten() {
+        let private = p["PrivatePort"].as_u64().unwrap_or(0);
+        let proto = p["Type"].as_str().unwrap_or("tcp");
+        let entry = match (p["IP"].as_str(), p["PublicPort"].as_u64()) {
+            (Some(ip), Some(public)) if ip.contains(':') => {
+                format!("[{ip}]:{public}->{private}/{proto}")
+            }
+            (Some(ip), Some(public)) => format!("{ip}:{public}->{private}/{proto}"),
+            _ => format!("{private}/{proto}"),
+        };
+        if !out.contains(&entry) {
+            out.push(entry);
+        }
+    }
+    out.join(", ")
+}
+
+/// `project/service` for containers started by Compose.
+fn compose_label(labels: &Value) -> Option<String> {
+    let project = labels["com.docker.compose.project"].as_str()?;
+    Some(match labels["com.docker.compose.service"].as_str() {
+        Some(service) => format!("{project}/{service}"),
+        None => project.to_string(),
+    })
+}
+
+/// Coarse age like `3 days ago` for a Unix timestamp.
+fn ago(created: i64, now: i64) -> String {
+    let secs = (now - created).max(0);
+    let (n, unit) = match secs {
+        s if s < 60 => return "Less than a minute ago".to_string(),
+        s if s < 3_600 => (s / 60, "minute"),
+        s if s < 86_400 => (s / 3_600, "hour"),
+        s if s < 14 * 86_400 => (s / 86_400, "day"),
+        s if s < 60 * 86_400 => (s / (7 * 86_400), "week"),
+        s if s < 365 * 86_400 => (s / (30 * 86_400), "month"),
+        s => (s / (365 * 86_400), "year"),
+    };
+    format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" })
+}
+
+/// List containers (running only unless `all`).
+pub async fn run_ps(all: bool, socket: Option<PathBuf>, output: OutputFormat) -> Result<()> {
+    let socket = socket_path(socket, std::env::var("DOCKER_HOST").ok())?;
+    let path = if all {
+        "/containers/json?all=1"
+    } else {
+        "/containers/json"
+    };
+    let list = api(&socket, path).await?.unwrap_or(Value::Null);
+    let containers = list.as_array().map(Vec::as_slice).unwrap_or_default();
+    let name = |c: &Value| {
+        c["Names"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|n| n.trim_start_matches('/'))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    match output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = containers
+                .iter()
+                .map(|c| {
+                    json!({
+                        "id": c["Id"],
+                        "name": name(c),
+                        "image": c["Image"],
+                        "state": c["State"],
+                        "status": c["Status"],
+                        "ports": format_ports(&c["Ports"]),
+                        "compose": compose_label(&c["Labels"]),
+                    })
+                })
+                .collect();
+            print_json(&Value::Array(items))
+        }
+        OutputFormat::Table => {
+            if containers.is_empty() {
+                eprintln!("No {}containers.", if all { "" } else { "running " });
+                return Ok(());
+            }
+            let rows: Vec<Vec<String>> = containers
+                .iter()
+                .map(|c| {
+                    vec![
+                        short_id(c["Id"].as_str().unwrap_or_default()),
+                        c["Image"].as_str().unwrap_or_default().to_string(),
+                        c["Status"].as_str().unwrap_or_default().to_string(),
+                        format_ports(&c["Ports"]),
+                        name(c),
+                        compose_label(&c["Labels"]).unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            table::print(
+                &[
+                    "CONTAINER ID",
+                    "IMAGE",
+                    "STATUS",
+                    "PORTS",
+                    "NAMES",
+                    "COMPOSE",
+                ],
+                &rows,
+            );
+            Ok(())
+        }
+    }
+}
+
+/// List images, one row per tag (untagged images show `<none>`).
+pub async fn run_images(all: bool, socket: Option<PathBuf>, output: OutputFormat) -> Result<()> {
+    let socket = socket_path(socket, std::env::var("DOCKER_HOST").ok())?;
+    let path = if all {
+        "/images/json?all=1"
+    } else {
+        "/images/json"
+    };
+    let list = api(&socket, path).await?.unwrap_or(Value::Null);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let mut rows = Vec::new();
+    let mut items = Vec::new();
+    for img in list.as_array().into_iter().flatten() {
+        let id = img["Id"].as_str().unwrap_or_default();
+        let created = img["Created"].as_i64().unwrap_or(0);
+        let size = img["Size"].as_u64().unwrap_or(0);
+        let mut tags: Vec<&str> = img["RepoTags"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        if tags.is_empty() {
+            tags.push("<none>:<none>");
+        }
+        for tag in tags {
+            let (repo, tag) = tag.rsplit_once(':').unwrap_or((tag, "<none>"));
+            rows.push(vec![
+                repo.to_string(),
+                tag.to_string(),
+                short_id(id),
+                ago(created, now),
+                human_size(size),
+            ]);
+            items.push(json!({
+                "repository": repo,
+                "tag": tag,
+                "id": id,
+                "created": created,
+                "size": size,
+            }));
+        }
+    }
+
+    match output {
+        OutputFormat::Json => print_json(&Value::Array(items)),
+        OutputFormat::Table => {
+            if rows.is_empty() {
+                eprintln!("No images.");
+                return Ok(());
+            }
+            table::print(&["REPOSITORY", "TAG", "IMAGE ID", "CREATED", "SIZE"], &rows);
+            Ok(())
+        }
+    }
+}
+
+/// Print the full inspect JSON for a container, falling back to an image.
+pub async fn run_inspect(target: &str, socket: Option<PathBuf>) -> Result<()> {
+    if target.is_empty() || target.contains(|c: char| c.is_whitespace() || c == '?' || c == '#') {
+        bail!("Invalid container or image reference: {target:?}");
+    }
+    let socket = socket_path(socket, std::env::var("DOCKER_HOST").ok())?;
+    if let Some(v) = api(&socket, &format!("/containers/{target}/json")).await? {
+        return print_json(&v);
+    }
+    match api(&socket, &format!("/images/{target}/json")).await? {
+        Some(v) => print_json(&v),
+        None => bail!("No such container or image: {target}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_path() {
+        let flag = Some(PathBuf::from("/tmp/d.sock"));
+        assert_eq!(socket_path(flag, None).unwrap(), Path::new("/tmp/d.sock"));
+        assert_eq!(
+            socket_path(None, Some("unix:///run/user/1000/docker.sock".into())).unwrap(),
+            Path::new("/run/user/1000/docker.sock")
+        );
+        assert_eq!(socket_path(None, None).unwrap(), Path::new(DEFAULT_SOCKET));
+        assert!(socket_path(None, Some("tcp://10.0.0.2:2375".into())).is_err());
+    }
+
+    #[test]
+    fn test_ports_and_labels() {
+        let ports = json!([
+            {"IP": "0.0.0.0", "PrivatePort": 80, "PublicPort": 8080, "Type": "tcp"},
+            {"IP": "::", "PrivatePort": 80, "PublicPort": 8080, "Type": "tcp"},
+            {"IP": "0.0.0.0", "PrivatePort": 80, "PublicPort": 8080, "Type": "tcp"},
+            {"PrivatePort": 53, "Type": "udp"}
+        ]);
+        assert_eq!(
+            format_ports(&ports),
+            "0.0.0.0:8080->80/tcp, [::]:8080->80/tcp, 53/udp"
+        );
+        let labels =
+            json!({"com.docker.compose.project": "shop", "com.docker.compose.service": "db"});
+        assert_eq!(compose_label(&labels).as_deref(), Some("shop/db"));
+        assert_eq!(compose_label(&json!({})), None);
+        assert_eq!(short_id("sha256:0123456789abcdef"), "0123456789ab");
+    }
+
+    #[test]
+    fn test_ago() {
+        assert_eq!(ago(100, 130), "Less than a minute ago");
+        assert_eq!(ago(0, 3_600), "1 hour ago");
+        assert_eq!(ago(0, 3 * 86_400), "3 days ago");
+        assert_eq!(ago(0, 21 * 86_400), "3 weeks ago");
+        assert_eq!(ago(0, 800 * 86_400), "2 years ago");
+    }
+}
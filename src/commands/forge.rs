@@ -0,0 +1,323 @@
+//! `forge gh|gl api`: call the GitHub or GitLab REST API with token auth,
+//! `{owner}/{repo}` placeholders filled from the current git remote, `Link`
+//! pagination, and waits when the rate limit is exhausted.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use std::process::Command;
+use std::time::Duration;
+use url::Url;
+
+use super::http_request::parse_method;
+use crate::helpers::{headers, pace, spinner::spinner};
+
+/// Which forge's conventions to use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forge {
+    Github,
+    Gitlab,
+}
+
+impl Forge {
+    fn default_host(self) -> &'static str {
+        match self {
+            Forge::Github => "api.github.com",
+            Forge::Gitlab => "gitlab.com",
+        }
+    }
+
+    /// API root for a host; self-hosted GitHub lives under `/api/v3`.
+    fn api_base(self, host: &str) -> String {
+        match self {
+            Forge::Github if host == "api.github.com" => "https://api.github.com".to_string(),
+            Forge::Github => format!("https://{host}/api/v3"),
+            Forge::Gitlab => format!("https://{host}/api/v4"),
+        }
+    }
+
+    fn token_vars(self) -> &'static [&'static str] {
+        match self {
+            Forge::Github => &["GITHUB_TOKEN", "GH_TOKEN"],
+            Forge::Gitlab => &["GITLAB_TOKEN", "GL_TOKEN"],
+        }
+    }
+
+    fn auth_headers(self, token: Option<&str>) -> Result<HeaderMap> {
+        let mut h = HeaderMap::new();
+        match self {
+            Forge::Github => {
+                h.insert(
+                    ACCEPT,
+                    HeaderValue::from_static("application/vnd.github+json"),
+                );
+                h.insert(
+                    "x-github-api-version",
+                    HeaderValue::from_static("2022-11-28"),
+                );
+                if let Some(t) = token {
+                    h.insert(
+                        AUTHORIZATION,
+                        HeaderValue::from_str(&format!("Bearer {t}"))?,
+                    );
+                }
+            }
+            Forge::Gitlab => {
+                if let Some(t) = token {
+                    h.insert("private-token", HeaderValue::from_str(t)?);
+                }
+            }
+        }
+        Ok(h)
+    }
+}
+
+/// Extract the `owner/repo` (or nested GitLab `group/sub/project`) path from a remote URL.
+fn remote_path(remote: &str) -> Option<String> {
+    let remote = remote.trim();
+    let path = if let Some((_, rest)) = remote.split_once("://") {
+        rest.split_once('/')?.1
+    } else {
+        // scp-like: git@github.com:owner/repo.git
+        remote.split_once(':')?.1
+    };
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    path.contains('/').then(|| path.to_string())
+}
+
+fn origin_path() -> Result<String> {
+    let out = Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .context("Failed to run git to resolve {owner}/{repo}")?;
+    if !out.status.success() {
+        bail!("No git remote 'origin' here to fill {{owner}}/{{repo}}");
+    }
+    let url = String::from_utf8_lossy(&out.stdout);
+    remote_path(&url).with_context(|| format!("Cannot parse owner/repo from remote: {url}"))
+}
+
+/// Fill `{owner}`, `{repo}`, and `{project}` (URL-encoded full path) placeholders.
+fn fill_placeholders(path: &str, repo_path: impl FnOnce() -> Result<String>) -> Result<String> {
+    if !path.contains("{owner}") && !path.contains("{repo}") && !path.contains("{project}") {
+        return Ok(path.to_string());
+    }
+    let full = repo_path()?;
+    let (owner, repo) = full.rsplit_once('/').unwrap_or(("", &full));
+    Ok(path
+        .replace("{owner}", owner)
+        .replace("{repo}", repo)
+        .replace("{project}", &full.replace('/', "%2F")))
+}
+
+/// Wait reported by an exhausted rate limit, if any.
+fn rate_limit_wait(h: &HeaderMap, status: StatusCode) -> Option<Duration> {
+    let rl = pace::parse(h, pace::now_epoch());
+    let limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN
+            && (rl.remaining == Some(0) || rl.retry_after.is_some()));
+    if limited {
+        return Some(
+            rl.retry_after
+                .or(rl.reset)
+                .unwrap_or(Duration::from_secs(60)),
+        );
+    }
+    (rl.remaining == Some(0)).then_some(rl.reset).flatten()
+}
+
+/// Options for one `forge ... api` call.
+pub struct ApiOptions {
+    pub path: String,
+    pub method: String,
+    pub data: Option<String>,
+    pub paginate: bool,
+    pub host: Option<String>,
+    pub token: Option<String>,
+    pub timeout: Option<u64>,
+}
+
+/// Call the API, following `rel="next"` links with --paginate; arrays are concatenated.
+pub async fn run(forge: Forge, opts: ApiOptions) -> Result<()> {
+    let host = opts.host.as_deref().unwrap_or(forge.default_host());
+    let token = opts.token.clone().or_else(|| {
+        forge
+            .token_vars()
+            .iter()
+            .find_map(|v| std::env::var(v).ok().filter(|t| !t.is_empty()))
+    });
+    let path = fill_placeholders(&opts.path, origin_path)?;
+    let mut url = if path.starts_with("http://") || path.starts_with("https://") {
+        Url::parse(&path)?
+    } else {
+        Url::parse(&format!(
+            "{}/{}",
+            forge.api_base(host),
+            path.trim_start_matches('/')
+        ))
+        .with_context(|| format!("Invalid API path: {path}"))?
+    };
+    let method = parse_method(&opts.method)?;
+    let client = Client::builder()
+        .user_agent(concat!("swiftline/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(opts.timeout.unwrap_or(30)))
+        .build()?;
+    let auth = forge.auth_headers(token.as_deref())?;
+
+    let pb = spinner("Requesting...");
+    let mut pages: Vec<Value> = Vec::new();
+    let mut limited_retries = 0;
+    loop {
+        let mut req = client
+            .request(method.clone(), url.clone())
+            .headers(auth.clone());
+        if let Some(d) = &opts.data {
+            req = req.header(CONTENT_TYPE, "application/json").body(d.clone());
+        }
+        let resp = req
+            .send()
+            .await
+            .context("Network error while sending request")?;
+        let status = resp.status();
+        let wait = rate_limit_wait(resp.headers(), status);
+
+        if !status.is_success() {
+            match wait {
+                Some(w) if limited_retries < 3 => {
+                    limited_retries += 1;
+                    pb.set_message(format!("Rate limited, waiting {}s...", w.as_secs()));
+                    tokio::time::sleep(w).await;
+                    continue;
+                }
+                _ => {
+                    let body = resp.text().await.unwrap_or_default();
+                    pb.finish_and_clear();
+                    let msg = serde_json::from_str::<Value>(&body)
+                        .ok()
+                        .and_then(|v| v["message"].as_str().map(str::to_string))
+                        .unwrap_or(body);
+                    bail!("{method} {url} failed ({status}): {msg}");
+                }
+            }
+        }
+        limited_retries = 0;
+
+        let next = headers::link_rel(resp.headers(), "next");
+        let text = resp.text().await?;
+        let body: Value = if text.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&text).context("Response is not JSON")?
+        };
+        pages.push(body);
+
+        match next {
+            Some(n) if opts.paginate => {
+                url = url.join(&n)?;
+                pb.set_message(format!("Fetching page {}...", pages.len() + 1));
+                if let Some(w) = wait {
+                    eprintln!(
+                        "{} rate limit exhausted, sleeping {}s",
+                        "Pacing:".yellow().bold(),
+                        w.as_secs()
+                    );
+                    tokio::time::sleep(w).await;
+                }
+            }
+            _ => break,
+        }
+    }
+    pb.finish_and_clear();
+
+    let out = if pages.len() == 1 {
+        pages.remove(0)
+    } else if pages.iter().all(Value::is_array) {
+        Value::Array(
+            pages
+                .into_iter()
+                .flat_map(|p| match p {
+                    Value::Array(items) => items,
+                    _ => Vec::new(),
+                })
+                .collect(),
+        )
+    } else {
+        Value::Array(pages)
+    };
+    println!("{}", colored_json::to_colored_json_auto(&out)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_path() {
+        assert_eq!(
+            remote_path("git@github.com:rust-lang/cargo.git\n").as_deref(),
+            Some("rust-lang/cargo")
+        );
+        assert_eq!(
+            remote_path("https://gitlab.com/group/sub/project").as_deref(),
+            Some("group/sub/project")
+        );
+        assert_eq!(
+            remote_path("ssh://git@host:22/o/r.git").as_deref(),
+            Some("o/r")
+        );
+        assert_eq!(remote_path("https://github.com/"), None);
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        let path = fill_placeholders("/repos/{owner}/{repo}/issues", || {
+            Ok("octo/hello".to_string())
+        })
+        .unwrap();
+        assert_eq!(path, "/repos/octo/hello/issues");
+        let path =
+            fill_placeholders("/projects/{project}/issues", || Ok("g/sub/p".to_string())).unwrap();
+        assert_eq!(path, "/projects/g%2Fsub%2Fp/issues");
+        let path = fill_placeholders("/user", || bail!("not called")).unwrap();
+        assert_eq!(path, "/user");
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        let mut h = HeaderMap::new();
+        h.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        h.insert("x-ratelimit-reset", HeaderValue::from_static("30"));
+        assert_eq!(
+            rate_limit_wait(&h, StatusCode::FORBIDDEN),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            rate_limit_wait(&h, StatusCode::OK),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            rate_limit_wait(&HeaderMap::new(), StatusCode::FORBIDDEN),
+            None
+        );
+    }
+
+    #[test]
+    fn test_api_base() {
+        assert_eq!(
+            Forge::Github.api_base("api.github.com"),
+            "https://api.github.com"
+        );
+        assert_eq!(
+            Forge::Github.api_base("ghe.corp"),
+            "https://ghe.corp/api/v3"
+        );
+        assert_eq!(
+            Forge::Gitlab.api_base("gitlab.com"),
+            "https://gitlab.com/api/v4"
+        );
+    }
+}
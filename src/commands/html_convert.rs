@@ -0,0 +1,589 @@
+//! `html to-md` / `html to-text`: convert HTML to Markdown or plain terminal text.
+//! A small tag scanner (no full HTML5 parser) that is good enough for docs pages.
+//! Input comes from a file, --url (fetched), or stdin.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::helpers::spinner::spinner;
+
+/// Target flavor of the conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Markdown,
+    Text,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Start {
+        name: String,
+        attrs: &'a str,
+        self_closing: bool,
+    },
+    End(String),
+    Text(&'a str),
+}
+
+/// Split HTML into start tags, end tags, and text. Comments and doctypes are dropped.
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+    let mut out = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            out.push(Token::Text(rest));
+            break;
+        };
+        if lt > 0 {
+            out.push(Token::Text(&rest[..lt]));
+        }
+        rest = &rest[lt..];
+
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |i| &after[i + 3..]);
+            continue;
+        }
+        let Some(gt) = rest.find('>') else {
+            out.push(Token::Text(rest));
+            break;
+        };
+        let inner = &rest[1..gt];
+        rest = &rest[gt + 1..];
+
+        if inner.starts_with('!') || inner.starts_with('?') {
+            continue;
+        }
+        if let Some(name) = inner.strip_prefix('/') {
+            out.push(Token::End(name.trim().to_ascii_lowercase()));
+            continue;
+        }
+        let self_closing = inner.ends_with('/');
+        let inner = inner.trim_end_matches('/');
+        let name_end = inner
+            .find(|c: char| c.is_whitespace())
+            .unwrap_or(inner.len());
+        let name = inner[..name_end].to_ascii_lowercase();
+        if name.is_empty() {
+            out.push(Token::Text("<"));
+            continue;
+        }
+        out.push(Token::Start {
+            name: name.clone(),
+            attrs: &inner[name_end..],
+            self_closing,
+        });
+
+        // Raw-text elements: skip their content entirely.
+        if matches!(name.as_str(), "script" | "style" | "noscript" | "template") {
+            let close = format!("</{name}");
+            let lower = rest.to_ascii_lowercase();
+            rest = match lower.find(&close) {
+                Some(i) => {
+                    let tail = &rest[i..];
+                    tail.find('>').map_or("", |j| &tail[j + 1..])
+                }
+                None => "",
+            };
+            out.push(Token::End(name));
+        }
+    }
+    out
+}
+
+/// Read an attribute value from the raw attribute string of a tag.
+fn attr(attrs: &str, key: &str) -> Option<String> {
+    let lower = attrs.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(key) {
+        let start = from + pos;
+        from = start + key.len();
+        let boundary = start == 0 || lower.as_bytes()[start - 1].is_ascii_whitespace();
+        let rest = attrs[from..].trim_start();
+        if !boundary || !rest.starts_with('=') {
+            continue;
+        }
+        let rest = rest[1..].trim_start();
+        let value = match rest.chars().next() {
+            Some(q @ ('"' | '\'')) => rest[1..].split(q).next().unwrap_or(""),
+            _ => rest.split(|c: char| c.is_whitespace()).next().unwrap_or(""),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+/// Decode the handful of named entities that show up in docs, plus numeric ones.
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&i| i <= 10).and_then(|semi| {
+            let name = &rest[1..semi];
+            let ch = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "copy" => Some('©'),
+                "reg" => Some('®'),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                _ => name
+                    .strip_prefix("#x")
+                    .or_else(|| name.strip_prefix("#X"))
+                    .and_then(|h| u32::from_str_radix(h, 16).ok())
+                    .or_else(|| name.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            ch.map(|c| (c, semi))
+        });
+        match decoded {
+            Some((c, semi)) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_block(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "div"
+            | "section"
+            | "article"
+            | "header"
+            | "footer"
+            | "main"
+            | "nav"
+            | "aside"
+            | "ul"
+            | "ol"
+            | "dl"
+            | "dt"
+            | "dd"
+            | "table"
+            | "figure"
+            | "form"
+            | "h1"
+            | "h2"
+            | "h3"
+            | "h4"
+            | "h5"
+            | "h6"
+    )
+}
+
+/// A nested output buffer, used for elements whose rendering wraps their content.
+struct Frame {
+    tag: String,
+    buf: String,
+    href: Option<String>,
+}
+
+struct Renderer {
+    mode: Mode,
+    frames: Vec<Frame>,
+    lists: Vec<Option<usize>>,
+    pre: usize,
+    hidden: usize,
+    row_cells: usize,
+    rows: usize,
+}
+
+impl Renderer {
+    fn new(mode: Mode) -> Self {
+        Renderer {
+            mode,
+            frames: vec![Frame {
+                tag: String::new(),
+                buf: String::new(),
+                href: None,
+            }],
+            lists: Vec::new(),
+            pre: 0,
+            hidden: 0,
+            row_cells: 0,
+            rows: 0,
+        }
+    }
+
+    fn md(&self) -> bool {
+        self.mode == Mode::Markdown
+    }
+
+    fn buf(&mut self) -> &mut String {
+        &mut self.frames.last_mut().expect("root frame").buf
+    }
+
+    fn push(&mut self, s: &str) {
+        if self.hidden == 0 {
+            self.buf().push_str(s);
+        }
+    }
+
+    /// Ensure the buffer ends with at least `n` newlines (n = 1 line break, 2 = blank line).
+    fn newlines(&mut self, n: usize) {
+        let buf = self.buf();
+        if buf.is_empty() {
+            return;
+        }
+        let have = buf.len() - buf.trim_end_matches('\n').len();
+        let trimmed = buf.trim_end_matches([' ', '\n']).len();
+        buf.truncate(trimmed);
+        buf.push_str(&"\n".repeat(n.max(have.min(2))));
+    }
+
+    fn text(&mut self, raw: &str) {
+        let decoded = decode_entities(raw);
+        if self.pre > 0 {
+            self.push(&decoded);
+            return;
+        }
+        let mut collapsed = String::with_capacity(decoded.len());
+        let mut last_space = self.buf().ends_with([' ', '\n']) || self.buf().is_empty();
+        for c in decoded.chars() {
+            if c.is_whitespace() {
+                if !last_space {
+                    collapsed.push(' ');
+                    last_space = true;
+                }
+            } else {
+                collapsed.push(c);
+                last_space = false;
+            }
+        }
+        self.push(&collapsed);
+    }
+
+    fn open_frame(&mut self, tag: &str, href: Option<String>) {
+        self.frames.push(Frame {
+            tag: tag.to_string(),
+            buf: String::new(),
+            href,
+        });
+    }
+
+    fn close_frame(&mut self, tag: &str) -> Option<Frame> {
+        if self.frames.len() > 1 && self.frames.last().is_some_and(|f| f.tag == tag) {
+            self.frames.pop()
+        } else {
+            None
+        }
+    }
+
+    fn start(&mut self, name: &str, attrs: &str) {
+        let md = self.md();
+        match name {
+            "head" | "title" | "svg" | "button" | "select" => self.hidden += 1,
+            "br" => self.push("\n"),
+            "hr" => {
+                self.newlines(2);
+                self.push(if md {
+                    "---"
+                } else {
+                    "────────"
+                });
+                self.newlines(2);
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.newlines(2);
+                if md {
+                    let level = name[1..].parse().unwrap_or(1);
+                    self.push(&format!("{} ", "#".repeat(level)));
+                }
+            }
+            "pre" => {
+                self.newlines(2);
+                if md {
+                    self.push("```\n");
+                }
+                self.pre += 1;
+            }
+            "code" if self.pre == 0 && md => self.push("`"),
+            "strong" | "b" if md => self.push("**"),
+            "em" | "i" if md => self.push("*"),
+            "a" => self.open_frame("a", attr(attrs, "href")),
+            "img" => {
+                let alt = attr(attrs, "alt").unwrap_or_default();
+                if md {
+                    let src = attr(attrs, "src").unwrap_or_default();
+                    self.push(&format!("![{alt}]({src})"));
+                } else if !alt.is_empty() {
+                    self.push(&format!("[{alt}]"));
+                }
+            }
+            "blockquote" => {
+                self.newlines(2);
+                self.open_frame("blockquote", None);
+            }
+            "ul" => {
+                self.newlines(1);
+                self.lists.push(None);
+            }
+            "ol" => {
+                self.newlines(1);
+                let start = attr(attrs, "start")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(1);
+                self.lists.push(Some(start));
+            }
+            "li" => {
+                self.newlines(1);
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}.", *n - 1)
+                    }
+                    _ if md => "-".to_string(),
+                    _ => "•".to_string(),
+                };
+                self.push(&format!("{}{marker} ", "  ".repeat(depth)));
+            }
+            "tr" => {
+                self.newlines(1);
+                self.row_cells = 0;
+            }
+            "td" | "th" => {
+                if md && self.row_cells == 0 {
+                    self.push("| ");
+                } else if self.row_cells > 0 {
+                    self.push(if md { " | " } else { "\t" });
+                }
+                self.row_cells += 1;
+            }
+            "table" => {
+                self.newlines(2);
+                self.rows = 0;
+            }
+            _ if is_block(name) => self.newlines(2),
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, name: &str) {
+        let md = self.md();
+        match name {
+            "head" | "title" | "svg" | "button" | "select" => {
+                self.hidden = self.hidden.saturating_sub(1)
+            }
+            "pre" => {
+                self.pre = self.pre.saturating_sub(1);
+                if md {
+                    self.newlines(1);
+                    self.push("```");
+                }
+                self.newlines(2);
+            }
+            "code" if self.pre == 0 && md => self.push("`"),
+            "strong" | "b" if md => self.push("**"),
+            "em" | "i" if md => self.push("*"),
+            "a" => {
+                if let Some(frame) = self.close_frame("a") {
+                    let text = frame.buf.trim().to_string();
+                    match frame.href {
+                        Some(href) if md && !href.is_empty() && !href.starts_with('#') => {
+                            self.push(&format!("[{text}]({href})"))
+                        }
+                        _ => self.push(&text),
+                    }
+                }
+            }
+            "blockquote" => {
+                if let Some(frame) = self.close_frame("blockquote") {
+                    let prefix = if md { "> " } else { "  │ " };
+                    let quoted: Vec<String> = frame
+                        .buf
+                        .trim()
+                        .lines()
+                        .map(|l| format!("{prefix}{l}").trim_end().to_string())
+                        .collect();
+                    self.push(&quoted.join("\n"));
+                    self.newlines(2);
+                }
+            }
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.newlines(if self.lists.is_empty() { 2 } else { 1 });
+            }
+            "tr" => {
+                if md && self.row_cells > 0 {
+                    self.push(" |");
+                    if self.rows == 0 {
+                        let sep = vec!["---"; self.row_cells].join(" | ");
+                        self.push(&format!("\n| {sep} |"));
+                    }
+                }
+                self.rows += 1;
+                self.newlines(1);
+            }
+            "table" => self.newlines(2),
+            _ if is_block(name) => self.newlines(2),
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> String {
+        // Flush any unclosed frames (e.g. a missing </a>) into their parent.
+        while self.frames.len() > 1 {
+            let frame = self.frames.pop().expect("non-root frame");
+            self.buf().push_str(&frame.buf);
+        }
+        let out = self.frames.pop().map(|f| f.buf).unwrap_or_default();
+        let lines: Vec<&str> = out.lines().map(str::trim_end).collect();
+        let mut result = String::new();
+        let mut blank = 0;
+        for line in lines {
+            if line.is_empty() {
+                blank += 1;
+                if blank > 1 {
+                    continue;
+                }
+            } else {
+                blank = 0;
+            }
+            result.push_str(line);
+            result.push('\n');
+        }
+        result.trim().to_string()
+    }
+}
+
+/// Convert an HTML document to Markdown or plain text.
+pub fn convert(html: &str, mode: Mode) -> String {
+    let mut r = Renderer::new(mode);
+    for token in tokenize(html) {
+        match token {
+            Token::Text(t) => r.text(t),
+            Token::Start {
+                name,
+                attrs,
+                self_closing,
+            } => {
+                r.start(&name, attrs);
+                if self_closing && !matches!(name.as_str(), "br" | "hr" | "img") {
+                    r.end(&name);
+                }
+            }
+            Token::End(name) => r.end(&name),
+        }
+    }
+    r.finish()
+}
+
+async fn fetch(url: &str, timeout_secs: Option<u64>) -> Result<String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs.unwrap_or(30)))
+        .build()?;
+    let pb = spinner("Fetching...");
+    let resp = client
+        .get(url)
+        .send()
+        .await
+        .context("Network error while sending request")?
+        .error_for_status()?;
+    let text = resp.text().await?;
+    pb.finish_and_clear();
+    Ok(text)
+}
+
+/// Convert HTML from a file, a URL, or stdin and print the result.
+pub async fn run(
+    mode: Mode,
+    file: Option<PathBuf>,
+    url: Option<String>,
+    timeout: Option<u64>,
+) -> Result<()> {
+    let html = if let Some(url) = url {
+        fetch(&url, timeout).await?
+    } else if let Some(path) = file {
+        fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?
+    } else {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf)?;
+        buf
+    };
+
+    println!("{}", convert(&html, mode));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headings_and_paragraphs() {
+        let html = "<h1>Title</h1><p>Hello   <b>world</b></p><p>Next</p>";
+        assert_eq!(
+            convert(html, Mode::Markdown),
+            "# Title\n\nHello **world**\n\nNext"
+        );
+        assert_eq!(convert(html, Mode::Text), "Title\n\nHello world\n\nNext");
+    }
+
+    #[test]
+    fn test_links_and_lists() {
+        let html = r#"<ul><li><a href="/a">A</a></li><li>B</li></ul><ol><li>x</li><li>y</li></ol>"#;
+        assert_eq!(
+            convert(html, Mode::Markdown),
+            "- [A](/a)\n- B\n\n1. x\n2. y"
+        );
+        assert_eq!(convert(html, Mode::Text), "• A\n• B\n\n1. x\n2. y");
+    }
+
+    #[test]
+    fn test_pre_keeps_whitespace_and_scripts_are_dropped() {
+        let html = "<script>var x = '<p>';</script><pre><code>a  b\n  c</code></pre>";
+        assert_eq!(convert(html, Mode::Markdown), "```\na  b\n  c\n```");
+    }
+
+    #[test]
+    fn test_table_markdown() {
+        let html = "<table><tr><th>k</th><th>v</th></tr><tr><td>a</td><td>1</td></tr></table>";
+        assert_eq!(
+            convert(html, Mode::Markdown),
+            "| k | v |\n| --- | --- |\n| a | 1 |"
+        );
+    }
+
+    #[test]
+    fn test_entities() {
+        assert_eq!(
+            decode_entities("a &amp; b &lt;c&gt; &#65;&#x42; &bogus"),
+            "a & b <c> AB &bogus"
+        );
+    }
+
+    #[test]
+    fn test_attr_lookup() {
+        assert_eq!(
+            attr(r#" class="x" href='/y'"#, "href"),
+            Some("/y".to_string())
+        );
+        assert_eq!(attr(r#" data-href="/z""#, "href"), None);
+    }
+}
@@ -0,0 +1,317 @@
+//! `http audit`: grade a site's security headers and TLS setup, observatory-style.
+//! Prints findings by severity, or a JSON report with `--json` for dashboards.
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use reqwest::redirect::Policy;
+use reqwest::Client;
+use rustls::pki_types::ServerName;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use url::Url;
+
+use crate::helpers::headers::{self, Finding, Severity};
+use crate::helpers::{spinner::spinner, style, table};
+
+/// What the TLS handshake negotiated, plus leaf certificate details.
+#[derive(Debug, Default)]
+struct TlsSummary {
+    version: String,
+    cipher: String,
+    alpn: Option<String>,
+    subject: String,
+    issuer: String,
+    not_after: i64,
+    days_left: i64,
+}
+
+/// Handshake with the server (verifying against the bundled web PKI roots).
+async fn probe_tls(host: &str, port: u16, timeout: Duration) -> Result<TlsSummary> {
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+    };
+    let mut config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    let name = ServerName::try_from(host.to_string()).context("Invalid TLS server name")?;
+    let tcp = tokio::time::timeout(timeout, TcpStream::connect((host, port)))
+        .await
+        .context("TCP connect timed out")??;
+    let tls = tokio::time::timeout(
+        timeout,
+        TlsConnector::from(Arc::new(config)).connect(name, tcp),
+    )
+    .await
+    .context("TLS handshake timed out")??;
+
+    let (_, conn) = tls.get_ref();
+    let mut summary = TlsSummary {
+        version: conn
+            .protocol_version()
+            .map(|v| format!("{v:?}").replace("TLSv1_", "TLS 1."))
+            .unwrap_or_default(),
+        cipher: conn
+            .negotiated_cipher_suite()
+            .map(|c| format!("{:?}", c.suite()))
+            .unwrap_or_default(),
+        alpn: conn
+            .alpn_protocol()
+            .map(|p| String::from_utf8_lossy(p).to_string()),
+        ..Default::default()
+    };
+
+    if let Some(leaf) = conn.peer_certificates().and_then(|c| c.first()) {
+        let (_, cert) = x509_parser::parse_x509_certificate(leaf.as_ref())
+            .map_err(|e| anyhow::anyhow!("Cannot parse server certificate: {e}"))?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        summary.subject = cert.subject().to_string();
+        summary.issuer = cert.issuer().to_string();
+        summary.not_after = cert.validity().not_after.timestamp();
+        summary.days_left = (summary.not_after - now).div_euclid(86_400);
+    }
+    Ok(summary)
+}
+
+fn tls_findings(tls: &TlsSummary) -> Vec<Finding> {
+    let mut out = Vec::new();
+    let mut add = |severity, message: String| {
+        out.push(Finding {
+            severity,
+            header: "tls".to_string(),
+            message,
+        })
+    };
+    if tls.version != "TLS 1.3" {
+        add(
+            Severity::Low,
+            format!("{} negotiated; TLS 1.3 not offered", tls.version),
+        );
+    }
+    match tls.days_left {
+        d if d < 0 => add(Severity::High, "Certificate has expired".to_string()),
+        d if d < 14 => add(Severity::High, format!("Certificate expires in {d} day(s)")),
+        d if d < 30 => add(Severity::Medium, format!("Certificate expires in {d} days")),
+        _ => {}
+    }
+    if tls.alpn.as_deref() != Some("h2") {
+        add(Severity::Info, "HTTP/2 not negotiated via ALPN".to_string());
+    }
+    out
+}
+
+/// Check whether plain HTTP on the same host redirects to HTTPS.
+async fn check_http_redirect(host: &str, timeout: Duration) -> Option<Finding> {
+    let client = Client::builder()
+        .redirect(Policy::none())
+        .timeout(timeout)
+        .build()
+        .ok()?;
+    let finding = |severity, message: &str| Finding {
+        severity,
+        header: "http".to_string(),
+        message: message.to_string(),
+    };
+    match client.get(format!("http://{host}/")).send().await {
+        Err(_) => Some(finding(Severity::Info, "Port 80 not reachable")),
+        Ok(resp) => {
+            let to_https = resp
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|l| l.starts_with("https://"));
+            if resp.status().is_redirection() && to_https {
+                None
+            } else {
+                Some(finding(
+                    Severity::Medium,
+                    "Plain HTTP does not redirect to HTTPS",
+                ))
+            }
+        }
+    }
+}
+
+/// Score out of 100 and a letter grade.
+fn grade(findings: &[Finding]) -> (u32, &'static str) {
+    let penalty: u32 = findings
+        .iter()
+        .map(|f| match f.severity {
+            Severity::High => 25,
+            Severity::Medium => 10,
+            Severity::Low => 5,
+            Severity::Info => 0,
+        })
+        .sum();
+    let score = 100u32.saturating_sub(penalty);
+    let letter = match score {
+        100 => "A+",
+        90..=99 => "A",
+        80..=89 => "B",
+        70..=79 => "C",
+        60..=69 => "D",
+        _ => "F",
+    };
+    (score, letter)
+}
+
+fn finding_json(f: &Finding) -> Value {
+    json!({"severity": f.severity.to_string(), "area": f.header, "message": f.message})
+}
+
+/// Audit a URL's headers and TLS configuration.
+pub async fn run(url: &str, timeout_secs: Option<u64>, as_json: bool) -> Result<()> {
+    let parsed = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let host = parsed.host_str().context("URL has no host")?.to_string();
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(30));
+
+    let pb = spinner("Auditing...");
+    let resp = Client::builder()
+        .timeout(timeout)
+        .build()?
+        .get(parsed.clone())
+        .send()
+        .await
+        .context("Network error while sending request")?;
+    let final_url = resp.url().clone();
+    let status = resp.status();
+    let https = final_url.scheme() == "https";
+
+    let mut findings = headers::check_security(resp.headers(), https);
+    if !https {
+        findings.push(Finding {
+            severity: Severity::High,
+            header: "http".to_string(),
+            message: "Site is served over plain HTTP".to_string(),
+        });
+    }
+
+    let tls = if https {
+        let tls_host = final_url.host_str().unwrap_or(&host).to_string();
+        let port = final_url.port_or_known_default().unwrap_or(443);
+        match probe_tls(&tls_host, port, timeout).await {
+            Ok(summary) => {
+                findings.extend(tls_findings(&summary));
+                Some(summary)
+            }
+            Err(e) => {
+                findings.push(Finding {
+                    severity: Severity::High,
+                    header: "tls".to_string(),
+                    message: format!("TLS handshake failed: {e}"),
+                });
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    if parsed.scheme() == "https" && parsed.port().is_none() {
+        findings.extend(check_http_redirect(&host, timeout).await);
+    }
+    pb.finish_and_clear();
+
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    let (score, letter) = grade(&findings);
+
+    if as_json {
+        let report = json!({
+            "url": url,
+            "final_url": final_url.as_str(),
+            "status": status.as_u16(),
+            "grade": letter,
+            "score": score,
+            "tls": tls.as_ref().map(|t| json!({
+                "version": t.version,
+                "cipher": t.cipher,
+                "alpn": t.alpn,
+                "subject": t.subject,
+                "issuer": t.issuer,
+                "not_after": t.not_after,
+                "days_left": t.days_left,
+            })),
+            "findings": findings.iter().map(finding_json).collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    style::title(&format!("Security audit: {final_url}"));
+    let colored_grade = match letter {
+        "A+" | "A" => letter.green().bold().to_string(),
+        "B" | "C" => letter.yellow().bold().to_string(),
+        _ => letter.red().bold().to_string(),
+    };
+    println!("{} {colored_grade} ({score}/100)", "Grade:".bold());
+    if let Some(t) = &tls {
+        println!(
+            "{} {} · {} · ALPN {}",
+            "TLS:".bold(),
+            t.version,
+            t.cipher,
+            t.alpn.as_deref().unwrap_or("none")
+        );
+        println!(
+            "{} {} (issuer: {}, {} days left)",
+            "Certificate:".bold(),
+            t.subject,
+            t.issuer,
+            t.days_left
+        );
+    }
+    println!();
+    if findings.is_empty() {
+        style::ok("No findings");
+    } else {
+        let rows: Vec<Vec<String>> = findings
+            .iter()
+            .map(|f| vec![f.severity.colored(), f.header.clone(), f.message.clone()])
+            .collect();
+        table::print(&["Severity", "Area", "Finding"], &rows);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn f(severity: Severity) -> Finding {
+        Finding {
+            severity,
+            header: "x".into(),
+            message: "m".into(),
+        }
+    }
+
+    #[test]
+    fn test_grade() {
+        assert_eq!(grade(&[]), (100, "A+"));
+        assert_eq!(grade(&[f(Severity::Info), f(Severity::Low)]), (95, "A"));
+        assert_eq!(grade(&[f(Severity::High), f(Severity::Medium)]), (65, "D"));
+        assert_eq!(grade(&vec![f(Severity::High); 5]), (0, "F"));
+    }
+
+    #[test]
+    fn test_tls_findings() {
+        let tls = TlsSummary {
+            version: "TLS 1.2".into(),
+            alpn: Some("h2".into()),
+            days_left: 10,
+            ..Default::default()
+        };
+        let found = tls_findings(&tls);
+        assert!(found.iter().any(|f| f.severity == Severity::Low));
+        assert!(found
+            .iter()
+            .any(|f| f.severity == Severity::High && f.message.contains("10 day")));
+    }
+}
@@ -0,0 +1,300 @@
+//! `http get --url-file urls.txt --save-dir dir/`: download many URLs over one
+//! shared client (so connections are reused), `--parallel` at a time, with a
+//! progress bar per download (and at most `--rate` starts per second). Files land as `name.part` and are renamed when
+//! complete, so an interrupted run never leaves a truncated file under the real name.
+
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use log::info;
+use owo_colors::OwoColorize;
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use url::Url;
+
+use crate::cli::RequestArgs;
+use crate::commands::http_request::{build_client, request_headers, retry_policy};
+use crate::helpers::percent;
+use crate::helpers::ratelimit::RateLimiter;
+use crate::helpers::retry::{self, RetryPolicy};
+
+/// One line of the URL file: `URL [file-name]`.
+#[derive(Debug, PartialEq)]
+struct Job {
+    url: Url,
+    name: String,
+}
+
+/// Replace characters that are unsafe in file names on any platform.
+fn sanitize(name: &str) -> String {
+    let clean: String = name
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    match clean.trim_matches(['.', ' ']) {
+        "" => "download".to_string(),
+        s => s.to_string(),
+    }
+}
+
+/// File name for a URL: its last path segment, or `host.html` for a bare host.
+fn file_name(url: &Url) -> String {
+    let last = url
+        .path_segments()
+        .and_then(|mut s| s.next_back())
+        .filter(|s| !s.is_empty());
+    match last {
+        Some(seg) => {
+            let decoded = percent::decode(seg).unwrap_or_else(|| seg.to_string());
+            sanitize(&decoded)
+        }
+        None => sanitize(&format!("{}.html", url.host_str().unwrap_or("index"))),
+    }
+}
+
+/// `report.pdf` taken → `report-1.pdf`, then `report-2.pdf`, ...
+fn unique_name(name: &str, taken: &mut HashSet<String>) -> String {
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    let mut candidate = name.to_string();
+    let mut n = 1;
+    while !taken.insert(candidate.to_lowercase()) {
+        candidate = format!("{stem}-{n}{ext}");
+        n += 1;
+    }
+    candidate
+}
+
+/// Parse the URL list; blank lines and `#` comments are skipped.
+fn parse_url_file(text: &str) -> Result<Vec<Job>> {
+    let mut taken = HashSet::new();
+    let mut jobs = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let raw = parts.next().unwrap_or_default();
+        let url = Url::parse(raw).with_context(|| format!("Line {}: invalid URL: {raw}", i + 1))?;
+        let name = match parts.next() {
+            Some(n) => sanitize(n),
+            None => file_name(&url),
+        };
+        jobs.push(Job {
+            name: unique_name(&name, &mut taken),
+            url,
+        });
+    }
+    Ok(jobs)
+}
+
+fn bar_style(known_len: bool) -> ProgressStyle {
+    let template = if known_len {
+        "{prefix:30!} {bar:30.cyan/blue} {bytes}/{total_bytes} ({eta})"
+    } else {
+        "{prefix:30!} {spinner} {bytes} ({bytes_per_sec})"
+    };
+    ProgressStyle::with_template(template).expect("valid progress template")
+}
+
+/// Fetch one job into `dir`, returning the byte count.
+async fn download(
+    client: &Client,
+    headers: &HeaderMap,
+    policy: &RetryPolicy,
+    job: &Job,
+    dir: &Path,
+    pb: &ProgressBar,
+) -> Result<u64> {
+    let req = client.get(job.url.clone()).headers(headers.clone());
+    let resp = retry::send(req, policy, pb).await?;
+    let status = resp.status();
+    if !status.is_success() {
+        bail!("status {status}");
+    }
+    if let Some(len) = resp.content_length() {
+        pb.set_length(len);
+        pb.set_style(bar_style(true));
+    }
+
+    let path = dir.join(&job.name);
+    let part = dir.join(format!("{}.part", job.name));
+    let mut file = tokio::fs::File::create(&part)
+        .await
+        .with_context(|| format!("Cannot create file: {}", part.display()))?;
+    let mut stream = resp.bytes_stream();
+    let mut written = 0u64;
+    let result: Result<()> = async {
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.context("Error reading response stream")?;
+            file.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+            pb.set_position(written);
+        }
+        file.flush().await?;
+        Ok(())
+    }
+    .await;
+    drop(file);
+    if let Err(e) = result {
+        tokio::fs::remove_file(&part).await.ok();
+        return Err(e);
+    }
+    tokio::fs::rename(&part, &path)
+        .await
+        .with_context(|| format!("Cannot move download into place: {}", path.display()))?;
+    Ok(written)
+}
+
+/// Download every URL in `url_file` into `save_dir`, `parallel` at a time and
+/// at most `rate` new downloads per second.
+pub async fn run(
+    url_file: &Path,
+    parallel: usize,
+    rate: Option<f64>,
+    save_dir: PathBuf,
+    args: RequestArgs,
+) -> Result<()> {
+    if parallel == 0 {
+        bail!("--parallel must be at least 1");
+    }
+    if args.curl {
+        bail!("--curl prints a single request; it cannot be combined with --url-file");
+    }
+    if args.format.is_some() || args.copy {
+        bail!(
+            "--format and --copy take a single response; they cannot be combined with --url-file"
+        );
+    }
+    if args.max_duration.is_some() {
+        bail!("--max-duration applies to a single request; it cannot be combined with --url-file");
+    }
+    let limiter = rate.map(RateLimiter::new).transpose()?;
+    let text = std::fs::read_to_string(url_file)
+        .with_context(|| format!("Failed to read file: {}", url_file.display()))?;
+    let jobs = parse_url_file(&text)?;
+    if jobs.is_empty() {
+        bail!("No URLs in {}", url_file.display());
+    }
+    std::fs::create_dir_all(&save_dir)
+        .with_context(|| format!("Cannot create directory: {}", save_dir.display()))?;
+
+    let headers = request_headers(&args)?;
+    let urls: Vec<&Url> = jobs.iter().map(|j| &j.url).collect();
+    let (client, jar) = build_client(&args, &urls)?;
+    let policy = retry_policy(&args)?;
+    match rate {
+        Some(r) => info!(
+            "Fetching {} URL(s), {parallel} at a time, {r} per second",
+            jobs.len()
+        ),
+        None => info!("Fetching {} URL(s), {parallel} at a time", jobs.len()),
+    }
+
+    let mp = MultiProgress::new();
+    let overall = mp.add(ProgressBar::new(jobs.len() as u64));
+    overall.set_style(
+        ProgressStyle::with_template("{prefix:30!} {wide_bar:.green} {pos}/{len}")
+            .expect("valid progress template"),
+    );
+    overall.set_prefix("Total");
+
+    let results: Vec<Result<u64>> = futures::stream::iter(&jobs)
+        .map(|job| {
+            let pb = mp.insert_before(&overall, ProgressBar::new_spinner());
+            pb.set_style(bar_style(false));
+            pb.set_prefix(job.name.clone());
+            let (client, headers, policy, dir, mp, overall, limiter) = (
+                &client, &headers, &policy, &save_dir, &mp, &overall, &limiter,
+            );
+            async move {
+                if let Some(limiter) = limiter {
+                    limiter.acquire().await;
+                }
+                let result = download(client, headers, policy, job, dir, &pb).await;
+                pb.finish_and_clear();
+                mp.remove(&pb);
+                let line = match &result {
+                    Ok(bytes) => format!("{} {} ({bytes} bytes)", "✓".green(), job.name),
+                    Err(e) => format!("{} {}: {e:#}", "✗".red(), job.url),
+                };
+                // Without a terminal the bars are hidden and so is MultiProgress::println.
+                if mp.is_hidden() {
+                    println!("{line}");
+                } else {
+                    mp.println(line).ok();
+                }
+                overall.inc(1);
+                result
+            }
+        })
+        .buffer_unordered(parallel)
+        .collect()
+        .await;
+    overall.finish_and_clear();
+    if let Some(jar) = jar {
+        jar.save()?;
+    }
+
+    let failed = results.iter().filter(|r| r.is_err()).count();
+    let bytes: u64 = results.iter().filter_map(|r| r.as_ref().ok()).sum();
+    println!(
+        "{} {}/{} file(s), {bytes} bytes, into {}",
+        "Downloaded".bold(),
+        jobs.len() - failed,
+        jobs.len(),
+        save_dir.display()
+    );
+    if failed > 0 {
+        bail!("{failed} download(s) failed");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url_file_names() {
+        let jobs = parse_url_file(
+            "# mirrors\n\
+             https://ex.com/files/report%20q1.pdf\n\
+             \n\
+             https://cdn.ex.com/a/report%20q1.pdf\n\
+             https://ex.com/\n\
+             https://ex.com/data?id=1 custom:name.json\n",
+        )
+        .unwrap();
+        let names: Vec<&str> = jobs.iter().map(|j| j.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "report q1.pdf",
+                "report q1-1.pdf",
+                "ex.com.html",
+                "custom_name.json"
+            ]
+        );
+        assert!(parse_url_file("not a url").is_err());
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("..\\..\\evil"), "_.._evil");
+        assert_eq!(sanitize(".."), "download");
+        assert_eq!(file_name(&Url::parse("https://x.io/a%2Fb").unwrap()), "a_b");
+    }
+}
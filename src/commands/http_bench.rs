@@ -0,0 +1,297 @@
+//! `http bench`: fire N requests at a URL with C in flight over one shared client
+//! (so keep-alive connections are reused), optionally capped at `--rate`
+//! requests per second, then report latency percentiles, throughput, and the
+//! status-code distribution.
+
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use owo_colors::OwoColorize;
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use url::Url;
+
+use crate::cli::OutputFormat;
+use crate::commands::http_request::parse_headers;
+use crate::helpers::ratelimit::RateLimiter;
+use crate::helpers::{spinner, style, table};
+
+/// What to send, and how hard.
+pub struct BenchOptions {
+    pub method: Method,
+    pub headers: Vec<String>,
+    pub data: Option<String>,
+    pub requests: usize,
+    pub concurrency: usize,
+    pub timeout: Option<u64>,
+    /// Requests per second across all connections; unlimited when `None`.
+    pub rate: Option<f64>,
+}
+
+/// Outcome of a whole run.
+#[derive(Debug, Default)]
+struct Report {
+    /// Latencies of requests that got a response, sorted ascending.
+    latencies: Vec<Duration>,
+    statuses: BTreeMap<u16, usize>,
+    errors: BTreeMap<String, usize>,
+    bytes: u64,
+    elapsed: Duration,
+}
+
+impl Report {
+    fn failed(&self) -> usize {
+        self.errors.values().sum()
+    }
+
+    fn completed(&self) -> usize {
+        self.latencies.len()
+    }
+
+    /// Nearest-rank percentile of the sorted latencies.
+    fn percentile(&self, p: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let rank = (p / 100.0 * self.latencies.len() as f64).ceil() as usize;
+        Some(self.latencies[rank.clamp(1, self.latencies.len()) - 1])
+    }
+
+    fn mean(&self) -> Option<Duration> {
+        let n = self.latencies.len() as u32;
+        (n > 0).then(|| self.latencies.iter().sum::<Duration>() / n)
+    }
+
+    fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.completed() as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// (label, value) rows for the latency summary.
+    fn latency_rows(&self) -> Vec<(&'static str, Option<Duration>)> {
+        vec![
+            ("min", self.latencies.first().copied()),
+            ("p50", self.percentile(50.0)),
+            ("p90", self.percentile(90.0)),
+            ("p99", self.percentile(99.0)),
+            ("max", self.latencies.last().copied()),
+            ("mean", self.mean()),
+        ]
+    }
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Send `opts.requests` requests, at most `opts.concurrency` at a time.
+async fn bench(url: Url, opts: &BenchOptions, pb: &indicatif::ProgressBar) -> Result<Report> {
+    let headers = parse_headers(&opts.headers)?;
+    let client = Client::builder()
+        .timeout(Duration::from_secs(opts.timeout.unwrap_or(30)))
+        .pool_max_idle_per_host(opts.concurrency)
+        .build()?;
+    let limiter = opts.rate.map(RateLimiter::new).transpose()?;
+
+    let started = Instant::now();
+    let mut results = futures::stream::iter(0..opts.requests)
+        .map(|_| {
+            let mut req = client
+                .request(opts.method.clone(), url.clone())
+                .headers(headers.clone());
+            if let Some(d) = &opts.data {
+                req = req.body(d.clone());
+            }
+            let limiter = limiter.as_ref();
+            async move {
+                // Time spent waiting for the rate limit is not latency.
+                if let Some(limiter) = limiter {
+                    limiter.acquire().await;
+                }
+                let t = Instant::now();
+                // The body is part of the latency: a response is done when it is fully read.
+                let outcome = match req.send().await {
+                    Ok(resp) => {
+                        let status = resp.status().as_u16();
+                        resp.bytes().await.map(|b| (status, b.len() as u64))
+                    }
+                    Err(e) => Err(e),
+                };
+                (t.elapsed(), outcome)
+            }
+        })
+        .buffer_unordered(opts.concurrency);
+
+    let mut report = Report::default();
+    while let Some((latency, outcome)) = results.next().await {
+        match outcome {
+            Ok((status, bytes)) => {
+                report.latencies.push(latency);
+                *report.statuses.entry(status).or_default() += 1;
+                report.bytes += bytes;
+            }
+            Err(e) => {
+                let kind = if e.is_timeout() {
+                    "timeout".to_string()
+                } else if e.is_connect() {
+                    "connection failed".to_string()
+                } else {
+                    e.to_string()
+                };
+                *report.errors.entry(kind).or_default() += 1;
+            }
+        }
+        pb.inc(1);
+    }
+    report.elapsed = started.elapsed();
+    report.latencies.sort();
+    Ok(report)
+}
+
+fn print_report(r: &Report, opts: &BenchOptions) {
+    let rate = opts
+        .rate
+        .map(|r| format!(", limited to {r} req/s"))
+        .unwrap_or_default();
+    println!(
+        "{} {} ({} ok, {} failed) with concurrency {}{rate}",
+        "Requests:".bold(),
+        opts.requests,
+        r.completed(),
+        r.failed(),
+        opts.concurrency
+    );
+    println!("{} {:.2}s", "Duration:".bold(), r.elapsed.as_secs_f64());
+    println!("{} {:.1} req/s", "Throughput:".bold(), r.throughput());
+    println!("{} {} bytes", "Transferred:".bold(), r.bytes);
+
+    if !r.latencies.is_empty() {
+        println!();
+        let rows: Vec<Vec<String>> = r
+            .latency_rows()
+            .into_iter()
+            .filter_map(|(label, d)| Some(vec![label.to_string(), format!("{:.2} ms", ms(d?))]))
+            .collect();
+        table::print(&["Latency", "Time"], &rows);
+    }
+
+    println!();
+    let mut rows: Vec<Vec<String>> = r
+        .statuses
+        .iter()
+        .map(|(code, n)| vec![style::status_colored(*code), n.to_string()])
+        .collect();
+    rows.extend(
+        r.errors
+            .iter()
+            .map(|(kind, n)| vec![kind.red().to_string(), n.to_string()]),
+    );
+    table::print(&["Status", "Count"], &rows);
+}
+
+fn report_json(r: &Report, opts: &BenchOptions) -> Value {
+    let latency: serde_json::Map<String, Value> = r
+        .latency_rows()
+        .into_iter()
+        .map(|(label, d)| (format!("{label}_ms"), json!(d.map(ms))))
+        .collect();
+    json!({
+        "requests": opts.requests,
+        "concurrency": opts.concurrency,
+        "rate": opts.rate,
+        "completed": r.completed(),
+        "failed": r.failed(),
+        "duration_s": r.elapsed.as_secs_f64(),
+        "requests_per_s": r.throughput(),
+        "bytes": r.bytes,
+        "latency": latency,
+        "status": r.statuses.iter().map(|(k, v)| (k.to_string(), json!(v))).collect::<serde_json::Map<_, _>>(),
+        "errors": r.errors,
+    })
+}
+
+/// Benchmark `url` and print the summary; fails only when no request got a response.
+pub async fn run(url: &str, mut opts: BenchOptions, output: OutputFormat) -> Result<()> {
+    let parsed = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    if opts.requests == 0 || opts.concurrency == 0 {
+        bail!("--requests and --concurrency must be at least 1");
+    }
+    opts.concurrency = opts.concurrency.min(opts.requests);
+
+    let pb = spinner::counter(opts.requests as u64);
+    let report = bench(parsed, &opts, &pb).await?;
+    pb.finish_and_clear();
+
+    match output {
+        OutputFormat::Json => println!(
+            "{}",
+            colored_json::to_colored_json_auto(&report_json(&report, &opts))?
+        ),
+        OutputFormat::Table => print_report(&report, &opts),
+    }
+    if report.completed() == 0 {
+        bail!("All {} requests failed", opts.requests);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_percentiles() {
+        let report = Report {
+            latencies: (1..=100).map(Duration::from_millis).collect(),
+            ..Default::default()
+        };
+        assert_eq!(report.percentile(50.0), Some(Duration::from_millis(50)));
+        assert_eq!(report.percentile(99.0), Some(Duration::from_millis(99)));
+        assert_eq!(report.percentile(0.0), Some(Duration::from_millis(1)));
+        assert_eq!(report.mean(), Some(Duration::from_micros(50_500)));
+        assert_eq!(Report::default().percentile(50.0), None);
+    }
+
+    #[tokio::test]
+    async fn test_bench_counts_statuses() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut conn, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    while conn.read(&mut buf).await.unwrap_or(0) > 0 {
+                        let reply = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+                        if conn.write_all(reply).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        let opts = BenchOptions {
+            method: Method::GET,
+            headers: vec![],
+            data: None,
+            requests: 20,
+            concurrency: 4,
+            timeout: Some(5),
+            rate: None,
+        };
+        let url = Url::parse(&format!("http://{addr}/")).unwrap();
+        let report = bench(url, &opts, &indicatif::ProgressBar::hidden())
+            .await
+            .unwrap();
+        assert_eq!(report.completed(), 20);
+        assert_eq!(report.statuses.get(&200), Some(&20));
+        assert_eq!(report.bytes, 40);
+        assert_eq!(report_json(&report, &opts)["failed"], json!(0));
+    }
+}
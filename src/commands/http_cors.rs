@@ -0,0 +1,290 @@
+//! `http cors`: send a CORS preflight (OPTIONS) the way a browser would and
+//! explain whether the real cross-origin request would be allowed.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method};
+use std::time::Duration;
+use url::Url;
+
+use crate::helpers::{headers, spinner::spinner, style};
+
+/// Headers a browser may send cross-origin without a preflight.
+const SAFELISTED: [&str; 4] = [
+    "accept",
+    "accept-language",
+    "content-language",
+    "content-type",
+];
+
+/// What the caller wants to send cross-origin.
+#[derive(Debug)]
+struct Intent {
+    origin: String,
+    method: String,
+    headers: Vec<String>,
+    credentials: bool,
+}
+
+/// One evaluated rule with a human explanation.
+#[derive(Debug)]
+struct Check {
+    ok: bool,
+    message: String,
+}
+
+fn split_list(v: Option<&str>) -> Vec<String> {
+    v.unwrap_or("")
+        .split(',')
+        .map(|s| s.trim().to_ascii_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn get<'a>(h: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    h.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Evaluate a preflight response against the intended request, per the Fetch spec rules.
+fn evaluate(intent: &Intent, status: u16, h: &HeaderMap) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut check = |ok: bool, message: String| checks.push(Check { ok, message });
+
+    check(
+        (200..300).contains(&status),
+        format!("Preflight status {status} (must be 2xx)"),
+    );
+
+    match get(h, "access-control-allow-origin") {
+        None => check(false, "Access-Control-Allow-Origin is missing".into()),
+        Some("*") if intent.credentials => check(
+            false,
+            "Access-Control-Allow-Origin is * but credentials require the exact origin".into(),
+        ),
+        Some("*") => check(
+            true,
+            "Access-Control-Allow-Origin: * allows any origin".into(),
+        ),
+        Some(v) if v == intent.origin => {
+            check(true, format!("Access-Control-Allow-Origin matches {v}"))
+        }
+        Some(v) => check(
+            false,
+            format!("Access-Control-Allow-Origin is {v}, not {}", intent.origin),
+        ),
+    }
+
+    if intent.credentials {
+        let allowed = get(h, "access-control-allow-credentials") == Some("true");
+        check(
+            allowed,
+            if allowed {
+                "Access-Control-Allow-Credentials: true".into()
+            } else {
+                "Access-Control-Allow-Credentials must be 'true' for credentialed requests".into()
+            },
+        );
+    }
+
+    let method = intent.method.to_ascii_uppercase();
+    let methods = split_list(get(h, "access-control-allow-methods"));
+    let simple = matches!(method.as_str(), "GET" | "HEAD" | "POST");
+    let wildcard = methods.iter().any(|m| m == "*") && !intent.credentials;
+    let listed = methods.iter().any(|m| m.eq_ignore_ascii_case(&method));
+    check(
+        simple || listed || wildcard,
+        if simple {
+            format!("{method} is a simple method (always allowed)")
+        } else if listed || wildcard {
+            format!("{method} is listed in Access-Control-Allow-Methods")
+        } else {
+            format!(
+                "{method} is not in Access-Control-Allow-Methods ({})",
+                get(h, "access-control-allow-methods").unwrap_or("missing")
+            )
+        },
+    );
+
+    let allowed_headers = split_list(get(h, "access-control-allow-headers"));
+    let header_wildcard = allowed_headers.iter().any(|a| a == "*") && !intent.credentials;
+    for name in &intent.headers {
+        let lower = name.to_ascii_lowercase();
+        if SAFELISTED.contains(&lower.as_str()) {
+            continue;
+        }
+        // `*` never covers Authorization.
+        let ok = allowed_headers.contains(&lower) || (header_wildcard && lower != "authorization");
+        check(
+            ok,
+            if ok {
+                format!("Header {name} is allowed")
+            } else {
+                format!("Header {name} is not in Access-Control-Allow-Headers")
+            },
+        );
+    }
+
+    checks
+}
+
+/// Run the preflight and print a verdict; exits non-zero when the request would be blocked.
+pub async fn run(
+    url: &str,
+    origin: String,
+    method: String,
+    req_headers: Vec<String>,
+    credentials: bool,
+    timeout_secs: Option<u64>,
+) -> Result<()> {
+    let parsed = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let intent = Intent {
+        origin,
+        method: method.to_ascii_uppercase(),
+        headers: req_headers,
+        credentials,
+    };
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(timeout_secs.unwrap_or(30)))
+        .build()?;
+    let mut req = client
+        .request(Method::OPTIONS, parsed)
+        .header("Origin", &intent.origin)
+        .header("Access-Control-Request-Method", &intent.method);
+    if !intent.headers.is_empty() {
+        let names: Vec<String> = intent
+            .headers
+            .iter()
+            .map(|h| h.to_ascii_lowercase())
+            .collect();
+        req = req.header("Access-Control-Request-Headers", names.join(","));
+    }
+
+    let pb = spinner("Sending preflight...");
+    let resp = req
+        .send()
+        .await
+        .context("Network error while sending request")?;
+    pb.finish_and_clear();
+
+    let status = resp.status();
+    style::title(&format!(
+        "CORS preflight: {} {} from {}",
+        intent.method,
+        resp.url(),
+        intent.origin
+    ));
+    println!("{} {}", "Status:".bold(), status);
+
+    let cors_headers: Vec<(String, String)> = resp
+        .headers()
+        .iter()
+        .filter(|(k, _)| headers::group_of(k.as_str()) == headers::Group::Cors)
+        .map(|(k, v)| {
+            (
+                k.to_string(),
+                String::from_utf8_lossy(v.as_bytes()).to_string(),
+            )
+        })
+        .collect();
+    for (k, v) in &cors_headers {
+        println!("  {} {}", format!("{k}:").cyan(), v);
+    }
+    println!();
+
+    let checks = evaluate(&intent, status.as_u16(), resp.headers());
+    for c in &checks {
+        if c.ok {
+            println!("  {} {}", "✓".green(), c.message);
+        } else {
+            println!("  {} {}", "✗".red(), c.message);
+        }
+    }
+    if let Some(age) = get(resp.headers(), "access-control-max-age") {
+        println!("  {} Preflight may be cached for {age}s", "ℹ".blue());
+    }
+    println!();
+
+    if checks.iter().all(|c| c.ok) {
+        style::ok(&format!(
+            "Allowed: the browser would send the {} request",
+            intent.method
+        ));
+        Ok(())
+    } else {
+        bail!(
+            "Blocked: the browser would not send the {} request",
+            intent.method
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn intent(method: &str, headers: &[&str], credentials: bool) -> Intent {
+        Intent {
+            origin: "https://app.example.com".into(),
+            method: method.into(),
+            headers: headers.iter().map(|s| s.to_string()).collect(),
+            credentials,
+        }
+    }
+
+    fn resp(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        for (k, v) in pairs {
+            h.insert(*k, HeaderValue::from_static(v));
+        }
+        h
+    }
+
+    #[test]
+    fn test_allowed_put_with_custom_header() {
+        let h = resp(&[
+            ("access-control-allow-origin", "https://app.example.com"),
+            ("access-control-allow-methods", "GET, PUT"),
+            ("access-control-allow-headers", "X-Custom"),
+        ]);
+        let checks = evaluate(
+            &intent("PUT", &["X-Custom", "Content-Type"], false),
+            204,
+            &h,
+        );
+        assert!(checks.iter().all(|c| c.ok), "{checks:?}");
+    }
+
+    #[test]
+    fn test_wildcard_with_credentials_is_blocked() {
+        let h = resp(&[
+            ("access-control-allow-origin", "*"),
+            ("access-control-allow-methods", "*"),
+        ]);
+        let checks = evaluate(&intent("DELETE", &[], true), 200, &h);
+        assert!(checks.iter().filter(|c| !c.ok).count() >= 2);
+    }
+
+    #[test]
+    fn test_missing_method_and_header() {
+        let h = resp(&[
+            ("access-control-allow-origin", "*"),
+            ("access-control-allow-methods", "GET"),
+            ("access-control-allow-headers", "*"),
+        ]);
+        let checks = evaluate(
+            &intent("PATCH", &["Authorization", "X-Other"], false),
+            200,
+            &h,
+        );
+        let failed: Vec<&str> = checks
+            .iter()
+            .filter(|c| !c.ok)
+            .map(|c| c.message.as_str())
+            .collect();
+        assert_eq!(failed.len(), 2, "{failed:?}");
+        assert!(failed[1].contains("Authorization"));
+    }
+}
@@ -0,0 +1,870 @@
+//! curl command lines in both directions: `--curl` on the HTTP commands prints
+//! the equivalent `curl` invocation instead of sending the request, and
+//! `http import-curl` translates a pasted `curl ...` snippet (as found in API
+//! docs or a browser's "Copy as cURL") into swiftline flags and runs it.
+
+use anyhow::{bail, Context, Result};
+use atty::Stream;
+use clap::Parser;
+use owo_colors::OwoColorize;
+use reqwest::Method;
+use std::io::Read;
+use url::Url;
+
+use crate::cli::{BodyArgs, Cli, Commands, HttpCommands, RequestArgs};
+use crate::commands::http_request::{self, looks_like_json, parse_method, takes_stdin_body};
+use crate::helpers::compression;
+
+/// Quote `arg` for a POSIX shell, leaving plain words bare.
+pub fn shell_quote(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        return arg.to_string();
+    }
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// Quote each item of one option group.
+fn group(items: &[&str]) -> Vec<String> {
+    items.iter().map(|a| shell_quote(a)).collect()
+}
+
+/// Join option groups (already quoted) into one line, or one option per line when long.
+fn render(program: &str, groups: &[Vec<String>]) -> String {
+    let parts: Vec<String> = groups.iter().map(|g| g.join(" ")).collect();
+    let line = format!("{program} {}", parts.join(" "));
+    if line.len() <= 100 {
+        return line;
+    }
+    format!("{program} {}", parts.join(" \\\n  "))
+}
+
+fn note(msg: &str) {
+    eprintln!("{}", msg.yellow());
+}
+
+/// The curl equivalent of a swiftline request, as option groups (URL first).
+fn curl_args(
+    method: &Method,
+    url: &str,
+    body: Option<&BodyArgs>,
+    args: &RequestArgs,
+    env_token: Option<String>,
+) -> Result<Vec<Vec<String>>> {
+    let g = group;
+    let mut out = vec![g(&[url])];
+
+    // Body first: it decides whether curl needs an explicit -X.
+    let mut body_groups = Vec::new();
+    let mut has_body = false;
+    let mut json = false;
+    if let Some(b) = body {
+        if let Some(d) = &b.data {
+            json = looks_like_json(d.as_bytes());
+            body_groups.push(g(&["--data-raw", d]));
+        } else if let Some(path) = &b.data_file {
+            json = std::fs::read(path).is_ok_and(|data| looks_like_json(&data));
+            body_groups.push(g(&["--data-binary", &format!("@{}", path.display())]));
+        } else if !b.form_file.is_empty() {
+            for field in &b.form {
+                body_groups.push(g(&["--form-string", field]));
+            }
+            for file in &b.form_file {
+                body_groups.push(g(&["-F", file]));
+            }
+        } else if !b.form.is_empty() {
+            for field in &b.form {
+                body_groups.push(g(&["--data-urlencode", field]));
+            }
+        } else if takes_stdin_body(method) && !atty::is(Stream::Stdin) {
+            body_groups.push(g(&["--data-binary", "@-"]));
+        }
+        has_body = !body_groups.is_empty();
+    }
+
+    if *method == Method::HEAD {
+        out.push(g(&["-I"]));
+    } else if !((*method == Method::GET && !has_body) || (*method == Method::POST && has_body)) {
+        out.push(g(&["-X", method.as_str()]));
+    }
+
+    let explicit_type = args
+        .headers
+        .iter()
+        .any(|h| h.to_ascii_lowercase().starts_with("content-type:"));
+    let explicit_auth = args
+        .headers
+        .iter()
+        .any(|h| h.to_ascii_lowercase().starts_with("authorization:"));
+    for h in &args.headers {
+        out.push(g(&["-H", h]));
+    }
+    if json && !explicit_type {
+        out.push(g(&["-H", "Content-Type: application/json"]));
+    }
+    if !explicit_auth {
+        if let Some(creds) = &args.auth {
+            out.push(g(&["-u", creds]));
+        } else if let Some(token) = &args.bearer {
+            out.push(g(&["-H", &format!("Authorization: Bearer {token}")]));
+        } else if env_token.is_some_and(|t| !t.trim().is_empty()) {
+            // Double quotes let the shell expand the token, keeping it out of the output.
+            out.push(vec![
+                "-H".into(),
+                "\"Authorization: Bearer $SWIFTLINE_TOKEN\"".into(),
+            ]);
+        }
+    }
+    out.extend(body_groups);
+
+    if !args.cookie.is_empty() {
+        out.push(g(&["-b", &args.cookie.join("; ")]));
+    }
+    if let Some(jar) = &args.cookie_jar {
+        let jar = jar.display().to_string();
+        out.push(g(&["-b", &jar, "-c", &jar]));
+        if !jar.ends_with(".txt") {
+            note("curl reads only Netscape cookies.txt files; a JSON --cookie-jar will not load");
+        }
+    }
+    for r in &args.resolve {
+        out.push(g(&["--resolve", r]));
+    }
+    if let Some(path) = &args.unix_socket {
+        out.push(g(&["--unix-socket", &path.display().to_string()]));
+    }
+    if let Some(t) = args.timeout {
+        out.push(g(&["--max-time", &t.to_string()]));
+    }
+    if let Some(t) = args.connect_timeout {
+        out.push(g(&["--connect-timeout", &t.to_string()]));
+    }
+    if let Some(t) = args.read_timeout {
+        // curl has no read timeout; aborting below 1 byte/s for that long is the closest match.
+        out.push(g(&["--speed-limit", "1", "--speed-time", &t.to_string()]));
+    }
+    if !args.no_follow {
+        out.push(g(&["-L"]));
+        if args.max_redirects != 10 {
+            out.push(g(&["--max-redirs", &args.max_redirects.to_string()]));
+        }
+    }
+    if args.retry > 0 {
+        out.push(g(&["--retry", &args.retry.to_string()]));
+    }
+    if let Some(proxy) = &args.proxy {
+        out.push(g(&["-x", proxy]));
+    }
+    if args.no_proxy {
+        out.push(g(&["--noproxy", "*"]));
+    }
+    if args.insecure {
+        out.push(g(&["-k"]));
+    }
+    for (flag, path) in [
+        ("--cacert", &args.cacert),
+        ("--cert", &args.cert),
+        ("--key", &args.key),
+    ] {
+        if let Some(p) = path {
+            out.push(g(&[flag, &p.display().to_string()]));
+        }
+    }
+    if args.http1_1 {
+        out.push(g(&["--http1.1"]));
+    } else if args.http2 {
+        let cleartext = url.starts_with("http://");
+        out.push(g(&[if cleartext {
+            "--http2-prior-knowledge"
+        } else {
+            "--http2"
+        }]));
+    } else if args.http3 {
+        out.push(g(&["--http3"]));
+    }
+    if args.include {
+        out.push(g(&["-i"]));
+    }
+    if args.headers_only {
+        out.push(g(&["-D", "-", "-o", "/dev/null"]));
+    }
+    if args.ndjson {
+        out.push(g(&["-N"]));
+    }
+    if args.compressed && args.no_decompress {
+        // curl keeps the body encoded when it did not ask via --compressed.
+        out.push(g(&[
+            "-H",
+            &format!("Accept-Encoding: {}", compression::ACCEPT),
+        ]));
+    } else if args.compressed {
+        out.push(g(&["--compressed"]));
+    }
+    if let Some(path) = &args.save {
+        out.push(g(&["-o", &path.display().to_string()]));
+        if args.resume {
+            out.push(g(&["-C", "-"]));
+        }
+        if args.save_headers {
+            // curl dumps the raw header block rather than JSON.
+            out.push(g(&["-D", &format!("{}.headers", path.display())]));
+        }
+    }
+    for (set, flag) in [
+        (args.sha256.is_some(), "--sha256"),
+        (args.md5.is_some(), "--md5"),
+        (args.verify_sig.is_some(), "--verify-sig"),
+    ] {
+        if set {
+            note(&format!(
+                "curl has no equivalent of {flag}; check the file separately"
+            ));
+        }
+    }
+    Ok(out)
+}
+
+/// Print the curl command for a request instead of sending it.
+pub fn print(
+    method: &Method,
+    url: &str,
+    body: Option<&BodyArgs>,
+    args: &RequestArgs,
+) -> Result<()> {
+    Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let groups = curl_args(
+        method,
+        url,
+        body,
+        args,
+        std::env::var("SWIFTLINE_TOKEN").ok(),
+    )?;
+    println!("{}", render("curl", &groups));
+    Ok(())
+}
+
+/// Split a shell command line: '...', "...", $'...' (as in "Copy as cURL"),
+/// backslash escapes, and backslash-newline continuations.
+pub(crate) fn split_command(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(w) = word.take() {
+                    words.push(w);
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(e) => word.get_or_insert_with(String::new).push(e),
+                None => bail!("Command ends with a lone backslash"),
+            },
+            '\'' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(ch) => w.push(ch),
+                        None => bail!("Unterminated ' quote"),
+                    }
+                }
+            }
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(e @ ('"' | '\\' | '$' | '`')) => w.push(e),
+                            Some('\n') => {}
+                            Some(e) => {
+                                w.push('\\');
+                                w.push(e);
+                            }
+                            None => bail!("Unterminated \" quote"),
+                        },
+                        Some(ch) => w.push(ch),
+                        None => bail!("Unterminated \" quote"),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => w.push('\n'),
+                            Some('t') => w.push('\t'),
+                            Some('r') => w.push('\r'),
+                            Some('x') => {
+                                let hex: String = (0..2).filter_map(|_| chars.next()).collect();
+                                let byte = u8::from_str_radix(&hex, 16)
+                                    .with_context(|| format!("Bad \\x escape: \\x{hex}"))?;
+                                w.push(char::from(byte));
+                            }
+                            Some('u') => {
+                                let hex: String = (0..4).filter_map(|_| chars.next()).collect();
+                                let ch = u32::from_str_radix(&hex, 16)
+                                    .ok()
+                                    .and_then(char::from_u32)
+                                    .with_context(|| format!("Bad \\u escape: \\u{hex}"))?;
+                                w.push(ch);
+                            }
+                            Some(e) => w.push(e),
+                            None => bail!("Unterminated $' quote"),
+                        },
+                        Some(ch) => w.push(ch),
+                        None => bail!("Unterminated $' quote"),
+                    }
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// curl options that take a value, by long name, with their short letters.
+const VALUE_OPTS: &[(&str, Option<char>)] = &[
+    ("request", Some('X')),
+    ("header", Some('H')),
+    ("data", Some('d')),
+    ("data-ascii", None),
+    ("data-raw", None),
+    ("data-binary", None),
+    ("data-urlencode", None),
+    ("json", None),
+    ("form", Some('F')),
+    ("form-string", None),
+    ("user", Some('u')),
+    ("user-agent", Some('A')),
+    ("referer", Some('e')),
+    ("cookie", Some('b')),
+    ("cookie-jar", Some('c')),
+    ("proxy", Some('x')),
+    ("noproxy", None),
+    ("max-time", Some('m')),
+    ("max-redirs", None),
+    ("output", Some('o')),
+    ("continue-at", Some('C')),
+    ("range", Some('r')),
+    ("cacert", None),
+    ("cert", Some('E')),
+    ("key", None),
+    ("oauth2-bearer", None),
+    ("retry", None),
+    ("url", None),
+    ("write-out", Some('w')),
+    ("connect-timeout", None),
+    ("resolve", None),
+    ("unix-socket", None),
+    ("retry-delay", None),
+    ("retry-max-time", None),
+    ("upload-file", Some('T')),
+    ("config", Some('K')),
+];
+
+/// curl switches without a value, by long name, with their short letters.
+const FLAG_OPTS: &[(&str, Option<char>)] = &[
+    ("location", Some('L')),
+    ("location-trusted", None),
+    ("insecure", Some('k')),
+    ("include", Some('i')),
+    ("head", Some('I')),
+    ("get", Some('G')),
+    ("remote-name", Some('O')),
+    ("http1.1", None),
+    ("http2", None),
+    ("http2-prior-knowledge", None),
+    ("silent", Some('s')),
+    ("show-error", Some('S')),
+    ("verbose", Some('v')),
+    ("progress-bar", Some('#')),
+    ("no-progress-meter", None),
+    ("compressed", None),
+    ("fail", Some('f')),
+    ("fail-with-body", None),
+    ("globoff", Some('g')),
+    ("no-buffer", Some('N')),
+    ("ipv4", Some('4')),
+    ("ipv6", Some('6')),
+];
+
+/// Options accepted but dropped because swiftline's behavior already covers them or they only affect curl's output.
+const IGNORED: &[&str] = &[
+    "silent",
+    "show-error",
+    "verbose",
+    "progress-bar",
+    "no-progress-meter",
+    "globoff",
+    "no-buffer",
+];
+
+/// Expand short clusters (`-sSL`, `-XPOST`) and resolve names to curl's long options.
+fn parse_curl_opts(words: &[String]) -> Result<Vec<(String, Option<String>)>> {
+    let mut words = words.iter();
+    match words.next() {
+        Some(w) if w == "curl" || w.ends_with("/curl") || w.eq_ignore_ascii_case("curl.exe") => {}
+        _ => bail!("Expected a command starting with curl"),
+    }
+    let mut out = Vec::new();
+    while let Some(w) = words.next() {
+        if let Some(name) = w.strip_prefix("--") {
+            if VALUE_OPTS.iter().any(|(n, _)| *n == name) {
+                let value = words
+                    .next()
+                    .with_context(|| format!("curl option --{name} needs a value"))?;
+                out.push((name.to_string(), Some(value.clone())));
+            } else if FLAG_OPTS.iter().any(|(n, _)| *n == name) {
+                out.push((name.to_string(), None));
+            } else {
+                bail!("Unsupported curl option: --{name}");
+            }
+        } else if let Some(cluster) = w.strip_prefix('-').filter(|c| !c.is_empty()) {
+            let mut rest = cluster;
+            while let Some(c) = rest.chars().next() {
+                rest = &rest[c.len_utf8()..];
+                if let Some((name, _)) = VALUE_OPTS.iter().find(|(_, s)| *s == Some(c)) {
+                    let value = if rest.is_empty() {
+                        words
+                            .next()
+                            .with_context(|| format!("curl option -{c} needs a value"))?
+                            .clone()
+                    } else {
+                        std::mem::take(&mut rest).to_string()
+                    };
+                    out.push((name.to_string(), Some(value)));
+                } else if let Some((name, _)) = FLAG_OPTS.iter().find(|(_, s)| *s == Some(c)) {
+                    out.push((name.to_string(), None));
+                } else {
+                    bail!("Unsupported curl option: -{c}");
+                }
+            }
+        } else {
+            out.push(("url".to_string(), Some(w.clone())));
+        }
+    }
+    Ok(out)
+}
+
+/// A curl command as swiftline arguments (without the program name), plus notes on what was dropped.
+struct Translation {
+    argv: Vec<String>,
+    notes: Vec<String>,
+}
+
+/// `name=value` as curl's --data-urlencode sends it (only the value is encoded).
+fn data_urlencode(item: &str) -> Result<String> {
+    let enc = |s: &str| url::form_urlencoded::byte_serialize(s.as_bytes()).collect::<String>();
+    match item.split_once('=') {
+        Some((name, value)) if !name.contains('@') => Ok(if name.is_empty() {
+            enc(value)
+        } else {
+            format!("{name}={}", enc(value))
+        }),
+        None if !item.contains('@') => Ok(enc(item)),
+        _ => bail!("--data-urlencode with @file is not supported; use --data-file"),
+    }
+}
+
+fn translate(words: &[String]) -> Result<Translation> {
+    let mut method: Option<String> = None;
+    let mut url: Option<String> = None;
+    let (mut head, mut get, mut follow, mut json) = (false, false, false, false);
+    let mut data: Vec<String> = Vec::new();
+    let mut data_file: Option<String> = None;
+    let mut stdin = false;
+    let (mut fields, mut files): (Vec<String>, Vec<String>) = (Vec::new(), Vec::new());
+    let mut headers: Vec<String> = Vec::new();
+    let mut flags: Vec<String> = Vec::new();
+    let mut notes: Vec<String> = Vec::new();
+    let mut save_remote = false;
+    let mut jar: Option<String> = None;
+
+    for (name, value) in parse_curl_opts(words)? {
+        let v = value.unwrap_or_default();
+        match name.as_str() {
+            "url" if url.is_none() => url = Some(v),
+            "url" => bail!("Only one URL per command is supported (got {v})"),
+            "request" => method = Some(v.to_ascii_uppercase()),
+            "header" => headers.push(v),
+            "data" | "data-ascii" | "data-binary" | "json" => {
+                json |= name == "json";
+                match v.strip_prefix('@') {
+                    Some("-") => stdin = true,
+                    Some(_) if data_file.is_some() => bail!("Only one @file body is supported"),
+                    Some(path) => data_file = Some(path.to_string()),
+                    None => data.push(v),
+                }
+            }
+            "data-raw" => data.push(v),
+            "data-urlencode" => data.push(data_urlencode(&v)?),
+            "form" => match v.split_once('=') {
+                Some((_, value)) if value.starts_with('@') => files.push(v),
+                Some((_, value)) if value.starts_with('<') => {
+                    bail!("-F name=<file (file contents as a text field) is not supported")
+                }
+                _ => fields.push(v),
+            },
+            "form-string" => fields.push(v),
+            "user" => flags.extend(["--auth".into(), v]),
+            "oauth2-bearer" => flags.extend(["--bearer".into(), v]),
+            "user-agent" => headers.push(format!("User-Agent: {v}")),
+            "referer" => {
+                let r = v.strip_suffix(";auto").unwrap_or(&v);
+                if !r.is_empty() {
+                    headers.push(format!("Referer: {r}"));
+                }
+            }
+            "range" => headers.push(format!("Range: bytes={v}")),
+            "cookie" if v.contains('=') => {
+                for pair in v.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+                    flags.extend(["--cookie".into(), pair.to_string()]);
+                }
+            }
+            "cookie" | "cookie-jar" => match &jar {
+                Some(j) if *j != v => {
+                    bail!("Different cookie files for -b and -c are not supported")
+                }
+                _ => jar = Some(v),
+            },
+            "location" | "location-trusted" => follow = true,
+            "max-redirs" => flags.extend(["--max-redirects".into(), v]),
+            "insecure" => flags.push("-k".into()),
+            "cacert" | "key" => flags.extend([format!("--{name}"), v]),
+            "cert" => flags.extend(["--cert".into(), v]),
+            "proxy" => {
+                let proxy = if v.contains("://") {
+                    v
+                } else {
+                    format!("http://{v}")
+                };
+                flags.extend(["--proxy".into(), proxy]);
+            }
+            "noproxy" if v == "*" => flags.push("--no-proxy".into()),
+            "resolve" => flags.extend(["--resolve".into(), v]),
+            "unix-socket" => flags.extend(["--unix-socket".into(), v]),
+            "max-time" => {
+                let secs: f64 = v.parse().context("--max-time must be a number")?;
+                flags.extend(["--timeout".into(), (secs.ceil() as u64).max(1).to_string()]);
+            }
+            "connect-timeout" => {
+                let secs: f64 = v.parse().context("--connect-timeout must be a number")?;
+                flags.extend([
+                    "--connect-timeout".into(),
+                    (secs.ceil() as u64).max(1).to_string(),
+                ]);
+            }
+            "retry" => flags.extend(["--retry".into(), v]),
+            "output" => flags.extend(["--save".into(), v]),
+            "remote-name" => save_remote = true,
+            "continue-at" if v == "-" => flags.push("--resume".into()),
+            "continue-at" => bail!("-C with a fixed offset is not supported; use -C -"),
+            "include" => flags.push("-i".into()),
+            "compressed" => flags.push("--compressed".into()),
+            "head" => head = true,
+            "get" => get = true,
+            "http1.1" => flags.push("--http1.1".into()),
+            "http2" | "http2-prior-knowledge" => flags.push("--http2".into()),
+            "upload-file" | "config" => bail!("Unsupported curl option: --{name}"),
+            n if IGNORED.contains(&n) => {}
+            n => notes.push(format!("--{n} has no swiftline equivalent and was ignored")),
+        }
+    }
+
+    let mut url = url.context("No URL in the curl command")?;
+    if json {
+        headers.push("Content-Type: application/json".into());
+        headers.push("Accept: application/json".into());
+    }
+    if get && !data.is_empty() {
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str(&data.join("&"));
+        data.clear();
+    }
+    if !data.is_empty() && (data_file.is_some() || stdin) {
+        bail!("Mixing inline -d data with an @file body is not supported");
+    }
+    let raw_body = !data.is_empty() || data_file.is_some() || stdin;
+    let form_body = !fields.is_empty() || !files.is_empty();
+    if raw_body && form_body {
+        bail!("curl does not allow -d and -F together");
+    }
+    let has_body = raw_body || form_body;
+    let method = method.unwrap_or_else(|| {
+        if head {
+            "HEAD"
+        } else if has_body && !get {
+            "POST"
+        } else {
+            "GET"
+        }
+        .to_string()
+    });
+
+    let mut argv: Vec<String> = vec!["http".into()];
+    match method.as_str() {
+        "GET" if !has_body => argv.extend(["get".into(), url.clone()]),
+        "POST" => argv.extend(["post".into(), url.clone()]),
+        m => argv.extend(["request".into(), m.to_string(), url.clone()]),
+    }
+    let has_type = headers
+        .iter()
+        .any(|h| h.to_ascii_lowercase().starts_with("content-type:"));
+    for h in headers {
+        argv.extend(["-H".into(), h]);
+    }
+    if !data.is_empty() {
+        let body = data.join("&");
+        // curl labels -d bodies as a form; swiftline only labels JSON on its own.
+        if !has_type && !looks_like_json(body.as_bytes()) {
+            argv.extend([
+                "-H".into(),
+                "Content-Type: application/x-www-form-urlencoded".into(),
+            ]);
+        }
+        argv.extend(["--data".into(), body]);
+    } else if let Some(path) = data_file {
+        argv.extend(["--data-file".into(), path]);
+    } else if stdin && method != "POST" && method != "PUT" && method != "PATCH" {
+        bail!("A body from stdin (@-) is only read for POST, PUT, and PATCH");
+    }
+    if !fields.is_empty() && files.is_empty() {
+        notes.push("-F fields without a file are sent urlencoded, not as multipart".into());
+    }
+    for f in fields {
+        argv.extend(["-F".into(), f]);
+    }
+    for f in files {
+        argv.extend(["--form-file".into(), f]);
+    }
+    if let Some(j) = jar {
+        argv.extend(["--cookie-jar".into(), j]);
+    }
+    if save_remote {
+        let name = Url::parse(&url)
+            .ok()
+            .and_then(|u| u.path_segments()?.next_back().map(str::to_string))
+            .filter(|n| !n.is_empty())
+            .context("-O needs a URL ending in a file name")?;
+        argv.extend(["--save".into(), name]);
+    }
+    argv.extend(flags);
+    if !follow {
+        argv.push("--no-follow".into());
+    }
+    if method == "HEAD" && !argv.iter().any(|a| a == "-i") {
+        argv.push("-i".into());
+    }
+    Ok(Translation { argv, notes })
+}
+
+/// Translate a curl command and run it, or with `print_only` show the swiftline command.
+pub async fn run_import(command: Option<String>, print_only: bool) -> Result<()> {
+    let command = match command {
+        Some(c) => c,
+        None => {
+            if atty::is(Stream::Stdin) {
+                bail!("Pass the curl command as an argument or on stdin");
+            }
+            let mut c = String::new();
+            std::io::stdin()
+                .read_to_string(&mut c)
+                .context("Error reading curl command from stdin")?;
+            c
+        }
+    };
+    let words = split_command(command.trim())?;
+    let Translation { argv, notes } = translate(&words)?;
+    for n in &notes {
+        note(n);
+    }
+    if print_only {
+        // The subcommand with its URL, then each flag with its value.
+        let head = if argv[1] == "request" { 4 } else { 3 };
+        let mut groups = vec![group(
+            &argv[..head].iter().map(String::as_str).collect::<Vec<_>>(),
+        )];
+        let mut flags: Vec<Vec<String>> = Vec::new();
+        for a in &argv[head..] {
+            match flags.last_mut() {
+                Some(g) if g.len() == 1 && !a.starts_with('-') => g.push(shell_quote(a)),
+                _ => flags.push(group(&[a])),
+            }
+        }
+        groups.extend(flags);
+        println!("{}", render("swiftline", &groups));
+        return Ok(());
+    }
+
+    let cli = Cli::try_parse_from(std::iter::once("swiftline".to_string()).chain(argv))
+        .map_err(|e| anyhow::anyhow!("Translated command is invalid: {}", e.to_string().trim()))?;
+    match cli.command {
+        Some(Commands::Http(HttpCommands::Get {
+            url: Some(url),
+            request,
+            ..
+        })) => http_request::run(Method::GET, &url, None, request, None).await,
+        Some(Commands::Http(HttpCommands::Post { url, body, request })) => {
+            http_request::run(Method::POST, &url, Some(&body), request, None).await
+        }
+        Some(Commands::Http(HttpCommands::Request {
+            method,
+            url,
+            body,
+            request,
+        })) => http_request::run(parse_method(&method)?, &url, Some(&body), request, None).await,
+        _ => unreachable!("translate only builds http get/post/request"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(s: &str) -> Vec<String> {
+        split_command(s).unwrap()
+    }
+
+    fn argv(s: &str) -> String {
+        translate(&words(s)).unwrap().argv.join(" ")
+    }
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            words("curl -H 'A: b c' \"x\\\"y\" \\\n  --data-raw $'l1\\nl2' a\\ b"),
+            [
+                "curl",
+                "-H",
+                "A: b c",
+                "x\"y",
+                "--data-raw",
+                "l1\nl2",
+                "a b"
+            ]
+        );
+        assert!(split_command("curl 'open").is_err());
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(
+            shell_quote("https://a.test/x?y=1"),
+            "'https://a.test/x?y=1'"
+        );
+        assert_eq!(shell_quote("-H"), "-H");
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(
+            argv("curl -sSL https://api.test/users -H 'Accept: application/json'"),
+            "http get https://api.test/users -H Accept: application/json"
+        );
+        assert_eq!(
+            argv(r#"curl -XPOST https://api.test/users -d '{"name":"a"}' -u me:pw"#),
+            r#"http post https://api.test/users --data {"name":"a"} --auth me:pw --no-follow"#
+        );
+        assert_eq!(
+            argv("curl https://api.test/s -d a=1 -d b=2 --data-urlencode 'q=x y'"),
+            "http post https://api.test/s -H Content-Type: application/x-www-form-urlencoded \
+             --data a=1&b=2&q=x+y --no-follow"
+        );
+        assert_eq!(
+            argv("curl -G https://api.test/s -d q=1 -X DELETE"),
+            "http request DELETE https://api.test/s?q=1 --no-follow"
+        );
+        assert_eq!(
+            argv("curl -I https://api.test/"),
+            "http request HEAD https://api.test/ --no-follow -i"
+        );
+        assert_eq!(
+            argv("curl -F name=a -F file=@x.png https://api.test/up -O"),
+            "http post https://api.test/up -F name=a --form-file file=@x.png --save up --no-follow"
+        );
+        assert_eq!(
+            argv("curl --unix-socket /var/run/docker.sock http://localhost/_ping"),
+            "http get http://localhost/_ping --unix-socket /var/run/docker.sock --no-follow"
+        );
+        assert_eq!(
+            argv("curl --compressed -sL https://api.test/big.json"),
+            "http get https://api.test/big.json --compressed"
+        );
+        assert!(translate(&words("curl --bogus https://a.test")).is_err());
+        assert!(translate(&words("wget https://a.test")).is_err());
+    }
+
+    #[test]
+    fn test_curl_export_round_trips() {
+        let cli = Cli::try_parse_from([
+            "swiftline",
+            "http",
+            "post",
+            "https://api.test/users",
+            "-d",
+            r#"{"name":"o'neil"}"#,
+            "-H",
+            "X-Trace: 1",
+            "--bearer",
+            "tok",
+            "--timeout",
+            "5",
+            "--connect-timeout",
+            "3",
+        ])
+        .unwrap();
+        let Some(Commands::Http(HttpCommands::Post { url, body, request })) = cli.command else {
+            unreachable!()
+        };
+        let groups = curl_args(&Method::POST, &url, Some(&body), &request, None).unwrap();
+        let line = render("curl", &groups);
+        assert_eq!(
+            line,
+            [
+                "curl https://api.test/users",
+                "-H 'X-Trace: 1'",
+                "-H 'Content-Type: application/json'",
+                "-H 'Authorization: Bearer tok'",
+                r#"--data-raw '{"name":"o'\''neil"}'"#,
+                "--max-time 5",
+                "--connect-timeout 3",
+                "-L",
+            ]
+            .join(" \\\n  ")
+        );
+        // Reading it back yields the same request.
+        assert_eq!(
+            argv(&line),
+            "http post https://api.test/users -H X-Trace: 1 -H Content-Type: application/json \
+             -H Authorization: Bearer tok --data {\"name\":\"o'neil\"} --timeout 5 --connect-timeout 3"
+        );
+
+        let groups = curl_args(&Method::GET, &url, None, &request, Some("t".into())).unwrap();
+        assert!(!groups
+            .iter()
+            .flatten()
+            .any(|a| a.contains("$SWIFTLINE_TOKEN")));
+        let env_only = Cli::try_parse_from(["swiftline", "http", "get", "https://a.test"]).unwrap();
+        let Some(Commands::Http(HttpCommands::Get { request, .. })) = env_only.command else {
+            unreachable!()
+        };
+        let groups = curl_args(
+            &Method::GET,
+            "https://a.test",
+            None,
+            &request,
+            Some("t".into()),
+        );
+        assert_eq!(
+            render("curl", &groups.unwrap()),
+            r#"curl https://a.test -H "Authorization: Bearer $SWIFTLINE_TOKEN" -L"#
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! `http run`: request collections in the `.http` format of the VS Code REST
+//! Client and JetBrains HTTP client. Requests are separated by `###` lines and
+//! named by the text after `###` or a `# @name` comment; `@var = value` lines
+//! define `{{var}}` substitutions. The selected request goes through the shared
+//! executor, so every output flag of `http get` applies.
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cli::{BodyArgs, RequestArgs};
+use crate::commands::http_request::{self, parse_method};
+use crate::helpers::{table, template};
+
+/// Where a request's body comes from.
+#[derive(Debug, PartialEq)]
+enum Body {
+    None,
+    Inline(String),
+    /// `< path` sends the file as is; `<@ path` substitutes variables in it first.
+    File {
+        path: String,
+        substitute: bool,
+    },
+}
+
+#[derive(Debug)]
+struct Request {
+    name: Option<String>,
+    method: String,
+    url: String,
+    headers: Vec<String>,
+    body: Body,
+}
+
+#[derive(Debug, Default)]
+struct Collection {
+    vars: Vec<(String, String)>,
+    requests: Vec<Request>,
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with('#') || line.starts_with("//")
+}
+
+/// `@name = value` (a file variable), but not `@name` inside a comment.
+fn parse_var(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.strip_prefix('@')?.split_once('=')?;
+    let name = name.trim();
+    (!name.is_empty() && !name.contains(char::is_whitespace))
+        .then(|| (name.to_string(), value.trim().to_string()))
+}
+
+/// `METHOD URL [HTTP/1.1]`, or a bare URL meaning GET.
+fn parse_request_line(line: &str) -> (String, String) {
+    let mut words: Vec<&str> = line.split_whitespace().collect();
+    if words.len() > 1 && words.last().is_some_and(|w| w.starts_with("HTTP/")) {
+        words.pop();
+    }
+    match words.as_slice() {
+        [method, rest @ ..]
+            if !rest.is_empty() && method.chars().all(|c| c.is_ascii_uppercase()) =>
+        {
+            (method.to_string(), rest.join(" "))
+        }
+        _ => ("GET".to_string(), words.join(" ")),
+    }
+}
+
+/// One `###` block; `None` when it holds only comments and variables.
+fn parse_block(title: &str, lines: &[&str], vars: &mut Vec<(String, String)>) -> Option<Request> {
+    let mut name = (!title.is_empty()).then(|| title.to_string());
+    let mut rest = lines.iter();
+    let (method, mut url) = loop {
+        let line = rest.next()?.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if is_comment(line) {
+            let text = line.trim_start_matches(['#', '/']).trim();
+            if let Some(n) = text.strip_prefix("@name") {
+                name = Some(n.trim().trim_start_matches('=').trim().to_string());
+            }
+            continue;
+        }
+        if let Some(var) = parse_var(line) {
+            vars.push(var);
+            continue;
+        }
+        break parse_request_line(line);
+    };
+
+    let mut headers = Vec::new();
+    let mut body_lines = Vec::new();
+    let mut in_body = false;
+    for line in rest {
+        if in_body {
+            body_lines.push(*line);
+            continue;
+        }
+        let t = line.trim();
+        if t.is_empty() {
+            in_body = true;
+        } else if headers.is_empty() && (t.starts_with('?') || t.starts_with('&')) {
+            // Query parameters continued on their own lines.
+            url.push_str(t);
+        } else if !is_comment(t) {
+            headers.push(t.to_string());
+        }
+    }
+    while body_lines.last().is_some_and(|l| l.trim().is_empty()) {
+        body_lines.pop();
+    }
+    let first = body_lines.iter().position(|l| !l.trim().is_empty());
+    let body = match first {
+        None => Body::None,
+        Some(i) => {
+            let text = body_lines[i..].join("\n");
+            let t = text.trim();
+            match t.strip_prefix("<@").or_else(|| t.strip_prefix("< ")) {
+                Some(path) if !t.contains('\n') => Body::File {
+                    path: path.trim().to_string(),
+                    substitute: t.starts_with("<@"),
+                },
+                _ => Body::Inline(text),
+            }
+        }
+    };
+    Some(Request {
+        name,
+        method,
+        url,
+        headers,
+        body,
+    })
+}
+
+fn parse(text: &str) -> Collection {
+    let mut collection = Collection::default();
+    let mut title = "";
+    let mut block: Vec<&str> = Vec::new();
+    for line in text.lines().chain(std::iter::once("###")) {
+        if let Some(next) = line.trim().strip_prefix("###") {
+            let vars = &mut collection.vars;
+            collection.requests.extend(parse_block(title, &block, vars));
+            title = next.trim();
+            block.clear();
+        } else {
+            block.push(line);
+        }
+    }
+    collection
+}
+
+/// The request whose name matches, or the 1-based position in the file.
+fn select<'a>(requests: &'a [Request], wanted: &str) -> Result<&'a Request> {
+    if let Some(r) = requests.iter().find(|r| r.name.as_deref() == Some(wanted)) {
+        return Ok(r);
+    }
+    match wanted.parse::<usize>() {
+        Ok(n) if (1..=requests.len()).contains(&n) => Ok(&requests[n - 1]),
+        _ => {
+            let names: Vec<&str> = requests.iter().filter_map(|r| r.name.as_deref()).collect();
+            bail!(
+                "No request named {wanted} (available: {})",
+                names.join(", ")
+            )
+        }
+    }
+}
+
+fn list(requests: &[Request]) {
+    let rows: Vec<Vec<String>> = requests
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            vec![
+                (i + 1).to_string(),
+                r.name.clone().unwrap_or_default(),
+                r.method.clone(),
+                r.url.clone(),
+            ]
+        })
+        .collect();
+    table::print(&["#", "NAME", "METHOD", "URL"], &rows);
+}
+
+/// Run the request `name` from `file`, or list the requests when no name is
+/// given and the file holds more than one. `--var name=value` overrides file variables.
+pub async fn run(file: &Path, name: Option<&str>, mut args: RequestArgs) -> Result<()> {
+    let text = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read request file: {}", file.display()))?;
+    let mut collection = parse(&text);
+    if collection.requests.is_empty() {
+        bail!("No requests in {}", file.display());
+    }
+    let request = match name {
+        Some(n) => select(&collection.requests, n)?,
+        None if collection.requests.len() == 1 => &collection.requests[0],
+        None => {
+            list(&collection.requests);
+            return Ok(());
+        }
+    };
+    collection.vars.extend(template::parse_vars(&args.vars)?);
+    let vars = &collection.vars;
+
+    let method = parse_method(&request.method)?;
+    let url = template::substitute(&request.url, vars)?;
+    // File headers first; a -H with the same name replaces them.
+    let given: Vec<String> = args
+        .headers
+        .iter()
+        .filter_map(|h| {
+            h.split_once(':')
+                .map(|(k, _)| k.trim().to_ascii_lowercase())
+        })
+        .collect();
+    let mut headers = Vec::new();
+    for h in &request.headers {
+        let h = template::substitute(h, vars)?;
+        let key = h
+            .split_once(':')
+            .map(|(k, _)| k.trim().to_ascii_lowercase());
+        if !key.is_some_and(|k| given.contains(&k)) {
+            headers.push(h);
+        }
+    }
+    headers.append(&mut args.headers);
+    args.headers = headers;
+
+    let base = file.parent().unwrap_or(Path::new("."));
+    let data = |data, data_file| BodyArgs {
+        data,
+        data_file,
+        form: Vec::new(),
+        form_file: Vec::new(),
+    };
+    let body = match &request.body {
+        Body::None => None,
+        Body::Inline(text) => Some(data(Some(template::substitute(text, vars)?), None)),
+        Body::File {
+            path,
+            substitute: subst,
+        } => {
+            let path: PathBuf = base.join(template::substitute(path, vars)?);
+            if *subst {
+                let text = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read body file: {}", path.display()))?;
+                Some(data(Some(template::substitute(&text, vars)?), None))
+            } else {
+                Some(data(None, Some(path)))
+            }
+        }
+    };
+    info!(
+        "Running {} from {}",
+        request.name.as_deref().unwrap_or("request"),
+        file.display()
+    );
+    http_request::run(method, &url, body.as_ref(), args, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "\
+@host = https://api.example.com
+@user = 42
+
+### list users
+GET {{host}}/users
+    ?page=2
+Accept: application/json
+
+###
+# @name create
+POST {{host}}/users HTTP/1.1
+Content-Type: application/json
+// a comment, not a header
+
+{\"id\": {{user}}}
+
+### upload
+PUT {{host}}/files
+
+< ./payload.bin
+";
+
+    #[test]
+    fn test_parse_collection() {
+        let c = parse(FILE);
+        assert_eq!(c.vars.len(), 2);
+        let names: Vec<_> = c.requests.iter().map(|r| r.name.as_deref()).collect();
+        assert_eq!(names, [Some("list users"), Some("create"), Some("upload")]);
+
+        let list = &c.requests[0];
+        assert_eq!(
+            (list.method.as_str(), list.url.as_str()),
+            ("GET", "{{host}}/users?page=2")
+        );
+        assert_eq!(list.headers, ["Accept: application/json"]);
+        assert_eq!(list.body, Body::None);
+
+        let create = &c.requests[1];
+        assert_eq!(create.url, "{{host}}/users");
+        assert_eq!(create.headers, ["Content-Type: application/json"]);
+        assert_eq!(create.body, Body::Inline("{\"id\": {{user}}}".into()));
+
+        assert_eq!(
+            c.requests[2].body,
+            Body::File {
+                path: "./payload.bin".into(),
+                substitute: false
+            }
+        );
+        assert_eq!(
+            parse_request_line("https://x.test/a"),
+            ("GET".into(), "https://x.test/a".into())
+        );
+    }
+
+    #[test]
+    fn test_file_body() {
+        let c = parse("POST https://x.test/\n\n<@ ./tmpl.json\n");
+        assert_eq!(
+            c.requests[0].body,
+            Body::File {
+                path: "./tmpl.json".into(),
+                substitute: true
+            }
+        );
+        assert!(parse("# only a comment\n@a = 1\n").requests.is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+//! `http graphql`: POST a query in the standard `{"query", "variables",
+//! "operationName"}` envelope and print the response's `data` and `errors`
+//! separately: data as colored JSON on stdout, each error with its location and
+//! path on stderr. Any error fails the command, so partial results are not mistaken for success.
+
+use anyhow::{bail, Context, Result};
+use atty::Stream;
+use log::info;
+use owo_colors::OwoColorize;
+use reqwest::header::{HeaderValue, ACCEPT, CONTENT_TYPE};
+use reqwest::Method;
+use serde_json::{json, Map, Value};
+use std::io::Read;
+use std::time::Instant;
+use url::Url;
+
+use crate::cli::{BodyArgs, RequestArgs};
+use crate::commands::http_curl;
+use crate::commands::http_request::{
+    build_client, check_budget, print_status, request_headers, retry_policy, version_label,
+};
+use crate::helpers::{retry, spinner::spinner};
+
+/// Where the query text comes from.
+pub enum QuerySource {
+    Inline(String),
+    File(std::path::PathBuf),
+    Stdin,
+}
+
+fn read_query(source: QuerySource) -> Result<String> {
+    let query = match source {
+        QuerySource::Inline(q) => q,
+        QuerySource::File(path) => std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read query file: {}", path.display()))?,
+        QuerySource::Stdin => {
+            if atty::is(Stream::Stdin) {
+                bail!("Provide the query with --query, --query-file, or on stdin");
+            }
+            let mut q = String::new();
+            std::io::stdin()
+                .read_to_string(&mut q)
+                .context("Error reading query from stdin")?;
+            q
+        }
+    };
+    if query.trim().is_empty() {
+        bail!("The GraphQL query is empty");
+    }
+    Ok(query)
+}
+
+/// The request body; `variables` must be a JSON object.
+fn envelope(query: &str, variables: Option<&str>, operation: Option<&str>) -> Result<Value> {
+    let mut body = Map::new();
+    body.insert("query".into(), json!(query));
+    if let Some(vars) = variables {
+        let vars: Value = serde_json::from_str(vars).context("--variables is not valid JSON")?;
+        if !vars.is_object() {
+            bail!("--variables must be a JSON object, e.g. '{{\"id\": 1}}'");
+        }
+        body.insert("variables".into(), vars);
+    }
+    if let Some(op) = operation {
+        body.insert("operationName".into(), json!(op));
+    }
+    Ok(Value::Object(body))
+}
+
+/// One error as `message (line 2:5) at user.posts.0`, plus extensions if any.
+fn format_error(err: &Value) -> String {
+    let mut out = err["message"]
+        .as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| err.to_string());
+    let locations: Vec<String> = err["locations"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| Some(format!("{}:{}", l["line"].as_u64()?, l["column"].as_u64()?)))
+        .collect();
+    if !locations.is_empty() {
+        out.push_str(&format!(" (line {})", locations.join(", ")));
+    }
+    if let Some(path) = err["path"].as_array() {
+        let path: Vec<String> = path
+            .iter()
+            .map(|p| p.as_str().map_or_else(|| p.to_string(), str::to_string))
+            .collect();
+        out.push_str(&format!(" at {}", path.join(".")));
+    }
+    if let Some(ext) = err.get("extensions").filter(|e| !e.is_null()) {
+        out.push_str(&format!(" {ext}"));
+    }
+    out
+}
+
+/// Send the query and print `data` / `errors`.
+pub async fn run(
+    url: &str,
+    query: QuerySource,
+    variables: Option<&str>,
+    operation: Option<&str>,
+    args: RequestArgs,
+) -> Result<()> {
+    if args.save.is_some() || args.headers_only || args.format.is_some() || args.copy {
+        bail!("--save, --headers-only, --format, and --copy are not supported by http graphql");
+    }
+    let parsed = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let body = envelope(&read_query(query)?, variables, operation)?;
+    if args.curl {
+        let body = BodyArgs {
+            data: Some(body.to_string()),
+            data_file: None,
+            form: Vec::new(),
+            form_file: Vec::new(),
+        };
+        return http_curl::print(&Method::POST, url, Some(&body), &args);
+    }
+
+    let mut hdrs = request_headers(&args)?;
+    if !hdrs.contains_key(CONTENT_TYPE) {
+        hdrs.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    }
+    if !hdrs.contains_key(ACCEPT) {
+        hdrs.insert(
+            ACCEPT,
+            HeaderValue::from_static("application/graphql-response+json, application/json"),
+        );
+    }
+    let (client, jar) = build_client(&args, &[&parsed])?;
+    let req = client
+        .request(Method::POST, parsed.clone())
+        .headers(hdrs)
+        .body(serde_json::to_vec(&body)?);
+    info!("POST {parsed} (GraphQL)");
+
+    let pb = spinner("Querying...");
+    let started = Instant::now();
+    let resp = retry::send(req, &retry_policy(&args)?, &pb).await?;
+    pb.finish_and_clear();
+    if let Some(jar) = jar {
+        jar.save()?;
+    }
+
+    let status = resp.status();
+    let https = parsed.scheme() == "https";
+    let resp_hdrs = resp.headers().clone();
+    print_status(
+        status,
+        version_label(resp.version()),
+        &resp_hdrs,
+        args.headers_table.then_some((&resp_hdrs, https)),
+        args.include,
+    );
+    let text = resp.text().await.context("Failed to read response body")?;
+    let Ok(reply) = serde_json::from_str::<Value>(&text) else {
+        println!("{text}");
+        bail!("Response is not GraphQL JSON ({status})");
+    };
+
+    let errors = reply["errors"].as_array().cloned().unwrap_or_default();
+    if !errors.is_empty() {
+        eprintln!("{}", format!("Errors ({}):", errors.len()).red().bold());
+        for err in &errors {
+            eprintln!("  {} {}", "•".red(), format_error(err));
+        }
+    }
+    match reply.get("data") {
+        Some(data) if !data.is_null() => {
+            println!("{}", "Data:".bold());
+            println!("{}", colored_json::to_colored_json_auto(data)?);
+        }
+        _ if errors.is_empty() => println!("{}", colored_json::to_colored_json_auto(&reply)?),
+        _ => {}
+    }
+
+    if !errors.is_empty() {
+        bail!("GraphQL returned {} error(s)", errors.len());
+    }
+    if !status.is_success() {
+        bail!("Request failed with status {status}");
+    }
+    check_budget(started, args.max_duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope() {
+        let body = envelope("{ me { id } }", Some(r#"{"id":1}"#), Some("Me")).unwrap();
+        assert_eq!(
+            body,
+            json!({"query": "{ me { id } }", "variables": {"id": 1}, "operationName": "Me"})
+        );
+        assert_eq!(
+            envelope("{ a }", None, None).unwrap(),
+            json!({"query": "{ a }"})
+        );
+        assert!(envelope("{ a }", Some("[1]"), None).is_err());
+        assert!(envelope("{ a }", Some("{id:"), None).is_err());
+    }
+
+    #[test]
+    fn test_format_error() {
+        let err = json!({
+            "message": "Not authorized",
+            "locations": [{"line": 2, "column": 5}],
+            "path": ["user", "posts", 0],
+            "extensions": {"code": "FORBIDDEN"}
+        });
+        assert_eq!(
+            format_error(&err),
+            r#"Not authorized (line 2:5) at user.posts.0 {"code":"FORBIDDEN"}"#
+        );
+        assert_eq!(format_error(&json!({"message": "boom"})), "boom");
+    }
+}
@@ -0,0 +1,418 @@
+//! `http listen`: receive webhooks locally and print each request (method,
+//! path, headers, pretty JSON body). With `--forward`, every request is also
+//! relayed to another URL with its body byte-for-byte and its headers intact,
+//! so signatures such as `X-Hub-Signature-256` or `Stripe-Signature` still
+//! verify; the upstream answer is passed back to the sender. Otherwise the
+//! sender gets a canned reply (`--respond-status`, `--respond-body`), optionally
+//! after `--respond-delay`, to emulate a failing or slow receiver. `--tunnel`
+//! gives the listener a public URL (see `helpers::tunnel`).
+
+use anyhow::{bail, Context, Result};
+use indicatif::ProgressBar;
+use owo_colors::OwoColorize;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, Method, StatusCode};
+use serde_json::Value;
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+use crate::commands::http_request::looks_like_json;
+use crate::commands::http_serve::{read_head, write_head, Head};
+use crate::helpers::retry::{self, RetryPolicy};
+use crate::helpers::spinner::spinner;
+use crate::helpers::tunnel::Provider;
+use crate::helpers::{chunked, config};
+
+/// Largest request body accepted.
+const MAX_BODY: usize = 10 * 1024 * 1024;
+
+/// Headers that describe one connection and must not be relayed.
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+    "content-length",
+];
+
+/// One received request.
+#[derive(Debug)]
+struct Received {
+    head: Head,
+    body: Vec<u8>,
+}
+
+/// Read one request: head, then a Content-Length or chunked body.
+async fn read_request(stream: &mut TcpStream) -> Result<Option<Received>> {
+    let Some((head, mut rest)) = read_head(stream).await? else {
+        return Ok(None);
+    };
+    let mut req = Received {
+        head,
+        body: Vec::new(),
+    };
+    let mut chunk = [0u8; 8192];
+
+    let chunked = req
+        .head
+        .header("transfer-encoding")
+        .is_some_and(|te| te.to_ascii_lowercase().contains("chunked"));
+    if chunked {
+        req.body = loop {
+            if let Some(body) = chunked::decode_partial(&rest, MAX_BODY)? {
+                break body;
+            }
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                bail!("connection closed mid-body");
+            }
+            rest.extend_from_slice(&chunk[..n]);
+        };
+    } else {
+        let len: usize = match req.head.header("content-length") {
+            Some(v) => v.parse().context("bad Content-Length")?,
+            None => 0,
+        };
+        if len > MAX_BODY {
+            bail!("request body too large");
+        }
+        while rest.len() < len {
+            let n = stream.read(&mut chunk).await?;
+            if n == 0 {
+                bail!("connection closed mid-body");
+            }
+            rest.extend_from_slice(&chunk[..n]);
+        }
+        rest.truncate(len);
+        req.body = rest;
+    }
+    Ok(Some(req))
+}
+
+/// The request's end-to-end headers, ready to relay unchanged.
+fn forward_headers(headers: &[(String, String)]) -> HeaderMap {
+    let mut out = HeaderMap::new();
+    for (name, value) in headers {
+        if HOP_BY_HOP.contains(&name.to_ascii_lowercase().as_str()) {
+            continue;
+        }
+        if let (Ok(n), Ok(v)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            out.append(n, v);
+        }
+    }
+    out
+}
+
+/// The request as printed: numbered request line, headers, then the body
+/// (pretty JSON, text, or a byte count for binary payloads).
+fn describe(n: usize, peer: SocketAddr, req: &Received) -> Result<String> {
+    let mut out = format!(
+        "{} {} {} {}\n",
+        format!("#{n}").dimmed(),
+        req.head.method.bold(),
+        req.head.target,
+        peer.ip().dimmed()
+    );
+    for (name, value) in &req.head.headers {
+        out.push_str(&format!("{}: {value}\n", name.cyan()));
+    }
+    if !req.body.is_empty() {
+        out.push('\n');
+        match serde_json::from_slice::<Value>(&req.body) {
+            Ok(v) => out.push_str(&colored_json::to_colored_json_auto(&v)?),
+            Err(_) => match std::str::from_utf8(&req.body) {
+                Ok(text) => out.push_str(text.trim_end()),
+                Err(_) => out.push_str(&format!("({} bytes of binary data)", req.body.len())),
+            },
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// The reply flags as given on the command line.
+pub struct Stub {
+    pub status: Option<u16>,
+    pub body: Option<String>,
+    pub delay: Option<Duration>,
+}
+
+/// The canned answer sent when not forwarding.
+#[derive(Debug)]
+struct Reply {
+    status: StatusCode,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Reply {
+    /// Resolve the flags: `@path` bodies are read once up front, and the default
+    /// body is `ok` for a 200 or the status line otherwise.
+    fn new(stub: &Stub) -> Result<Reply> {
+        let status = match stub.status {
+            Some(code) => StatusCode::from_u16(code)
+                .ok()
+                .filter(|s| !s.is_informational())
+                .with_context(|| format!("Invalid --respond-status: {code}"))?,
+            None => StatusCode::OK,
+        };
+        let body = match stub.body.as_deref() {
+            Some(arg) => match arg.strip_prefix('@') {
+                Some(path) => {
+                    fs::read(path).with_context(|| format!("Failed to read file: {path}"))?
+                }
+                None => arg.as_bytes().to_vec(),
+            },
+            None if status == StatusCode::OK => b"ok\n".to_vec(),
+            None => format!("{status}\n").into_bytes(),
+        };
+        let content_type = if looks_like_json(&body) {
+            "application/json"
+        } else {
+            "text/plain; charset=utf-8"
+        };
+        Ok(Reply {
+            status,
+            content_type,
+            body,
+        })
+    }
+}
+
+/// Where and how requests are relayed.
+struct Forward {
+    client: Client,
+    url: Url,
+    policy: RetryPolicy,
+}
+
+/// Relay `req` to the forward URL; returns the upstream status, Content-Type, and body.
+async fn relay(fwd: &Forward, req: &Received) -> Result<(StatusCode, Option<String>, Vec<u8>)> {
+    let method = Method::from_bytes(req.head.method.as_bytes()).context("invalid method")?;
+    let builder = fwd
+        .client
+        .request(method, fwd.url.clone())
+        .headers(forward_headers(&req.head.headers))
+        .body(req.body.clone());
+    let resp = retry::send(builder, &fwd.policy, &ProgressBar::hidden()).await?;
+    let status = resp.status();
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body = resp.bytes().await?.to_vec();
+    Ok((status, content_type, body))
+}
+
+/// Receive, print, and (optionally) relay one request, then answer the sender.
+async fn handle(
+    stream: &mut TcpStream,
+    peer: SocketAddr,
+    n: usize,
+    forward: Option<&Forward>,
+    reply: &Reply,
+    delay: Option<Duration>,
+) -> Result<()> {
+    let Some(req) = read_request(stream).await? else {
+        return Ok(());
+    };
+    let mut shown = describe(n, peer, &req)?;
+
+    let (status, content_type, body) = match forward {
+        Some(fwd) => {
+            let start = Instant::now();
+            match relay(fwd, &req).await {
+                Ok((status, ct, body)) => {
+                    let code = if status.is_success() {
+                        status.to_string().green().to_string()
+                    } else {
+                        status.to_string().red().to_string()
+                    };
+                    shown.push_str(&format!(
+                        "{} {} {code} {}\n",
+                        "→".dimmed(),
+                        fwd.url,
+                        format!("({} ms)", start.elapsed().as_millis()).dimmed()
+                    ));
+                    (status, ct, body)
+                }
+                Err(e) => {
+                    shown.push_str(&format!(
+                        "{} {}\n",
+                        "→".dimmed(),
+                        format!("forward failed: {e:#}").red()
+                    ));
+                    (StatusCode::BAD_GATEWAY, None, b"forward failed\n".to_vec())
+                }
+            }
+        }
+        None => (
+            reply.status,
+            Some(reply.content_type.to_string()),
+            reply.body.clone(),
+        ),
+    };
+    // One print per request keeps concurrent deliveries from interleaving.
+    println!("{shown}");
+
+    if let Some(delay) = delay {
+        tokio::time::sleep(delay).await;
+    }
+    let content_type = content_type.unwrap_or_else(|| "text/plain; charset=utf-8".into());
+    let headers = [
+        ("Content-Type", content_type),
+        ("Content-Length", body.len().to_string()),
+    ];
+    write_head(stream, status, &headers).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await.ok();
+    Ok(())
+}
+
+/// Listen on `bind:port` until interrupted, relaying to `forward` when given.
+pub async fn run(
+    port: u16,
+    bind: IpAddr,
+    forward: Option<String>,
+    retries: u32,
+    timeout: Option<u64>,
+    stub: Stub,
+    tunnel: Option<String>,
+) -> Result<()> {
+    let provider = match &tunnel {
+        Some(name) => Some(Provider::resolve(name, &config::load()?)?),
+        None => None,
+    };
+    let reply = Arc::new(Reply::new(&stub)?);
+    let delay = stub.delay;
+    let forward = match forward {
+        Some(url) => {
+            let url = Url::parse(&url).with_context(|| format!("Invalid --forward URL: {url}"))?;
+            let client = Client::builder()
+                .user_agent(concat!("swiftline/", env!("CARGO_PKG_VERSION")))
+                .timeout(Duration::from_secs(timeout.unwrap_or(30)))
+                // Pass redirects back to the sender rather than re-POSTing elsewhere.
+                .redirect(reqwest::redirect::Policy::none())
+                .build()?;
+            let policy = RetryPolicy {
+                retries,
+                base: Duration::from_secs(1),
+                statuses: vec![502, 503, 504],
+            };
+            Some(Arc::new(Forward {
+                client,
+                url,
+                policy,
+            }))
+        }
+        None => None,
+    };
+
+    let addr = SocketAddr::new(bind, port);
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Cannot listen on {addr}"))?;
+    let local = listener.local_addr()?;
+    let shown = if bind.is_unspecified() {
+        format!("http://localhost:{}/", local.port())
+    } else {
+        format!("http://{local}/")
+    };
+    let mut relay = match &forward {
+        Some(f) => format!(", forwarding to {}", f.url),
+        None if reply.status != StatusCode::OK => format!(", answering {}", reply.status),
+        None => String::new(),
+    };
+    if let Some(d) = delay {
+        relay.push_str(&format!(" after {d:?}"));
+    }
+    // Held for the whole session: dropping it stops the provider.
+    let tunnel = match &provider {
+        Some(p) => {
+            let pb = spinner(&format!("Opening {} tunnel...", p.name));
+            let tunnel = p.open(local.port()).await;
+            pb.finish_and_clear();
+            Some(tunnel?)
+        }
+        None => None,
+    };
+    println!(
+        "{} at {}{relay}  {}",
+        "Listening".bold(),
+        shown.cyan(),
+        "(Ctrl-C to stop)".dimmed()
+    );
+    if let Some(t) = &tunnel {
+        println!("{} {}", "Public URL:".bold(), t.url.cyan());
+    }
+    println!();
+
+    let counter = Arc::new(AtomicUsize::new(0));
+    loop {
+        let (mut stream, peer) = listener.accept().await?;
+        let forward = forward.clone();
+        let counter = counter.clone();
+        let reply = reply.clone();
+        tokio::spawn(async move {
+            let n = counter.fetch_add(1, Ordering::Relaxed) + 1;
+            if let Err(e) = handle(&mut stream, peer, n, forward.as_deref(), &reply, delay).await {
+                log::debug!("{peer}: {e:#}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::http_serve::parse_head;
+
+    #[test]
+    fn test_forward_headers() {
+        let head = "POST /hook?x=1 HTTP/1.1\r\nHost: localhost:9000\r\nContent-Type: application/json\r\nX-Hub-Signature-256: sha256=ab\r\nConnection: keep-alive\r\nContent-Length: 2";
+        let req = parse_head(head).unwrap();
+        let fwd = forward_headers(&req.headers);
+        assert_eq!(fwd.get("x-hub-signature-256").unwrap(), "sha256=ab");
+        assert_eq!(fwd.get("content-type").unwrap(), "application/json");
+        for gone in ["host", "connection", "content-length"] {
+            assert!(fwd.get(gone).is_none(), "{gone}");
+        }
+    }
+
+    #[test]
+    fn test_reply() {
+        let stub = |status, body: Option<&str>| Stub {
+            status,
+            body: body.map(str::to_string),
+            delay: None,
+        };
+        let ok = Reply::new(&stub(None, None)).unwrap();
+        assert_eq!(
+            (ok.status, ok.body.as_slice()),
+            (StatusCode::OK, &b"ok\n"[..])
+        );
+        let failing = Reply::new(&stub(Some(503), None)).unwrap();
+        assert_eq!(failing.body, b"503 Service Unavailable\n");
+        let json = Reply::new(&stub(Some(201), Some(r#"{"id":1}"#))).unwrap();
+        assert_eq!(json.content_type, "application/json");
+        assert!(Reply::new(&stub(Some(99), None)).is_err());
+        assert!(Reply::new(&stub(Some(101), None)).is_err());
+        assert!(Reply::new(&stub(None, Some("@/nonexistent/ok.json"))).is_err());
+    }
+}
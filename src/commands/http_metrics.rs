@@ -0,0 +1,386 @@
+//! `http metrics`: scrape a Prometheus text-format endpoint, select series with a
+//! PromQL-style selector (`name{label="v",other=~"re"}`), and optionally aggregate
+//! them (`--agg sum --by code`).
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use url::Url;
+
+use crate::cli::{MetricsAgg, OutputFormat};
+use crate::helpers::{spinner::spinner, style, table};
+
+/// One sample line: metric name, labels (in exposition order), and value.
+#[derive(Debug, Clone, PartialEq)]
+struct Sample {
+    name: String,
+    labels: Vec<(String, String)>,
+    value: f64,
+}
+
+impl Sample {
+    fn label(&self, key: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// `name{a="1",b="2"}` as Prometheus prints it.
+    fn series(&self) -> String {
+        if self.labels.is_empty() {
+            return self.name.clone();
+        }
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .map(|(k, v)| format!("{k}=\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect();
+        format!("{}{{{}}}", self.name, labels.join(","))
+    }
+}
+
+/// Parse a `{k="v",...}` label block starting right after `{`; returns labels and the rest.
+fn parse_labels(s: &str) -> Result<(Vec<(String, String)>, &str)> {
+    let mut labels = Vec::new();
+    let mut rest = s.trim_start();
+    loop {
+        if let Some(r) = rest.strip_prefix('}') {
+            return Ok((labels, r));
+        }
+        let eq = rest.find('=').context("Label without '='")?;
+        let key = rest[..eq].trim().to_string();
+        rest = rest[eq + 1..].trim_start();
+        let mut chars = rest.char_indices();
+        if chars.next().map(|(_, c)| c) != Some('"') {
+            bail!("Label value for {key} must be quoted");
+        }
+        let mut value = String::new();
+        let mut end = None;
+        let mut escaped = false;
+        for (i, c) in chars {
+            match (escaped, c) {
+                (true, 'n') => value.push('\n'),
+                (true, c) => value.push(c),
+                (false, '\\') => {
+                    escaped = true;
+                    continue;
+                }
+                (false, '"') => {
+                    end = Some(i);
+                    break;
+                }
+                (false, c) => value.push(c),
+            }
+            escaped = false;
+        }
+        let end = end.with_context(|| format!("Unterminated value for label {key}"))?;
+        labels.push((key, value));
+        rest = rest[end + 1..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+}
+
+fn parse_value(s: &str) -> Option<f64> {
+    match s {
+        "+Inf" | "Inf" => Some(f64::INFINITY),
+        "-Inf" => Some(f64::NEG_INFINITY),
+        "NaN" => Some(f64::NAN),
+        _ => s.parse().ok(),
+    }
+}
+
+/// Parse the Prometheus text exposition format (comments and blank lines skipped).
+fn parse_exposition(text: &str) -> Result<Vec<Sample>> {
+    let mut out = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let name_end = line.find(['{', ' ', '\t']).unwrap_or(line.len());
+        let name = line[..name_end].to_string();
+        let (labels, rest) = match line[name_end..].strip_prefix('{') {
+            Some(r) => parse_labels(r).with_context(|| format!("Line {}: {line}", n + 1))?,
+            None => (Vec::new(), &line[name_end..]),
+        };
+        let value = rest
+            .split_whitespace()
+            .next()
+            .and_then(parse_value)
+            .with_context(|| format!("Line {}: missing or invalid value: {line}", n + 1))?;
+        out.push(Sample {
+            name,
+            labels,
+            value,
+        });
+    }
+    Ok(out)
+}
+
+/// One label matcher of a selector.
+#[derive(Debug)]
+enum Matcher {
+    Eq(String, String),
+    Ne(String, String),
+    Re(String, Regex),
+    NotRe(String, Regex),
+}
+
+/// A series selector: optional metric name plus label matchers.
+#[derive(Debug, Default)]
+struct Selector {
+    name: Option<String>,
+    matchers: Vec<Matcher>,
+}
+
+impl Selector {
+    fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let brace = s.find('{').unwrap_or(s.len());
+        let name = s[..brace].trim();
+        let mut sel = Selector {
+            name: (!name.is_empty()).then(|| name.to_string()),
+            matchers: Vec::new(),
+        };
+        if brace == s.len() {
+            return Ok(sel);
+        }
+        let body = s[brace + 1..]
+            .strip_suffix('}')
+            .with_context(|| format!("Selector is missing a closing '}}': {s}"))?;
+        for part in split_matchers(body) {
+            let part = part.trim();
+            if part.is_empty() {
+                continue;
+            }
+            // The operator is the first of these after the label name.
+            let at = part
+                .find(['=', '!'])
+                .with_context(|| format!("Invalid matcher: {part}"))?;
+            let op = ["!~", "=~", "!=", "="]
+                .into_iter()
+                .find(|op| part[at..].starts_with(op))
+                .with_context(|| format!("Invalid matcher: {part}"))?;
+            let key = part[..at].trim().to_string();
+            let value = part[at + op.len()..].trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .with_context(|| format!("Matcher value must be quoted: {part}"))?
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\");
+            let anchored = || Regex::new(&format!("^(?:{value})$"));
+            sel.matchers.push(match op {
+                "=" => Matcher::Eq(key, value),
+                "!=" => Matcher::Ne(key, value),
+                "=~" => Matcher::Re(key, anchored()?),
+                _ => Matcher::NotRe(key, anchored()?),
+            });
+        }
+        Ok(sel)
+    }
+
+    fn matches(&self, s: &Sample) -> bool {
+        if self.name.as_deref().is_some_and(|n| n != s.name) {
+            return false;
+        }
+        // Missing labels compare as the empty string, like Prometheus.
+        self.matchers.iter().all(|m| match m {
+            Matcher::Eq(k, v) => s.label(k).unwrap_or("") == v,
+            Matcher::Ne(k, v) => s.label(k).unwrap_or("") != v,
+            Matcher::Re(k, re) => re.is_match(s.label(k).unwrap_or("")),
+            Matcher::NotRe(k, re) => !re.is_match(s.label(k).unwrap_or("")),
+        })
+    }
+}
+
+/// Split matchers on commas that are outside quoted values.
+fn split_matchers(body: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => {
+                parts.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&body[start..]);
+    parts
+}
+
+/// Group samples by the `by` labels and fold each group with `agg`.
+fn aggregate(samples: &[Sample], agg: MetricsAgg, by: &[String]) -> Vec<Sample> {
+    let mut groups: BTreeMap<Vec<(String, String)>, Vec<f64>> = BTreeMap::new();
+    for s in samples {
+        let key = by
+            .iter()
+            .map(|l| (l.clone(), s.label(l).unwrap_or("").to_string()))
+            .collect();
+        groups.entry(key).or_default().push(s.value);
+    }
+    groups
+        .into_iter()
+        .map(|(labels, values)| {
+            let value = match agg {
+                MetricsAgg::Sum => values.iter().sum(),
+                MetricsAgg::Avg => values.iter().sum::<f64>() / values.len() as f64,
+                MetricsAgg::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+                MetricsAgg::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                MetricsAgg::Count => values.len() as f64,
+            };
+            Sample {
+                name: format!("{agg:?}").to_lowercase(),
+                labels,
+                value,
+            }
+        })
+        .collect()
+}
+
+fn number(v: f64) -> Value {
+    serde_json::Number::from_f64(v)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::String(v.to_string()))
+}
+
+/// Scrape `url`, filter series with `filter`, optionally aggregate, and print.
+pub async fn run(
+    url: &str,
+    filter: Option<String>,
+    agg: Option<MetricsAgg>,
+    by: Vec<String>,
+    output: OutputFormat,
+    timeout_secs: Option<u64>,
+) -> Result<()> {
+    let parsed = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let selector = match &filter {
+        Some(f) => Selector::parse(f)?,
+        None => Selector::default(),
+    };
+    let client = Client::builder()
+        .timeout(Duration::from_secs(timeout_secs.unwrap_or(30)))
+        .build()?;
+
+    let pb = spinner("Scraping...");
+    let resp = client
+        .get(parsed)
+        .header("Accept", "text/plain;version=0.0.4")
+        .send()
+        .await
+        .context("Network error while sending request")?;
+    let status = resp.status();
+    let text = resp.text().await?;
+    pb.finish_and_clear();
+    if !status.is_success() {
+        bail!("Scrape failed with status {status}");
+    }
+
+    let mut samples: Vec<Sample> = parse_exposition(&text)?
+        .into_iter()
+        .filter(|s| selector.matches(s))
+        .collect();
+    if let Some(agg) = agg {
+        samples = aggregate(&samples, agg, &by);
+    } else if !by.is_empty() {
+        bail!("--by requires --agg");
+    }
+
+    match output {
+        OutputFormat::Json => {
+            let rows: Vec<Value> = samples
+                .iter()
+                .map(|s| {
+                    let labels: serde_json::Map<String, Value> = s
+                        .labels
+                        .iter()
+                        .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+                        .collect();
+                    json!({"name": s.name, "labels": labels, "value": number(s.value)})
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+        }
+        OutputFormat::Table => {
+            if samples.is_empty() {
+                style::warn_line("No matching series");
+                return Ok(());
+            }
+            let rows: Vec<Vec<String>> = samples
+                .iter()
+                .map(|s| vec![s.series(), s.value.to_string()])
+                .collect();
+            table::print(&["Series", "Value"], &rows);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"
+# HELP http_requests_total Total requests.
+# TYPE http_requests_total counter
+http_requests_total{method="get",code="200"} 1027 1395066363000
+http_requests_total{method="post",code="200"} 3
+http_requests_total{method="get",code="500"} 7
+msg{text="a \"quoted\", value"} 1
+up 1
+latency_bucket{le="+Inf"} +Inf
+"#;
+
+    #[test]
+    fn test_parse_exposition() {
+        let s = parse_exposition(TEXT).unwrap();
+        assert_eq!(s.len(), 6);
+        assert_eq!(s[0].label("code"), Some("200"));
+        assert_eq!(s[0].value, 1027.0);
+        assert_eq!(s[3].label("text"), Some("a \"quoted\", value"));
+        assert_eq!(s[4].name, "up");
+        assert!(s[5].value.is_infinite());
+        assert_eq!(
+            s[1].series(),
+            r#"http_requests_total{method="post",code="200"}"#
+        );
+    }
+
+    #[test]
+    fn test_selector_matchers() {
+        let samples = parse_exposition(TEXT).unwrap();
+        let count = |sel: &str| {
+            let sel = Selector::parse(sel).unwrap();
+            samples.iter().filter(|s| sel.matches(s)).count()
+        };
+        assert_eq!(count(r#"http_requests_total{code="500"}"#), 1);
+        assert_eq!(count(r#"http_requests_total{code!="500"}"#), 2);
+        assert_eq!(count(r#"http_requests_total{code=~"2.."}"#), 2);
+        assert_eq!(count(r#"{method!~"g.*"}"#), 4);
+        assert_eq!(count(r#"msg{text="a \"quoted\", value"}"#), 1);
+        assert_eq!(count("up"), 1);
+        assert_eq!(count(r#"{code=""}"#), 3);
+    }
+
+    #[test]
+    fn test_aggregate_by_label() {
+        let samples = parse_exposition(TEXT).unwrap();
+        let sel = Selector::parse("http_requests_total").unwrap();
+        let picked: Vec<Sample> = samples.into_iter().filter(|s| sel.matches(s)).collect();
+        let out = aggregate(&picked, MetricsAgg::Sum, &["code".to_string()]);
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].series(), r#"sum{code="200"}"#);
+        assert_eq!(out[0].value, 1030.0);
+        let total = aggregate(&picked, MetricsAgg::Count, &[]);
+        assert_eq!(total[0].value, 3.0);
+    }
+}
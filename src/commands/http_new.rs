@@ -0,0 +1,349 @@
+//! `http new`: build a request step by step at a prompt (method, URL, headers,
+//! auth, body), checking each answer before moving on, then send it through the
+//! shared executor and offer to append it to a `.http` file for `http run`.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use owo_colors::OwoColorize;
+use reqwest::Method;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use crate::cli::{BodyArgs, RequestArgs};
+use crate::commands::http_request::{self, parse_headers, parse_method, takes_stdin_body};
+use crate::helpers::style;
+
+#[derive(Debug, PartialEq)]
+enum Auth {
+    None,
+    Basic(String),
+    Bearer(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Body {
+    None,
+    Text(String),
+    File(PathBuf),
+}
+
+/// The answers collected by the wizard.
+#[derive(Debug)]
+struct Draft {
+    method: Method,
+    url: String,
+    headers: Vec<String>,
+    auth: Auth,
+    body: Body,
+}
+
+/// Ask until `check` accepts the answer; an empty answer takes `default`.
+/// Prompts and complaints go to stderr so stdout stays the response.
+fn ask<R: BufRead, T>(
+    input: &mut R,
+    prompt: &str,
+    default: Option<&str>,
+    check: impl Fn(&str) -> Result<T>,
+) -> Result<T> {
+    loop {
+        match default {
+            Some(d) if !d.is_empty() => {
+                eprint!("{} {}: ", prompt.bold(), format!("[{d}]").dimmed())
+            }
+            _ => eprint!("{}: ", prompt.bold()),
+        }
+        io::stderr().flush().ok();
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            bail!("Input ended before the request was complete");
+        }
+        let answer = match line.trim() {
+            "" => default.unwrap_or_default(),
+            a => a,
+        };
+        match check(answer) {
+            Ok(v) => return Ok(v),
+            Err(e) => eprintln!("  {}", format!("{e:#}").red()),
+        }
+    }
+}
+
+fn check_url(s: &str) -> Result<String> {
+    if s.is_empty() {
+        bail!("A URL is required");
+    }
+    // {{VAR}} placeholders are filled at send time (--var or the environment).
+    if !s.contains("{{") {
+        let url = Url::parse(s).with_context(|| format!("Not a valid URL: {s}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("Only http:// and https:// URLs are supported");
+        }
+    }
+    Ok(s.to_string())
+}
+
+fn check_header(s: &str) -> Result<Option<String>> {
+    if s.is_empty() {
+        return Ok(None);
+    }
+    parse_headers(&[s.to_string()])?;
+    Ok(Some(s.to_string()))
+}
+
+fn check_body(s: &str) -> Result<Body> {
+    if s.is_empty() {
+        return Ok(Body::None);
+    }
+    if let Some(path) = s.strip_prefix('@') {
+        let path = PathBuf::from(path.trim());
+        if !path.is_file() {
+            bail!("No such file: {}", path.display());
+        }
+        return Ok(Body::File(path));
+    }
+    if s.starts_with(['{', '[']) {
+        serde_json::from_str::<serde_json::Value>(s).context("Invalid JSON")?;
+    }
+    Ok(Body::Text(s.to_string()))
+}
+
+/// Run the prompts over `input`.
+fn wizard<R: BufRead>(input: &mut R) -> Result<Draft> {
+    let method = ask(input, "Method", Some("GET"), parse_method)?;
+    let url = ask(input, "URL", None, check_url)?;
+
+    let mut headers = Vec::new();
+    while let Some(h) = ask(
+        input,
+        "Header (Name: value, empty to finish)",
+        None,
+        check_header,
+    )? {
+        headers.push(h);
+    }
+
+    let auth = ask(
+        input,
+        "Auth (none, basic, bearer)",
+        Some("none"),
+        |s| match s.to_ascii_lowercase().as_str() {
+            "none" | "no" => Ok(0),
+            "basic" => Ok(1),
+            "bearer" => Ok(2),
+            _ => bail!("Answer none, basic, or bearer"),
+        },
+    )?;
+    let auth = match auth {
+        1 => Auth::Basic(ask(
+            input,
+            "Credentials (user:password)",
+            None,
+            |s| match s.split_once(':') {
+                Some((user, _)) if !user.is_empty() => Ok(s.to_string()),
+                _ => bail!("Expected user:password"),
+            },
+        )?),
+        2 => Auth::Bearer(ask(input, "Token", None, |s| {
+            if s.is_empty() || s.contains(char::is_whitespace) {
+                bail!("Expected a token without spaces");
+            }
+            Ok(s.to_string())
+        })?),
+        _ => Auth::None,
+    };
+
+    let body = if takes_stdin_body(&method) || method == Method::DELETE {
+        ask(
+            input,
+            "Body (JSON or text, @file, empty for none)",
+            None,
+            check_body,
+        )?
+    } else {
+        Body::None
+    };
+    Ok(Draft {
+        method,
+        url,
+        headers,
+        auth,
+        body,
+    })
+}
+
+impl Draft {
+    /// `post-users` for POST https://api.example.com/v1/users?page=2.
+    fn default_name(&self) -> String {
+        let path = self.url.split(['?', '#']).next().unwrap_or_default();
+        let last = path
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|s| !s.is_empty() && !s.contains(':'))
+            .unwrap_or("request");
+        format!("{}-{last}", self.method.as_str().to_ascii_lowercase())
+    }
+
+    /// The request as a `.http` entry titled `name`; file bodies become `< path`.
+    fn to_http_entry(&self, name: &str) -> String {
+        let mut out = format!("### {name}\n{} {}\n", self.method, self.url);
+        for h in &self.headers {
+            out.push_str(&format!("{h}\n"));
+        }
+        match &self.auth {
+            Auth::None => {}
+            Auth::Basic(creds) => out.push_str(&format!(
+                "Authorization: Basic {}\n",
+                base64::engine::general_purpose::STANDARD.encode(creds)
+            )),
+            Auth::Bearer(token) => out.push_str(&format!("Authorization: Bearer {token}\n")),
+        }
+        let json =
+            matches!(&self.body, Body::Text(t) if http_request::looks_like_json(t.as_bytes()));
+        let has_type = self
+            .headers
+            .iter()
+            .any(|h| h.to_ascii_lowercase().starts_with("content-type"));
+        if json && !has_type {
+            out.push_str("Content-Type: application/json\n");
+        }
+        match &self.body {
+            Body::None => {}
+            Body::Text(text) => out.push_str(&format!("\n{text}\n")),
+            Body::File(path) => out.push_str(&format!("\n< {}\n", path.display())),
+        }
+        out
+    }
+}
+
+/// Append `entry` to the `.http` file at `path`, separated from what is there.
+fn append_entry(path: &Path, entry: &str) -> Result<()> {
+    let existing = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Cannot write {}", path.display()))?;
+    if existing > 0 {
+        file.write_all(b"\n")?;
+    }
+    file.write_all(entry.as_bytes())?;
+    Ok(())
+}
+
+/// Prompt for a request, send it, then offer to save it as a `.http` entry.
+pub async fn run(mut args: RequestArgs) -> Result<()> {
+    let stdin = io::stdin();
+    let draft = wizard(&mut stdin.lock())?;
+
+    args.headers.extend(draft.headers.iter().cloned());
+    match &draft.auth {
+        Auth::Basic(creds) => args.auth = Some(creds.clone()),
+        Auth::Bearer(token) => args.bearer = Some(token.clone()),
+        Auth::None => {}
+    }
+    let body = match &draft.body {
+        Body::None => None,
+        Body::Text(text) => Some(BodyArgs {
+            data: Some(text.clone()),
+            data_file: None,
+            form: Vec::new(),
+            form_file: Vec::new(),
+        }),
+        Body::File(path) => Some(BodyArgs {
+            data: None,
+            data_file: Some(path.clone()),
+            form: Vec::new(),
+            form_file: Vec::new(),
+        }),
+    };
+    eprintln!();
+    http_request::run(draft.method.clone(), &draft.url, body.as_ref(), args, None).await?;
+
+    eprintln!();
+    let mut input = stdin.lock();
+    let path = ask(
+        &mut input,
+        "Save to .http file (path, empty to skip)",
+        None,
+        |s| Ok(PathBuf::from(s)),
+    )?;
+    if path.as_os_str().is_empty() {
+        return Ok(());
+    }
+    let default_name = draft.default_name();
+    let name = ask(&mut input, "Request name", Some(&default_name), |s| {
+        if s.contains('\n') {
+            bail!("Use a single line");
+        }
+        Ok(s.to_string())
+    })?;
+    append_entry(&path, &draft.to_http_entry(&name))?;
+    if draft.auth != Auth::None {
+        style::warn_line("The entry includes credentials; keep the file out of version control");
+    }
+    style::ok(&format!(
+        "Saved to {} (run it with: swiftline http run {} \"{name}\")",
+        path.display(),
+        path.display()
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_wizard_reprompts_until_valid() {
+        let answers = "\
+GE T
+post
+ftp://example.com
+https://api.example.com/users
+Accept application/json
+Accept: application/json
+
+token
+bearer
+abc 123
+abc123
+{\"name\":
+{\"name\":\"Alice\"}
+";
+        let draft = wizard(&mut Cursor::new(answers)).unwrap();
+        assert_eq!(draft.method, Method::POST);
+        assert_eq!(draft.url, "https://api.example.com/users");
+        assert_eq!(draft.headers, vec!["Accept: application/json"]);
+        assert_eq!(draft.auth, Auth::Bearer("abc123".into()));
+        assert_eq!(draft.body, Body::Text("{\"name\":\"Alice\"}".into()));
+
+        // GET skips the body; empty answers take the defaults.
+        let draft = wizard(&mut Cursor::new("\nhttps://a.test/{{id}}\n\n\n")).unwrap();
+        assert_eq!(
+            (draft.method, draft.auth, draft.body),
+            (Method::GET, Auth::None, Body::None)
+        );
+        assert!(wizard(&mut Cursor::new("GET\n")).is_err());
+    }
+
+    #[test]
+    fn test_to_http_entry() {
+        let draft = Draft {
+            method: Method::POST,
+            url: "https://api.example.com/users".into(),
+            headers: vec!["X-Team: core".into()],
+            auth: Auth::Basic("me:pw".into()),
+            body: Body::Text("{\"id\": 1}".into()),
+        };
+        assert_eq!(draft.default_name(), "post-users");
+        assert_eq!(
+            draft.to_http_entry("create user"),
+            "### create user\nPOST https://api.example.com/users\nX-Team: core\n\
+             Authorization: Basic bWU6cHc=\nContent-Type: application/json\n\n{\"id\": 1}\n"
+        );
+    }
+}
@@ -0,0 +1,481 @@
+//! `http openapi <spec> list|call`: requests built from an OpenAPI 3 document.
+//! `call` looks up an operationId, fills its path template, query, header, and
+//! cookie parameters from `--param name=value`, and checks required
+//! parameters, enums, and scalar types before anything is sent. The request
+//! then goes through the shared executor like `http request`.
+
+use anyhow::{bail, Context, Result};
+use atty::Stream;
+use log::info;
+use reqwest::Method;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use url::Url;
+
+use crate::cli::{BodyArgs, RequestArgs};
+use crate::commands::http_request::{self, takes_stdin_body};
+use crate::helpers::table;
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// A parsed spec and where it came from (relative server URLs resolve against a spec URL).
+pub struct Spec {
+    doc: Value,
+    base: Option<Url>,
+}
+
+struct Param {
+    name: String,
+    location: String,
+    required: bool,
+    schema: Value,
+}
+
+struct Operation<'a> {
+    id: String,
+    method: &'a str,
+    path: &'a str,
+    summary: String,
+    path_item: &'a Value,
+    op: &'a Value,
+}
+
+impl Spec {
+    /// Load a YAML or JSON spec from a file path or an http(s) URL.
+    pub async fn load(source: &str) -> Result<Spec> {
+        let (text, base) = if source.starts_with("http://") || source.starts_with("https://") {
+            let resp = reqwest::get(source).await?;
+            if !resp.status().is_success() {
+                bail!("Failed to fetch spec {source}: {}", resp.status());
+            }
+            (resp.text().await?, Some(Url::parse(source)?))
+        } else {
+            let text = std::fs::read_to_string(source)
+                .with_context(|| format!("Failed to read spec: {source}"))?;
+            (text, None)
+        };
+        Self::parse(&text, base)
+    }
+
+    fn parse(text: &str, base: Option<Url>) -> Result<Spec> {
+        // YAML is a superset of JSON, so one parser covers both.
+        let doc: Value =
+            serde_yaml::from_str(text).context("Spec is neither valid YAML nor JSON")?;
+        match doc.get("openapi").and_then(Value::as_str) {
+            Some(v) if v.starts_with('3') => Ok(Spec { doc, base }),
+            Some(v) => bail!("Unsupported OpenAPI version {v} (only 3.x)"),
+            None if doc.get("swagger").is_some() => {
+                bail!("Swagger 2.0 specs are not supported; convert to OpenAPI 3 first")
+            }
+            None => bail!("Not an OpenAPI document (no `openapi` field)"),
+        }
+    }
+
+    /// Follow a local `$ref` ("#/components/..."); other values are returned as is.
+    fn resolve<'a>(&'a self, value: &'a Value) -> Result<&'a Value> {
+        let mut value = value;
+        for _ in 0..16 {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+                return Ok(value);
+            };
+            let pointer = reference
+                .strip_prefix('#')
+                .with_context(|| format!("Only local $refs are supported, got {reference}"))?;
+            value = self
+                .doc
+                .pointer(pointer)
+                .with_context(|| format!("Unresolved $ref {reference}"))?;
+        }
+        bail!("$ref chain too deep")
+    }
+
+    fn operations(&self) -> Vec<Operation<'_>> {
+        let mut ops = Vec::new();
+        let Some(paths) = self.doc.get("paths").and_then(Value::as_object) else {
+            return ops;
+        };
+        for (path, item) in paths {
+            let item = self.resolve(item).unwrap_or(item);
+            for method in METHODS {
+                let Some(op) = item.get(method) else { continue };
+                let id = op
+                    .get("operationId")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_default();
+                let summary = op
+                    .get("summary")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string();
+                ops.push(Operation {
+                    id,
+                    method,
+                    path,
+                    summary,
+                    path_item: item,
+                    op,
+                });
+            }
+        }
+        ops
+    }
+
+    /// Path-level parameters overridden by operation-level ones with the same name and location.
+    fn parameters(&self, op: &Operation) -> Result<Vec<Param>> {
+        let mut params: Vec<Param> = Vec::new();
+        for list in [op.path_item.get("parameters"), op.op.get("parameters")] {
+            for raw in list.and_then(Value::as_array).into_iter().flatten() {
+                let p = self.resolve(raw)?;
+                let name = p.get("name").and_then(Value::as_str).unwrap_or_default();
+                let location = p.get("in").and_then(Value::as_str).unwrap_or("query");
+                let schema = match p.get("schema") {
+                    Some(s) => self.resolve(s)?.clone(),
+                    None => Value::Null,
+                };
+                let param = Param {
+                    name: name.to_string(),
+                    location: location.to_string(),
+                    // Path parameters are always required.
+                    required: location == "path"
+                        || p.get("required").and_then(Value::as_bool) == Some(true),
+                    schema,
+                };
+                params.retain(|q| !(q.name == param.name && q.location == param.location));
+                params.push(param);
+            }
+        }
+        Ok(params)
+    }
+
+    /// The first server of the operation, path item, or document, with `{variables}` at their defaults.
+    fn server(&self, op: &Operation) -> Result<Url> {
+        let servers = [op.op, op.path_item, &self.doc].into_iter().find_map(|v| {
+            v.get("servers")
+                .and_then(Value::as_array)
+                .filter(|s| !s.is_empty())
+        });
+        let (mut url, vars) = match servers.and_then(|s| s.first()) {
+            Some(s) => (
+                s.get("url")
+                    .and_then(Value::as_str)
+                    .unwrap_or("/")
+                    .to_string(),
+                s.get("variables"),
+            ),
+            None => ("/".to_string(), None),
+        };
+        for (name, var) in vars.and_then(Value::as_object).into_iter().flatten() {
+            if let Some(default) = var.get("default").and_then(Value::as_str) {
+                url = url.replace(&format!("{{{name}}}"), default);
+            }
+        }
+        match (Url::parse(&url), &self.base) {
+            (Ok(u), _) => Ok(u),
+            (Err(_), Some(base)) => base
+                .join(&url)
+                .with_context(|| format!("Invalid server URL: {url}")),
+            (Err(_), None) => bail!("The spec's server URL {url} is relative; pass --server"),
+        }
+    }
+}
+
+/// Check a value against the scalar type and enum of its schema.
+fn check_value(param: &Param, value: &str) -> Result<()> {
+    let schema = &param.schema;
+    let ok = match schema.get("type").and_then(Value::as_str) {
+        Some("integer") => value.parse::<i64>().is_ok(),
+        Some("number") => value.parse::<f64>().is_ok(),
+        Some("boolean") => matches!(value, "true" | "false"),
+        _ => true,
+    };
+    if !ok {
+        bail!(
+            "Parameter {} must be {}, got {value}",
+            param.name,
+            schema["type"].as_str().unwrap_or_default()
+        );
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        let names: Vec<String> = allowed
+            .iter()
+            .map(|v| v.as_str().map_or_else(|| v.to_string(), str::to_string))
+            .collect();
+        if !names.iter().any(|n| n == value) {
+            bail!(
+                "Parameter {} must be one of: {}",
+                param.name,
+                names.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Escape a value substituted into a path template.
+fn encode_segment(s: &str) -> String {
+    s.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+/// The URL and header/cookie strings for an operation, after validating `given` against its parameters.
+fn build(
+    spec: &Spec,
+    op: &Operation,
+    server: Option<&str>,
+    given: &[(String, String)],
+) -> Result<(Url, Vec<String>, Vec<String>)> {
+    let params = spec.parameters(op)?;
+    let mut values: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (name, value) in given {
+        let Some(param) = params.iter().find(|p| p.name == *name) else {
+            let known: Vec<&str> = params.iter().map(|p| p.name.as_str()).collect();
+            bail!(
+                "{} has no parameter {name} (parameters: {})",
+                op.id,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            );
+        };
+        check_value(param, value)?;
+        values.entry(param.name.as_str()).or_default().push(value);
+    }
+    let missing: Vec<String> = params
+        .iter()
+        .filter(|p| p.required && !values.contains_key(p.name.as_str()))
+        .map(|p| format!("{} ({})", p.name, p.location))
+        .collect();
+    if !missing.is_empty() {
+        bail!("{} requires --param for: {}", op.id, missing.join(", "));
+    }
+
+    let mut path = op.path.to_string();
+    let (mut query, mut headers, mut cookies) = (Vec::new(), Vec::new(), Vec::new());
+    for p in &params {
+        for v in values.get(p.name.as_str()).into_iter().flatten() {
+            match p.location.as_str() {
+                "path" => path = path.replace(&format!("{{{}}}", p.name), &encode_segment(v)),
+                "header" => headers.push(format!("{}: {v}", p.name)),
+                "cookie" => cookies.push(format!("{}={v}", p.name)),
+                _ => query.push((p.name.as_str(), *v)),
+            }
+        }
+    }
+
+    let mut url = match server {
+        Some(s) => Url::parse(s).with_context(|| format!("Invalid --server URL: {s}"))?,
+        None => spec.server(op)?,
+    };
+    let joined = format!("{}{path}", url.path().trim_end_matches('/'));
+    url.set_path(&joined);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    Ok((url, headers, cookies))
+}
+
+/// Table of the spec's operations.
+pub fn run_list(spec: &Spec) -> Result<()> {
+    let rows: Vec<Vec<String>> = spec
+        .operations()
+        .iter()
+        .map(|o| {
+            vec![
+                o.id.clone(),
+                o.method.to_ascii_uppercase(),
+                o.path.to_string(),
+                o.summary.clone(),
+            ]
+        })
+        .collect();
+    if rows.is_empty() {
+        bail!("The spec defines no operations");
+    }
+    table::print(&["OPERATION", "METHOD", "PATH", "SUMMARY"], &rows);
+    Ok(())
+}
+
+/// Send the operation `id` with `--param name=value` pairs; `server` overrides the spec's servers.
+pub async fn run_call(
+    spec: &Spec,
+    id: &str,
+    params: &[String],
+    server: Option<&str>,
+    body: BodyArgs,
+    mut args: RequestArgs,
+) -> Result<()> {
+    let ops = spec.operations();
+    let Some(op) = ops.iter().find(|o| o.id == id) else {
+        let lower = id.to_ascii_lowercase();
+        let close: Vec<&str> = ops
+            .iter()
+            .filter(|o| !o.id.is_empty() && o.id.to_ascii_lowercase().contains(&lower))
+            .map(|o| o.id.as_str())
+            .collect();
+        if close.is_empty() {
+            bail!("No operation {id} in the spec (see `http openapi <spec> list`)");
+        }
+        bail!(
+            "No operation {id} in the spec; did you mean: {}",
+            close.join(", ")
+        );
+    };
+    let given = params
+        .iter()
+        .map(|p| {
+            p.split_once('=')
+                .map(|(k, v)| (k.trim().to_string(), v.to_string()))
+                .with_context(|| format!("--param must be name=value, got: {p}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let (url, headers, cookies) = build(spec, op, server, &given)?;
+    let method = Method::from_bytes(op.method.to_ascii_uppercase().as_bytes())?;
+
+    let has_body = body.data.is_some()
+        || body.data_file.is_some()
+        || !body.form.is_empty()
+        || !body.form_file.is_empty()
+        || (takes_stdin_body(&method) && !atty::is(Stream::Stdin));
+    if let Some(request_body) = op.op.get("requestBody") {
+        let request_body = spec.resolve(request_body)?;
+        if !has_body && request_body.get("required").and_then(Value::as_bool) == Some(true) {
+            bail!("{id} requires a request body (--data, --data-file, --form, or stdin)");
+        }
+        // A raw body gets the spec's media type when it names exactly one.
+        let raw = body.data.is_some() || body.data_file.is_some();
+        let given_type = args
+            .headers
+            .iter()
+            .any(|h| h.to_ascii_lowercase().starts_with("content-type:"));
+        if let Some(content) = request_body.get("content").and_then(Value::as_object) {
+            if raw && !given_type && content.len() == 1 {
+                let media = content.keys().next().expect("one media type");
+                args.headers.push(format!("Content-Type: {media}"));
+            }
+        }
+    }
+    args.headers.splice(0..0, headers);
+    args.cookie.extend(cookies);
+    info!("{id}: {} {}", method, url);
+    http_request::run(method, url.as_str(), Some(&body), args, None).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+openapi: 3.0.3
+servers:
+  - url: https://{region}.api.example.com/v1
+    variables:
+      region: { default: eu }
+paths:
+  /pets/{petId}:
+    parameters:
+      - $ref: '#/components/parameters/PetId'
+    get:
+      operationId: getPet
+      summary: Fetch one pet
+      parameters:
+        - { name: fields, in: query, schema: { type: string } }
+        - { name: X-Trace, in: header }
+    delete:
+      operationId: deletePet
+  /pets:
+    post:
+      operationId: createPet
+      parameters:
+        - { name: kind, in: query, required: true, schema: { enum: [cat, dog] } }
+      requestBody:
+        required: true
+        content:
+          application/json: {}
+components:
+  parameters:
+    PetId: { name: petId, in: path, schema: { type: integer } }
+"#;
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_operations() {
+        let spec = Spec::parse(SPEC, None).unwrap();
+        let ops: Vec<(String, &str)> = spec
+            .operations()
+            .into_iter()
+            .map(|o| (o.id, o.method))
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                ("getPet".to_string(), "get"),
+                ("deletePet".to_string(), "delete"),
+                ("createPet".to_string(), "post")
+            ]
+        );
+        assert!(Spec::parse("swagger: '2.0'", None).is_err());
+    }
+
+    #[test]
+    fn test_build_request() {
+        let spec = Spec::parse(SPEC, None).unwrap();
+        let ops = spec.operations();
+        let get = &ops[0];
+        let given = pairs(&[("petId", "5"), ("fields", "name age"), ("X-Trace", "abc")]);
+        let (url, headers, _) = build(&spec, get, None, &given).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://eu.api.example.com/v1/pets/5?fields=name+age"
+        );
+        assert_eq!(headers, ["X-Trace: abc"]);
+
+        let (url, _, _) = build(
+            &spec,
+            get,
+            Some("http://localhost:8080/"),
+            &pairs(&[("petId", "7")]),
+        )
+        .unwrap();
+        assert_eq!(url.as_str(), "http://localhost:8080/pets/7");
+    }
+
+    #[test]
+    fn test_validation() {
+        let spec = Spec::parse(SPEC, None).unwrap();
+        let ops = spec.operations();
+        let err = |op: usize, given: &[(&str, &str)]| {
+            build(&spec, &ops[op], None, &pairs(given))
+                .err()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(err(0, &[]), "getPet requires --param for: petId (path)");
+        assert_eq!(
+            err(0, &[("petId", "five")]),
+            "Parameter petId must be integer, got five"
+        );
+        assert!(err(0, &[("petId", "1"), ("color", "red")]).contains("has no parameter color"));
+        assert_eq!(
+            err(2, &[("kind", "fish")]),
+            "Parameter kind must be one of: cat, dog"
+        );
+    }
+}
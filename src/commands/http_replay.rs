@@ -0,0 +1,299 @@
+//! `http replay`: re-send a request recorded in a HAR file (browser DevTools
+//! "Save all as HAR") with targeted overrides: headers set or dropped, query
+//! parameters replaced, another method or base URL, or a new body. The request
+//! goes through the shared executor, so every output flag of `http get` applies.
+
+use anyhow::{bail, Context, Result};
+use log::info;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use url::Url;
+
+use crate::cli::{BodyArgs, RequestArgs};
+use crate::commands::http_request::{self, parse_method};
+use crate::helpers::table;
+
+/// Recorded headers that belong to the original connection, not the request.
+const SKIPPED: &[&str] = &[
+    "host",
+    "content-length",
+    "connection",
+    "keep-alive",
+    "transfer-encoding",
+    "upgrade",
+    "te",
+    "proxy-connection",
+];
+
+/// One recorded request.
+#[derive(Debug, Clone, PartialEq)]
+struct Recorded {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Option<String>,
+    status: Option<u64>,
+}
+
+/// Changes applied to the recorded request before it is sent.
+#[derive(Debug, Default)]
+pub struct Overrides {
+    pub method: Option<String>,
+    pub base_url: Option<String>,
+    pub set_headers: Vec<String>,
+    pub drop_headers: Vec<String>,
+    pub set_query: Vec<String>,
+}
+
+/// The requests of a HAR document, in recorded order.
+fn parse_har(doc: &Value) -> Result<Vec<Recorded>> {
+    let entries = doc
+        .pointer("/log/entries")
+        .and_then(Value::as_array)
+        .context("Not a HAR file (no log.entries)")?;
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            let req = e
+                .get("request")
+                .with_context(|| format!("Entry {} has no request", i + 1))?;
+            let text =
+                |v: &Value, key: &str| v.get(key).and_then(Value::as_str).map(str::to_string);
+            let headers = req
+                .get("headers")
+                .and_then(Value::as_array)
+                .map(|hs| {
+                    hs.iter()
+                        .filter_map(|h| Some((text(h, "name")?, text(h, "value")?)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            // Form posts may be recorded as params only.
+            let body = req.get("postData").and_then(|p| {
+                text(p, "text").filter(|t| !t.is_empty()).or_else(|| {
+                    let params = p.get("params")?.as_array()?;
+                    let mut form = url::form_urlencoded::Serializer::new(String::new());
+                    for param in params {
+                        form.append_pair(
+                            &text(param, "name")?,
+                            &text(param, "value").unwrap_or_default(),
+                        );
+                    }
+                    Some(form.finish()).filter(|f| !f.is_empty())
+                })
+            });
+            Ok(Recorded {
+                method: text(req, "method").unwrap_or_else(|| "GET".into()),
+                url: text(req, "url").with_context(|| format!("Entry {} has no URL", i + 1))?,
+                headers,
+                body,
+                status: e.pointer("/response/status").and_then(Value::as_u64),
+            })
+        })
+        .collect()
+}
+
+/// The recorded request with `o` applied. Headers named in `--set-header` (or
+/// `-H`, listed in `given`) replace the recorded ones; HTTP/2 pseudo-headers and
+/// connection headers are never replayed.
+fn apply(mut rec: Recorded, o: &Overrides, given: &[String]) -> Result<Recorded> {
+    if let Some(m) = &o.method {
+        rec.method = m.to_ascii_uppercase();
+    }
+    let mut url =
+        Url::parse(&rec.url).with_context(|| format!("Invalid recorded URL: {}", rec.url))?;
+    if let Some(base) = &o.base_url {
+        let base = Url::parse(base).with_context(|| format!("Invalid --base-url: {base}"))?;
+        let prefix = base.path().trim_end_matches('/');
+        let path = format!("{prefix}{}", url.path());
+        let query = url.query().map(str::to_string);
+        url = base;
+        url.set_path(&path);
+        url.set_query(query.as_deref());
+    }
+    for pair in &o.set_query {
+        let (name, value) = pair
+            .split_once('=')
+            .with_context(|| format!("--set-query must be name=value, got: {pair}"))?;
+        let kept: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(k, _)| k != name)
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(kept)
+            .append_pair(name, value);
+    }
+    rec.url = url.to_string();
+
+    let mut set = Vec::new();
+    for h in &o.set_headers {
+        let (name, value) = h
+            .split_once(':')
+            .with_context(|| format!("--set-header must be \"Name: value\", got: {h}"))?;
+        set.push((name.trim().to_string(), value.trim().to_string()));
+    }
+    let replaced = |name: &str| {
+        let lower = name.to_ascii_lowercase();
+        name.starts_with(':')
+            || SKIPPED.contains(&lower.as_str())
+            || o.drop_headers.iter().any(|d| d.eq_ignore_ascii_case(name))
+            || set.iter().any(|(n, _)| n.eq_ignore_ascii_case(name))
+            || given.contains(&lower)
+    };
+    rec.headers.retain(|(name, _)| !replaced(name));
+    rec.headers.extend(set);
+    Ok(rec)
+}
+
+fn list(entries: &[Recorded]) {
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| {
+            let mut url = e.url.clone();
+            if url.chars().count() > 90 {
+                url = url.chars().take(89).collect::<String>() + "…";
+            }
+            vec![
+                (i + 1).to_string(),
+                e.method.clone(),
+                e.status.map(|s| s.to_string()).unwrap_or_default(),
+                url,
+            ]
+        })
+        .collect();
+    table::print(&["#", "METHOD", "STATUS", "URL"], &rows);
+}
+
+/// Replay entry `entry` (1-based) of `har`, or list the entries when none is given.
+/// A --data/--data-file/--form body replaces the recorded one.
+pub async fn run(
+    har: &Path,
+    entry: Option<usize>,
+    overrides: Overrides,
+    body: BodyArgs,
+    mut args: RequestArgs,
+) -> Result<()> {
+    let text = fs::read_to_string(har)
+        .with_context(|| format!("Failed to read HAR file: {}", har.display()))?;
+    let doc: Value = serde_json::from_str(&text)
+        .with_context(|| format!("Invalid HAR file: {}", har.display()))?;
+    let entries = parse_har(&doc)?;
+    if entries.is_empty() {
+        bail!("No requests in {}", har.display());
+    }
+    let Some(n) = entry else {
+        list(&entries);
+        return Ok(());
+    };
+    let recorded = match n.checked_sub(1).and_then(|i| entries.get(i)) {
+        Some(r) => r.clone(),
+        None => bail!(
+            "No entry {n} ({} has {} entries)",
+            har.display(),
+            entries.len()
+        ),
+    };
+
+    let given: Vec<String> = args
+        .headers
+        .iter()
+        .filter_map(|h| {
+            h.split_once(':')
+                .map(|(k, _)| k.trim().to_ascii_lowercase())
+        })
+        .collect();
+    let rec = apply(recorded, &overrides, &given)?;
+    let mut headers: Vec<String> = rec
+        .headers
+        .iter()
+        .map(|(k, v)| format!("{k}: {v}"))
+        .collect();
+    headers.append(&mut args.headers);
+    args.headers = headers;
+
+    let overridden = body.data.is_some()
+        || body.data_file.is_some()
+        || !body.form.is_empty()
+        || !body.form_file.is_empty();
+    let body = if overridden {
+        Some(body)
+    } else {
+        rec.body.map(|text| BodyArgs {
+            data: Some(text),
+            data_file: None,
+            form: Vec::new(),
+            form_file: Vec::new(),
+        })
+    };
+    info!("Replaying entry {n} of {}", har.display());
+    http_request::run(
+        parse_method(&rec.method)?,
+        &rec.url,
+        body.as_ref(),
+        args,
+        None,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn har() -> Value {
+        json!({"log": {"version": "1.2", "entries": [
+            {"request": {"method": "GET", "url": "https://app.example.com/api/items?page=2&sort=asc",
+                "headers": [
+                    {"name": ":authority", "value": "app.example.com"},
+                    {"name": "Authorization", "value": "Bearer old"},
+                    {"name": "Cookie", "value": "sid=1"},
+                    {"name": "Accept", "value": "application/json"}
+                ]},
+             "response": {"status": 500}},
+            {"request": {"method": "POST", "url": "https://app.example.com/login", "headers": [],
+                "postData": {"mimeType": "application/x-www-form-urlencoded",
+                    "params": [{"name": "user", "value": "a b"}, {"name": "pw", "value": "x"}]}},
+             "response": {"status": 302}}
+        ]}})
+    }
+
+    #[test]
+    fn test_parse_har() {
+        let entries = parse_har(&har()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].status, Some(500));
+        assert_eq!(entries[0].headers.len(), 4);
+        assert_eq!(entries[1].body.as_deref(), Some("user=a+b&pw=x"));
+        assert!(parse_har(&json!({"entries": []})).is_err());
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let rec = parse_har(&har()).unwrap().remove(0);
+        let o = Overrides {
+            method: Some("head".into()),
+            base_url: Some("http://localhost:3000".into()),
+            set_headers: vec!["Authorization: Bearer new".into()],
+            drop_headers: vec!["cookie".into()],
+            set_query: vec!["page=3".into()],
+        };
+        let out = apply(rec, &o, &["accept".into()]).unwrap();
+        assert_eq!(out.method, "HEAD");
+        assert_eq!(out.url, "http://localhost:3000/api/items?sort=asc&page=3");
+        assert_eq!(
+            out.headers,
+            vec![("Authorization".to_string(), "Bearer new".to_string())]
+        );
+        let bad = Overrides {
+            set_query: vec!["page".into()],
+            ..Default::default()
+        };
+        assert!(apply(parse_har(&har()).unwrap().remove(0), &bad, &[]).is_err());
+    }
+}
@@ -0,0 +1,1340 @@
+//! `http request|get|post`: the shared request executor. Sends any HTTP method
+//! with headers, timeout, and an optional body, then prints the response
+//! (optional save with progress and resume, pretty colored JSON, header table,
+//! pacing).
+
+use anyhow::{bail, Context, Result};
+use atty::Stream;
+use base64::Engine;
+use encoding_rs::{Encoding, UTF_8};
+use indicatif::ProgressBar;
+use log::info;
+use md5::Md5;
+use owo_colors::OwoColorize;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT_ENCODING, AUTHORIZATION, CONTENT_RANGE,
+    CONTENT_TYPE, LOCATION, RANGE,
+};
+use reqwest::{
+    redirect, Certificate, Client, ClientBuilder, Identity, Method, NoProxy, Proxy, StatusCode,
+    Version,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use url::Url;
+
+use crate::cli::{BodyArgs, RequestArgs};
+use crate::commands::http_curl;
+use crate::commands::verify_sig::Verifier;
+use crate::helpers::compression::{self, Coding};
+use crate::helpers::http_cache::{self, Entry};
+use crate::helpers::retry::{self, RetryPolicy};
+use crate::helpers::spinner::{self, spinner};
+use crate::helpers::table::strip_ansi;
+use crate::helpers::write_out::{self, Template};
+use crate::helpers::{
+    clipboard, cookies, form, headers, hex, highlight, pace, preview, resolve, style, template,
+};
+
+/// Convert repeated "key:value" list into a HeaderMap.
+/// Supports multiple values for same key via append.
+pub fn parse_headers(items: &[String]) -> Result<HeaderMap> {
+    let mut map: HeaderMap = HeaderMap::new();
+    for h in items {
+        let (k, v) = h
+            .split_once(':')
+            .with_context(|| format!("Header must be key:value, got: {h}"))?;
+
+        let key: HeaderName = k
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid header key: {k}"))?;
+
+        let val: HeaderValue = v
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid header value for {k}"))?;
+
+        map.append(key, val); // Use append to allow multiple values for same key
+    }
+    Ok(map)
+}
+
+/// Environment variable holding a default bearer token.
+const TOKEN_VAR: &str = "SWIFTLINE_TOKEN";
+
+/// Authorization from --auth (Basic) or --bearer, else a non-empty `$SWIFTLINE_TOKEN`.
+/// `env_token` is passed in so the precedence can be tested without touching the environment.
+fn auth_header(args: &RequestArgs, env_token: Option<String>) -> Result<Option<HeaderValue>> {
+    let value = if let Some(creds) = &args.auth {
+        let creds = if creds.contains(':') {
+            creds.clone()
+        } else {
+            format!("{creds}:")
+        };
+        format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode(creds)
+        )
+    } else if let Some(token) = args
+        .bearer
+        .clone()
+        .or(env_token.filter(|t| !t.trim().is_empty()))
+    {
+        format!("Bearer {}", token.trim())
+    } else {
+        return Ok(None);
+    };
+    let mut value =
+        HeaderValue::from_str(&value).context("Credentials contain invalid characters")?;
+    value.set_sensitive(true);
+    Ok(Some(value))
+}
+
+/// Parse a method name case-insensitively (`get`, `PATCH`, custom tokens like `PURGE`).
+pub fn parse_method(name: &str) -> Result<Method> {
+    Method::from_bytes(name.to_ascii_uppercase().as_bytes())
+        .with_context(|| format!("Invalid HTTP method: {name}"))
+}
+
+/// Methods whose body is read from piped stdin when no --data/--data-file is given.
+pub(crate) fn takes_stdin_body(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH)
+}
+
+/// Body source priority: --data > --data-file > piped stdin (only for `stdin_ok` methods).
+fn read_body(body: &BodyArgs, stdin_ok: bool) -> Result<Vec<u8>> {
+    if let Some(d) = &body.data {
+        return Ok(d.clone().into_bytes());
+    }
+    if let Some(path) = &body.data_file {
+        return fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()));
+    }
+    let mut buf = Vec::new();
+    if stdin_ok && !atty::is(Stream::Stdin) {
+        io::stdin().read_to_end(&mut buf)?;
+    }
+    Ok(buf)
+}
+
+/// True when the body parses as a JSON object or array.
+pub(crate) fn looks_like_json(body: &[u8]) -> bool {
+    matches!(
+        serde_json::from_slice::<Value>(body),
+        Ok(Value::Object(_) | Value::Array(_))
+    )
+}
+
+/// Redirect policy from --no-follow / --max-redirects. Every followed hop is
+/// logged (visible with -v) as the status that triggered it plus its Location.
+fn redirect_policy(args: &RequestArgs) -> redirect::Policy {
+    if args.no_follow {
+        return redirect::Policy::none();
+    }
+    let max = args.max_redirects;
+    redirect::Policy::custom(move |attempt| {
+        let hop = attempt.previous().len();
+        if hop > max {
+            return attempt.error(format!("too many redirects (--max-redirects {max})"));
+        }
+        info!(
+            "Redirect {hop}: {} {} -> {}",
+            attempt.status(),
+            attempt.previous().last().map(Url::as_str).unwrap_or(""),
+            attempt.url()
+        );
+        attempt.follow()
+    })
+}
+
+/// Parse `Content-Range: bytes START-END/SIZE` (or `bytes */SIZE`) into (start, size).
+fn content_range(hdrs: &HeaderMap) -> (Option<u64>, Option<u64>) {
+    let Some(v) = hdrs.get(CONTENT_RANGE).and_then(|v| v.to_str().ok()) else {
+        return (None, None);
+    };
+    let spec = v.trim().strip_prefix("bytes").unwrap_or(v).trim();
+    let (range, size) = spec.split_once('/').unwrap_or((spec, "*"));
+    let start = range.split_once('-').and_then(|(s, _)| s.parse().ok());
+    (start, size.parse().ok())
+}
+
+/// Expected digest from --sha256 / --md5, lowercased; `hex_len` is the digest size in hex digits.
+fn expected_digest(flag: &str, value: Option<&str>, hex_len: usize) -> Result<Option<String>> {
+    let Some(v) = value else { return Ok(None) };
+    let v = v.trim().to_ascii_lowercase();
+    if v.len() != hex_len || !v.bytes().all(|b| b.is_ascii_hexdigit()) {
+        bail!("--{flag} must be {hex_len} hex digits");
+    }
+    Ok(Some(v))
+}
+
+/// Digests of a --save download, updated chunk by chunk while it streams.
+#[derive(Default)]
+struct Checksums {
+    sha256: Option<Sha256>,
+    md5: Option<Md5>,
+}
+
+impl Checksums {
+    fn new(args: &RequestArgs) -> Self {
+        Checksums {
+            sha256: (args.sha256.is_some() || args.print_checksum).then(Sha256::new),
+            md5: args.md5.is_some().then(Md5::new),
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.sha256.is_some() || self.md5.is_some()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        if let Some(h) = &mut self.sha256 {
+            h.update(data);
+        }
+        if let Some(h) = &mut self.md5 {
+            h.update(data);
+        }
+    }
+
+    /// Feed the bytes already on disk, for a --resume that appends to `path`.
+    fn update_from_file(&mut self, path: &std::path::Path) -> Result<()> {
+        let mut file = fs::File::open(path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            match file.read(&mut buf)? {
+                0 => return Ok(()),
+                n => self.update(&buf[..n]),
+            }
+        }
+    }
+
+    /// (label, expected, actual) for each computed digest.
+    fn finish(self, args: &RequestArgs) -> Result<Vec<(&'static str, Option<String>, String)>> {
+        let mut out = Vec::new();
+        if let Some(h) = self.sha256 {
+            let expected = expected_digest("sha256", args.sha256.as_deref(), 64)?;
+            out.push(("SHA-256", expected, hex::encode(&h.finalize())));
+        }
+        if let Some(h) = self.md5 {
+            let expected = expected_digest("md5", args.md5.as_deref(), 32)?;
+            out.push(("MD5", expected, hex::encode(&h.finalize())));
+        }
+        Ok(out)
+    }
+}
+
+/// Apply --proxy / --no-proxy. Without either, reqwest already honors
+/// HTTP_PROXY, HTTPS_PROXY, ALL_PROXY, and NO_PROXY from the environment.
+fn with_proxy(builder: ClientBuilder, args: &RequestArgs) -> Result<ClientBuilder> {
+    if args.no_proxy {
+        return Ok(builder.no_proxy());
+    }
+    let Some(url) = &args.proxy else {
+        return Ok(builder);
+    };
+    let proxy = Proxy::all(url.as_str())
+        .with_context(|| format!("Invalid proxy URL: {url}"))?
+        .no_proxy(NoProxy::from_env());
+    if let Ok(u) = Url::parse(url) {
+        info!(
+            "Using proxy {}://{}",
+            u.scheme(),
+            u.host_str().unwrap_or_default()
+        );
+    }
+    Ok(builder.proxy(proxy))
+}
+
+/// Apply --insecure, --cacert, and --cert/--key to the client.
+fn with_tls(mut builder: ClientBuilder, args: &RequestArgs) -> Result<ClientBuilder> {
+    if args.insecure {
+        style::warn_line("TLS certificate verification is disabled (--insecure)");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(path) = &args.cacert {
+        let pem = fs::read(path)
+            .with_context(|| format!("Failed to read CA bundle: {}", path.display()))?;
+        let certs = Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid PEM in CA bundle: {}", path.display()))?;
+        if certs.is_empty() {
+            bail!("No certificates found in {}", path.display());
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+    if let Some(path) = &args.cert {
+        let mut pem = fs::read(path)
+            .with_context(|| format!("Failed to read client certificate: {}", path.display()))?;
+        if let Some(key) = &args.key {
+            pem.push(b'\n');
+            pem.extend(
+                fs::read(key)
+                    .with_context(|| format!("Failed to read client key: {}", key.display()))?,
+            );
+        }
+        let identity = Identity::from_pem(&pem).with_context(|| {
+            format!(
+                "Invalid client certificate/key in {} (PEM certificate plus private key expected)",
+                path.display()
+            )
+        })?;
+        builder = builder.identity(identity);
+    }
+    Ok(builder)
+}
+
+/// Install the --resolve overrides that apply to `urls`; like curl, an entry
+/// whose host and port match none of them is ignored.
+fn with_resolve(
+    builder: ClientBuilder,
+    args: &RequestArgs,
+    urls: &[&Url],
+) -> Result<ClientBuilder> {
+    if args.resolve.is_empty() {
+        return Ok(builder);
+    }
+    let mut overrides = Vec::new();
+    for spec in &args.resolve {
+        let o = resolve::Override::parse(spec)?;
+        if urls.iter().any(|u| o.applies_to(u)) {
+            info!("Resolving {}:{} to {:?}", o.host, o.port, o.addrs);
+            overrides.push(o);
+        } else {
+            style::warn_line(&format!(
+                "--resolve {spec} matches no request host and port; ignored"
+            ));
+        }
+    }
+    Ok(builder.dns_resolver(Arc::new(resolve::StaticResolver::new(overrides))))
+}
+
+/// Send every request over --unix-socket instead of TCP.
+fn with_unix_socket(builder: ClientBuilder, args: &RequestArgs) -> Result<ClientBuilder> {
+    let Some(path) = &args.unix_socket else {
+        return Ok(builder);
+    };
+    if !path.exists() {
+        bail!("Unix socket not found: {}", path.display());
+    }
+    info!("Connecting through {}", path.display());
+    #[cfg(unix)]
+    return Ok(builder.unix_socket(path.as_path()));
+    #[cfg(not(unix))]
+    bail!("--unix-socket is only supported on Unix");
+}
+
+/// Overall request timeout: --timeout, else 30 s unless --read-timeout bounds the stream instead.
+fn overall_timeout(args: &RequestArgs) -> Option<Duration> {
+    match (args.timeout, args.read_timeout) {
+        (Some(t), _) => Some(Duration::from_secs(t)),
+        (None, Some(_)) => None,
+        (None, None) => Some(Duration::from_secs(30)),
+    }
+}
+
+/// Apply the overall timeout plus --connect-timeout and --read-timeout.
+fn with_timeouts(mut builder: ClientBuilder, args: &RequestArgs) -> ClientBuilder {
+    if let Some(t) = overall_timeout(args) {
+        builder = builder.timeout(t);
+    }
+    if let Some(t) = args.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(t));
+    }
+    if let Some(t) = args.read_timeout {
+        builder = builder.read_timeout(Duration::from_secs(t));
+    }
+    builder
+}
+
+/// Apply --http1.1 / --http2. Without either, HTTP/2 is used when ALPN offers it.
+/// --http3 fails: the HTTP client only has HTTP/3 behind an unstable build flag.
+fn with_version(builder: ClientBuilder, args: &RequestArgs) -> Result<ClientBuilder> {
+    if args.http3 {
+        bail!(
+            "--http3 is not supported yet; use --http2, or --curl for a curl command with --http3"
+        );
+    }
+    Ok(if args.http1_1 {
+        builder.http1_only()
+    } else if args.http2 {
+        builder.http2_prior_knowledge()
+    } else {
+        builder
+    })
+}
+
+/// Protocol name as shown on the status line.
+pub(crate) fn version_label(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_11 => "HTTP/1.1",
+        Version::HTTP_2 => "HTTP/2",
+        Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP/?",
+    }
+}
+
+/// The --save-headers record: final URL, status, protocol, and headers (a name
+/// sent more than once maps to an array), plus the bytes in the saved file.
+fn header_record(
+    url: &Url,
+    status: StatusCode,
+    version: Version,
+    hdrs: &HeaderMap,
+    bytes: u64,
+) -> Value {
+    let mut map = serde_json::Map::new();
+    for name in hdrs.keys() {
+        let values: Vec<Value> = hdrs
+            .get_all(name)
+            .iter()
+            .map(|v| Value::String(String::from_utf8_lossy(v.as_bytes()).into_owned()))
+            .collect();
+        let value = match <[Value; 1]>::try_from(values) {
+            Ok([one]) => one,
+            Err(many) => Value::Array(many),
+        };
+        map.insert(name.to_string(), value);
+    }
+    serde_json::json!({
+        "url": url.as_str(),
+        "status": status.as_u16(),
+        "version": version_label(version),
+        "headers": map,
+        "bytes": bytes,
+    })
+}
+
+/// `<file>.headers` next to a --save file.
+fn headers_path(path: &std::path::Path) -> std::path::PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".headers");
+    name.into()
+}
+
+/// Body as text, decoded by the Content-Type charset (UTF-8 by default) like reqwest's `text()`.
+fn decode_text(hdrs: &HeaderMap, body: &[u8]) -> String {
+    let charset = hdrs
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|ct| {
+            ct.split(';').skip(1).find_map(|param| {
+                let (k, v) = param.split_once('=')?;
+                k.trim()
+                    .eq_ignore_ascii_case("charset")
+                    .then(|| v.trim().trim_matches('"'))
+            })
+        });
+    let encoding = charset
+        .and_then(|c| Encoding::for_label(c.as_bytes()))
+        .unwrap_or(UTF_8);
+    encoding.decode(body).0.into_owned()
+}
+
+/// Print the status line with the negotiated protocol, preceded by the annotated
+/// header table when requested and followed by the raw headers for -i/--headers-only.
+/// Unfollowed redirects otherwise show where they point.
+pub(crate) fn print_status(
+    status: reqwest::StatusCode,
+    proto: &str,
+    hdrs: &HeaderMap,
+    table: Option<(&HeaderMap, bool)>,
+    list: bool,
+) {
+    if let Some((hdrs, https)) = table {
+        headers::print_table(hdrs, https);
+    }
+    println!(
+        "{} {} {}",
+        "Status:".bold(),
+        status.to_string().green().bold(),
+        proto.dimmed()
+    );
+    if list {
+        for (name, value) in hdrs {
+            style::header(name.as_str(), &String::from_utf8_lossy(value.as_bytes()));
+        }
+        println!();
+    } else if status.is_redirection() {
+        if let Some(loc) = hdrs.get(LOCATION).and_then(|v| v.to_str().ok()) {
+            println!("{} {loc}", "Location:".bold());
+        }
+    }
+}
+
+/// Headers from -H plus the auth flags; an explicit -H "Authorization: ..." wins.
+pub(crate) fn request_headers(args: &RequestArgs) -> Result<HeaderMap> {
+    let mut hdrs = parse_headers(&args.headers)?;
+    if !hdrs.contains_key(AUTHORIZATION) {
+        if let Some(auth) = auth_header(args, std::env::var(TOKEN_VAR).ok())? {
+            hdrs.insert(AUTHORIZATION, auth);
+        }
+    }
+    if args.compressed && !hdrs.contains_key(ACCEPT_ENCODING) {
+        hdrs.insert(
+            ACCEPT_ENCODING,
+            HeaderValue::from_static(compression::ACCEPT),
+        );
+    }
+    Ok(hdrs)
+}
+
+/// Client honoring the shared request flags. --cookie pairs are scoped to each of `urls`.
+pub(crate) fn build_client(
+    args: &RequestArgs,
+    urls: &[&Url],
+) -> Result<(Client, Option<Arc<cookies::Jar>>)> {
+    let mut builder = with_timeouts(Client::builder(), args).redirect(redirect_policy(args));
+    builder = with_proxy(builder, args)?;
+    builder = with_resolve(builder, args, urls)?;
+    builder = with_unix_socket(builder, args)?;
+    builder = with_tls(builder, args)?;
+    builder = with_version(builder, args)?;
+    // Cookies are only tracked when asked for, so plain requests stay stateless.
+    let jar = if args.cookie_jar.is_some() || !args.cookie.is_empty() {
+        let jar = Arc::new(cookies::Jar::load(args.cookie_jar.as_deref())?);
+        for url in urls {
+            for pair in &args.cookie {
+                jar.add_pair(pair, url)?;
+            }
+        }
+        builder = builder.cookie_provider(jar.clone());
+        Some(jar)
+    } else {
+        None
+    };
+    Ok((builder.build()?, jar))
+}
+
+pub(crate) fn retry_policy(args: &RequestArgs) -> Result<RetryPolicy> {
+    Ok(RetryPolicy {
+        retries: args.retry,
+        base: Duration::try_from_secs_f64(args.retry_delay)
+            .context("--retry-delay must be a non-negative number of seconds")?,
+        statuses: args.retry_on_status.clone(),
+    })
+}
+
+/// Fill `{{NAME}}` placeholders in the URL, headers, and inline body (--data and
+/// --form values) from --var and the environment. --data-file bodies are sent as is.
+fn fill_templates(
+    url: &str,
+    body: Option<&BodyArgs>,
+    args: &mut RequestArgs,
+) -> Result<(String, Option<BodyArgs>)> {
+    let vars = template::parse_vars(&args.vars)?;
+    let fill = |text: &String| template::substitute(text, &vars);
+    for h in &mut args.headers {
+        *h = fill(h)?;
+    }
+    let body = match body {
+        Some(b) => Some(BodyArgs {
+            data: b.data.as_ref().map(fill).transpose()?,
+            data_file: b.data_file.clone(),
+            form: b.form.iter().map(fill).collect::<Result<_>>()?,
+            form_file: b.form_file.clone(),
+        }),
+        None => None,
+    };
+    Ok((template::substitute(url, &vars)?, body))
+}
+
+/// Send `method` to `url` with an optional body; Content-Type is inferred for JSON unless set via -H.
+/// With a `cache` policy the response is stored and revalidated (see `helpers::http_cache`).
+pub async fn run(
+    method: Method,
+    url: &str,
+    body: Option<&BodyArgs>,
+    mut args: RequestArgs,
+    cache: Option<http_cache::Policy>,
+) -> Result<()> {
+    let (url, body) = fill_templates(url, body, &mut args)?;
+    let (url, body) = (url.as_str(), body.as_ref());
+    if args.curl {
+        return http_curl::print(&method, url, body, &args);
+    }
+    let parsed = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let mut hdrs = request_headers(&args)?;
+    let template = args.format.as_deref().map(Template::parse).transpose()?;
+
+    let cached = match &cache {
+        Some(_) => http_cache::load(&parsed)?,
+        None => None,
+    };
+    if let (Some(policy), Some(entry)) = (&cache, &cached) {
+        let now = pace::now_epoch();
+        let age = entry.age(now);
+        if entry.is_fresh(policy, now) {
+            info!("Cached response is {age}s old; no request sent");
+            let https = parsed.scheme() == "https";
+            let proto = format!("cached {age}s ago");
+            return print_body(
+                entry.status,
+                &proto,
+                &entry.headers,
+                https,
+                &entry.body,
+                &args,
+            );
+        }
+        entry.add_validators(&mut hdrs);
+    }
+    let (client, jar) = build_client(&args, &[&parsed])?;
+    let mut req = client.request(method.clone(), parsed.clone());
+
+    // Multipart uploads show a byte progress bar instead of the spinner.
+    let mut pb: Option<ProgressBar> = None;
+    match body {
+        Some(b) if !b.form_file.is_empty() => {
+            let files = b
+                .form_file
+                .iter()
+                .map(|f| form::parse_file_field(f))
+                .collect::<Result<Vec<_>>>()?;
+            let total = form::total_size(&files).await?;
+            let bar = spinner::bar(total);
+            req = req.multipart(form::multipart(form::parse_fields(&b.form)?, files, &bar).await?);
+            info!("{method} {parsed} (multipart, {total} byte(s) of files)");
+            pb = Some(bar);
+        }
+        Some(b) if !b.form.is_empty() => {
+            let encoded = form::urlencoded(&form::parse_fields(&b.form)?);
+            if !hdrs.contains_key(CONTENT_TYPE) {
+                hdrs.insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/x-www-form-urlencoded"),
+                );
+            }
+            info!("{method} {parsed} ({} byte form)", encoded.len());
+            req = req.body(encoded);
+        }
+        Some(b) => {
+            let payload = read_body(b, takes_stdin_body(&method))?;
+            if !hdrs.contains_key(CONTENT_TYPE) && looks_like_json(&payload) {
+                hdrs.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            }
+            if payload.is_empty() {
+                info!("{method} {parsed}");
+            } else {
+                info!("{method} {parsed} ({} byte body)", payload.len());
+                req = req.body(payload);
+            }
+        }
+        None => info!("{method} {parsed}"),
+    }
+
+    let policy = retry_policy(&args)?;
+    expected_digest("sha256", args.sha256.as_deref(), 64)?;
+    expected_digest("md5", args.md5.as_deref(), 32)?;
+    // --resume asks only for the bytes the partial file is missing.
+    let offset = match (&args.save, args.resume) {
+        (Some(path), true) => fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+        _ => 0,
+    };
+    if offset > 0 && !hdrs.contains_key(RANGE) {
+        info!("Resuming from byte {offset}");
+        hdrs.insert(RANGE, HeaderValue::from_str(&format!("bytes={offset}-"))?);
+    }
+
+    // Load the signature and key up front so a bad --verify-key fails before the download.
+    let verifier = match (&args.verify_sig, &args.verify_key) {
+        (Some(sig), Some(key)) => {
+            let sig_bytes = if sig.starts_with("http://") || sig.starts_with("https://") {
+                info!("Fetching signature {sig}");
+                let resp = client.get(sig.as_str()).send().await?;
+                if !resp.status().is_success() {
+                    bail!("Failed to fetch signature {sig}: {}", resp.status());
+                }
+                resp.bytes().await?.to_vec()
+            } else {
+                fs::read(sig).with_context(|| format!("Failed to read signature: {sig}"))?
+            };
+            let key_bytes = fs::read(key)
+                .with_context(|| format!("Failed to read public key: {}", key.display()))?;
+            Some(Verifier::new(&sig_bytes, &key_bytes)?)
+        }
+        _ => None,
+    };
+
+    let pb = pb.unwrap_or_else(|| spinner("Requesting..."));
+    let budget = args.max_duration;
+    let started = Instant::now();
+    let resp = retry::send(req.headers(hdrs), &policy, &pb).await?;
+    if let Some(jar) = jar {
+        jar.save()?;
+    }
+    match (template, cache) {
+        (Some(template), _) => respond_format(resp, pb, &method, &template, started, &args).await?,
+        (None, Some(policy)) => respond_cached(resp, pb, args, cached, policy).await?,
+        (None, None) => respond(resp, pb, args, offset, verifier).await?,
+    }
+    check_budget(started, budget)
+}
+
+/// Print the `--format` line for a response in place of the response itself.
+async fn respond_format(
+    resp: reqwest::Response,
+    pb: ProgressBar,
+    method: &Method,
+    template: &Template,
+    started: Instant,
+    args: &RequestArgs,
+) -> Result<()> {
+    let status = resp.status();
+    let url = resp.url().clone();
+    let resp_headers = resp.headers().clone();
+    let body = resp.bytes().await.context("Error reading response body")?;
+    let elapsed = started.elapsed();
+    pb.finish_and_clear();
+    let body = decoded_body(&resp_headers, body.to_vec()).await?;
+    let line = template.render(&write_out::Response {
+        method,
+        url: &url,
+        status,
+        headers: &resp_headers,
+        body: &body,
+        elapsed,
+    });
+    println!("{line}");
+    if args.copy {
+        clipboard::copy(&line)?;
+    }
+    if args.pace {
+        pace::pace(&resp_headers, status).await;
+    }
+    Ok(())
+}
+
+/// Exit code for a request that finished but exceeded `--max-duration`.
+pub const EXIT_OVER_BUDGET: u8 = 3;
+
+/// Fail with [`OverBudget`] when more than `budget` has passed since `started`.
+pub(crate) fn check_budget(started: Instant, budget: Option<Duration>) -> Result<()> {
+    match budget {
+        Some(budget) if started.elapsed() > budget => Err(OverBudget {
+            elapsed: started.elapsed(),
+            budget,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// The request succeeded but took longer than its `--max-duration` budget.
+#[derive(Debug)]
+pub struct OverBudget {
+    elapsed: Duration,
+    budget: Duration,
+}
+
+impl std::fmt::Display for OverBudget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Request took {}ms, over the --max-duration budget of {}ms",
+            self.elapsed.as_millis(),
+            self.budget.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for OverBudget {}
+
+/// Print a response for `http get --cache`: a 304 serves the stored body, a
+/// storable 200 replaces it.
+async fn respond_cached(
+    resp: reqwest::Response,
+    pb: ProgressBar,
+    args: RequestArgs,
+    cached: Option<Entry>,
+    policy: http_cache::Policy,
+) -> Result<()> {
+    let status = resp.status();
+    let url = resp.url().clone();
+    let https = url.scheme() == "https";
+    let proto = version_label(resp.version());
+    let resp_headers = resp.headers().clone();
+    let now = pace::now_epoch();
+
+    let (entry, proto) = match cached {
+        Some(mut entry) if status == StatusCode::NOT_MODIFIED => {
+            pb.finish_and_clear();
+            info!("Not modified; serving the cached body");
+            entry.revalidate(&resp_headers, now);
+            http_cache::save(&url, &entry)?;
+            (entry, format!("{proto}, cached body"))
+        }
+        _ => {
+            let body = resp.bytes().await.context("Error reading response body")?;
+            pb.finish_and_clear();
+            let entry = Entry {
+                status,
+                headers: resp_headers.clone(),
+                stored_at: now,
+                body: body.to_vec(),
+            };
+            if entry.is_storable(&policy) {
+                http_cache::save(&url, &entry)?;
+            } else {
+                info!("Response not cached (needs a 200 with ETag/Last-Modified, or --cache-ttl)");
+            }
+            (entry, proto.to_string())
+        }
+    };
+    // The cache keeps the body as received; decode it for display.
+    let body = decoded_body(&entry.headers, entry.body).await?;
+    print_body(status, &proto, &entry.headers, https, &body, &args)?;
+    if args.pace {
+        pace::pace(&resp_headers, status).await;
+    }
+    Ok(())
+}
+
+/// A fully read body with its Content-Encoding removed, logging both sizes.
+async fn decoded_body(hdrs: &HeaderMap, body: Vec<u8>) -> Result<Vec<u8>> {
+    match Coding::of(hdrs) {
+        Some(coding) => {
+            let decoded = compression::decode(coding, &body).await?;
+            compression::log_sizes(coding, body.len() as u64, decoded.len() as u64);
+            Ok(decoded)
+        }
+        None => Ok(body),
+    }
+}
+
+/// Whether a Content-Type is newline-delimited JSON (NDJSON or JSON Lines).
+fn is_ndjson(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or_default().trim();
+    [
+        "application/x-ndjson",
+        "application/ndjson",
+        "application/jsonl",
+        "application/x-jsonlines",
+        "application/jsonlines",
+    ]
+    .iter()
+    .any(|m| mime.eq_ignore_ascii_case(m))
+}
+
+/// Print one NDJSON record: pretty on a terminal or with --pretty, otherwise
+/// compact so piped output stays one record per line. Lines that are not JSON
+/// are printed as they are rather than ending the stream.
+fn print_ndjson_line(line: &[u8], pretty: bool) -> Result<()> {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim();
+    if line.is_empty() {
+        return Ok(());
+    }
+    match serde_json::from_str::<Value>(line) {
+        Ok(v) if pretty || atty::is(Stream::Stdout) => {
+            println!("{}", colored_json::to_colored_json_auto(&v)?)
+        }
+        Ok(v) => println!("{v}"),
+        Err(_) => println!("{line}"),
+    }
+    Ok(())
+}
+
+/// Print the status line and a fully read body, pretty-printing JSON with --pretty
+/// (cut to a few entries per level with --preview).
+fn print_body(
+    status: StatusCode,
+    proto: &str,
+    hdrs: &HeaderMap,
+    https: bool,
+    body: &[u8],
+    args: &RequestArgs,
+) -> Result<()> {
+    let table = args.headers_table.then_some((hdrs, https));
+    let list = args.include || args.headers_only;
+    let content_type = hdrs
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    if args.ndjson || is_ndjson(content_type) {
+        print_status(status, proto, hdrs, table, list);
+        for line in body.split(|&b| b == b'\n') {
+            print_ndjson_line(line, args.pretty)?;
+        }
+        if args.copy {
+            clipboard::copy(&decode_text(hdrs, body))?;
+        }
+    } else if (args.pretty || args.preview.is_some()) && content_type.contains("application/json") {
+        let body: Value = serde_json::from_slice(body)
+            .with_context(|| format!("Failed to parse JSON (status {status})"))?;
+        print_status(status, proto, hdrs, table, list);
+
+        // Auto-colored JSON (disables colors when not a TTY).
+        let pretty_colored = match args.preview {
+            Some(limit) => preview::render(&body, limit)?,
+            None => colored_json::to_colored_json_auto(&body)?,
+        };
+        println!("{pretty_colored}");
+        if args.copy {
+            clipboard::copy(&strip_ansi(&pretty_colored))?;
+        }
+    } else {
+        let mut text = decode_text(hdrs, body);
+        // XML, HTML, YAML, and JavaScript get colors on a terminal only.
+        if style::is_tty() {
+            if let Some(lang) = highlight::detect(content_type, &text) {
+                text = highlight::highlight(lang, &text);
+            }
+        }
+        print_status(status, proto, hdrs, table, list);
+        // HEAD and 204 responses have no body worth a blank line.
+        if !text.is_empty() {
+            println!("{text}");
+        }
+        if args.copy {
+            clipboard::copy(&strip_ansi(&text))?;
+        }
+    }
+    Ok(())
+}
+
+/// Print (or save) a response: status, optional header table, body, then pacing.
+/// `pb` is the request spinner, cleared once the body has been read. `offset` is
+/// the length of the partial --save file a --resume request asked to continue;
+/// `verifier` checks the saved file's --verify-sig signature.
+async fn respond(
+    resp: reqwest::Response,
+    pb: ProgressBar,
+    args: RequestArgs,
+    offset: u64,
+    verifier: Option<Verifier>,
+) -> Result<()> {
+    let status = resp.status();
+    let version = resp.version();
+    let final_url = resp.url().clone();
+    let https = final_url.scheme() == "https";
+    let resp_headers = resp.headers().clone();
+    let table = args.headers_table.then_some((&resp_headers, https));
+    let list = args.include || args.headers_only;
+
+    // If saving to file, stream bytes with a progress indicator.
+    if let Some(path) = &args.save {
+        let (range_start, range_size) = content_range(&resp_headers);
+        if offset > 0 && status == StatusCode::RANGE_NOT_SATISFIABLE {
+            pb.finish_and_clear();
+            print_status(status, version_label(version), &resp_headers, table, list);
+            match range_size {
+                Some(size) if size == offset => {
+                    style::ok(&format!("Already complete: {}", path.display()));
+                    return Ok(());
+                }
+                Some(size) => bail!(
+                    "Cannot resume: {} is {offset} bytes but the server has {size}",
+                    path.display()
+                ),
+                None => bail!("Cannot resume: server rejected the range"),
+            }
+        }
+
+        // Append only when the server honored the range; a 200 means start over.
+        let start = if status == StatusCode::PARTIAL_CONTENT && offset > 0 {
+            if range_start != Some(offset) {
+                bail!("Server resumed at an unexpected offset ({range_start:?}, wanted {offset})");
+            }
+            offset
+        } else {
+            if offset > 0 {
+                style::warn_line("Server ignored the range request; downloading from the start");
+            }
+            0
+        };
+        let mut file = if start > 0 {
+            OpenOptions::new().append(true).open(&path).await
+        } else {
+            File::create(&path).await
+        }
+        .with_context(|| format!("Cannot create file: {}", path.display()))?;
+
+        let coding = Coding::of(&resp_headers).filter(|_| !args.no_decompress);
+        if let (Some(c), true) = (coding, start > 0) {
+            bail!(
+                "Cannot resume a {}-encoded download into decoded bytes; use --no-decompress",
+                c.name()
+            );
+        }
+        let mut sums = Checksums::new(&args);
+        if start > 0 && sums.enabled() {
+            sums.update_from_file(path)?;
+        }
+
+        let total = resp.content_length().map(|n| n + start);
+        let mut downloaded: u64 = start;
+        let wire = Arc::new(AtomicU64::new(0));
+        let mut body = compression::body_reader(resp, coding, wire.clone());
+        let mut buf = vec![0u8; 64 * 1024];
+
+        let pbar = match total {
+            Some(t) => {
+                let bar = spinner::bar(t);
+                bar.set_position(start);
+                bar
+            }
+            None => spinner("Downloading..."),
+        };
+
+        // Progress follows the bytes on the wire, which Content-Length counts.
+        loop {
+            let n = body
+                .read(&mut buf)
+                .await
+                .context("Error reading response stream")?;
+            if n == 0 {
+                break;
+            }
+            file.write_all(&buf[..n]).await?;
+            sums.update(&buf[..n]);
+            downloaded += n as u64;
+            if total.is_some() {
+                pbar.set_position(start + wire.load(Ordering::Relaxed));
+            }
+        }
+        if let Some(c) = coding {
+            compression::log_sizes(c, wire.load(Ordering::Relaxed), downloaded);
+        }
+
+        pbar.finish_and_clear();
+        pb.finish_and_clear();
+
+        file.flush().await?;
+        drop(file);
+
+        print_status(status, version_label(version), &resp_headers, table, list);
+        let sums = sums.finish(&args)?;
+        for (label, expected, actual) in &sums {
+            if let Some(want) = expected.as_ref().filter(|w| *w != actual) {
+                // A corrupt file must not be mistaken for a good one (or resumed later).
+                fs::remove_file(path).ok();
+                bail!(
+                    "{label} mismatch for {}: expected {want}, got {actual} (file removed)",
+                    path.display()
+                );
+            }
+        }
+        let signature = match &verifier {
+            Some(v) => match v.verify_file(path) {
+                Ok(msg) => Some(msg),
+                Err(e) => {
+                    fs::remove_file(path).ok();
+                    bail!("{e} (file removed)");
+                }
+            },
+            None => None,
+        };
+        style::ok(&format!("Saved to: {}", path.display()));
+        if args.save_headers {
+            let record = header_record(&final_url, status, version, &resp_headers, downloaded);
+            let meta = headers_path(path);
+            fs::write(&meta, serde_json::to_string_pretty(&record)? + "\n")
+                .with_context(|| format!("Cannot write {}", meta.display()))?;
+            style::ok(&format!("Headers saved to: {}", meta.display()));
+        }
+        for (label, expected, actual) in sums {
+            if expected.is_some() {
+                style::ok(&format!("{label} verified: {actual}"));
+            } else {
+                println!("{} {actual}", format!("{label}:").bold());
+            }
+        }
+        if let Some(msg) = signature {
+            style::ok(&msg);
+        }
+        if args.pace {
+            pace::pace(&resp_headers, status).await;
+        }
+        return Ok(());
+    }
+
+    // Not saving: pretty-print JSON or print plain text.
+    if args.headers_only {
+        // The body is never read; dropping the response closes the connection.
+        pb.finish_and_clear();
+        print_status(status, version_label(version), &resp_headers, table, list);
+    } else if args.ndjson
+        || resp_headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(is_ndjson)
+    {
+        // Streams can stay open indefinitely: print each record as its line completes.
+        pb.finish_and_clear();
+        print_status(status, version_label(version), &resp_headers, table, list);
+        let coding = Coding::of(&resp_headers);
+        let wire = Arc::new(AtomicU64::new(0));
+        let mut body = compression::body_reader(resp, coding, wire.clone());
+        let mut buf = vec![0u8; 16 * 1024];
+        let mut pending: Vec<u8> = Vec::new();
+        let mut copied: Vec<u8> = Vec::new();
+        loop {
+            let n = body
+                .read(&mut buf)
+                .await
+                .context("Error reading response stream")?;
+            if n == 0 {
+                break;
+            }
+            if args.copy {
+                copied.extend_from_slice(&buf[..n]);
+            }
+            pending.extend_from_slice(&buf[..n]);
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                print_ndjson_line(&line, args.pretty)?;
+            }
+        }
+        print_ndjson_line(&pending, args.pretty)?;
+        if args.copy {
+            clipboard::copy(&decode_text(&resp_headers, &copied))?;
+        }
+    } else {
+        let body = resp.bytes().await.context("Error reading response body")?;
+        pb.finish_and_clear();
+        let body = decoded_body(&resp_headers, body.to_vec()).await?;
+        print_body(
+            status,
+            version_label(version),
+            &resp_headers,
+            https,
+            &body,
+            &args,
+        )?;
+    }
+
+    // Sleep before exiting so the next scripted call stays under the limit.
+    if args.pace {
+        pace::pace(&resp_headers, status).await;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_over_budget_message() {
+        let e = OverBudget {
+            elapsed: Duration::from_millis(1250),
+            budget: Duration::from_millis(800),
+        };
+        assert_eq!(
+            e.to_string(),
+            "Request took 1250ms, over the --max-duration budget of 800ms"
+        );
+        let err: anyhow::Error = e.into();
+        assert!(err.downcast_ref::<OverBudget>().is_some());
+    }
+
+    #[test]
+    fn test_looks_like_json() {
+        assert!(looks_like_json(br#"{"a": 1}"#));
+        assert!(looks_like_json(b" [1, 2] "));
+        assert!(!looks_like_json(b"a=1&b=2"));
+        assert!(!looks_like_json(b"42"));
+        assert!(!looks_like_json(b""));
+    }
+
+    #[test]
+    fn test_is_ndjson() {
+        assert!(is_ndjson("application/x-ndjson"));
+        assert!(is_ndjson("application/jsonl; charset=utf-8"));
+        assert!(is_ndjson("Application/NDJSON"));
+        assert!(!is_ndjson("application/json"));
+        assert!(!is_ndjson("text/plain"));
+    }
+
+    #[test]
+    fn test_data_takes_priority() {
+        let body = BodyArgs {
+            data: Some("x".into()),
+            data_file: Some("missing".into()),
+            form: Vec::new(),
+            form_file: Vec::new(),
+        };
+        assert_eq!(read_body(&body, true).unwrap(), b"x");
+    }
+
+    #[test]
+    fn test_auth_header() {
+        let basic = parse_args(&["--auth", "aladdin:opensesame"]).unwrap();
+        assert_eq!(
+            auth_header(&basic, Some("env".into())).unwrap().unwrap(),
+            "Basic YWxhZGRpbjpvcGVuc2VzYW1l"
+        );
+        let bearer = parse_args(&["--bearer", "abc"]).unwrap();
+        assert_eq!(
+            auth_header(&bearer, Some("env".into())).unwrap().unwrap(),
+            "Bearer abc"
+        );
+        let none = parse_args(&[]).unwrap();
+        assert_eq!(
+            auth_header(&none, Some("env".into())).unwrap().unwrap(),
+            "Bearer env"
+        );
+        assert!(auth_header(&none, Some(" ".into())).unwrap().is_none());
+        assert!(parse_args(&["--auth", "a:b", "--bearer", "c"]).is_err());
+    }
+
+    #[test]
+    fn test_include_and_headers_only_conflicts() {
+        assert!(parse_args(&["-i", "--headers-only"]).is_err());
+        assert!(parse_args(&["--headers-only", "--save", "out.bin"]).is_err());
+        assert!(parse_args(&["-i"]).unwrap().include);
+        assert!(parse_args(&["--headers-only"]).unwrap().headers_only);
+    }
+
+    #[test]
+    fn test_tls_options() {
+        // --key needs --cert; unreadable or non-PEM files fail before any request.
+        assert!(parse_args(&["--key", "k.pem"]).is_err());
+        let dir = std::env::temp_dir().join(format!("swiftline-tls-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let junk = dir.join("junk.pem");
+        fs::write(&junk, "not a certificate").unwrap();
+        let junk = junk.to_str().unwrap();
+        let tls = |argv: &[&str]| with_tls(Client::builder(), &parse_args(argv).unwrap()).err();
+        assert!(tls(&["-k"]).is_none());
+        assert!(tls(&["--cacert", junk]).is_some());
+        assert!(tls(&["--cert", junk]).is_some());
+        assert!(tls(&["--cacert", "/nonexistent/ca.pem"]).is_some());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_http_version() {
+        for pair in [
+            ["--http1.1", "--http2"],
+            ["--http1.1", "--http3"],
+            ["--http2", "--http3"],
+        ] {
+            assert!(parse_args(&pair).is_err(), "{pair:?}");
+        }
+        assert!(parse_args(&["--http1.1"]).unwrap().http1_1);
+        assert!(with_version(Client::builder(), &parse_args(&["--http2"]).unwrap()).is_ok());
+        assert!(with_version(Client::builder(), &parse_args(&["--http3"]).unwrap()).is_err());
+        assert_eq!(version_label(Version::HTTP_2), "HTTP/2");
+        assert_eq!(version_label(Version::HTTP_11), "HTTP/1.1");
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(
+            expected_digest("md5", Some(" 900150983CD24FB0D6963F7D28E17F72 "), 32).unwrap(),
+            Some("900150983cd24fb0d6963f7d28e17f72".into())
+        );
+        assert!(expected_digest("md5", Some("abc"), 32).is_err());
+        assert!(expected_digest("sha256", Some(&"g".repeat(64)), 64).is_err());
+
+        let args = <RequestArgsWrap as clap::Parser>::try_parse_from([
+            "t",
+            "--save",
+            "out.bin",
+            "--md5",
+            "900150983cd24fb0d6963f7d28e17f72",
+            "--print-checksum",
+        ])
+        .unwrap()
+        .args;
+        // Chunk boundaries don't matter: "a" + "bc" hashes like "abc".
+        let mut sums = Checksums::new(&args);
+        sums.update(b"a");
+        sums.update(b"bc");
+        let out = sums.finish(&args).unwrap();
+        assert_eq!(out[0].0, "SHA-256");
+        assert_eq!(out[0].1, None);
+        assert_eq!(
+            out[0].2,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(out[1].1.as_deref(), Some(out[1].2.as_str()));
+        assert!(
+            <RequestArgsWrap as clap::Parser>::try_parse_from(["t", "--sha256", "ab"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_header_record() {
+        let mut h = HeaderMap::new();
+        h.insert(CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+        h.append("set-cookie", HeaderValue::from_static("a=1"));
+        h.append("set-cookie", HeaderValue::from_static("b=2"));
+        let url = Url::parse("https://cdn.example.com/f.zip").unwrap();
+        let record = header_record(&url, StatusCode::OK, Version::HTTP_2, &h, 42);
+        assert_eq!(
+            record,
+            serde_json::json!({
+                "url": "https://cdn.example.com/f.zip",
+                "status": 200,
+                "version": "HTTP/2",
+                "headers": {"content-type": "application/zip", "set-cookie": ["a=1", "b=2"]},
+                "bytes": 42,
+            })
+        );
+        assert_eq!(
+            headers_path(std::path::Path::new("out/f.zip")),
+            std::path::PathBuf::from("out/f.zip.headers")
+        );
+    }
+
+    #[test]
+    fn test_overall_timeout() {
+        let secs = |argv: &[&str]| overall_timeout(&parse_args(argv).unwrap()).map(|d| d.as_secs());
+        assert_eq!(secs(&[]), Some(30));
+        assert_eq!(secs(&["--connect-timeout", "2"]), Some(30));
+        assert_eq!(secs(&["--read-timeout", "15"]), None);
+        assert_eq!(
+            secs(&["--read-timeout", "15", "--timeout", "600"]),
+            Some(600)
+        );
+    }
+
+    #[derive(clap::Parser)]
+    struct RequestArgsWrap {
+        #[command(flatten)]
+        args: RequestArgs,
+    }
+
+    /// The request flags parsed from `argv` (without a program name).
+    fn parse_args(argv: &[&str]) -> Result<RequestArgs, clap::Error> {
+        <RequestArgsWrap as clap::Parser>::try_parse_from(
+            std::iter::once("t").chain(argv.iter().copied()),
+        )
+        .map(|w| w.args)
+    }
+
+    #[test]
+    fn test_content_range() {
+        let mut h = HeaderMap::new();
+        assert_eq!(content_range(&h), (None, None));
+        h.insert(
+            CONTENT_RANGE,
+            HeaderValue::from_static("bytes 100-199/1000"),
+        );
+        assert_eq!(content_range(&h), (Some(100), Some(1000)));
+        h.insert(CONTENT_RANGE, HeaderValue::from_static("bytes */1000"));
+        assert_eq!(content_range(&h), (None, Some(1000)));
+    }
+
+    #[test]
+    fn test_parse_method() {
+        assert_eq!(parse_method("patch").unwrap(), Method::PATCH);
+        assert_eq!(parse_method("PURGE").unwrap().as_str(), "PURGE");
+        assert!(parse_method("bad method").is_err());
+        assert!(!takes_stdin_body(&Method::DELETE));
+    }
+}
@@ -0,0 +1,448 @@
+//! `http serve`: share a directory over HTTP. A small HTTP/1.1 server on the
+//! tokio runtime: GET and HEAD only, one request per connection, directory
+//! listings (or `index.html`), and Content-Type from the file extension.
+//! Requests never resolve outside the served directory, symlinks included.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use reqwest::StatusCode;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Component, Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::helpers::format::human_size;
+use crate::helpers::{percent, style};
+
+/// Largest request head (request line plus headers) accepted.
+const MAX_HEAD: usize = 16 * 1024;
+
+/// Content-Type for a file, by extension.
+fn mime_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" | "map" => "application/json",
+        "txt" | "log" | "md" | "rs" | "toml" | "yaml" | "yml" | "csv" | "ini" | "sh" => {
+            "text/plain; charset=utf-8"
+        }
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Map a request path onto `root` (already canonical). `None` when it is malformed
+/// or would leave `root`, via `..` or a symlink.
+fn resolve(root: &Path, target: &str) -> Option<PathBuf> {
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    let decoded = percent::decode(path)?;
+    if !decoded.starts_with('/') || decoded.contains('\0') {
+        return None;
+    }
+    let mut out = root.to_path_buf();
+    for comp in Path::new(decoded.trim_start_matches('/')).components() {
+        match comp {
+            Component::Normal(seg) => out.push(seg),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    // A missing file resolves as-is (and 404s); an existing one must stay under root.
+    match out.canonicalize() {
+        Ok(real) if real.starts_with(root) => Some(real),
+        Ok(_) => None,
+        Err(_) => Some(out),
+    }
+}
+
+/// Redirect target adding the trailing slash to a directory path. Leading
+/// slashes collapse to one, so `//host/dir` cannot turn into a protocol-relative
+/// redirect to another host (browsers read `/\` the same way).
+fn dir_location(url_path: &str) -> String {
+    format!("/{}/", url_path.trim_start_matches(['/', '\\']))
+}
+
+/// HTML listing of `dir`, directories first, for the request path `url_path`.
+fn listing(url_path: &str, dir: &Path) -> Result<String> {
+    let mut entries: Vec<(bool, String, u64)> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| {
+            let meta = e.metadata().ok();
+            let is_dir = meta.as_ref().is_some_and(|m| m.is_dir());
+            let size = meta.map(|m| m.len()).unwrap_or(0);
+            (is_dir, e.file_name().to_string_lossy().into_owned(), size)
+        })
+        .collect();
+    entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+    let title = html_escape(url_path);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Index of {title}</title>\
+         <style>body{{font-family:sans-serif;margin:2em}}td{{padding:2px 16px 2px 0}}\
+         td.size{{text-align:right;color:#666}}</style></head>\n\
+         <body><h1>Index of {title}</h1>\n<table>\n"
+    );
+    if url_path != "/" {
+        html.push_str("<tr><td><a href=\"../\">../</a></td><td></td></tr>\n");
+    }
+    for (is_dir, name, size) in entries {
+        let slash = if is_dir { "/" } else { "" };
+        let size = if is_dir {
+            String::new()
+        } else {
+            human_size(size)
+        };
+        html.push_str(&format!(
+            "<tr><td><a href=\"{}{slash}\">{}{slash}</a></td><td class=\"size\">{size}</td></tr>\n",
+            percent::encode(&name),
+            html_escape(&name)
+        ));
+    }
+    html.push_str("</table>\n</body></html>\n");
+    Ok(html)
+}
+
+/// A request line and headers; the body, if any, is read separately.
+#[derive(Debug)]
+pub(crate) struct Head {
+    pub method: String,
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Head {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Request line and headers of a head (without the blank line that ends it).
+pub(crate) fn parse_head(head: &str) -> Result<Head> {
+    let mut lines = head.split("\r\n");
+    let mut parts = lines.next().unwrap_or_default().split(' ');
+    let (method, target) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(m), Some(t), Some(v)) if v.starts_with("HTTP/") => (m.to_string(), t.to_string()),
+        _ => bail!("malformed request line"),
+    };
+    let headers = lines
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let (name, value) = l.split_once(':').context("malformed header line")?;
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Head {
+        method,
+        target,
+        headers,
+    })
+}
+
+/// Read and parse the request head. Returns it with whatever arrived after it
+/// (the start of the body), or `None` if the peer sent nothing.
+pub(crate) async fn read_head(stream: &mut TcpStream) -> Result<Option<(Head, Vec<u8>)>> {
+    let mut buf = Vec::with_capacity(4096);
+    let mut chunk = [0u8; 8192];
+    let head_end = loop {
+        if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i;
+        }
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            if buf.is_empty() {
+                return Ok(None);
+            }
+            bail!("connection closed mid-request");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.len() > MAX_HEAD && !buf[..MAX_HEAD].windows(4).any(|w| w == b"\r\n\r\n") {
+            bail!("request head too large");
+        }
+    };
+    let head = parse_head(&String::from_utf8_lossy(&buf[..head_end]))?;
+    Ok(Some((head, buf.split_off(head_end + 4))))
+}
+
+/// Write a status line and headers; the connection closes after the body.
+pub(crate) async fn write_head(
+    stream: &mut TcpStream,
+    status: StatusCode,
+    headers: &[(&str, String)],
+) -> Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nServer: swiftline\r\nConnection: close\r\n",
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    );
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+    Ok(())
+}
+
+/// Send a small in-memory response.
+async fn send(
+    stream: &mut TcpStream,
+    status: StatusCode,
+    content_type: &str,
+    body: &[u8],
+    head_only: bool,
+    extra: &[(&str, String)],
+) -> Result<u64> {
+    let mut headers = vec![
+        ("Content-Type", content_type.to_string()),
+        ("Content-Length", body.len().to_string()),
+    ];
+    headers.extend(extra.iter().cloned());
+    write_head(stream, status, &headers).await?;
+    if !head_only {
+        stream.write_all(body).await?;
+    }
+    Ok(if head_only { 0 } else { body.len() as u64 })
+}
+
+/// Plain-text error response.
+async fn error(stream: &mut TcpStream, status: StatusCode, head_only: bool) -> Result<u64> {
+    let body = format!("{status}\n");
+    send(
+        stream,
+        status,
+        "text/plain; charset=utf-8",
+        body.as_bytes(),
+        head_only,
+        &[],
+    )
+    .await
+}
+
+/// Answer one request; returns (method, target, status, body bytes) for the access log.
+async fn handle(
+    stream: &mut TcpStream,
+    root: &Path,
+) -> Result<Option<(String, String, StatusCode, u64)>> {
+    let Some((Head { method, target, .. }, _)) = read_head(stream).await? else {
+        return Ok(None);
+    };
+    let head_only = method == "HEAD";
+    let (status, bytes) = if method != "GET" && !head_only {
+        let status = StatusCode::METHOD_NOT_ALLOWED;
+        let body = format!("{status}\n");
+        let extra = [("Allow", "GET, HEAD".to_string())];
+        let n = send(stream, status, "text/plain", body.as_bytes(), false, &extra).await?;
+        (status, n)
+    } else {
+        match resolve(root, &target) {
+            None => (
+                StatusCode::FORBIDDEN,
+                error(stream, StatusCode::FORBIDDEN, head_only).await?,
+            ),
+            Some(path) if path.is_dir() => {
+                let url_path = target.split(['?', '#']).next().unwrap_or("/");
+                if !url_path.ends_with('/') {
+                    // Relative links in the listing need the trailing slash.
+                    let location = [("Location", dir_location(url_path))];
+                    let status = StatusCode::MOVED_PERMANENTLY;
+                    (
+                        status,
+                        send(stream, status, "text/plain", b"", head_only, &location).await?,
+                    )
+                } else if path.join("index.html").is_file() {
+                    serve_file(stream, &path.join("index.html"), head_only).await?
+                } else {
+                    let decoded = percent::decode(url_path).unwrap_or_default();
+                    let html = listing(&decoded, &path)?;
+                    let ct = "text/html; charset=utf-8";
+                    (
+                        StatusCode::OK,
+                        send(stream, StatusCode::OK, ct, html.as_bytes(), head_only, &[]).await?,
+                    )
+                }
+            }
+            Some(path) if path.is_file() => serve_file(stream, &path, head_only).await?,
+            Some(_) => (
+                StatusCode::NOT_FOUND,
+                error(stream, StatusCode::NOT_FOUND, head_only).await?,
+            ),
+        }
+    };
+    stream.shutdown().await.ok();
+    Ok(Some((method, target, status, bytes)))
+}
+
+/// Stream a file from disk.
+async fn serve_file(
+    stream: &mut TcpStream,
+    path: &Path,
+    head_only: bool,
+) -> Result<(StatusCode, u64)> {
+    let Ok(mut file) = tokio::fs::File::open(path).await else {
+        return Ok((
+            StatusCode::FORBIDDEN,
+            error(stream, StatusCode::FORBIDDEN, head_only).await?,
+        ));
+    };
+    let len = file.metadata().await?.len();
+    let headers = [
+        ("Content-Type", mime_type(path).to_string()),
+        ("Content-Length", len.to_string()),
+    ];
+    write_head(stream, StatusCode::OK, &headers).await?;
+    let sent = if head_only {
+        0
+    } else {
+        tokio::io::copy(&mut file, stream).await?
+    };
+    Ok((StatusCode::OK, sent))
+}
+
+fn log_line(peer: SocketAddr, method: &str, target: &str, status: StatusCode, bytes: u64) {
+    let code = style::status_colored(status.as_u16());
+    println!(
+        "{} {method} {target} {code} {}",
+        peer.ip().dimmed(),
+        format!("{bytes}B").dimmed()
+    );
+}
+
+/// Serve `dir` on `bind:port` until interrupted.
+pub async fn run(dir: PathBuf, port: u16, bind: IpAddr) -> Result<()> {
+    let root = dir
+        .canonicalize()
+        .with_context(|| format!("Cannot serve {}", dir.display()))?;
+    if !root.is_dir() {
+        bail!("Not a directory: {}", dir.display());
+    }
+    let addr = SocketAddr::new(bind, port);
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Cannot listen on {addr}"))?;
+    let local = listener.local_addr()?;
+    let shown = if bind.is_unspecified() {
+        format!("http://localhost:{}/", local.port())
+    } else {
+        format!("http://{local}/")
+    };
+    println!(
+        "{} {} at {}  {}",
+        "Serving".bold(),
+        root.display(),
+        shown.cyan(),
+        "(Ctrl-C to stop)".dimmed()
+    );
+
+    loop {
+        let (mut stream, peer) = listener.accept().await?;
+        let root = root.clone();
+        tokio::spawn(async move {
+            match handle(&mut stream, &root).await {
+                Ok(Some((method, target, status, bytes))) => {
+                    log_line(peer, &method, &target, status, bytes)
+                }
+                Ok(None) => {}
+                Err(e) => log::debug!("{peer}: {e:#}"),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_stays_in_root() {
+        let root = std::env::temp_dir().join(format!("swiftline-serve-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("sub dir")).unwrap();
+        std::fs::write(root.join("sub dir/a.txt"), "a").unwrap();
+        let root = root.canonicalize().unwrap();
+
+        assert_eq!(
+            resolve(&root, "/sub%20dir/a.txt?x=1"),
+            Some(root.join("sub dir/a.txt"))
+        );
+        assert_eq!(resolve(&root, "/missing"), Some(root.join("missing")));
+        assert_eq!(resolve(&root, "/../etc/passwd"), None);
+        assert_eq!(resolve(&root, "/sub%20dir/%2E%2E/%2E%2E/x"), None);
+        assert_eq!(resolve(&root, "relative"), None);
+        assert_eq!(resolve(&root, "/bad%zz"), None);
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/", root.join("escape")).unwrap();
+            assert_eq!(resolve(&root, "/escape/etc"), None);
+        }
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_parse_head() {
+        let head = parse_head("GET /a%20b?x=1 HTTP/1.1\r\nHost: localhost\r\nX-Id:  7 ").unwrap();
+        assert_eq!(
+            (head.method.as_str(), head.target.as_str()),
+            ("GET", "/a%20b?x=1")
+        );
+        assert_eq!(head.header("x-id"), Some("7"));
+        assert_eq!(head.header("accept"), None);
+        assert!(parse_head("garbage").is_err());
+        assert!(parse_head("GET / HTTP/1.1\r\nno colon").is_err());
+    }
+
+    #[test]
+    fn test_dir_location() {
+        assert_eq!(dir_location("/sub%20dir"), "/sub%20dir/");
+        assert_eq!(dir_location("//evil.example/x"), "/evil.example/x/");
+        assert_eq!(dir_location("/\\evil.example"), "/evil.example/");
+    }
+
+    #[test]
+    fn test_mime_and_escaping() {
+        assert_eq!(
+            mime_type(Path::new("a/INDEX.HTML")),
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(mime_type(Path::new("app.wasm")), "application/wasm");
+        assert_eq!(mime_type(Path::new("blob")), "application/octet-stream");
+        assert_eq!(
+            html_escape("<a href=\"x\">"),
+            "&lt;a href=&quot;x&quot;&gt;"
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! `http snapshot`: record the inferred shape of a JSON response and report
+//! schema drift (added/removed/type-changed fields) on later runs.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use reqwest::Client;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use url::Url;
+
+use crate::helpers::format::type_name;
+use crate::helpers::{hex, spinner::spinner, style};
+
+/// Flattened schema: path (`items[].name`) → set of JSON type names.
+type Schema = BTreeMap<String, BTreeSet<&'static str>>;
+
+/// Walk a value, recording the type seen at every path. Array elements share `[]`.
+fn infer(value: &Value, path: &str, schema: &mut Schema) {
+    let key = if path.is_empty() { "$" } else { path };
+    schema
+        .entry(key.to_string())
+        .or_default()
+        .insert(type_name(value));
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map {
+                let child = if path.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{path}.{k}")
+                };
+                infer(v, &child, schema);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                infer(item, &format!("{path}[]"), schema);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn infer_schema(value: &Value) -> Schema {
+    let mut schema = Schema::new();
+    infer(value, "", &mut schema);
+    schema
+}
+
+fn types_label(types: &BTreeSet<&str>) -> String {
+    types.iter().copied().collect::<Vec<_>>().join("|")
+}
+
+fn schema_to_json(schema: &Schema) -> Value {
+    let map: Map<String, Value> = schema
+        .iter()
+        .map(|(k, v)| (k.clone(), Value::String(types_label(v))))
+        .collect();
+    Value::Object(map)
+}
+
+/// Load a stored snapshot: `{ "url": ..., "schema": { path: "type|type" } }`.
+fn load(path: &Path) -> Result<BTreeMap<String, String>> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read snapshot: {}", path.display()))?;
+    let doc: Value = serde_json::from_str(&raw)
+        .with_context(|| format!("Corrupt snapshot: {}", path.display()))?;
+    let schema = doc
+        .get("schema")
+        .and_then(Value::as_object)
+        .with_context(|| format!("Snapshot has no schema: {}", path.display()))?;
+    Ok(schema
+        .iter()
+        .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
+        .collect())
+}
+
+/// A single difference between the stored and current schema.
+#[derive(Debug, PartialEq, Eq)]
+enum Drift {
+    Added(String, String),
+    Removed(String, String),
+    Changed(String, String, String),
+}
+
+fn diff(old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> Vec<Drift> {
+    let mut out = Vec::new();
+    for (path, old_t) in old {
+        match new.get(path) {
+            None => out.push(Drift::Removed(path.clone(), old_t.clone())),
+            Some(new_t) if new_t != old_t => {
+                out.push(Drift::Changed(path.clone(), old_t.clone(), new_t.clone()))
+            }
+            _ => {}
+        }
+    }
+    for (path, new_t) in new {
+        if !old.contains_key(path) {
+            out.push(Drift::Added(path.clone(), new_t.clone()));
+        }
+    }
+    out
+}
+
+/// Snapshot file name derived from the URL: readable slug plus a short hash.
+fn snapshot_name(url: &Url) -> String {
+    let slug: String = format!("{}{}", url.host_str().unwrap_or("local"), url.path())
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let digest = Sha256::digest(url.as_str().as_bytes());
+    let short = hex::encode(&digest[..4]);
+    format!("{}_{short}.json", slug.trim_matches('_'))
+}
+
+/// Fetch `url`, infer its schema, and compare with (or create) the stored snapshot.
+pub async fn run(url: &str, store: PathBuf, update: bool, timeout_secs: Option<u64>) -> Result<()> {
+    let parsed = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let client = Client::builder()
+        .timeout(Duration::from_secs(timeout_secs.unwrap_or(30)))
+        .build()?;
+
+    let pb = spinner("Requesting...");
+    let resp = client
+        .get(parsed.clone())
+        .send()
+        .await
+        .context("Network error while sending request")?;
+    let status = resp.status();
+    let body: Value = resp
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse JSON (status {status})"))?;
+    pb.finish_and_clear();
+
+    let schema = schema_to_json(&infer_schema(&body));
+    let current: BTreeMap<String, String> = schema
+        .as_object()
+        .map(|m| {
+            m.iter()
+                .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    fs::create_dir_all(&store)
+        .with_context(|| format!("Cannot create store: {}", store.display()))?;
+    let path = store.join(snapshot_name(&parsed));
+    let save = || -> Result<()> {
+        let doc = serde_json::json!({"url": url, "schema": schema});
+        fs::write(&path, serde_json::to_string_pretty(&doc)? + "\n")
+            .with_context(|| format!("Failed to write snapshot: {}", path.display()))
+    };
+
+    if !path.exists() {
+        save()?;
+        style::ok(&format!(
+            "Saved baseline with {} field(s): {}",
+            current.len(),
+            path.display()
+        ));
+        return Ok(());
+    }
+
+    let drift = diff(&load(&path)?, &current);
+    if drift.is_empty() {
+        style::ok(&format!("No schema drift ({} field(s))", current.len()));
+        return Ok(());
+    }
+
+    style::title(&format!("Schema drift for {url}"));
+    for d in &drift {
+        match d {
+            Drift::Added(p, t) => println!("  {} {p}: {t}", "+".green().bold()),
+            Drift::Removed(p, t) => println!("  {} {p}: {t}", "-".red().bold()),
+            Drift::Changed(p, a, b) => {
+                println!("  {} {p}: {a} → {b}", "~".yellow().bold())
+            }
+        }
+    }
+    if update {
+        save()?;
+        style::ok(&format!("Baseline updated: {}", path.display()));
+        return Ok(());
+    }
+    bail!(
+        "{} schema change(s) detected (use --update to accept)",
+        drift.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn flat(v: &Value) -> BTreeMap<String, String> {
+        infer_schema(v)
+            .iter()
+            .map(|(k, t)| (k.clone(), types_label(t)))
+            .collect()
+    }
+
+    #[test]
+    fn test_infer_merges_array_elements() {
+        let s = flat(&json!({"items": [{"id": 1, "tag": null}, {"id": 2, "tag": "x"}]}));
+        assert_eq!(s["items"], "array");
+        assert_eq!(s["items[].id"], "integer");
+        assert_eq!(s["items[].tag"], "null|string");
+        assert_eq!(s["$"], "object");
+    }
+
+    #[test]
+    fn test_diff_reports_all_kinds() {
+        let old = flat(&json!({"a": 1, "b": "x", "c": true}));
+        let new = flat(&json!({"a": 1.5, "c": true, "d": []}));
+        let d = diff(&old, &new);
+        assert!(d.contains(&Drift::Changed(
+            "a".into(),
+            "integer".into(),
+            "number".into()
+        )));
+        assert!(d.contains(&Drift::Removed("b".into(), "string".into())));
+        assert!(d.contains(&Drift::Added("d".into(), "array".into())));
+        assert_eq!(d.len(), 3);
+    }
+
+    #[test]
+    fn test_snapshot_name_is_stable_and_safe() {
+        let u = Url::parse("https://api.example.com/v1/users?page=2").unwrap();
+        let name = snapshot_name(&u);
+        assert!(name.starts_with("api.example.com_v1_users_"));
+        assert!(name.ends_with(".json"));
+        assert_eq!(name, snapshot_name(&u));
+    }
+}
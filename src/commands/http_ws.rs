@@ -0,0 +1,156 @@
+//! `http ws`: a line-oriented WebSocket client. Each stdin line is sent as a
+//! text message and incoming messages are printed as they arrive (pretty JSON
+//! with `--json`). End of stdin sends a close frame unless `--keep-open`, so
+//! `echo '{"op":"ping"}' | swiftline http ws ...` makes a one-shot request.
+
+use anyhow::{bail, Context, Result};
+use futures::{SinkExt, StreamExt};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use url::Url;
+
+use crate::commands::http_request::parse_headers;
+
+/// `http://` and `https://` URLs are accepted as `ws://` and `wss://`.
+fn ws_url(url: &str) -> Result<Url> {
+    let mut parsed = Url::parse(url).with_context(|| format!("Invalid URL: {url}"))?;
+    let scheme = match parsed.scheme() {
+        "http" => "ws",
+        "https" => "wss",
+        other => other,
+    }
+    .to_string();
+    if !matches!(scheme.as_str(), "ws" | "wss") || parsed.set_scheme(&scheme).is_err() {
+        bail!("Not a WebSocket URL (ws://, wss://, http://, https://): {url}");
+    }
+    Ok(parsed)
+}
+
+/// Printable form of an incoming data message; control frames print nothing.
+fn render(msg: &Message, json: bool) -> Option<String> {
+    match msg {
+        Message::Text(text) => {
+            let pretty = json
+                .then(|| serde_json::from_str::<Value>(text).ok())
+                .flatten()
+                .and_then(|v| colored_json::to_colored_json_auto(&v).ok());
+            Some(pretty.unwrap_or_else(|| text.to_string()))
+        }
+        Message::Binary(data) => Some(
+            format!("[binary, {} bytes]", data.len())
+                .dimmed()
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+/// `1001: restart` style description of a close frame.
+fn close_reason(frame: Option<&CloseFrame>) -> String {
+    match frame {
+        Some(f) if f.reason.is_empty() => u16::from(f.code).to_string(),
+        Some(f) => format!("{}: {}", u16::from(f.code), f.reason),
+        None => "no status".to_string(),
+    }
+}
+
+/// Connect to `url`, pump stdin lines out and incoming messages to stdout until either side closes.
+pub async fn run(
+    url: &str,
+    headers: Vec<String>,
+    json: bool,
+    keep_open: bool,
+    timeout: Option<u64>,
+) -> Result<()> {
+    let target = ws_url(url)?;
+    let mut request = target.as_str().into_client_request()?;
+    request.headers_mut().extend(parse_headers(&headers)?);
+
+    let connect = tokio_tungstenite::connect_async(request);
+    let (ws, resp) = tokio::time::timeout(Duration::from_secs(timeout.unwrap_or(30)), connect)
+        .await
+        .with_context(|| format!("Timed out connecting to {target}"))?
+        .with_context(|| format!("WebSocket handshake with {target} failed"))?;
+    // Status lines go to stderr so stdout carries only messages.
+    eprintln!(
+        "{} {target} {}",
+        "Connected:".bold(),
+        format!("({})", resp.status()).dimmed()
+    );
+
+    let (mut sink, mut stream) = ws.split();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdin_open = true;
+    loop {
+        tokio::select! {
+            line = lines.next_line(), if stdin_open => match line.context("Error reading stdin")? {
+                Some(line) => sink.send(Message::text(line)).await?,
+                None => {
+                    stdin_open = false;
+                    if !keep_open {
+                        // The server answers with its own close frame, which ends the loop.
+                        sink.send(Message::Close(None)).await?;
+                    }
+                }
+            },
+            msg = stream.next() => match msg {
+                None => break,
+                Some(Err(e)) => return Err(e).context("WebSocket connection failed"),
+                Some(Ok(Message::Close(frame))) => {
+                    eprintln!("{} {}", "Closed:".bold(), close_reason(frame.as_ref()));
+                    break;
+                }
+                Some(Ok(msg)) => {
+                    if let Some(text) = render(&msg, json) {
+                        println!("{text}");
+                    }
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::table::strip_ansi;
+    use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+    #[test]
+    fn test_ws_url() {
+        assert_eq!(
+            ws_url("https://ex.com/feed?x=1").unwrap().as_str(),
+            "wss://ex.com/feed?x=1"
+        );
+        assert_eq!(ws_url("ws://localhost:9000").unwrap().scheme(), "ws");
+        assert!(ws_url("ftp://ex.com").is_err());
+    }
+
+    #[test]
+    fn test_render_and_close() {
+        let msg = Message::text(r#"{"a":1}"#);
+        assert_eq!(render(&msg, false).unwrap(), r#"{"a":1}"#);
+        assert_eq!(strip_ansi(&render(&msg, true).unwrap()), "{\n  \"a\": 1\n}");
+        assert_eq!(
+            render(&Message::text("not json"), true).unwrap(),
+            "not json"
+        );
+        assert_eq!(
+            strip_ansi(&render(&Message::binary(vec![0u8; 3]), false).unwrap()),
+            "[binary, 3 bytes]"
+        );
+        assert!(render(&Message::Ping(vec![].into()), false).is_none());
+        let frame = CloseFrame {
+            code: CloseCode::Away,
+            reason: "restart".into(),
+        };
+        assert_eq!(close_reason(Some(&frame)), "1001: restart");
+        assert_eq!(close_reason(None), "no status");
+    }
+}
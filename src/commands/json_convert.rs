@@ -0,0 +1,47 @@
+//! `json convert`: convert JSON from --text, --file, or stdin to YAML, TOML, or
+//! CSV (or back with --from), using the readers and writers of `convert`.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use crate::cli::DataFormat;
+use crate::commands::convert;
+use crate::helpers::style;
+
+/// Input bytes, with the priority of `json select`: --file > --text > stdin.
+fn input_bytes(text: Option<String>, file: Option<PathBuf>) -> Result<Vec<u8>> {
+    if let Some(path) = file {
+        return fs::read(&path).with_context(|| format!("Failed to read file: {}", path.display()));
+    }
+    if let Some(t) = text {
+        return Ok(t.into_bytes());
+    }
+    let mut buf = Vec::new();
+    io::stdin().read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Convert the input from `from` to `to` and print it. With `flatten`, nested
+/// objects in each row become columns joined by `separator` (for CSV).
+pub fn run(
+    text: Option<String>,
+    file: Option<PathBuf>,
+    from: DataFormat,
+    to: DataFormat,
+    flatten: bool,
+    separator: &str,
+) -> Result<()> {
+    let data = input_bytes(text, file)?;
+    let mut value = convert::read(from, &data)?;
+    if flatten {
+        value = convert::flatten_rows(value, separator);
+    }
+    let bytes = convert::write(to, &value)?;
+    if matches!(to, DataFormat::Msgpack) && style::is_tty() {
+        bail!("Refusing to write binary MessagePack to a terminal; redirect the output");
+    }
+    io::stdout().write_all(&bytes)?;
+    Ok(())
+}
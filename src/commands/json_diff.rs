@@ -0,0 +1,231 @@
+//! `json diff`: structural comparison of two JSON documents. Objects are
+//! compared key by key and arrays index by index; the result is a list of
+//! added, removed, and changed paths, or an RFC 6902 JSON Patch with --patch.
+
+use anyhow::{bail, Result};
+use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+use std::path::Path;
+
+use crate::commands::json_select::{push_key, read_json_file};
+use crate::helpers::style;
+
+/// One step from a parent value to a child.
+#[derive(Debug, Clone, PartialEq)]
+enum Seg {
+    Key(String),
+    Index(usize),
+}
+
+/// One difference between the documents, at `path`.
+#[derive(Debug, PartialEq)]
+enum Change {
+    Added(Vec<Seg>, Value),
+    Removed(Vec<Seg>, Value),
+    Changed(Vec<Seg>, Value, Value),
+}
+
+/// Collect the changes that turn `a` into `b`, in an order that is valid as a
+/// patch: removals of trailing array elements run from the highest index down.
+fn diff(a: &Value, b: &Value, path: &mut Vec<Seg>, out: &mut Vec<Change>) {
+    match (a, b) {
+        (Value::Object(ma), Value::Object(mb)) => {
+            for (k, va) in ma {
+                path.push(Seg::Key(k.clone()));
+                match mb.get(k) {
+                    Some(vb) => diff(va, vb, path, out),
+                    None => out.push(Change::Removed(path.clone(), va.clone())),
+                }
+                path.pop();
+            }
+            for (k, vb) in mb.iter().filter(|(k, _)| !ma.contains_key(*k)) {
+                path.push(Seg::Key(k.clone()));
+                out.push(Change::Added(path.clone(), vb.clone()));
+                path.pop();
+            }
+        }
+        (Value::Array(xa), Value::Array(xb)) => {
+            for (i, (va, vb)) in xa.iter().zip(xb).enumerate() {
+                path.push(Seg::Index(i));
+                diff(va, vb, path, out);
+                path.pop();
+            }
+            for (i, va) in xa.iter().enumerate().skip(xb.len()).rev() {
+                path.push(Seg::Index(i));
+                out.push(Change::Removed(path.clone(), va.clone()));
+                path.pop();
+            }
+            for (i, vb) in xb.iter().enumerate().skip(xa.len()) {
+                path.push(Seg::Index(i));
+                out.push(Change::Added(path.clone(), vb.clone()));
+                path.pop();
+            }
+        }
+        _ if a != b => out.push(Change::Changed(path.clone(), a.clone(), b.clone())),
+        _ => {}
+    }
+}
+
+/// `a.b[0]["odd key"]`, the `json select` path syntax; the root is `$`.
+fn display_path(path: &[Seg]) -> String {
+    if path.is_empty() {
+        return "$".to_string();
+    }
+    let mut out = String::new();
+    for seg in path {
+        match seg {
+            Seg::Index(i) => out.push_str(&format!("[{i}]")),
+            Seg::Key(k) => push_key(&mut out, k),
+        }
+    }
+    out
+}
+
+/// RFC 6901 JSON Pointer: `/a/0/b~1c`.
+fn pointer(path: &[Seg]) -> String {
+    path.iter()
+        .map(|seg| match seg {
+            Seg::Key(k) => format!("/{}", k.replace('~', "~0").replace('/', "~1")),
+            Seg::Index(i) => format!("/{i}"),
+        })
+        .collect()
+}
+
+/// The changes as RFC 6902 operations.
+fn to_patch(changes: &[Change]) -> Value {
+    Value::Array(
+        changes
+            .iter()
+            .map(|c| match c {
+                Change::Added(p, v) => json!({"op": "add", "path": pointer(p), "value": v}),
+                Change::Removed(p, _) => json!({"op": "remove", "path": pointer(p)}),
+                Change::Changed(p, _, v) => {
+                    json!({"op": "replace", "path": pointer(p), "value": v})
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Compare two JSON files. Lists the changes and fails when they differ, or
+/// prints the JSON Patch from `a` to `b` with `patch`.
+pub fn run(a: &Path, b: &Path, json5: bool, patch: bool) -> Result<()> {
+    let (va, vb) = (read_json_file(a, json5)?, read_json_file(b, json5)?);
+    let mut changes = Vec::new();
+    diff(&va, &vb, &mut Vec::new(), &mut changes);
+
+    if patch {
+        println!(
+            "{}",
+            colored_json::to_colored_json_auto(&to_patch(&changes))?
+        );
+        return Ok(());
+    }
+    if changes.is_empty() {
+        style::ok("Documents are identical");
+        return Ok(());
+    }
+
+    let (added, removed, changed) = print_changes(&changes);
+    bail!("Documents differ: {added} added, {removed} removed, {changed} changed")
+}
+
+/// Print each change on its own colored line; returns the added, removed, and changed counts.
+fn print_changes(changes: &[Change]) -> (usize, usize, usize) {
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for c in changes {
+        match c {
+            Change::Added(p, v) => {
+                added += 1;
+                println!("{} {}: {v}", "+".green().bold(), display_path(p).green());
+            }
+            Change::Removed(p, v) => {
+                removed += 1;
+                println!("{} {}: {v}", "-".red().bold(), display_path(p).red());
+            }
+            Change::Changed(p, old, new) => {
+                changed += 1;
+                println!(
+                    "{} {}: {} → {}",
+                    "~".yellow().bold(),
+                    display_path(p).yellow(),
+                    old.red(),
+                    new.green()
+                );
+            }
+        }
+    }
+    (added, removed, changed)
+}
+
+/// Print the differences between `a` and `b`, for previews of edits.
+pub(crate) fn print_diff(a: &Value, b: &Value) {
+    let mut changes = Vec::new();
+    diff(a, b, &mut Vec::new(), &mut changes);
+    print_changes(&changes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::json_select::get_by_path;
+
+    fn changes(a: Value, b: Value) -> Vec<Change> {
+        let mut out = Vec::new();
+        diff(&a, &b, &mut Vec::new(), &mut out);
+        out
+    }
+
+    #[test]
+    fn test_diff_objects_and_arrays() {
+        let found = changes(
+            json!({"a": 1, "b": {"c": [1, 2, 3]}, "gone": true}),
+            json!({"a": 2, "b": {"c": [1, 5]}, "new": null}),
+        );
+        let paths: Vec<String> = found
+            .iter()
+            .map(|c| match c {
+                Change::Added(p, _) => format!("+{}", display_path(p)),
+                Change::Removed(p, _) => format!("-{}", display_path(p)),
+                Change::Changed(p, _, _) => format!("~{}", display_path(p)),
+            })
+            .collect();
+        assert_eq!(paths, vec!["~a", "~b.c[1]", "-b.c[2]", "-gone", "+new"]);
+        assert!(changes(json!([1, {"x": "y"}]), json!([1, {"x": "y"}])).is_empty());
+    }
+
+    #[test]
+    fn test_patch_order_and_escaping() {
+        let patch = to_patch(&changes(
+            json!({"a/b": [1, 2, 3]}),
+            json!({"a/b": [1], "~": 0}),
+        ));
+        assert_eq!(
+            patch,
+            json!([
+                {"op": "remove", "path": "/a~1b/2"},
+                {"op": "remove", "path": "/a~1b/1"},
+                {"op": "add", "path": "/~0", "value": 0}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_display_path_quotes_odd_keys() {
+        let path = vec![Seg::Key("a b".into()), Seg::Index(0), Seg::Key("c".into())];
+        assert_eq!(display_path(&path), r#"["a b"][0].c"#);
+
+        let doc = json!({"a b": [{"c": 1}], "x.y": {"]": 2}});
+        for p in [path, vec![Seg::Key("x.y".into()), Seg::Key("]".into())]] {
+            let mut node = &doc;
+            for seg in &p {
+                node = match seg {
+                    Seg::Key(k) => &node[k.as_str()],
+                    Seg::Index(i) => &node[*i],
+                };
+            }
+            let shown = display_path(&p);
+            assert_eq!(get_by_path(&doc, &shown).as_deref(), Some(node), "{shown}");
+        }
+    }
+}
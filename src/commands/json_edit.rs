@@ -0,0 +1,69 @@
+//! `json set` / `json delete`: change one path of a document, printing the
+//! result or writing it back with --in-place. `set` creates missing objects
+//! and arrays on the way, so `a.b[0].c` works on an empty document.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::json_replace::parse_value;
+use crate::commands::json_select::{delete_by_path, get_input, parse_json, set_by_path};
+use crate::helpers::style;
+
+/// Print the edited document, or write it back to `file` with `in_place`.
+fn finish(doc: &Value, file: Option<&Path>, in_place: bool, done: &str) -> Result<()> {
+    match file {
+        Some(path) if in_place => {
+            fs::write(path, serde_json::to_string_pretty(doc)? + "\n")
+                .with_context(|| format!("Failed to write file: {}", path.display()))?;
+            style::ok(&format!("{done} in {}", path.display()));
+        }
+        _ => println!("{}", colored_json::to_colored_json_auto(doc)?),
+    }
+    Ok(())
+}
+
+/// Set `path` to `value` (JSON, or a plain string) in the JSON from text
+/// input, file, or stdin.
+pub fn run_set(
+    text: Option<String>,
+    file: Option<PathBuf>,
+    json5: bool,
+    path: &str,
+    value: &str,
+    in_place: bool,
+) -> Result<()> {
+    let raw = get_input(&text, &file)?;
+    let mut doc = parse_json(raw.trim(), json5)?;
+    set_by_path(&mut doc, path.trim(), parse_value(value))?;
+    finish(&doc, file.as_deref(), in_place, &format!("Set {path}"))
+}
+
+/// Remove each of `paths` from the JSON from text input, file, or stdin; a
+/// path that is not there fails unless `missing_ok`.
+pub fn run_delete(
+    text: Option<String>,
+    file: Option<PathBuf>,
+    json5: bool,
+    paths: &[String],
+    missing_ok: bool,
+    in_place: bool,
+) -> Result<()> {
+    let raw = get_input(&text, &file)?;
+    let mut doc = parse_json(raw.trim(), json5)?;
+    let mut deleted = 0;
+    for path in paths {
+        if delete_by_path(&mut doc, path.trim())? {
+            deleted += 1;
+        } else if !missing_ok {
+            bail!("Nothing at {path} to delete (use --missing-ok to skip it)");
+        }
+    }
+    finish(
+        &doc,
+        file.as_deref(),
+        in_place,
+        &format!("Deleted {deleted} path(s)"),
+    )
+}
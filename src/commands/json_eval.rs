@@ -0,0 +1,61 @@
+//! `json eval`: evaluate a small expression over imported JSON fragments and
+//! environment variables to generate config documents. See `helpers::expr` for the syntax.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::commands::json_select::parse_json;
+use crate::helpers::expr;
+
+/// Parse `name=path` and load the file as JSON (JSON5 accepted).
+fn load_import(item: &str) -> Result<(String, Value)> {
+    let (name, path) = item
+        .split_once('=')
+        .with_context(|| format!("--import must be name=path, got: {item}"))?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        bail!("Invalid import name '{name}' (use letters, digits, and _)");
+    }
+    if name == "env" {
+        bail!("Import name 'env' is reserved for environment variables");
+    }
+    let raw = fs::read_to_string(path).with_context(|| format!("Failed to read import: {path}"))?;
+    let value = parse_json(raw.trim(), true).with_context(|| format!("In import: {path}"))?;
+    Ok((name.to_string(), value))
+}
+
+/// Evaluate an expression and print the resulting JSON.
+pub fn run(
+    expr_text: Option<String>,
+    expr_file: Option<PathBuf>,
+    imports: Vec<String>,
+    compact: bool,
+) -> Result<()> {
+    let source = match (expr_text, expr_file) {
+        (_, Some(path)) => fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?,
+        (Some(text), None) => text,
+        (None, None) => bail!("Provide an expression with --expr or --expr-file"),
+    };
+
+    let mut env: HashMap<String, Value> = HashMap::new();
+    let vars: serde_json::Map<String, Value> = std::env::vars()
+        .map(|(k, v)| (k, Value::String(v)))
+        .collect();
+    env.insert("env".to_string(), Value::Object(vars));
+    for item in &imports {
+        let (name, value) = load_import(item)?;
+        env.insert(name, value);
+    }
+
+    let result = expr::eval(source.trim(), &env)?;
+    if compact {
+        println!("{result}");
+    } else {
+        println!("{}", colored_json::to_colored_json_auto(&result)?);
+    }
+    Ok(())
+}
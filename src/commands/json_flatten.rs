@@ -0,0 +1,183 @@
+//! `json flatten` / `json unflatten`: nested JSON to a flat map of paths like
+//! `a.b[0].c` (the `json select` syntax, so a key such as `a.b` is written
+//! `["a.b"]`) and back. Flat output can also be
+//! `path = value` lines or `A_B_0_C=value` environment assignments.
+
+use anyhow::{bail, Result};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+use crate::cli::FlatOutput;
+use crate::commands::json_select::{get_input, parse_json, push_key, set_by_path};
+
+/// Every leaf of `value` keyed by its path. Empty objects and arrays are kept
+/// as leaves so unflattening gives the same document back.
+fn flatten(value: &Value) -> Map<String, Value> {
+    fn walk(prefix: String, value: &Value, out: &mut Map<String, Value>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (k, v) in map {
+                    let mut path = prefix.clone();
+                    push_key(&mut path, k);
+                    walk(path, v, out);
+                }
+            }
+            Value::Array(items) if !items.is_empty() => {
+                for (i, v) in items.iter().enumerate() {
+                    walk(format!("{prefix}[{i}]"), v, out);
+                }
+            }
+            leaf => {
+                out.insert(prefix, leaf.clone());
+            }
+        }
+    }
+    let mut out = Map::new();
+    walk(String::new(), value, &mut out);
+    out
+}
+
+/// Rebuild a nested document from a flat map of paths. Array elements must
+/// come in index order, as `flatten` writes them.
+fn unflatten(flat: &Map<String, Value>) -> Result<Value> {
+    let mut root = Value::Null;
+    for (key, value) in flat {
+        let old = set_by_path(&mut root, key, value.clone())?;
+        if !old.is_null() {
+            bail!("Conflicting values for {key}");
+        }
+    }
+    Ok(root)
+}
+
+/// `a.b[0].c` as an environment variable name: `A_B_0_C`.
+fn env_name(path: &str) -> String {
+    let mut name = String::new();
+    for c in path.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_uppercase());
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+    }
+    name.trim_end_matches('_').to_string()
+}
+
+/// A value for a shell assignment: strings raw, others as JSON, single-quoted
+/// unless plainly safe.
+fn shell_value(value: &Value) -> String {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let safe = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@,+".contains(c));
+    if safe {
+        text
+    } else {
+        format!("'{}'", text.replace('\'', r"'\''"))
+    }
+}
+
+/// Flatten JSON from text input, file, or stdin and print it in `output` form.
+pub fn run_flatten(
+    text: Option<String>,
+    file: Option<PathBuf>,
+    json5: bool,
+    output: FlatOutput,
+) -> Result<()> {
+    let raw = get_input(&text, &file)?;
+    let flat = flatten(&parse_json(raw.trim(), json5)?);
+    match output {
+        FlatOutput::Json => println!(
+            "{}",
+            colored_json::to_colored_json_auto(&Value::Object(flat))?
+        ),
+        FlatOutput::Lines => {
+            for (path, value) in &flat {
+                println!("{path} = {value}");
+            }
+        }
+        FlatOutput::Env => {
+            for (path, value) in &flat {
+                println!("{}={}", env_name(path), shell_value(value));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rebuild nested JSON from a flat object of paths and print it.
+pub fn run_unflatten(text: Option<String>, file: Option<PathBuf>, json5: bool) -> Result<()> {
+    let raw = get_input(&text, &file)?;
+    let Value::Object(flat) = parse_json(raw.trim(), json5)? else {
+        bail!("Input must be an object of path: value pairs");
+    };
+    println!(
+        "{}",
+        colored_json::to_colored_json_auto(&unflatten(&flat)?)?
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_round_trip() {
+        let doc = json!({"a": {"b": [{"c": 1}, 2]}, "e": {}, "f": [], "g": null});
+        let flat = flatten(&doc);
+        assert_eq!(
+            Value::Object(flat.clone()),
+            json!({"a.b[0].c": 1, "a.b[1]": 2, "e": {}, "f": [], "g": null})
+        );
+        assert_eq!(unflatten(&flat).unwrap(), doc);
+        assert_eq!(
+            flatten(&json!(5)),
+            Map::from_iter([(String::new(), json!(5))])
+        );
+    }
+
+    #[test]
+    fn test_flatten_quotes_odd_keys() {
+        let doc = json!({"a.b": 1, "a": {"b": 2, "c[0]": 3, "": 4}});
+        let flat = flatten(&doc);
+        assert_eq!(
+            Value::Object(flat.clone()),
+            json!({r#"["a.b"]"#: 1, "a.b": 2, r#"a["c[0]"]"#: 3, r#"a[""]"#: 4})
+        );
+        assert_eq!(unflatten(&flat).unwrap(), doc);
+    }
+
+    #[test]
+    fn test_unflatten_rejects_gaps_and_conflicts() {
+        let flat = json!({"list[0]": "x", "list[1]": "y", "top[0][0]": true});
+        assert_eq!(
+            unflatten(flat.as_object().unwrap()).unwrap(),
+            json!({"list": ["x", "y"], "top": [[true]]})
+        );
+        for bad in [
+            json!({"a": 1, "a.b": 2}),
+            json!({"a.b": 1, "a": 2}),
+            json!({"list[2]": "x"}),
+            json!({"list[99999999999]": "x"}),
+            json!({"a[*]": 1}),
+            json!({"a..b": 1}),
+        ] {
+            assert!(unflatten(bad.as_object().unwrap()).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_env_output() {
+        assert_eq!(env_name("db.hosts[0].name"), "DB_HOSTS_0_NAME");
+        assert_eq!(env_name("api-key"), "API_KEY");
+        assert_eq!(shell_value(&json!("it's")), r"'it'\''s'");
+        assert_eq!(shell_value(&json!(8080)), "8080");
+        assert_eq!(shell_value(&json!("")), "''");
+    }
+}
@@ -0,0 +1,126 @@
+//! `json inspect`: a short description of a value instead of the value
+//! itself — its type, length, serialized size, and for objects each key with
+//! its type. Meant for exploring large payloads without dumping them.
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::commands::json_select::{get_by_path, get_input, parse_json};
+use crate::helpers::format::{human_size, type_name};
+use crate::helpers::{style, table};
+
+/// Keys of an object, items of an array, characters of a string.
+fn length(v: &Value) -> Option<usize> {
+    match v {
+        Value::Object(map) => Some(map.len()),
+        Value::Array(items) => Some(items.len()),
+        Value::String(s) => Some(s.chars().count()),
+        _ => None,
+    }
+}
+
+/// Bytes the value takes as compact JSON.
+fn size(v: &Value) -> u64 {
+    v.to_string().len() as u64
+}
+
+/// Element types of an array with their counts, most common first
+/// (ties keep first-seen order).
+fn element_types(items: &[Value]) -> Vec<(&'static str, usize)> {
+    let mut counts: Vec<(&'static str, usize)> = Vec::new();
+    for v in items {
+        let name = type_name(v);
+        match counts.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, c)) => *c += 1,
+            None => counts.push((name, 1)),
+        }
+    }
+    counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+    counts
+}
+
+/// Describe the JSON from text input, file, or stdin, or the value at `path` in it.
+pub fn run(
+    text: Option<String>,
+    file: Option<PathBuf>,
+    json5: bool,
+    path: Option<String>,
+) -> Result<()> {
+    let raw = get_input(&text, &file)?;
+    let json = parse_json(raw.trim(), json5)?;
+    let value = match &path {
+        Some(p) => get_by_path(&json, p.trim()).with_context(|| format!("No value at {p}"))?,
+        None => std::borrow::Cow::Borrowed(&json),
+    };
+
+    let label = |s: &str| format!("{s:<7}").bold().to_string();
+    style::title("JSON Inspect");
+    if let Some(p) = &path {
+        println!("  {} {p}", label("Path:"));
+    }
+    println!("  {} {}", label("Type:"), type_name(&value));
+    if let Some(n) = length(&value) {
+        let unit = match *value {
+            Value::Object(_) => "key(s)",
+            Value::Array(_) => "item(s)",
+            _ => "char(s)",
+        };
+        println!("  {} {n} {unit}", label("Length:"));
+    }
+    println!("  {} ~{}", label("Size:"), human_size(size(&value)));
+
+    match &*value {
+        Value::Object(map) if !map.is_empty() => {
+            println!();
+            let rows: Vec<Vec<String>> = map
+                .iter()
+                .map(|(k, v)| {
+                    vec![
+                        k.clone(),
+                        type_name(v).to_string(),
+                        length(v).map_or_else(|| "-".to_string(), |n| n.to_string()),
+                        human_size(size(v)),
+                    ]
+                })
+                .collect();
+            table::print(&["Key", "Type", "Length", "Size"], &rows);
+        }
+        Value::Array(items) if !items.is_empty() => {
+            println!();
+            let rows: Vec<Vec<String>> = element_types(items)
+                .into_iter()
+                .map(|(name, n)| vec![name.to_string(), n.to_string()])
+                .collect();
+            table::print(&["Item type", "Count"], &rows);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_type_length_and_size() {
+        assert_eq!(type_name(&json!(1)), "integer");
+        assert_eq!(type_name(&json!(1.5)), "number");
+        assert_eq!(length(&json!({"a": 1, "b": 2})), Some(2));
+        assert_eq!(length(&json!("héllo")), Some(5));
+        assert_eq!(length(&json!(true)), None);
+        assert_eq!(size(&json!({"a": [1, 2]})), 11);
+    }
+
+    #[test]
+    fn test_element_types_most_common_first() {
+        let items = [json!(null), json!({}), json!({}), json!("x"), json!({})];
+        assert_eq!(
+            element_types(&items),
+            vec![("object", 3), ("null", 1), ("string", 1)]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! `json merge`: deep-merge JSON documents left to right. Objects merge key by
+//! key; arrays are replaced or concatenated per --arrays; other values are replaced.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::ArrayMerge;
+use crate::commands::json_select::read_json_file;
+use crate::helpers::style;
+
+/// Merge `b` into `a`.
+fn merge(a: &mut Value, b: Value, arrays: ArrayMerge) {
+    match (a, b) {
+        (Value::Object(am), Value::Object(bm)) => {
+            for (k, bv) in bm {
+                match am.get_mut(&k) {
+                    Some(av) => merge(av, bv, arrays),
+                    None => {
+                        am.insert(k, bv);
+                    }
+                }
+            }
+        }
+        (Value::Array(ax), Value::Array(bx)) if matches!(arrays, ArrayMerge::Concat) => {
+            ax.extend(bx);
+        }
+        (a, b) => *a = b,
+    }
+}
+
+/// Merge `files` in order and print the result, or write it over the first file with `in_place`.
+pub fn run(files: Vec<PathBuf>, arrays: ArrayMerge, json5: bool, in_place: bool) -> Result<()> {
+    let Some((first, rest)) = files.split_first() else {
+        bail!("Give at least two files to merge");
+    };
+    let mut merged = read_json_file(first, json5)?;
+    for file in rest {
+        merge(&mut merged, read_json_file(file, json5)?, arrays);
+    }
+
+    if in_place {
+        fs::write(first, serde_json::to_string_pretty(&merged)? + "\n")
+            .with_context(|| format!("Failed to write file: {}", first.display()))?;
+        style::ok(&format!(
+            "Merged {} file(s) into {}",
+            rest.len(),
+            first.display()
+        ));
+    } else {
+        println!("{}", colored_json::to_colored_json_auto(&merged)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_array_strategies() {
+        let base = json!({"a": {"x": 1, "list": [1]}, "b": "keep"});
+        let over = json!({"a": {"y": 2, "list": [2]}, "b": null});
+
+        let mut replaced = base.clone();
+        merge(&mut replaced, over.clone(), ArrayMerge::Replace);
+        assert_eq!(
+            replaced,
+            json!({"a": {"x": 1, "list": [2], "y": 2}, "b": null})
+        );
+
+        let mut concatenated = base;
+        merge(&mut concatenated, over, ArrayMerge::Concat);
+        assert_eq!(concatenated["a"]["list"], json!([1, 2]));
+    }
+}
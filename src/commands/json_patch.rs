@@ -0,0 +1,261 @@
+//! `json patch`: apply an RFC 6902 JSON Patch (add, remove, replace, move,
+//! copy, test) or, with --merge-patch, an RFC 7386 merge patch to a document.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::commands::json_select::read_json_file;
+use crate::helpers::style;
+
+/// Undo RFC 6901 escaping of one pointer token.
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Split a non-root pointer into its parent pointer and last token.
+fn split_pointer(path: &str) -> Result<(&str, String)> {
+    if !path.starts_with('/') {
+        bail!("Invalid JSON Pointer '{path}' (must be empty or start with /)");
+    }
+    let (parent, last) = path.rsplit_once('/').expect("starts with /");
+    Ok((parent, unescape(last)))
+}
+
+/// Array position for `token`; `-` (append) and `len` are allowed only when inserting.
+fn index(token: &str, len: usize, inserting: bool) -> Result<usize> {
+    if inserting && token == "-" {
+        return Ok(len);
+    }
+    let i: usize = match token.parse() {
+        Ok(i) if token == "0" || !token.starts_with('0') => i,
+        _ => bail!("Invalid array index '{token}'"),
+    };
+    let max = if inserting {
+        len
+    } else {
+        len.saturating_sub(1)
+    };
+    if i > max || (!inserting && len == 0) {
+        bail!("Array index {i} out of bounds (length {len})");
+    }
+    Ok(i)
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<()> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, key) = split_pointer(path)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(key, value);
+        }
+        Some(Value::Array(items)) => {
+            let i = index(&key, items.len(), true)?;
+            items.insert(i, value);
+        }
+        Some(_) => bail!("Cannot add to '{path}': parent is not an object or array"),
+        None => bail!("Path not found: {parent}"),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value> {
+    if path.is_empty() {
+        bail!("Cannot remove the whole document");
+    }
+    let (parent, key) = split_pointer(path)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(map)) => map
+            .shift_remove(&key)
+            .with_context(|| format!("Path not found: {path}")),
+        Some(Value::Array(items)) => {
+            let i = index(&key, items.len(), false)?;
+            Ok(items.remove(i))
+        }
+        _ => bail!("Path not found: {path}"),
+    }
+}
+
+fn get<'a>(doc: &'a Value, path: &str) -> Result<&'a Value> {
+    doc.pointer(path)
+        .with_context(|| format!("Path not found: {path}"))
+}
+
+/// Apply one operation object to `doc`.
+fn apply_op(doc: &mut Value, op: &Value) -> Result<()> {
+    let field = |name: &str| -> Result<&str> {
+        op.get(name)
+            .and_then(Value::as_str)
+            .with_context(|| format!("Operation is missing \"{name}\""))
+    };
+    let value = || {
+        op.get("value")
+            .cloned()
+            .context("Operation is missing \"value\"")
+    };
+    let path = field("path")?;
+    match field("op")? {
+        "add" => add(doc, path, value()?),
+        "remove" => remove(doc, path).map(drop),
+        "replace" => {
+            let slot = doc
+                .pointer_mut(path)
+                .with_context(|| format!("Path not found: {path}"))?;
+            *slot = value()?;
+            Ok(())
+        }
+        "move" => {
+            let from = field("from")?;
+            if path.starts_with(&format!("{from}/")) {
+                bail!("Cannot move '{from}' into its own child '{path}'");
+            }
+            let moved = remove(doc, from)?;
+            add(doc, path, moved)
+        }
+        "copy" => {
+            let copied = get(doc, field("from")?)?.clone();
+            add(doc, path, copied)
+        }
+        "test" => {
+            let expected = value()?;
+            let actual = get(doc, path)?;
+            if *actual != expected {
+                bail!("Test failed at '{path}': expected {expected}, found {actual}");
+            }
+            Ok(())
+        }
+        other => bail!("Unknown operation \"{other}\""),
+    }
+}
+
+/// Apply an RFC 6902 patch; on any failure the document is left unchanged.
+fn apply_patch(doc: &Value, patch: &Value) -> Result<Value> {
+    let ops = patch
+        .as_array()
+        .context("A JSON Patch must be an array of operations")?;
+    let mut out = doc.clone();
+    for (i, op) in ops.iter().enumerate() {
+        apply_op(&mut out, op).with_context(|| format!("Patch operation {i} failed: {op}"))?;
+    }
+    Ok(out)
+}
+
+/// Apply an RFC 7386 merge patch: objects merge key by key, `null` deletes a
+/// key, and anything else replaces the target.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(changes) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let map = target.as_object_mut().expect("just made an object");
+    for (k, v) in changes {
+        if v.is_null() {
+            map.shift_remove(k);
+        } else {
+            merge_patch(map.entry(k.clone()).or_insert(Value::Null), v);
+        }
+    }
+}
+
+/// Patch `file` with `patch_file` and print the result, or write it back with `in_place`.
+pub fn run(
+    file: PathBuf,
+    patch_file: PathBuf,
+    merge: bool,
+    json5: bool,
+    in_place: bool,
+) -> Result<()> {
+    let doc = read_json_file(&file, json5)?;
+    let patch = read_json_file(&patch_file, json5)?;
+    let patched = if merge {
+        let mut doc = doc;
+        merge_patch(&mut doc, &patch);
+        doc
+    } else {
+        apply_patch(&doc, &patch)?
+    };
+
+    if in_place {
+        fs::write(&file, serde_json::to_string_pretty(&patched)? + "\n")
+            .with_context(|| format!("Failed to write file: {}", file.display()))?;
+        style::ok(&format!("Patched {}", file.display()));
+    } else {
+        println!("{}", colored_json::to_colored_json_auto(&patched)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_patch_ops() {
+        let doc = json!({"a": [1, 2], "b": {"c~d": 1}, "e/f": true});
+        let patch = json!([
+            {"op": "add", "path": "/a/1", "value": 9},
+            {"op": "add", "path": "/a/-", "value": 3},
+            {"op": "remove", "path": "/e~1f"},
+            {"op": "replace", "path": "/b/c~0d", "value": 2},
+            {"op": "copy", "from": "/b", "path": "/g"},
+            {"op": "move", "from": "/a/0", "path": "/h"},
+            {"op": "test", "path": "/g/c~0d", "value": 2}
+        ]);
+        assert_eq!(
+            apply_patch(&doc, &patch).unwrap(),
+            json!({"a": [9, 2, 3], "b": {"c~d": 2}, "g": {"c~d": 2}, "h": 1})
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_errors() {
+        let doc = json!({"a": [1]});
+        for op in [
+            json!({"op": "test", "path": "/a/0", "value": 2}),
+            json!({"op": "remove", "path": "/a/1"}),
+            json!({"op": "replace", "path": "/missing", "value": 1}),
+            json!({"op": "add", "path": "/a/01", "value": 1}),
+            json!({"op": "move", "from": "/a", "path": "/a/0"}),
+            json!({"op": "frobnicate", "path": ""}),
+        ] {
+            assert!(apply_patch(&doc, &json!([op])).is_err(), "{op}");
+        }
+    }
+
+    #[test]
+    fn test_merge_patch_rfc_example() {
+        let mut doc = json!({
+            "title": "Goodbye!",
+            "author": {"givenName": "John", "familyName": "Doe"},
+            "tags": ["example", "sample"],
+            "content": "This will be unchanged"
+        });
+        merge_patch(
+            &mut doc,
+            &json!({
+                "title": "Hello!",
+                "phoneNumber": "+01-123-456-7890",
+                "author": {"familyName": null},
+                "tags": ["example"]
+            }),
+        );
+        assert_eq!(
+            doc,
+            json!({
+                "title": "Hello!",
+                "author": {"givenName": "John"},
+                "tags": ["example"],
+                "content": "This will be unchanged",
+                "phoneNumber": "+01-123-456-7890"
+            })
+        );
+    }
+}
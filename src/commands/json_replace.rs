@@ -0,0 +1,100 @@
+//! `json replace`: substitute a value wherever a path (with `[*]` and slices)
+//! matches, e.g. every `servers[*].host`. --dry-run shows the changes as a diff.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::commands::json_diff;
+use crate::commands::json_select::{for_each_match_mut, read_json_file};
+use crate::helpers::style;
+
+/// A `--from`/`--to` argument: JSON when it parses, otherwise a plain string.
+pub(crate) fn parse_value(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string()))
+}
+
+/// Set every match of `path` that equals `from` (or every match, without
+/// `from`) to `to`; returns how many were replaced.
+fn replace(doc: &mut Value, path: &str, from: Option<&Value>, to: &Value) -> Result<usize> {
+    let mut replaced = 0;
+    for_each_match_mut(doc, path.trim(), &mut |v| {
+        if from.is_none_or(|from| v == from) {
+            *v = to.clone();
+            replaced += 1;
+        }
+    })
+    .with_context(|| format!("Invalid path: {path}"))?;
+    Ok(replaced)
+}
+
+/// Replace matching values in `file` and print the document, write it back
+/// with `in_place`, or only show the changes with `dry_run`.
+pub fn run(
+    file: PathBuf,
+    path: &str,
+    from: Option<&str>,
+    to: &str,
+    json5: bool,
+    in_place: bool,
+    dry_run: bool,
+) -> Result<()> {
+    let original = read_json_file(&file, json5)?;
+    let mut doc = original.clone();
+    let from = from.map(parse_value);
+    let count = replace(&mut doc, path, from.as_ref(), &parse_value(to))?;
+
+    if dry_run {
+        json_diff::print_diff(&original, &doc);
+        style::warn_line(&format!("Dry run: {count} value(s) would be replaced"));
+    } else if in_place {
+        if count == 0 {
+            bail!(
+                "No values matched {path}; {} left unchanged",
+                file.display()
+            );
+        }
+        fs::write(&file, serde_json::to_string_pretty(&doc)? + "\n")
+            .with_context(|| format!("Failed to write file: {}", file.display()))?;
+        style::ok(&format!("Replaced {count} value(s) in {}", file.display()));
+    } else {
+        println!("{}", colored_json::to_colored_json_auto(&doc)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_replace_matching_values() {
+        let mut doc = json!({"servers": [{"host": "old"}, {"host": "keep"}, {"host": "old"}]});
+        let n = replace(
+            &mut doc,
+            "servers[*].host",
+            Some(&json!("old")),
+            &json!("new"),
+        )
+        .unwrap();
+        assert_eq!(n, 2);
+        assert_eq!(
+            doc,
+            json!({"servers": [{"host": "new"}, {"host": "keep"}, {"host": "new"}]})
+        );
+        assert_eq!(
+            replace(&mut doc, "servers[0:2].host", None, &json!(null)).unwrap(),
+            2
+        );
+        assert_eq!(doc["servers"][2]["host"], json!("new"));
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("8080"), json!(8080));
+        assert_eq!(parse_value("new.example.com"), json!("new.example.com"));
+        assert_eq!(parse_value(r#""8080""#), json!("8080"));
+    }
+}
@@ -1,16 +1,21 @@
 //! `json select`: select a value by a simple path like `a.b[0].c`.
-//! Supports input from --text, --file, or stdin with optional JSON5 relaxed parsing.
+//! Supports input from --text, --file, or stdin as JSON (optionally JSON5), YAML, or TOML.
 
 use anyhow::{Context, Result};
+use serde::Deserialize;
 use serde_json::Value;
+use std::borrow::Cow;
 use std::fs;
 use std::io::{self, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::helpers::style;
+use crate::cli::{CoerceTo, DataFormat, SelectFormat};
+use crate::commands::convert;
+use crate::helpers::jsonpath::JsonPath;
+use crate::helpers::{clipboard, style, table};
 
 /// Input source priority: --file > --text > stdin
-fn get_input(text: &Option<String>, file: &Option<PathBuf>) -> Result<String> {
+pub(crate) fn get_input(text: &Option<String>, file: &Option<PathBuf>) -> Result<String> {
     if let Some(path) = file {
         return fs::read_to_string(path)
             .with_context(|| format!("Failed to read file: {}", path.display()));
@@ -54,7 +59,7 @@ fn analyze_json_error(input: &str, error: &serde_json::Error) -> String {
 }
 
 /// Parse JSON with fallback to JSON5 if enabled and strict parsing fails
-fn parse_json(input: &str, use_json5: bool) -> Result<Value> {
+pub(crate) fn parse_json(input: &str, use_json5: bool) -> Result<Value> {
     // Try strict JSON first
     match serde_json::from_str(input) {
         Ok(value) => Ok(value),
@@ -78,54 +83,572 @@ fn parse_json(input: &str, use_json5: bool) -> Result<Value> {
     }
 }
 
-/// Simple path resolver supporting object and array access:
-/// - Dots traverse objects: `a.b.c`
-/// - [idx] traverses arrays: `items[0]`
-/// - One [idx] per segment is supported, e.g. `a.b[2].c`
-fn get_by_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
-    let mut cur = value;
-    for seg in path.split('.') {
-        if seg.is_empty() {
+/// The format `--format auto` picks for `file`: YAML or TOML by extension, else JSON.
+fn detect_format(file: Option<&Path>) -> SelectFormat {
+    let ext = file
+        .and_then(Path::extension)
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    match ext.as_deref() {
+        Some("yaml" | "yml") => SelectFormat::Yaml,
+        Some("toml") => SelectFormat::Toml,
+        _ => SelectFormat::Json,
+    }
+}
+
+/// Parse input in `format`. A YAML stream of several `---` documents (as in
+/// Kubernetes manifests) becomes an array of them.
+fn parse_input(input: &str, format: SelectFormat, json5: bool) -> Result<Value> {
+    match format {
+        SelectFormat::Auto | SelectFormat::Json => parse_json(input, json5),
+        SelectFormat::Yaml => {
+            let mut docs = serde_yaml::Deserializer::from_str(input)
+                .map(Value::deserialize)
+                .collect::<Result<Vec<_>, _>>()
+                .context("Invalid YAML")?;
+            Ok(match docs.len() {
+                0 => Value::Null,
+                1 => docs.remove(0),
+                _ => Value::Array(docs),
+            })
+        }
+        SelectFormat::Toml => convert::read(DataFormat::Toml, input.as_bytes()),
+    }
+}
+
+/// Read and parse a JSON file, naming the file in errors.
+pub(crate) fn read_json_file(path: &Path, json5: bool) -> Result<Value> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    parse_json(raw.trim(), json5).with_context(|| format!("In {}", path.display()))
+}
+
+/// One `[...]` selector of a path segment.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Selector {
+    /// `[2]`, or `[-1]` counting from the end.
+    Index(i64),
+    /// `[1:3]`, `[:2]`, `[-2:]`: a half-open range, clamped like Python slices.
+    Slice(Option<i64>, Option<i64>),
+    /// `[*]`: every element of an array (or value of an object).
+    All,
+    /// `["odd key"]`: an object key as a JSON string, for keys holding `.`,
+    /// `[`, spaces, and the like.
+    Key(String),
+}
+
+/// Split a path at the dots between segments, leaving dots inside `["..."]`
+/// keys alone.
+pub(crate) fn split_path(path: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+    for (i, c) in path.char_indices() {
+        if quoted {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => quoted = false,
+                _ => {}
+            }
+        } else if c == '"' && path[..i].ends_with('[') {
+            quoted = true;
+        } else if c == '.' {
+            parts.push(&path[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&path[start..]);
+    parts
+}
+
+/// Append object key `key` to `path` in this syntax: `.key`, or `["odd key"]`
+/// when the key has characters a plain segment cannot hold.
+pub(crate) fn push_key(path: &mut String, key: &str) {
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if plain {
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(key);
+    } else {
+        path.push_str(&format!("[{}]", Value::String(key.to_string())));
+    }
+}
+
+/// Split `name[0][1:3]` into its name and selectors; `None` if malformed.
+pub(crate) fn parse_segment(seg: &str) -> Option<(&str, Vec<Selector>)> {
+    let (name, mut rest) = match seg.find('[') {
+        Some(i) => (&seg[..i], &seg[i..]),
+        None => (seg, ""),
+    };
+    let mut selectors = Vec::new();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix("[\"") {
+            // A JSON string, so `]` and escapes inside it are part of the key.
+            let mut stream = serde_json::Deserializer::from_str(&rest[1..]).into_iter::<String>();
+            let key = stream.next()?.ok()?;
+            rest = quoted[stream.byte_offset() - 1..].strip_prefix(']')?;
+            selectors.push(Selector::Key(key));
+            continue;
+        }
+        let close = rest.find(']')?;
+        let inner = rest.strip_prefix('[')?.get(..close - 1)?.trim();
+        selectors.push(match inner.split_once(':') {
+            _ if inner == "*" => Selector::All,
+            Some((a, b)) => {
+                let bound = |s: &str| -> Option<Option<i64>> {
+                    let s = s.trim();
+                    if s.is_empty() {
+                        Some(None)
+                    } else {
+                        s.parse().ok().map(Some)
+                    }
+                };
+                Selector::Slice(bound(a)?, bound(b)?)
+            }
+            None => Selector::Index(inner.parse().ok()?),
+        });
+        rest = &rest[close + 1..];
+    }
+    if name.is_empty() && selectors.is_empty() {
+        return None;
+    }
+    Some((name, selectors))
+}
+
+/// Position `i` in an array of `len` (negative counts from the end); `None`
+/// when it falls before the start.
+fn index(i: i64, len: usize) -> Option<usize> {
+    if i < 0 {
+        len.checked_sub(i.unsigned_abs() as usize)
+    } else {
+        Some(i as usize)
+    }
+}
+
+/// Position `i` (negative counts from the end) clamped to `0..=len`.
+fn clamp(i: i64, len: usize) -> usize {
+    let len = len as i64;
+    (if i < 0 { i + len } else { i }).clamp(0, len) as usize
+}
+
+/// Apply one selector to `v`, pushing what it selects onto `out`.
+fn select_into<'a>(v: &'a Value, sel: &Selector, out: &mut Vec<&'a Value>) {
+    match (sel, v) {
+        (Selector::Index(i), Value::Array(items)) => {
+            if let Some(item) = index(*i, items.len()).and_then(|idx| items.get(idx)) {
+                out.push(item);
+            }
+        }
+        (Selector::Slice(a, b), Value::Array(items)) => {
+            let start = a.map_or(0, |a| clamp(a, items.len()));
+            let end = b.map_or(items.len(), |b| clamp(b, items.len()));
+            if start < end {
+                out.extend(&items[start..end]);
+            }
+        }
+        (Selector::All, Value::Array(items)) => out.extend(items),
+        (Selector::All, Value::Object(map)) => out.extend(map.values()),
+        (Selector::Key(k), Value::Object(map)) => out.extend(map.get(k)),
+        _ => {}
+    }
+}
+
+/// The nodes `path` resolves to, and whether it maps over elements (`[*]` or a
+/// slice); `None` if a plain path does not resolve.
+fn resolve<'a>(value: &'a Value, path: &str) -> Option<(Vec<&'a Value>, bool)> {
+    let mut cur = vec![value];
+    let mut many = false;
+    for seg in split_path(path) {
+        let (name, selectors) = parse_segment(seg)?;
+        if !name.is_empty() {
+            cur = cur.into_iter().filter_map(|v| v.get(name)).collect();
+        }
+        for sel in &selectors {
+            many |= !matches!(sel, Selector::Index(_) | Selector::Key(_));
+            let mut next = Vec::new();
+            for v in cur {
+                select_into(v, sel, &mut next);
+            }
+            cur = next;
+        }
+        if cur.is_empty() && !many {
             return None;
         }
+    }
+    Some((cur, many))
+}
+
+/// Path resolver supporting object and array access:
+/// - Dots traverse objects: `a.b.c`
+/// - `[idx]` traverses arrays, `[-1]` from the end: `items[0]`, `a.b[2][-1].c`
+/// - `[*]` maps over every element and `[1:3]` over a slice: `items[*].name`
+/// - `["..."]` names a key that is not a plain word: `headers["content-type"]`,
+///   `a["x.y"]`
+///
+/// A path with `[*]` or a slice yields an array of what the rest of the path
+/// finds in each element (elements where it finds nothing are skipped).
+pub(crate) fn get_by_path<'a>(value: &'a Value, path: &str) -> Option<Cow<'a, Value>> {
+    let (cur, many) = resolve(value, path)?;
+    if many {
+        Some(Cow::Owned(Value::Array(cur.into_iter().cloned().collect())))
+    } else {
+        cur.first().map(|v| Cow::Borrowed(*v))
+    }
+}
+
+/// How many nodes `path` matches: 0 or 1 for a plain path, any number with
+/// `[*]` or a slice.
+fn count_matches(value: &Value, path: &str) -> usize {
+    resolve(value, path.trim()).map_or(0, |(nodes, _)| nodes.len())
+}
+
+/// One move of a path walk: into an object key, or through a selector.
+enum Step<'a> {
+    Key(&'a str),
+    Select(&'a Selector),
+}
+
+/// Call `f` on every node `path` matches, by the rules of `get_by_path`, so
+/// matches can be edited in place. `None` if the path is malformed.
+pub(crate) fn for_each_match_mut(
+    value: &mut Value,
+    path: &str,
+    f: &mut dyn FnMut(&mut Value),
+) -> Option<()> {
+    let segments = split_path(path)
+        .into_iter()
+        .map(parse_segment)
+        .collect::<Option<Vec<_>>>()?;
+    let mut steps = Vec::new();
+    for (name, selectors) in &segments {
+        if !name.is_empty() {
+            steps.push(Step::Key(name));
+        }
+        steps.extend(selectors.iter().map(Step::Select));
+    }
+    walk_mut(value, &steps, f);
+    Some(())
+}
 
-        // Handle `name[index]` form
-        if let Some((name, rest)) = seg.split_once('[') {
-            if !name.is_empty() {
-                cur = cur.get(name)?;
+/// Follow `steps` from `v`, calling `f` where each branch ends.
+fn walk_mut(v: &mut Value, steps: &[Step], f: &mut dyn FnMut(&mut Value)) {
+    let Some((step, rest)) = steps.split_first() else {
+        f(v);
+        return;
+    };
+    match (step, v) {
+        (Step::Key(name), v) => {
+            if let Some(child) = v.get_mut(*name) {
+                walk_mut(child, rest, f);
             }
-            if !rest.ends_with(']') {
-                return None;
+        }
+        (Step::Select(Selector::Index(i)), Value::Array(items)) => {
+            if let Some(item) = index(*i, items.len()).and_then(|idx| items.get_mut(idx)) {
+                walk_mut(item, rest, f);
             }
-            let idx_str = &rest[..rest.len() - 1];
-            let idx: usize = idx_str.parse().ok()?;
-            cur = cur.get(idx)?;
-        } else {
-            cur = cur.get(seg)?;
         }
+        (Step::Select(Selector::Slice(a, b)), Value::Array(items)) => {
+            let start = a.map_or(0, |a| clamp(a, items.len()));
+            let end = b.map_or(items.len(), |b| clamp(b, items.len()));
+            if start < end {
+                for item in &mut items[start..end] {
+                    walk_mut(item, rest, f);
+                }
+            }
+        }
+        (Step::Select(Selector::All), Value::Array(items)) => {
+            for item in items {
+                walk_mut(item, rest, f);
+            }
+        }
+        (Step::Select(Selector::All), Value::Object(map)) => {
+            for item in map.values_mut() {
+                walk_mut(item, rest, f);
+            }
+        }
+        (Step::Select(Selector::Key(k)), Value::Object(map)) => {
+            if let Some(item) = map.get_mut(k) {
+                walk_mut(item, rest, f);
+            }
+        }
+        _ => {}
     }
-    Some(cur)
 }
 
-/// Select JSON value by path from text input, file, or stdin.
-pub fn run(text: Option<String>, file: Option<PathBuf>, json5: bool, path: String) -> Result<()> {
-    style::title("JSON Select");
+/// One step of a plain path (no `[*]` or slices), for edits.
+enum Plain<'a> {
+    Key(Cow<'a, str>),
+    Index(i64),
+}
 
+fn plain_steps(path: &str) -> Result<Vec<Plain<'_>>> {
+    let mut steps = Vec::new();
+    for seg in split_path(path) {
+        let (name, selectors) =
+            parse_segment(seg).with_context(|| format!("Invalid path: {path}"))?;
+        if !name.is_empty() {
+            steps.push(Plain::Key(Cow::Borrowed(name)));
+        }
+        for sel in selectors {
+            match sel {
+                Selector::Index(i) => steps.push(Plain::Index(i)),
+                Selector::Key(k) => steps.push(Plain::Key(Cow::Owned(k))),
+                _ => anyhow::bail!(
+                    "Only keys and [N] indexes can be edited, not [*] or slices: {path}"
+                ),
+            }
+        }
+    }
+    Ok(steps)
+}
+
+/// Set the value at a plain `path`, the mutable counterpart of `get_by_path`.
+/// Missing objects and arrays on the way are created (null counts as missing);
+/// an index may name an existing element or the one just past the end, which
+/// appends, so a stray large index cannot blow up the array. Returns the value
+/// that was there (null if nothing was).
+pub(crate) fn set_by_path(value: &mut Value, path: &str, new: Value) -> Result<Value> {
+    let mut node = value;
+    for step in plain_steps(path)? {
+        node = match step {
+            Plain::Key(name) => {
+                if node.is_null() {
+                    *node = Value::Object(serde_json::Map::new());
+                }
+                let Value::Object(map) = node else {
+                    anyhow::bail!("{path}: cannot set key '{name}' on {node}");
+                };
+                map.entry(name).or_insert(Value::Null)
+            }
+            Plain::Index(i) => {
+                if node.is_null() {
+                    *node = Value::Array(Vec::new());
+                }
+                let Value::Array(items) = node else {
+                    anyhow::bail!("{path}: cannot set index [{i}] on {node}");
+                };
+                let idx = index(i, items.len())
+                    .filter(|&idx| idx <= items.len())
+                    .with_context(|| {
+                        format!(
+                            "{path}: index [{i}] is out of range for {} element(s)",
+                            items.len()
+                        )
+                    })?;
+                if idx == items.len() {
+                    items.push(Value::Null);
+                }
+                &mut items[idx]
+            }
+        };
+    }
+    Ok(std::mem::replace(node, new))
+}
+
+/// Remove the value at a plain `path`: an object key, or an array element
+/// (later elements shift down). `Ok(false)` if there is nothing there.
+pub(crate) fn delete_by_path(value: &mut Value, path: &str) -> Result<bool> {
+    let steps = plain_steps(path)?;
+    let Some((last, parents)) = steps.split_last() else {
+        return Ok(false);
+    };
+    let mut node = value;
+    for step in parents {
+        let child = match (step, node) {
+            (Plain::Key(name), Value::Object(map)) => map.get_mut(name.as_ref()),
+            (Plain::Index(i), Value::Array(items)) => {
+                index(*i, items.len()).and_then(|idx| items.get_mut(idx))
+            }
+            _ => None,
+        };
+        match child {
+            Some(child) => node = child,
+            None => return Ok(false),
+        }
+    }
+    Ok(match (last, node) {
+        (Plain::Key(name), Value::Object(map)) => map.shift_remove(name.as_ref()).is_some(),
+        (Plain::Index(i), Value::Array(items)) => match index(*i, items.len()) {
+            Some(idx) if idx < items.len() => {
+                items.remove(idx);
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    })
+}
+
+/// The values at `paths`, `None` where a path does not resolve.
+fn select_all<'a>(value: &'a Value, paths: &[String]) -> Vec<Option<Cow<'a, Value>>> {
+    paths.iter().map(|p| get_by_path(value, p.trim())).collect()
+}
+
+/// How `json select` prints what it found.
+pub struct Output {
+    /// One JSON array of all values.
+    pub as_array: bool,
+    /// Strings without quotes.
+    pub raw: bool,
+    /// Single-line JSON.
+    pub compact: bool,
+    /// The number of matches instead of the values.
+    pub count: bool,
+    /// Type to coerce each value to.
+    pub coerce: Option<CoerceTo>,
+    /// Also place the output on the clipboard.
+    pub copy: bool,
+}
+
+/// `v` as `to`: `"42"` → 42, `1` → true, `true` → `"true"`. Arrays (as from
+/// `[*]`) are coerced element by element; anything else impossible is an error.
+fn coerce(v: &Value, to: CoerceTo) -> Result<Value> {
+    let fail = || anyhow::anyhow!("Cannot coerce {v} to {}", format!("{to:?}").to_lowercase());
+    Ok(match (to, v) {
+        (_, Value::Array(items)) => Value::Array(
+            items
+                .iter()
+                .map(|item| coerce(item, to))
+                .collect::<Result<_>>()?,
+        ),
+        (CoerceTo::String, Value::String(_)) => v.clone(),
+        (CoerceTo::String, Value::Number(_) | Value::Bool(_)) => Value::String(v.to_string()),
+        (CoerceTo::Number, Value::Number(_)) => v.clone(),
+        (CoerceTo::Number, Value::String(s)) => {
+            Value::Number(serde_json::from_str(s.trim()).map_err(|_| fail())?)
+        }
+        (CoerceTo::Number, Value::Bool(b)) => Value::from(*b as i64),
+        (CoerceTo::Bool, Value::Bool(_)) => v.clone(),
+        (CoerceTo::Bool, Value::Number(n)) => match n.as_f64() {
+            Some(0.0) => Value::Bool(false),
+            Some(1.0) => Value::Bool(true),
+            _ => return Err(fail()),
+        },
+        (CoerceTo::Bool, Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Value::Bool(true),
+            "false" | "no" | "off" | "0" => Value::Bool(false),
+            _ => return Err(fail()),
+        },
+        _ => return Err(fail()),
+    })
+}
+
+/// `v` as printed: raw strings, compact JSON, or pretty JSON (colored on a TTY).
+/// `one_line` forces compact output where several values share the stream.
+fn render(v: &Value, out: &Output, one_line: bool) -> Result<String> {
+    Ok(match v {
+        Value::String(s) if out.raw => s.clone(),
+        _ if out.compact || one_line => v.to_string(),
+        _ => colored_json::to_colored_json_auto(v)?,
+    })
+}
+
+/// Select JSON values by path from text input, file, or stdin. One path prints
+/// pretty JSON; several print one compact value per line, in path order. A
+/// --jsonpath query prints the array of its matches (one per line with --raw).
+/// With `count`, each path (or the query) prints how many nodes it matched.
+/// With `copy`, the printed text also goes to the clipboard.
+pub fn run(
+    text: Option<String>,
+    file: Option<PathBuf>,
+    format: SelectFormat,
+    json5: bool,
+    paths: Vec<String>,
+    jsonpath: Option<String>,
+    out: Output,
+) -> Result<()> {
+    // Pipelines get the values only.
+    if style::is_tty() {
+        style::title("JSON Select");
+    }
+
+    // Compile first so a bad query fails before stdin is read.
+    let query = jsonpath.as_deref().map(JsonPath::compile).transpose()?;
+    let format = match format {
+        SelectFormat::Auto => detect_format(file.as_deref()),
+        other => other,
+    };
     let raw = get_input(&text, &file)?;
-    let json = parse_json(raw.trim(), json5)?;
+    let json = parse_input(raw.trim(), format, json5)?;
+
+    let lines = select_lines(&json, query, &paths, &out)?;
+    for line in &lines {
+        println!("{line}");
+    }
+    if out.copy {
+        clipboard::copy(&table::strip_ansi(&lines.join("\n")))?;
+    }
+    Ok(())
+}
 
-    match get_by_path(&json, &path) {
-        Some(v) => {
-            // Pretty JSON; colored if TTY, plain otherwise.
-            let pretty = colored_json::to_colored_json_auto(v)?;
-            println!("{pretty}");
+/// The lines `run` prints for `json`: a `--jsonpath` query's matches, or the
+/// values (or match counts) at `paths`.
+fn select_lines(
+    json: &Value,
+    query: Option<JsonPath>,
+    paths: &[String],
+    out: &Output,
+) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    if let Some(query) = query {
+        let mut matches: Vec<Value> = query.select(json).into_iter().cloned().collect();
+        if let Some(to) = out.coerce {
+            matches = matches
+                .iter()
+                .map(|v| coerce(v, to))
+                .collect::<Result<_>>()?;
         }
-        None => {
+        if out.count {
+            lines.push(matches.len().to_string());
+        } else if out.raw {
+            for v in &matches {
+                lines.push(render(v, out, true)?);
+            }
+        } else {
+            lines.push(render(&Value::Array(matches), out, false)?);
+        }
+        return Ok(lines);
+    }
+    if out.count {
+        for path in paths {
+            lines.push(count_matches(json, path).to_string());
+        }
+        return Ok(lines);
+    }
+    let mut selected = select_all(json, paths);
+    if let Some(to) = out.coerce {
+        for (v, path) in selected.iter_mut().zip(paths) {
+            if let Some(found) = v {
+                let coerced = coerce(found, to).with_context(|| format!("At path {path}"))?;
+                *v = Some(Cow::Owned(coerced));
+            }
+        }
+    }
+
+    if out.as_array {
+        let array = Value::Array(
+            selected
+                .into_iter()
+                .map(|v| v.map_or(Value::Null, Cow::into_owned))
+                .collect(),
+        );
+        lines.push(render(&array, out, false)?);
+        return Ok(lines);
+    }
+
+    for v in selected {
+        match v {
+            // One line per path so scripts can `read` the values in order.
+            Some(v) => lines.push(render(&v, out, paths.len() > 1)?),
             // Intentionally minimal for scripting pipelines.
-            println!("(null)");
+            None => lines.push("(null)".to_string()),
         }
     }
-    Ok(())
+    Ok(lines)
 }
 
 #[cfg(test)]
@@ -136,41 +659,233 @@ mod tests {
     #[test]
     fn test_get_by_path_object_access() {
         let data = json!({"a": {"b": {"c": "value"}}});
-        assert_eq!(get_by_path(&data, "a.b.c"), Some(&json!("value")));
+        assert_eq!(
+            get_by_path(&data, "a.b.c").as_deref(),
+            Some(&json!("value"))
+        );
     }
 
     #[test]
     fn test_get_by_path_array_access() {
         let data = json!({"items": [1, 2, 3]});
-        assert_eq!(get_by_path(&data, "items[0]"), Some(&json!(1)));
-        assert_eq!(get_by_path(&data, "items[2]"), Some(&json!(3)));
+        assert_eq!(get_by_path(&data, "items[0]").as_deref(), Some(&json!(1)));
+        assert_eq!(get_by_path(&data, "items[2]").as_deref(), Some(&json!(3)));
     }
 
     #[test]
     fn test_get_by_path_mixed_access() {
         let data = json!({"a": {"b": [{"c": "found"}]}});
-        assert_eq!(get_by_path(&data, "a.b[0].c"), Some(&json!("found")));
+        assert_eq!(
+            get_by_path(&data, "a.b[0].c").as_deref(),
+            Some(&json!("found"))
+        );
     }
 
     #[test]
     fn test_get_by_path_missing_path() {
         let data = json!({"a": {"b": "value"}});
-        assert_eq!(get_by_path(&data, "a.x"), None);
-        assert_eq!(get_by_path(&data, "missing"), None);
+        assert_eq!(get_by_path(&data, "a.x").as_deref(), None);
+        assert_eq!(get_by_path(&data, "missing").as_deref(), None);
     }
 
     #[test]
     fn test_get_by_path_invalid_array_index() {
         let data = json!({"items": [1, 2]});
-        assert_eq!(get_by_path(&data, "items[5]"), None);
-        assert_eq!(get_by_path(&data, "items[abc]"), None);
+        assert_eq!(get_by_path(&data, "items[5]").as_deref(), None);
+        assert_eq!(get_by_path(&data, "items[abc]").as_deref(), None);
+    }
+
+    #[test]
+    fn test_get_by_path_negative_index() {
+        let data = json!({"items": [1, 2, 3], "grid": [[1, 2], [3, 4]]});
+        assert_eq!(get_by_path(&data, "items[-1]").as_deref(), Some(&json!(3)));
+        assert_eq!(
+            get_by_path(&data, "grid[1][-2]").as_deref(),
+            Some(&json!(3))
+        );
+        assert_eq!(get_by_path(&data, "items[-4]").as_deref(), None);
+    }
+
+    #[test]
+    fn test_get_by_path_wildcards_and_slices() {
+        let data = json!({"items": [
+            {"name": "a", "tags": ["x"]},
+            {"name": "b", "tags": []},
+            {"id": 3},
+            {"name": "d", "tags": ["y", "z"]}
+        ]});
+        let get = |p| get_by_path(&data, p).map(Cow::into_owned);
+        assert_eq!(get("items[*].name"), Some(json!(["a", "b", "d"])));
+        assert_eq!(get("items[1:3].name"), Some(json!(["b"])));
+        assert_eq!(get("items[-2:].id"), Some(json!([3])));
+        assert_eq!(get("items[:2].tags[0]"), Some(json!(["x"])));
+        assert_eq!(get("items[*].missing"), Some(json!([])));
+        assert_eq!(get("items[3:1]"), Some(json!([])));
+        assert_eq!(get("items[1:x]"), None);
+        assert_eq!(get("items[0"), None);
+    }
+
+    #[test]
+    fn test_get_by_path_quoted_keys() {
+        let data = json!({"a.b": {"c d": [1, 2]}, "x": {"]\"": true}, "": 0});
+        assert_eq!(
+            get_by_path(&data, r#"["a.b"]["c d"][1]"#).unwrap().as_ref(),
+            &json!(2)
+        );
+        assert_eq!(
+            get_by_path(&data, r#"x["]\""]"#).unwrap().as_ref(),
+            &json!(true)
+        );
+        assert_eq!(get_by_path(&data, r#"[""]"#).unwrap().as_ref(), &json!(0));
+        assert!(get_by_path(&data, r#"["nope"]"#).is_none());
+        assert!(get_by_path(&data, r#"["a.b"#).is_none());
+
+        let mut doc = json!({});
+        set_by_path(&mut doc, r#"["k.1"].v"#, json!(1)).unwrap();
+        assert_eq!(doc, json!({"k.1": {"v": 1}}));
+        assert!(delete_by_path(&mut doc, r#"["k.1"]"#).unwrap());
+        assert_eq!(doc, json!({}));
+
+        let mut path = String::new();
+        for key in ["a", "b c", "d.e", ""] {
+            push_key(&mut path, key);
+        }
+        assert_eq!(path, r#"a["b c"]["d.e"][""]"#);
     }
 
     #[test]
     fn test_get_by_path_empty_segments() {
         let data = json!({"a": "value"});
-        assert_eq!(get_by_path(&data, ""), None);
-        assert_eq!(get_by_path(&data, "a..b"), None);
+        assert_eq!(get_by_path(&data, "").as_deref(), None);
+        assert_eq!(get_by_path(&data, "a..b").as_deref(), None);
+    }
+
+    #[test]
+    fn test_select_all_keeps_path_order() {
+        let data = json!({"a": 1, "b": {"c": [true]}});
+        let paths = ["b.c[0]", "missing", " a"].map(String::from);
+        let selected: Vec<Option<Value>> = select_all(&data, &paths)
+            .into_iter()
+            .map(|v| v.map(Cow::into_owned))
+            .collect();
+        assert_eq!(selected, vec![Some(json!(true)), None, Some(json!(1))]);
+    }
+
+    #[test]
+    fn test_count_matches() {
+        let data = json!({"items": [{"s": "failed"}, {"s": "ok"}, {}], "list": [1, 2]});
+        assert_eq!(count_matches(&data, "items[*].s"), 2);
+        assert_eq!(count_matches(&data, "items[0:2]"), 2);
+        assert_eq!(count_matches(&data, "list"), 1);
+        assert_eq!(count_matches(&data, "missing"), 0);
+        assert_eq!(count_matches(&data, "missing[*]"), 0);
+    }
+
+    #[test]
+    fn test_for_each_match_mut() {
+        let mut data =
+            json!({"servers": [{"host": "a"}, {"host": "b"}, {"port": 1}], "x": {"y": [1, 2]}});
+        let mut seen = 0;
+        for_each_match_mut(&mut data, "servers[*].host", &mut |v| {
+            seen += 1;
+            *v = json!("z");
+        })
+        .unwrap();
+        for_each_match_mut(&mut data, "x.y[-1]", &mut |v| *v = json!(3)).unwrap();
+        assert_eq!(seen, 2);
+        assert_eq!(
+            data,
+            json!({"servers": [{"host": "z"}, {"host": "z"}, {"port": 1}], "x": {"y": [1, 3]}})
+        );
+        assert!(for_each_match_mut(&mut data, "a..b", &mut |_| {}).is_none());
+    }
+
+    #[test]
+    fn test_parse_input_formats() {
+        let yaml = "kind: Service\n---\nkind: Deployment\nspec:\n  replicas: 2\n";
+        let docs = parse_input(yaml, SelectFormat::Yaml, false).unwrap();
+        assert_eq!(
+            get_by_path(&docs, "[1].spec.replicas").as_deref(),
+            Some(&json!(2))
+        );
+        assert_eq!(
+            parse_input("a: 1", SelectFormat::Yaml, false).unwrap(),
+            json!({"a": 1})
+        );
+
+        let cargo = "[package]\nname = \"swiftline\"\n[dependencies]\nclap = \"4\"\n";
+        let value = parse_input(cargo, SelectFormat::Toml, false).unwrap();
+        assert_eq!(
+            get_by_path(&value, "package.name").as_deref(),
+            Some(&json!("swiftline"))
+        );
+
+        assert!(matches!(
+            detect_format(Some(Path::new("deploy/app.YML"))),
+            SelectFormat::Yaml
+        ));
+        assert!(matches!(detect_format(None), SelectFormat::Json));
+    }
+
+    #[test]
+    fn test_coerce() {
+        assert_eq!(coerce(&json!("42"), CoerceTo::Number).unwrap(), json!(42));
+        assert_eq!(
+            coerce(&json!(" 2.5 "), CoerceTo::Number).unwrap(),
+            json!(2.5)
+        );
+        assert_eq!(coerce(&json!(true), CoerceTo::Number).unwrap(), json!(1));
+        assert_eq!(coerce(&json!(1), CoerceTo::Bool).unwrap(), json!(true));
+        assert_eq!(coerce(&json!("No"), CoerceTo::Bool).unwrap(), json!(false));
+        assert_eq!(coerce(&json!(7), CoerceTo::String).unwrap(), json!("7"));
+        assert_eq!(
+            coerce(&json!(["1", "0"]), CoerceTo::Bool).unwrap(),
+            json!([true, false])
+        );
+        for (v, to) in [
+            (json!("abc"), CoerceTo::Number),
+            (json!(2), CoerceTo::Bool),
+            (json!("maybe"), CoerceTo::Bool),
+            (json!(null), CoerceTo::String),
+            (json!({"a": 1}), CoerceTo::Number),
+        ] {
+            assert!(coerce(&v, to).is_err(), "{v} as {to:?}");
+        }
+        assert_eq!(
+            coerce(&json!("abc"), CoerceTo::Number)
+                .unwrap_err()
+                .to_string(),
+            r#"Cannot coerce "abc" to number"#
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let out = |raw, compact| Output {
+            as_array: false,
+            raw,
+            compact,
+            count: false,
+            coerce: None,
+            copy: false,
+        };
+        let v = json!({"a": [1, 2]});
+        assert_eq!(
+            render(&json!("x y"), &out(true, false), false).unwrap(),
+            "x y"
+        );
+        assert_eq!(
+            render(&json!("x y"), &out(false, true), false).unwrap(),
+            "\"x y\""
+        );
+        assert_eq!(
+            render(&v, &out(true, true), false).unwrap(),
+            r#"{"a":[1,2]}"#
+        );
+        assert_eq!(
+            render(&v, &out(false, false), true).unwrap(),
+            r#"{"a":[1,2]}"#
+        );
     }
 
     #[test]
@@ -200,4 +915,29 @@ mod tests {
         assert!(msg.contains("PowerShell"));
         assert!(msg.contains("CMD"));
     }
+
+    #[test]
+    fn test_set_creates_intermediates() {
+        let mut doc = json!({"a": {"x": 1}});
+        set_by_path(&mut doc, "a.b[0].c", json!("hello")).unwrap();
+        assert_eq!(doc, json!({"a": {"x": 1, "b": [{"c": "hello"}]}}));
+        set_by_path(&mut doc, "a.b[1]", json!(4)).unwrap();
+        set_by_path(&mut doc, "a.b[-1]", json!(5)).unwrap();
+        assert_eq!(doc["a"]["b"], json!([{"c": "hello"}, 5]));
+        assert!(set_by_path(&mut doc, "a.b[3]", json!(1)).is_err());
+        assert!(set_by_path(&mut doc, "a.b[9223372036854775807]", json!(1)).is_err());
+        assert!(set_by_path(&mut doc, "a.x.y", json!(1)).is_err());
+        assert!(set_by_path(&mut doc, "a.b[-9]", json!(1)).is_err());
+        assert!(set_by_path(&mut doc, "a.b[*]", json!(1)).is_err());
+    }
+
+    #[test]
+    fn test_delete_keys_and_elements() {
+        let mut doc = json!({"a": {"b": [1, 2, 3], "c": true}, "d": 0});
+        assert!(delete_by_path(&mut doc, "a.b[-2]").unwrap());
+        assert!(delete_by_path(&mut doc, "a.c").unwrap());
+        assert!(!delete_by_path(&mut doc, "a.missing.x").unwrap());
+        assert!(!delete_by_path(&mut doc, "a.b[7]").unwrap());
+        assert_eq!(doc, json!({"a": {"b": [1, 3]}, "d": 0}));
+    }
 }
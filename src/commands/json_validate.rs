@@ -0,0 +1,257 @@
+//! `json validate`: check that many JSON files parse and, with --schema, that
+//! they conform to a JSON Schema. Files are checked on several threads and
+//! summarized in one pass/fail table; `--file` reports every violation of one document.
+
+use anyhow::{anyhow, bail, Context, Result};
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use jsonschema::{JsonType, Validator};
+use owo_colors::OwoColorize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::commands::json_select::parse_json;
+use crate::helpers::{files, style, table};
+
+/// What checking one file found.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Valid,
+    /// Schema violations, each as `path: message`.
+    Invalid(Vec<String>),
+    /// The file could not be read or is not JSON.
+    Broken(String),
+}
+
+/// Load a schema file and compile it (the draft is taken from `$schema`).
+fn load_schema(path: &Path, json5: bool) -> Result<Validator> {
+    let raw = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read schema: {}", path.display()))?;
+    let schema =
+        parse_json(raw.trim(), json5).with_context(|| format!("In schema: {}", path.display()))?;
+    jsonschema::validator_for(&schema)
+        .map_err(|e| anyhow!("Invalid schema {}: {e}", path.display()))
+}
+
+/// `path: message` for a violation; the root is shown as `/`. Type mismatches
+/// read `expected integer, got string`.
+fn describe(error: &jsonschema::ValidationError) -> String {
+    let path = error.instance_path.to_string();
+    let path = if path.is_empty() { "/" } else { &path };
+    let message = match &error.kind {
+        ValidationErrorKind::Type { kind } => {
+            let expected = match kind {
+                TypeKind::Single(t) => t.to_string(),
+                TypeKind::Multiple(set) => set
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" or "),
+            };
+            let got = JsonType::from(error.instance.as_ref());
+            format!("expected {expected}, got {got}")
+        }
+        _ => error.to_string(),
+    };
+    format!("{path}: {message}")
+}
+
+/// Parse `text` and check it against `validator`, if any.
+fn check(text: &str, json5: bool, validator: Option<&Validator>) -> Outcome {
+    // parse_json's strict error is advice for --text users; keep the parser's own.
+    let parsed = if json5 {
+        parse_json(text.trim(), true)
+    } else {
+        serde_json::from_str(text).map_err(Into::into)
+    };
+    let value = match parsed {
+        Ok(value) => value,
+        Err(e) => return Outcome::Broken(e.to_string().lines().last().unwrap_or("").into()),
+    };
+    let Some(validator) = validator else {
+        return Outcome::Valid;
+    };
+    let errors: Vec<String> = validator
+        .iter_errors(&value)
+        .map(|e| describe(&e))
+        .collect();
+    if errors.is_empty() {
+        Outcome::Valid
+    } else {
+        Outcome::Invalid(errors)
+    }
+}
+
+/// Check every file on up to `jobs` threads; outcomes keep the order of `paths`.
+fn check_all(
+    paths: &[PathBuf],
+    jobs: usize,
+    json5: bool,
+    validator: Option<&Validator>,
+) -> Vec<Outcome> {
+    let next = AtomicUsize::new(0);
+    let mut found: Vec<(usize, Outcome)> = thread::scope(|s| {
+        let workers: Vec<_> = (0..jobs.min(paths.len()))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(i) else { break };
+                        let outcome = match fs::read_to_string(path) {
+                            Ok(text) => check(&text, json5, validator),
+                            Err(e) => Outcome::Broken(e.to_string()),
+                        };
+                        done.push((i, outcome));
+                    }
+                    done
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("validation thread panicked"))
+            .collect()
+    });
+    found.sort_by_key(|(i, _)| *i);
+    found.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+/// Validate files (globs expanded) and print a summary table; fails if any file does.
+/// Validate one document against the schema, printing each violation.
+fn run_one(file: &Path, validator: &Validator, json5: bool) -> Result<()> {
+    let text = fs::read_to_string(file)
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+    style::title("JSON Validate");
+    match check(&text, json5, Some(validator)) {
+        Outcome::Valid => {
+            style::ok(&format!("✓ {} matches the schema", file.display()));
+            Ok(())
+        }
+        Outcome::Invalid(errors) => {
+            for error in &errors {
+                println!("{} {error}", "✗".red());
+            }
+            bail!("{} violation(s) in {}", errors.len(), file.display())
+        }
+        Outcome::Broken(e) => bail!("Invalid JSON in {}: {e}", file.display()),
+    }
+}
+
+/// Validate `file` against the schema, or every file matching `patterns`
+/// with a summary table; fails if anything does not validate.
+pub fn run(
+    patterns: Vec<String>,
+    file: Option<PathBuf>,
+    schema: Option<PathBuf>,
+    jobs: Option<usize>,
+    json5: bool,
+) -> Result<()> {
+    let jobs = jobs.unwrap_or_else(|| thread::available_parallelism().map_or(4, |n| n.get()));
+    if jobs == 0 {
+        bail!("--jobs must be at least 1");
+    }
+    let validator = schema
+        .as_deref()
+        .map(|p| load_schema(p, json5))
+        .transpose()?;
+    if let Some(file) = file {
+        let validator = validator.context("--file requires --schema")?;
+        return run_one(&file, &validator, json5);
+    }
+    let paths = files::expand_globs(&patterns)?;
+    if paths.is_empty() {
+        bail!("No files to validate");
+    }
+
+    style::title("JSON Validate");
+    let outcomes = check_all(&paths, jobs, json5, validator.as_ref());
+
+    let rows: Vec<Vec<String>> = paths
+        .iter()
+        .zip(&outcomes)
+        .map(|(path, outcome)| {
+            let (status, detail) = match outcome {
+                Outcome::Valid => ("✓ valid".green().to_string(), String::new()),
+                Outcome::Invalid(errors) => {
+                    let more = match errors.len() {
+                        1 => String::new(),
+                        n => format!(" (+{} more)", n - 1),
+                    };
+                    (
+                        format!("✗ {} error(s)", errors.len()).red().to_string(),
+                        format!("{}{more}", errors[0]),
+                    )
+                }
+                Outcome::Broken(e) => ("✗ unreadable".red().to_string(), e.clone()),
+            };
+            vec![path.display().to_string(), status, detail]
+        })
+        .collect();
+    table::print(&["File", "Result", "Detail"], &rows);
+
+    let failed = outcomes.iter().filter(|o| **o != Outcome::Valid).count();
+    println!(
+        "{} {}/{} file(s) valid",
+        "Validated".bold(),
+        paths.len() - failed,
+        paths.len()
+    );
+    if failed > 0 {
+        bail!("{failed} file(s) failed validation");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_against_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"id": {"type": "integer"}},
+            "required": ["id"]
+        });
+        let validator = jsonschema::validator_for(&schema).unwrap();
+        assert_eq!(
+            check(r#"{"id": 1}"#, false, Some(&validator)),
+            Outcome::Valid
+        );
+        match check(r#"{"id": "x"}"#, false, Some(&validator)) {
+            Outcome::Invalid(errors) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0], "/id: expected integer, got string");
+            }
+            other => panic!("expected violations, got {other:?}"),
+        }
+        assert!(matches!(check("{", false, None), Outcome::Broken(_)));
+        match check("{}", false, Some(&validator)) {
+            Outcome::Invalid(errors) => {
+                assert_eq!(errors, vec![r#"/: "id" is a required property"#])
+            }
+            other => panic!("expected violations, got {other:?}"),
+        }
+        assert_eq!(check("{a: 1}", true, None), Outcome::Valid);
+    }
+
+    #[test]
+    fn test_check_all_keeps_order() {
+        let dir = std::env::temp_dir().join(format!("swiftline-validate-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let paths: Vec<PathBuf> = (0..5)
+            .map(|i| {
+                let path = dir.join(format!("{i}.json"));
+                fs::write(&path, if i % 2 == 0 { "[]" } else { "nope" }).unwrap();
+                path
+            })
+            .collect();
+        let outcomes = check_all(&paths, 3, false, None);
+        fs::remove_dir_all(&dir).ok();
+        let valid: Vec<bool> = outcomes.iter().map(|o| *o == Outcome::Valid).collect();
+        assert_eq!(valid, vec![true, false, true, false, true]);
+    }
+}
@@ -0,0 +1,300 @@
+//! `kafka peek`: read a few messages from a topic through the Confluent REST
+//! Proxy (v2 consumer API), so no native Kafka client is needed. A throwaway
+//! consumer instance is created, subscribed, polled until `--count` messages
+//! arrive or the timeout passes, and deleted again. Offsets are never committed.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use log::{info, warn};
+use owo_colors::OwoColorize;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use url::Url;
+
+use crate::cli::{KafkaFormat, KafkaOffset, OutputFormat};
+use crate::helpers::pace;
+
+const V2: &str = "application/vnd.kafka.v2+json";
+
+impl KafkaFormat {
+    fn name(self) -> &'static str {
+        match self {
+            KafkaFormat::Json => "json",
+            KafkaFormat::Avro => "avro",
+            KafkaFormat::Binary => "binary",
+        }
+    }
+}
+
+/// What to read and how long to wait for it.
+pub struct PeekOptions {
+    pub topic: String,
+    pub count: usize,
+    pub format: KafkaFormat,
+    pub from: KafkaOffset,
+    pub group: Option<String>,
+    pub timeout: Duration,
+    pub user: Option<String>,
+    pub output: OutputFormat,
+}
+
+struct Proxy {
+    client: Client,
+    base: Url,
+    user: Option<String>,
+}
+
+impl Proxy {
+    fn new(url: &str, user: Option<String>) -> Result<Self> {
+        let base = Url::parse(url).with_context(|| format!("Invalid REST proxy URL: {url}"))?;
+        if base.cannot_be_a_base() {
+            bail!("Invalid REST proxy URL: {url}");
+        }
+        Ok(Proxy {
+            client: Client::builder()
+                .timeout(Duration::from_secs(30))
+                .user_agent(concat!("swiftline/", env!("CARGO_PKG_VERSION")))
+                .build()?,
+            base,
+            user,
+        })
+    }
+
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .expect("checked in new")
+            .pop_if_empty()
+            .extend(segments);
+        url
+    }
+
+    /// Send a v2 request; the JSON response body, or `Null` for 204.
+    async fn send(
+        &self,
+        method: Method,
+        url: Url,
+        accept: &str,
+        body: Option<Value>,
+    ) -> Result<Value> {
+        let mut req = self
+            .client
+            .request(method, url.clone())
+            .header(ACCEPT, accept);
+        if let Some(user) = &self.user {
+            let (name, pass) = user.split_once(':').unwrap_or((user, ""));
+            req = req.basic_auth(name, Some(pass));
+        }
+        if let Some(body) = body {
+            req = req.header(CONTENT_TYPE, V2).body(body.to_string());
+        }
+        let resp = req
+            .send()
+            .await
+            .with_context(|| format!("Network error calling {url}"))?;
+        let status = resp.status();
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            // Proxy errors look like {"error_code": 40403, "message": "Consumer instance not found."}
+            match body["message"].as_str() {
+                Some(m) => bail!("REST proxy request failed ({status}): {m}"),
+                None => bail!("REST proxy request failed ({status})"),
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// A binary-format key or value: base64 decoded to JSON or text when it is either.
+fn decode_binary(value: &Value) -> Value {
+    let Some(encoded) = value.as_str() else {
+        return value.clone();
+    };
+    let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(encoded) else {
+        return value.clone();
+    };
+    match serde_json::from_slice(&bytes) {
+        Ok(json) => json,
+        Err(_) => match String::from_utf8(bytes) {
+            Ok(text) => Value::String(text),
+            Err(_) => value.clone(),
+        },
+    }
+}
+
+/// The message as printed: partition, offset, key, and value.
+fn message(record: &Value, format: KafkaFormat) -> Value {
+    let (key, value) = match format {
+        KafkaFormat::Binary => (
+            decode_binary(&record["key"]),
+            decode_binary(&record["value"]),
+        ),
+        _ => (record["key"].clone(), record["value"].clone()),
+    };
+    json!({
+        "partition": record["partition"],
+        "offset": record["offset"],
+        "key": key,
+        "value": value,
+    })
+}
+
+fn print_message(m: &Value, output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Json => println!("{m}"),
+        OutputFormat::Table => {
+            let key = match &m["key"] {
+                Value::Null => "(no key)".dimmed().to_string(),
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            println!(
+                "{} {}  {} {key}",
+                format!("partition {} offset {}", m["partition"], m["offset"]).bold(),
+                "·".dimmed(),
+                "key:".cyan()
+            );
+            match &m["value"] {
+                Value::String(s) => println!("{s}"),
+                v => println!("{}", colored_json::to_colored_json_auto(v)?),
+            }
+            println!();
+        }
+    }
+    Ok(())
+}
+
+/// `url` with `segment` appended to its path.
+fn with_segment(url: &Url, segment: &str) -> Result<Url> {
+    let mut out = url.clone();
+    out.path_segments_mut()
+        .ok()
+        .with_context(|| format!("Cannot append /{segment} to {url}"))?
+        .push(segment);
+    Ok(out)
+}
+
+/// Subscribe and poll until `opts.count` messages are printed or the timeout passes.
+async fn consume(proxy: &Proxy, instance: &Url, opts: &PeekOptions) -> Result<usize> {
+    let subscription = with_segment(instance, "subscription")?;
+    proxy
+        .send(
+            Method::POST,
+            subscription,
+            V2,
+            Some(json!({ "topics": [opts.topic] })),
+        )
+        .await?;
+
+    let accept = format!("application/vnd.kafka.{}.v2+json", opts.format.name());
+    let deadline = Instant::now() + opts.timeout;
+    let mut printed = 0;
+    while printed < opts.count && Instant::now() < deadline {
+        let mut records = with_segment(instance, "records")?;
+        records.set_query(Some("timeout=1000"));
+        // The first polls often come back empty while the consumer joins its group.
+        let batch = proxy.send(Method::GET, records, &accept, None).await?;
+        for record in batch.as_array().into_iter().flatten() {
+            if printed == opts.count {
+                break;
+            }
+            print_message(&message(record, opts.format), opts.output)?;
+            printed += 1;
+        }
+    }
+    Ok(printed)
+}
+
+/// Print up to `opts.count` messages of `opts.topic` read through the REST proxy at `url`.
+pub async fn run_peek(url: &str, opts: PeekOptions) -> Result<()> {
+    if opts.count == 0 {
+        bail!("--count must be at least 1");
+    }
+    let proxy = Proxy::new(url, opts.user.clone())?;
+    let suffix = format!("{}-{}", std::process::id(), pace::now_epoch());
+    let group = opts
+        .group
+        .clone()
+        .unwrap_or_else(|| format!("swiftline-peek-{suffix}"));
+    let name = format!("swiftline-{suffix}");
+    let reset = match opts.from {
+        KafkaOffset::Earliest => "earliest",
+        KafkaOffset::Latest => "latest",
+    };
+
+    info!("Creating consumer {name} in group {group}");
+    proxy
+        .send(
+            Method::POST,
+            proxy.url(&["consumers", &group]),
+            V2,
+            Some(json!({
+                "name": name,
+                "format": opts.format.name(),
+                "auto.offset.reset": reset,
+                "auto.commit.enable": "false",
+            })),
+        )
+        .await?;
+    // Address the instance through the URL we were given; the proxy's own
+    // base_uri often names an internal host.
+    let instance = proxy.url(&["consumers", &group, "instances", &name]);
+
+    let result = consume(&proxy, &instance, &opts).await;
+    if let Err(e) = proxy.send(Method::DELETE, instance, V2, None).await {
+        warn!("Could not delete consumer instance {name}: {e:#}");
+    }
+    let printed = result?;
+    if printed < opts.count {
+        eprintln!(
+            "{}",
+            format!(
+                "{printed} message(s) from {} within {}s",
+                opts.topic,
+                opts.timeout.as_secs()
+            )
+            .dimmed()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_segment() {
+        let base = Url::parse("http://proxy:8082/consumers/g/instances/i").unwrap();
+        assert_eq!(
+            with_segment(&base, "records").unwrap().as_str(),
+            "http://proxy:8082/consumers/g/instances/i/records"
+        );
+        assert!(with_segment(&Url::parse("mailto:ops@example.com").unwrap(), "records").is_err());
+    }
+
+    #[test]
+    fn test_decode_binary() {
+        let b64 = |s: &[u8]| json!(base64::engine::general_purpose::STANDARD.encode(s));
+        assert_eq!(decode_binary(&b64(br#"{"id":1}"#)), json!({"id": 1}));
+        assert_eq!(decode_binary(&b64(b"plain text")), json!("plain text"));
+        assert_eq!(decode_binary(&b64(&[0xff, 0xfe])), b64(&[0xff, 0xfe]));
+        assert_eq!(decode_binary(&Value::Null), Value::Null);
+    }
+
+    #[test]
+    fn test_message_shape() {
+        let record = json!({"topic": "orders", "key": "k1", "value": {"id": 7}, "partition": 2, "offset": 40});
+        assert_eq!(
+            message(&record, KafkaFormat::Json),
+            json!({"partition": 2, "offset": 40, "key": "k1", "value": {"id": 7}})
+        );
+        let proxy = Proxy::new("http://proxy:8082/kafka/", None).unwrap();
+        assert_eq!(
+            proxy.url(&["consumers", "g 1"]).as_str(),
+            "http://proxy:8082/kafka/consumers/g%201"
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! `logs parse`: turn classic text logs (NCSA common/combined as written by nginx
+//! and Apache, or logfmt) into JSON records for `logs tail`, `json select`, and jq.
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use regex::Regex;
+use serde_json::{Map, Number, Value};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+use crate::cli::{LogFormat, LogOutput};
+use crate::helpers::files;
+
+/// Common Log Format, optionally followed by the combined `"referer" "user-agent"` pair.
+const NCSA: &str = r#"^(\S+) (\S+) (\S+) \[([^\]]+)\] "((?:[^"\\]|\\.)*)" (\d{3}) (\d+|-)(?: "((?:[^"\\]|\\.)*)" "((?:[^"\\]|\\.)*)")?"#;
+
+/// Undo nginx/Apache escaping inside quoted fields (`\"`, `\\`, `\xHH`).
+fn unescape(s: &str) -> String {
+    if !s.contains('\\') {
+        return s.to_string();
+    }
+    let mut out = Vec::with_capacity(s.len());
+    let b = s.as_bytes();
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'\\' && i + 1 < b.len() {
+            if b[i + 1] == b'x'
+                && b.get(i + 2..i + 4)
+                    .is_some_and(|h| h.iter().all(u8::is_ascii_hexdigit))
+            {
+                out.push(u8::from_str_radix(&s[i + 2..i + 4], 16).unwrap_or(0));
+                i += 4;
+                continue;
+            }
+            out.push(b[i + 1]);
+            i += 2;
+        } else {
+            out.push(b[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// `10/Oct/2000:13:55:36 -0700` as RFC 3339 (`2000-10-10T13:55:36-07:00`).
+fn clf_time(s: &str) -> Option<String> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (stamp, zone) = s.split_once(' ')?;
+    let mut parts = stamp.splitn(4, [':', '/']);
+    let (day, mon, year) = (parts.next()?, parts.next()?, parts.next()?);
+    let clock = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == mon)? + 1;
+    let digits = |v: &str, n: usize| v.len() == n && v.bytes().all(|c| c.is_ascii_digit());
+    if !digits(day, 2) || !digits(year, 4) || clock.len() != 8 {
+        return None;
+    }
+    let (sign, off) = zone.split_at_checked(1)?;
+    if !matches!(sign, "+" | "-") || !digits(off, 4) {
+        return None;
+    }
+    Some(format!(
+        "{year}-{month:02}-{day}T{clock}{sign}{}:{}",
+        &off[..2],
+        &off[2..]
+    ))
+}
+
+/// `-` means "absent" in NCSA logs.
+fn dash_null(s: &str) -> Value {
+    if s == "-" {
+        Value::Null
+    } else {
+        Value::String(unescape(s))
+    }
+}
+
+/// Parse one NCSA line; `combined` requires the referer and user-agent fields.
+fn parse_ncsa(re: &Regex, line: &str, combined: bool) -> Option<Value> {
+    let c = re.captures(line)?;
+    if combined && c.get(8).is_none() {
+        return None;
+    }
+    let mut rec = Map::new();
+    rec.insert("remote_addr".into(), Value::String(c[1].to_string()));
+    rec.insert("ident".into(), dash_null(&c[2]));
+    rec.insert("remote_user".into(), dash_null(&c[3]));
+    rec.insert(
+        "time".into(),
+        Value::String(clf_time(&c[4]).unwrap_or_else(|| c[4].to_string())),
+    );
+    let request = unescape(&c[5]);
+    let parts: Vec<&str> = request.split(' ').collect();
+    if let [method, path, protocol] = parts[..] {
+        rec.insert("method".into(), Value::String(method.to_string()));
+        rec.insert("path".into(), Value::String(path.to_string()));
+        rec.insert("protocol".into(), Value::String(protocol.to_string()));
+    } else {
+        // Probes and TLS garbage on plain ports leave no usable request line.
+        rec.insert("request".into(), Value::String(request.clone()));
+    }
+    rec.insert("status".into(), Value::from(c[6].parse::<u16>().ok()?));
+    rec.insert(
+        "bytes".into(),
+        c[7].parse::<u64>()
+            .map(Value::from)
+            .unwrap_or(Value::from(0)),
+    );
+    if let (Some(referer), Some(agent)) = (c.get(8), c.get(9)) {
+        rec.insert("referer".into(), dash_null(referer.as_str()));
+        rec.insert("user_agent".into(), dash_null(agent.as_str()));
+    }
+    Some(Value::Object(rec))
+}
+
+/// Bare logfmt values become numbers or booleans when unambiguous; quoted ones stay strings.
+fn logfmt_value(v: &str) -> Value {
+    match v {
+        "true" => return Value::Bool(true),
+        "false" => return Value::Bool(false),
+        _ => {}
+    }
+    if let Ok(n) = v.parse::<i64>() {
+        if n.to_string() == v {
+            return Value::from(n);
+        }
+    }
+    if v.contains('.') && !v.starts_with(['.', '+']) && !v.ends_with('.') {
+        if let Some(n) = v.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Value::Number(n);
+        }
+    }
+    Value::String(v.to_string())
+}
+
+/// Parse `key=value key2="quoted value" flag`; `None` when no key=value pair is present.
+fn parse_logfmt(line: &str) -> Option<Value> {
+    let mut rec = Map::new();
+    let mut pairs = 0;
+    let mut chars = line.trim().chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+            key.push(c);
+        }
+        if chars.next_if_eq(&'=').is_none() {
+            // A bare key is a flag.
+            if !key.is_empty() {
+                rec.insert(key, Value::Bool(true));
+            }
+            continue;
+        }
+        let value = if chars.next_if_eq(&'"').is_some() {
+            let mut s = String::new();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('n') => s.push('\n'),
+                        Some('t') => s.push('\t'),
+                        Some(other) => s.push(other),
+                        None => {}
+                    },
+                    _ => s.push(c),
+                }
+            }
+            Value::String(s)
+        } else {
+            let mut s = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                s.push(c);
+            }
+            logfmt_value(&s)
+        };
+        if !key.is_empty() {
+            rec.insert(key, value);
+            pairs += 1;
+        }
+    }
+    (pairs > 0).then_some(Value::Object(rec))
+}
+
+/// Parse every line of `inputs` (globs allowed; stdin when empty) and print JSON records.
+pub fn run(inputs: Vec<String>, format: LogFormat, output: LogOutput) -> Result<()> {
+    let re = Regex::new(NCSA).expect("NCSA pattern is valid");
+    let parse = |line: &str| match format {
+        LogFormat::Nginx => parse_ncsa(&re, line, true),
+        LogFormat::Clf => parse_ncsa(&re, line, false),
+        LogFormat::Logfmt => parse_logfmt(line),
+    };
+
+    let readers: Vec<Box<dyn BufRead>> = if inputs.is_empty() {
+        vec![Box::new(BufReader::new(io::stdin()))]
+    } else {
+        files::expand_globs(&inputs)?
+            .into_iter()
+            .map(|path| {
+                File::open(&path)
+                    .map(|f| Box::new(BufReader::new(f)) as Box<dyn BufRead>)
+                    .with_context(|| format!("Failed to read: {}", path.display()))
+            })
+            .collect::<Result<_>>()?
+    };
+
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    let mut records = Vec::new();
+    let mut skipped = 0usize;
+    for reader in readers {
+        for line in reader.split(b'\n') {
+            let line = String::from_utf8_lossy(&line?).into_owned();
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some(record) = parse(line) else {
+                skipped += 1;
+                continue;
+            };
+            match output {
+                LogOutput::Ndjson => {
+                    // A closed pipe (e.g. `| head`) ends the run quietly.
+                    if writeln!(out, "{record}").is_err() {
+                        return Ok(());
+                    }
+                }
+                LogOutput::Json => records.push(record),
+            }
+        }
+    }
+    if let LogOutput::Json = output {
+        writeln!(
+            out,
+            "{}",
+            colored_json::to_colored_json_auto(&Value::Array(records))?
+        )
+        .ok();
+    }
+    out.flush().ok();
+    if skipped > 0 {
+        eprintln!(
+            "{}",
+            format!(
+                "Skipped {skipped} line{} not in {} format",
+                if skipped == 1 { "" } else { "s" },
+                match format {
+                    LogFormat::Nginx => "nginx/combined",
+                    LogFormat::Clf => "common log",
+                    LogFormat::Logfmt => "logfmt",
+                }
+            )
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_combined_and_common() {
+        let re = Regex::new(NCSA).unwrap();
+        let line = r#"203.0.113.9 - alice [10/Oct/2000:13:55:36 -0700] "GET /a?b=1 HTTP/1.1" 200 2326 "https://ex.com/" "curl/8.0 \x22x\x22""#;
+        let rec = parse_ncsa(&re, line, true).unwrap();
+        assert_eq!(rec["time"], json!("2000-10-10T13:55:36-07:00"));
+        assert_eq!(rec["method"], json!("GET"));
+        assert_eq!(rec["path"], json!("/a?b=1"));
+        assert_eq!(rec["status"], json!(200));
+        assert_eq!(rec["ident"], Value::Null);
+        assert_eq!(rec["remote_user"], json!("alice"));
+        assert_eq!(rec["user_agent"], json!("curl/8.0 \"x\""));
+
+        let common = r#"::1 - - [01/Jan/2024:00:00:00 +0000] "\x16\x03\x01" 400 -"#;
+        let rec = parse_ncsa(&re, common, false).unwrap();
+        assert_eq!(rec["bytes"], json!(0));
+        assert_eq!(rec["request"], json!("\u{16}\u{3}\u{1}"));
+        assert!(rec.get("referer").is_none());
+        assert!(parse_ncsa(&re, common, true).is_none());
+        assert!(parse_ncsa(&re, "not a log line", false).is_none());
+        assert_eq!(unescape(r"\xé"), "xé");
+    }
+
+    #[test]
+    fn test_logfmt() {
+        let rec = parse_logfmt(
+            r#"ts=2024-01-01T00:00:00Z level=info msg="request done \"ok\"" status=200 took=1.5 id="42" cached"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rec,
+            json!({
+                "ts": "2024-01-01T00:00:00Z",
+                "level": "info",
+                "msg": "request done \"ok\"",
+                "status": 200,
+                "took": 1.5,
+                "id": "42",
+                "cached": true
+            })
+        );
+        assert_eq!(logfmt_value("007"), json!("007"));
+        assert_eq!(logfmt_value("1.2.3"), json!("1.2.3"));
+        assert!(parse_logfmt("just some words").is_none());
+    }
+
+    #[test]
+    fn test_clf_time() {
+        assert_eq!(
+            clf_time("01/Feb/2024:09:05:00 +0530").as_deref(),
+            Some("2024-02-01T09:05:00+05:30")
+        );
+        assert_eq!(clf_time("1/Feb/2024:09:05:00 +0530"), None);
+        assert_eq!(clf_time("01/Foo/2024:09:05:00 +0000"), None);
+    }
+}
@@ -0,0 +1,266 @@
+//! `logs tail`: a structured `tail -f` for NDJSON logs. Each line is parsed as
+//! JSON, filtered with a `--where` expression (see `helpers::expr`), reduced to
+//! the `--path` fields, and printed with its severity colorized.
+
+use anyhow::{Context, Result};
+use owo_colors::OwoColorize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::commands::json_select::get_by_path;
+use crate::helpers::expr::{self, Compiled};
+
+/// How often a followed file is checked for new data.
+const POLL: Duration = Duration::from_millis(250);
+
+/// Field names that commonly carry the severity (logrus, zap, bunyan/pino, GCP).
+const LEVEL_KEYS: [&str; 4] = ["level", "severity", "lvl", "log.level"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Severity from a level string or a bunyan/pino numeric level.
+fn severity(level: &Value) -> Option<Severity> {
+    if let Some(n) = level.as_u64() {
+        return Some(match n {
+            0..=10 => Severity::Trace,
+            11..=20 => Severity::Debug,
+            21..=30 => Severity::Info,
+            31..=40 => Severity::Warn,
+            _ => Severity::Error,
+        });
+    }
+    let s = level.as_str()?.to_ascii_lowercase();
+    Some(match s.as_str() {
+        "trace" | "verbose" => Severity::Trace,
+        "debug" => Severity::Debug,
+        "info" | "information" | "notice" => Severity::Info,
+        "warn" | "warning" => Severity::Warn,
+        "error" | "err" | "fatal" | "panic" | "critical" | "crit" | "alert" | "emergency" => {
+            Severity::Error
+        }
+        _ => return None,
+    })
+}
+
+/// The record's level field name, if any.
+fn level_key(record: &Value) -> Option<&'static str> {
+    LEVEL_KEYS
+        .into_iter()
+        .find(|k| record.get(*k).is_some_and(|v| severity(v).is_some()))
+}
+
+fn paint(text: &str, sev: Option<Severity>) -> String {
+    match sev {
+        Some(Severity::Error) => text.red().bold().to_string(),
+        Some(Severity::Warn) => text.yellow().to_string(),
+        Some(Severity::Debug | Severity::Trace) => text.dimmed().to_string(),
+        Some(Severity::Info) | None => text.to_string(),
+    }
+}
+
+/// Line filter and field selection, applied to every line.
+struct View {
+    paths: Vec<String>,
+    filter: Option<Compiled>,
+}
+
+impl View {
+    fn new(paths: Option<&str>, filter: Option<&str>) -> Result<Self> {
+        let paths = paths
+            .map(|p| {
+                p.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let filter = filter.map(Compiled::new).transpose()?;
+        Ok(View { paths, filter })
+    }
+
+    /// Render one log line, or `None` when the filter drops it. Lines that are
+    /// not JSON pass through dimmed unless a filter is set.
+    fn render(&self, line: &str) -> Option<String> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() {
+            return None;
+        }
+        let Ok(record) = serde_json::from_str::<Value>(line) else {
+            return self.filter.is_none().then(|| line.dimmed().to_string());
+        };
+        if let Some(filter) = &self.filter {
+            // A filter that errors on a record (e.g. comparing a string with a number) drops it.
+            if !filter.eval_record(&record).is_ok_and(|v| expr::truthy(&v)) {
+                return None;
+            }
+        }
+        let key = level_key(&record);
+        let sev = key.and_then(|k| severity(&record[k]));
+        if self.paths.is_empty() {
+            return Some(paint(line, sev));
+        }
+        let fields: Vec<String> = self
+            .paths
+            .iter()
+            .map(|p| {
+                let text = match get_by_path(&record, p).as_deref() {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(v) => v.to_string(),
+                    None => "-".to_string(),
+                };
+                if Some(p.as_str()) == key {
+                    paint(&text, sev)
+                } else {
+                    text
+                }
+            })
+            .collect();
+        Some(fields.join("  "))
+    }
+}
+
+/// Read whatever was appended since `pos`, returning complete lines and keeping
+/// a trailing partial line in `partial`. A shrunken file is read from the start.
+fn read_new(path: &Path, pos: &mut u64, partial: &mut String) -> Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len < *pos {
+        eprintln!("{}", "-- file truncated, reading from start --".dimmed());
+        *pos = 0;
+        partial.clear();
+    }
+    if len == *pos {
+        return Ok(Vec::new());
+    }
+    file.seek(SeekFrom::Start(*pos))?;
+    let mut buf = Vec::new();
+    file.take(len - *pos).read_to_end(&mut buf)?;
+    *pos += buf.len() as u64;
+    partial.push_str(&String::from_utf8_lossy(&buf));
+    let mut lines: Vec<String> = partial.split('\n').map(str::to_string).collect();
+    *partial = lines.pop().unwrap_or_default();
+    Ok(lines)
+}
+
+/// Print the last `last` matching lines of `file`, then keep printing new ones if `follow`.
+pub fn run(
+    file: &Path,
+    paths: Option<String>,
+    filter: Option<String>,
+    follow: bool,
+    last: usize,
+) -> Result<()> {
+    let view = View::new(paths.as_deref(), filter.as_deref())?;
+    let f = File::open(file).with_context(|| format!("Failed to read file: {}", file.display()))?;
+    let mut pos = f.metadata()?.len();
+
+    let mut tail: VecDeque<String> = VecDeque::with_capacity(last);
+    let mut reader = BufReader::new(f.take(pos));
+    let mut line = String::new();
+    // An unterminated last line is still being written; --follow completes it.
+    let mut partial = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        if follow && !line.ends_with('\n') {
+            partial = std::mem::take(&mut line);
+            break;
+        }
+        if let Some(out) = view.render(&line) {
+            if tail.len() == last {
+                tail.pop_front();
+            }
+            if last > 0 {
+                tail.push_back(out);
+            }
+        }
+        line.clear();
+    }
+    for out in tail {
+        println!("{out}");
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    loop {
+        std::thread::sleep(POLL);
+        // The file may briefly disappear during rotation; keep waiting for it.
+        let Ok(lines) = read_new(file, &mut pos, &mut partial) else {
+            continue;
+        };
+        for line in lines {
+            if let Some(out) = view.render(&line) {
+                println!("{out}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::table::strip_ansi;
+    use serde_json::json;
+
+    #[test]
+    fn test_severity() {
+        assert_eq!(severity(&json!("WARNING")), Some(Severity::Warn));
+        assert_eq!(severity(&json!(50)), Some(Severity::Error));
+        assert_eq!(severity(&json!("chatty")), None);
+        assert_eq!(
+            level_key(&json!({"severity": "ERROR", "msg": "x"})),
+            Some("severity")
+        );
+    }
+
+    #[test]
+    fn test_render_select_and_filter() {
+        let view = View::new(Some("level, msg, req.id"), Some("level == 'error'")).unwrap();
+        let out = view
+            .render(r#"{"level":"error","msg":"boom","req":{"id":7}}"#)
+            .unwrap();
+        assert_eq!(strip_ansi(&out), "error  boom  7");
+        assert!(view.render(r#"{"level":"info","msg":"ok"}"#).is_none());
+        assert!(view.render("plain text line").is_none());
+
+        let all = View::new(None, None).unwrap();
+        assert_eq!(
+            all.render("plain").map(|s| strip_ansi(&s)),
+            Some("plain".into())
+        );
+        assert!(all.render("   ").is_none());
+    }
+
+    #[test]
+    fn test_read_new_handles_partial_and_truncation() {
+        let path = std::env::temp_dir().join(format!("swiftline-tail-{}.log", std::process::id()));
+        std::fs::write(&path, "a\nb").unwrap();
+        let (mut pos, mut partial) = (0, String::new());
+        assert_eq!(read_new(&path, &mut pos, &mut partial).unwrap(), vec!["a"]);
+        assert_eq!(partial, "b");
+
+        let mut f = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut f, b"c\nd\n").unwrap();
+        assert_eq!(
+            read_new(&path, &mut pos, &mut partial).unwrap(),
+            vec!["bc", "d"]
+        );
+
+        std::fs::write(&path, "x\n").unwrap();
+        assert_eq!(read_new(&path, &mut pos, &mut partial).unwrap(), vec!["x"]);
+        std::fs::remove_file(&path).ok();
+    }
+}
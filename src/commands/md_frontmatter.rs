@@ -0,0 +1,212 @@
+//! `md frontmatter`: extract the YAML (`---`) or TOML (`+++`) front-matter
+//! block of a Markdown file, print it as JSON/YAML/TOML, or update keys with `--set`.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::FrontmatterFormat;
+use crate::helpers::style;
+
+/// Front-matter flavor, recognized by its fence line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Yaml,
+    Toml,
+}
+
+impl Kind {
+    fn fence(self) -> &'static str {
+        match self {
+            Kind::Yaml => "---",
+            Kind::Toml => "+++",
+        }
+    }
+}
+
+/// A Markdown document split into its front-matter block and the remaining body.
+#[derive(Debug)]
+struct Document<'a> {
+    kind: Kind,
+    raw: &'a str,
+    body: &'a str,
+}
+
+/// Split a document on its leading fence. Returns None when there is no front-matter.
+fn split(input: &str) -> Option<Document<'_>> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let first_line = input.lines().next()?;
+    let kind = match first_line.trim_end() {
+        "---" => Kind::Yaml,
+        "+++" => Kind::Toml,
+        _ => return None,
+    };
+
+    // Walk line by line so byte offsets stay exact (CRLF included).
+    let start = first_line.len() + line_break_len(&input[first_line.len()..]);
+    let mut offset = start;
+    for line in input[start..].split_inclusive('\n') {
+        if line.trim_end() == kind.fence() {
+            let raw = &input[start..offset];
+            let body = &input[offset + line.len()..];
+            return Some(Document { kind, raw, body });
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn line_break_len(rest: &str) -> usize {
+    if rest.starts_with("\r\n") {
+        2
+    } else if rest.starts_with('\n') {
+        1
+    } else {
+        0
+    }
+}
+
+/// Parse the raw front-matter into a JSON value.
+fn parse(kind: Kind, raw: &str) -> Result<Value> {
+    match kind {
+        Kind::Yaml => {
+            if raw.trim().is_empty() {
+                return Ok(Value::Object(Default::default()));
+            }
+            serde_yaml::from_str(raw).context("Invalid YAML front-matter")
+        }
+        Kind::Toml => {
+            let table: toml::Table = toml::from_str(raw).context("Invalid TOML front-matter")?;
+            Ok(serde_json::to_value(table)?)
+        }
+    }
+}
+
+/// Render a front-matter value back to YAML or TOML text (without fences).
+fn render(kind: Kind, value: &Value) -> Result<String> {
+    let text = match kind {
+        Kind::Yaml => serde_yaml::to_string(value)?,
+        Kind::Toml => toml::to_string(value).context("Value cannot be represented as TOML")?,
+    };
+    Ok(text)
+}
+
+/// Parse `key=value`. The value is read as JSON when possible
+/// (`draft=false`, `tags=["a","b"]`), otherwise kept as a plain string.
+fn parse_assignment(item: &str) -> Result<(String, Value)> {
+    let (k, v) = item
+        .split_once('=')
+        .with_context(|| format!("--set must be key=value, got: {item}"))?;
+    let key = k.trim();
+    if key.is_empty() {
+        bail!("--set key must not be empty: {item}");
+    }
+    let value = serde_json::from_str(v).unwrap_or_else(|_| Value::String(v.to_string()));
+    Ok((key.to_string(), value))
+}
+
+/// Extract, print, or update the front-matter of a Markdown file.
+pub fn run(
+    file: PathBuf,
+    output: FrontmatterFormat,
+    set: Vec<String>,
+    in_place: bool,
+) -> Result<()> {
+    let input = fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read file: {}", file.display()))?;
+
+    if set.is_empty() {
+        if in_place {
+            bail!("-i/--in-place requires at least one --set key=value");
+        }
+        let doc = split(&input)
+            .with_context(|| format!("No front-matter found in {}", file.display()))?;
+        let value = parse(doc.kind, doc.raw)?;
+        match output {
+            FrontmatterFormat::Json => println!("{}", colored_json::to_colored_json_auto(&value)?),
+            FrontmatterFormat::Yaml => print!("{}", render(Kind::Yaml, &value)?),
+            FrontmatterFormat::Toml => print!("{}", render(Kind::Toml, &value)?),
+        }
+        return Ok(());
+    }
+
+    // Files without front-matter get a fresh YAML block.
+    let (kind, mut value, body) = match split(&input) {
+        Some(doc) => (doc.kind, parse(doc.kind, doc.raw)?, doc.body),
+        None => (
+            Kind::Yaml,
+            Value::Object(Default::default()),
+            input.as_str(),
+        ),
+    };
+    let map = value
+        .as_object_mut()
+        .context("Front-matter must be a mapping to use --set")?;
+    for item in &set {
+        let (key, val) = parse_assignment(item)?;
+        map.insert(key, val);
+    }
+
+    let updated = format!(
+        "{fence}\n{}{fence}\n{body}",
+        render(kind, &value)?,
+        fence = kind.fence()
+    );
+
+    if in_place {
+        fs::write(&file, updated)
+            .with_context(|| format!("Failed to write file: {}", file.display()))?;
+        style::ok(&format!("Updated front-matter in {}", file.display()));
+    } else {
+        print!("{updated}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_split_yaml() {
+        let doc = split("---\ntitle: Hi\n---\n# Body\n").unwrap();
+        assert_eq!(doc.kind, Kind::Yaml);
+        assert_eq!(doc.raw, "title: Hi\n");
+        assert_eq!(doc.body, "# Body\n");
+    }
+
+    #[test]
+    fn test_split_toml_crlf() {
+        let doc = split("+++\r\ntitle = \"Hi\"\r\n+++\r\nBody").unwrap();
+        assert_eq!(doc.kind, Kind::Toml);
+        assert_eq!(doc.raw, "title = \"Hi\"\r\n");
+        assert_eq!(doc.body, "Body");
+    }
+
+    #[test]
+    fn test_split_without_frontmatter() {
+        assert!(split("# Just a heading\n").is_none());
+        assert!(split("---\nunterminated: true\n").is_none());
+    }
+
+    #[test]
+    fn test_parse_both_kinds() {
+        assert_eq!(
+            parse(Kind::Yaml, "title: Hi\ntags: [a, b]\n").unwrap(),
+            json!({"title": "Hi", "tags": ["a", "b"]})
+        );
+        assert_eq!(
+            parse(Kind::Toml, "title = \"Hi\"\ndraft = true\n").unwrap(),
+            json!({"title": "Hi", "draft": true})
+        );
+    }
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(parse_assignment("draft=false").unwrap().1, json!(false));
+        assert_eq!(parse_assignment("title=Hello").unwrap().1, json!("Hello"));
+        assert!(parse_assignment("novalue").is_err());
+    }
+}
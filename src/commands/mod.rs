@@ -1,2 +1,65 @@
-pub mod http_get;
+pub mod avro;
+pub mod bin_diff;
+pub mod cert_convert;
+pub mod cert_gen;
+pub mod cert_inspect;
+pub mod cloud_metadata;
+pub mod convert;
+pub mod db_query;
+pub mod db_redis;
+pub mod db_sqlite;
+pub mod deps;
+pub mod docker;
+pub mod forge;
+pub mod html_convert;
+pub mod http_audit;
+pub mod http_batch;
+pub mod http_bench;
+pub mod http_cors;
+pub mod http_curl;
+pub mod http_file;
+pub mod http_graphql;
+pub mod http_listen;
+pub mod http_metrics;
+pub mod http_new;
+pub mod http_openapi;
+pub mod http_replay;
+pub mod http_request;
+pub mod http_serve;
+pub mod http_snapshot;
+pub mod http_ws;
+pub mod json_convert;
+pub mod json_diff;
+pub mod json_edit;
+pub mod json_eval;
+pub mod json_flatten;
+pub mod json_format;
+pub mod json_inspect;
+pub mod json_merge;
+pub mod json_patch;
+pub mod json_replace;
 pub mod json_select;
+pub mod json_validate;
+pub mod kafka;
+pub mod logs_parse;
+pub mod logs_tail;
+pub mod md_frontmatter;
+pub mod net_cidr;
+pub mod net_discover;
+pub mod net_mac;
+pub mod net_ntp;
+pub mod net_udp;
+pub mod num_format;
+pub mod oci;
+pub mod rand_password;
+pub mod registry;
+pub mod s3;
+pub mod sbom;
+pub mod ssh_fingerprint;
+pub mod text_convert;
+pub mod text_lines;
+pub mod text_normalize;
+pub mod text_width;
+pub mod url_build;
+pub mod verify_sig;
+pub mod verify_webhook;
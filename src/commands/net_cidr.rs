@@ -0,0 +1,275 @@
+//! `net cidr`: IPv4/IPv6 subnet calculator. Prints network, broadcast, usable
+//! host range and address count; `contains` tests membership and `split`
+//! lists the subnets of a longer prefix.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::cli::CidrCommands;
+use crate::helpers::style;
+
+/// A parsed `address/prefix`, kept as a 128-bit integer plus the family width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cidr {
+    addr: u128,
+    prefix: u32,
+    width: u32,
+}
+
+fn to_bits(ip: IpAddr) -> (u128, u32) {
+    match ip {
+        IpAddr::V4(v4) => (u128::from(u32::from(v4)), 32),
+        IpAddr::V6(v6) => (u128::from(v6), 128),
+    }
+}
+
+impl Cidr {
+    /// Parse `10.0.0.0/22` or `2001:db8::/48`; a bare address is a single-host network.
+    fn parse(s: &str) -> Result<Self> {
+        let (ip, prefix) = match s.split_once('/') {
+            Some((ip, p)) => (ip, Some(p)),
+            None => (s, None),
+        };
+        let ip: IpAddr = ip
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid IP address: {ip}"))?;
+        let (addr, width) = to_bits(ip);
+        let prefix = match prefix {
+            Some(p) => p
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|p| *p <= width)
+                .with_context(|| format!("Prefix must be 0-{width}, got: {p}"))?,
+            None => width,
+        };
+        Ok(Self {
+            addr,
+            prefix,
+            width,
+        })
+    }
+
+    /// Bits set for the host part, within the family width.
+    fn host_mask(&self) -> u128 {
+        let host_bits = self.width - self.prefix;
+        if host_bits == 128 {
+            u128::MAX
+        } else {
+            (1u128 << host_bits) - 1
+        }
+    }
+
+    fn network(&self) -> u128 {
+        self.addr & !self.host_mask()
+    }
+
+    fn last(&self) -> u128 {
+        self.network() | self.host_mask()
+    }
+
+    fn contains(&self, addr: u128, width: u32) -> bool {
+        width == self.width && addr >= self.network() && addr <= self.last()
+    }
+
+    /// Usable host range. IPv4 reserves network and broadcast except on /31 and /32
+    /// (RFC 3021); IPv6 has no broadcast, so every address is usable.
+    fn hosts(&self) -> (u128, u128) {
+        if self.width == 32 && self.prefix < 31 {
+            (self.network() + 1, self.last() - 1)
+        } else {
+            (self.network(), self.last())
+        }
+    }
+
+    fn ip(&self, bits: u128) -> IpAddr {
+        if self.width == 32 {
+            IpAddr::V4(Ipv4Addr::from(bits as u32))
+        } else {
+            IpAddr::V6(Ipv6Addr::from(bits))
+        }
+    }
+
+    /// Number of addresses as text (2^128 does not fit in a u128).
+    fn count_label(&self, bits: u32) -> String {
+        match 1u128.checked_shl(bits) {
+            Some(n) => n.to_string(),
+            None => "2^128".to_string(),
+        }
+    }
+
+    fn netmask(&self) -> IpAddr {
+        let full = if self.width == 32 {
+            u128::from(u32::MAX)
+        } else {
+            u128::MAX
+        };
+        self.ip(full & !self.host_mask())
+    }
+}
+
+/// Subnets of `cidr` with prefix `new_prefix`, at most `limit` of them.
+fn split(cidr: &Cidr, new_prefix: u32, limit: usize) -> Result<(Vec<Cidr>, String)> {
+    if new_prefix < cidr.prefix || new_prefix > cidr.width {
+        bail!(
+            "Split prefix must be between /{} and /{}",
+            cidr.prefix,
+            cidr.width
+        );
+    }
+    let step_bits = cidr.width - new_prefix;
+    let total_bits = new_prefix - cidr.prefix;
+    let mut out = Vec::new();
+    let mut addr = cidr.network();
+    while out.len() < limit {
+        out.push(Cidr {
+            addr,
+            prefix: new_prefix,
+            width: cidr.width,
+        });
+        match 1u128
+            .checked_shl(step_bits)
+            .and_then(|s| addr.checked_add(s))
+        {
+            Some(next) if next <= cidr.last() => addr = next,
+            _ => break,
+        }
+    }
+    Ok((out, cidr.count_label(total_bits)))
+}
+
+/// Print the subnet summary, or answer `contains` / `split`.
+pub fn run(cidr: &str, action: Option<CidrCommands>) -> Result<()> {
+    let c = Cidr::parse(cidr)?;
+    let label = format!("{}/{}", c.ip(c.network()), c.prefix);
+
+    if let Some(CidrCommands::Contains { ip }) = &action {
+        let ip: IpAddr = ip
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid IP address: {ip}"))?;
+        let (bits, width) = to_bits(ip);
+        if c.contains(bits, width) {
+            style::ok(&format!("{ip} is in {label}"));
+            return Ok(());
+        }
+        bail!("{ip} is not in {label}");
+    }
+
+    if let Some(CidrCommands::Split { prefix, limit }) = action {
+        let (subnets, total) = split(&c, prefix, limit)?;
+        for s in &subnets {
+            println!("{}/{}", s.ip(s.network()), s.prefix);
+        }
+        if subnets.len().to_string() != total {
+            style::warn_line(&format!(
+                "Showing {} of {total} subnets (raise --limit for more)",
+                subnets.len()
+            ));
+        }
+        return Ok(());
+    }
+
+    let (first, last) = c.hosts();
+    let usable_bits = c.width - c.prefix;
+    let usable = if c.width == 32 && c.prefix < 31 {
+        ((1u128 << usable_bits) - 2).to_string()
+    } else {
+        c.count_label(usable_bits)
+    };
+    let rows = [
+        ("Network", label.clone()),
+        ("Netmask", c.netmask().to_string()),
+        (
+            if c.width == 32 {
+                "Broadcast"
+            } else {
+                "Last address"
+            },
+            c.ip(c.last()).to_string(),
+        ),
+        ("Host range", format!("{} - {}", c.ip(first), c.ip(last))),
+        ("Addresses", c.count_label(usable_bits)),
+        ("Usable hosts", usable),
+    ];
+    for (k, v) in rows {
+        println!("{} {v}", format!("{k}:").bold());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipv4_summary() {
+        let c = Cidr::parse("10.0.1.77/22").unwrap();
+        assert_eq!(c.ip(c.network()).to_string(), "10.0.0.0");
+        assert_eq!(c.ip(c.last()).to_string(), "10.0.3.255");
+        assert_eq!(c.netmask().to_string(), "255.255.252.0");
+        let (a, b) = c.hosts();
+        assert_eq!(
+            (c.ip(a).to_string(), c.ip(b).to_string()),
+            ("10.0.0.1".into(), "10.0.3.254".into())
+        );
+        assert_eq!(c.count_label(10), "1024");
+    }
+
+    #[test]
+    fn test_point_to_point_and_host_routes() {
+        let c = Cidr::parse("192.168.0.4/31").unwrap();
+        let (a, b) = c.hosts();
+        assert_eq!((a, b), (c.network(), c.last()));
+        assert_eq!(Cidr::parse("1.2.3.4").unwrap().prefix, 32);
+        assert!(Cidr::parse("1.2.3.4/33").is_err());
+    }
+
+    #[test]
+    fn test_contains() {
+        let c = Cidr::parse("10.0.0.0/22").unwrap();
+        let (inside, w) = to_bits("10.0.1.5".parse().unwrap());
+        assert!(c.contains(inside, w));
+        let (outside, w) = to_bits("10.0.4.0".parse().unwrap());
+        assert!(!c.contains(outside, w));
+        let (v6, w) = to_bits("::1".parse().unwrap());
+        assert!(!c.contains(v6, w));
+    }
+
+    #[test]
+    fn test_split() {
+        let c = Cidr::parse("10.0.0.0/22").unwrap();
+        let (subnets, total) = split(&c, 24, 100).unwrap();
+        let labels: Vec<String> = subnets
+            .iter()
+            .map(|s| format!("{}/{}", s.ip(s.network()), s.prefix))
+            .collect();
+        assert_eq!(
+            labels,
+            ["10.0.0.0/24", "10.0.1.0/24", "10.0.2.0/24", "10.0.3.0/24"]
+        );
+        assert_eq!(total, "4");
+        assert!(split(&c, 20, 10).is_err());
+
+        let v6 = Cidr::parse("2001:db8::/32").unwrap();
+        let (subnets, total) = split(&v6, 48, 2).unwrap();
+        assert_eq!(subnets.len(), 2);
+        assert_eq!(total, "65536");
+        assert_eq!(
+            subnets[1].ip(subnets[1].network()).to_string(),
+            "2001:db8:1::"
+        );
+    }
+
+    #[test]
+    fn test_ipv6_whole_space() {
+        let c = Cidr::parse("::/0").unwrap();
+        assert_eq!(c.count_label(128), "2^128");
+        assert_eq!(
+            c.ip(c.last()).to_string(),
+            "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! `net discover`: browse the local network for DNS-SD services over mDNS
+//! (RFC 6762/6763). A one-shot PTR query goes to 224.0.0.251:5353 from an
+//! ephemeral port, so responders answer us directly ("legacy unicast") with the
+//! SRV/TXT/address records in the same packet. Without `--service` the
+//! advertised service types are listed instead.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::Instant;
+
+use crate::cli::OutputFormat;
+use crate::helpers::{spinner::spinner, table};
+
+const MDNS_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(224, 0, 0, 251)), 5353);
+
+/// Meta-query that enumerates advertised service types.
+const SERVICE_TYPES: &str = "_services._dns-sd._udp.local";
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_AAAA: u16 = 28;
+const TYPE_SRV: u16 = 33;
+
+/// Resource record payloads we care about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RData {
+    Ptr(String),
+    Srv { port: u16, target: String },
+    Txt(Vec<String>),
+    Addr(IpAddr),
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Record {
+    name: String,
+    data: RData,
+}
+
+/// One resolved service instance.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Service {
+    instance: String,
+    host: String,
+    port: u16,
+    addrs: Vec<IpAddr>,
+    txt: Vec<String>,
+}
+
+/// `_http._tcp` -> `_http._tcp.local`.
+fn normalize_service(s: &str) -> String {
+    let s = s.trim().trim_end_matches('.');
+    if s.ends_with(".local") {
+        s.to_string()
+    } else {
+        format!("{s}.local")
+    }
+}
+
+/// A single-question PTR query.
+fn build_query(name: &str) -> Vec<u8> {
+    // id 0, standard query, one question
+    let mut buf = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        buf.push(label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+    }
+    buf.push(0);
+    buf.extend_from_slice(&TYPE_PTR.to_be_bytes());
+    buf.extend_from_slice(&1u16.to_be_bytes()); // class IN
+    buf
+}
+
+fn read_u16(buf: &[u8], pos: usize) -> Result<u16> {
+    buf.get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .context("Truncated DNS message")
+}
+
+/// Read a possibly compressed name at `pos`; returns the name and the offset after it.
+fn read_name(buf: &[u8], mut pos: usize) -> Result<(String, usize)> {
+    let mut labels: Vec<String> = Vec::new();
+    let mut end = None;
+    for _ in 0..128 {
+        let len = *buf.get(pos).context("Truncated DNS name")? as usize;
+        if len == 0 {
+            return Ok((labels.join("."), end.unwrap_or(pos + 1)));
+        }
+        if len & 0xC0 == 0xC0 {
+            let target = (read_u16(buf, pos)? & 0x3FFF) as usize;
+            end.get_or_insert(pos + 2);
+            pos = target;
+            continue;
+        }
+        let label = buf
+            .get(pos + 1..pos + 1 + len)
+            .context("Truncated DNS label")?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += 1 + len;
+    }
+    bail!("DNS name compression loop")
+}
+
+/// All answer, authority, and additional records of a response.
+fn parse_message(buf: &[u8]) -> Result<Vec<Record>> {
+    let questions = read_u16(buf, 4)?;
+    let records =
+        read_u16(buf, 6)? as usize + read_u16(buf, 8)? as usize + read_u16(buf, 10)? as usize;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = read_name(buf, pos)?.1 + 4;
+    }
+
+    let mut out = Vec::with_capacity(records);
+    for _ in 0..records {
+        let (name, p) = read_name(buf, pos)?;
+        let rtype = read_u16(buf, p)?;
+        let rdlen = read_u16(buf, p + 8)? as usize;
+        let start = p + 10;
+        let rdata = buf
+            .get(start..start + rdlen)
+            .context("Truncated DNS record")?;
+        let data = match rtype {
+            TYPE_PTR => RData::Ptr(read_name(buf, start)?.0),
+            TYPE_SRV => RData::Srv {
+                port: read_u16(buf, start + 4)?,
+                target: read_name(buf, start + 6)?.0,
+            },
+            TYPE_TXT => {
+                let mut txt = Vec::new();
+                let mut i = 0;
+                while i < rdata.len() {
+                    let len = rdata[i] as usize;
+                    if let Some(s) = rdata.get(i + 1..i + 1 + len) {
+                        if !s.is_empty() {
+                            txt.push(String::from_utf8_lossy(s).into_owned());
+                        }
+                    }
+                    i += 1 + len;
+                }
+                RData::Txt(txt)
+            }
+            TYPE_A if rdlen == 4 => RData::Addr(IpAddr::V4(Ipv4Addr::new(
+                rdata[0], rdata[1], rdata[2], rdata[3],
+            ))),
+            TYPE_AAAA if rdlen == 16 => {
+                let mut b = [0u8; 16];
+                b.copy_from_slice(rdata);
+                RData::Addr(IpAddr::V6(Ipv6Addr::from(b)))
+            }
+            _ => RData::Other,
+        };
+        out.push(Record { name, data });
+        pos = start + rdlen;
+    }
+    Ok(out)
+}
+
+/// Join PTR -> SRV/TXT -> A/AAAA records into service instances of `service`.
+fn collect(records: &[Record], service: &str) -> Vec<Service> {
+    let eq = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+    let mut found: BTreeMap<String, Service> = BTreeMap::new();
+    for r in records {
+        if let RData::Ptr(instance) = &r.data {
+            if eq(&r.name, service) {
+                found
+                    .entry(instance.to_ascii_lowercase())
+                    .or_insert(Service {
+                        instance: instance.clone(),
+                        ..Service::default()
+                    });
+            }
+        }
+    }
+    for svc in found.values_mut() {
+        for r in records.iter().filter(|r| eq(&r.name, &svc.instance)) {
+            match &r.data {
+                RData::Srv { port, target } => {
+                    svc.port = *port;
+                    svc.host = target.clone();
+                }
+                RData::Txt(t) if svc.txt.is_empty() => svc.txt = t.clone(),
+                _ => {}
+            }
+        }
+        for r in records.iter().filter(|r| eq(&r.name, &svc.host)) {
+            if let RData::Addr(a) = r.data {
+                if !svc.addrs.contains(&a) {
+                    svc.addrs.push(a);
+                }
+            }
+        }
+    }
+    found.into_values().collect()
+}
+
+/// Instance label without the service suffix: `Office._ipp._tcp.local` -> `Office`.
+fn short_instance<'a>(instance: &'a str, service: &str) -> &'a str {
+    instance
+        .strip_suffix(service)
+        .map(|s| s.trim_end_matches('.'))
+        .filter(|s| !s.is_empty())
+        .unwrap_or(instance)
+}
+
+/// Browse for `service` (or list service types) for `wait`, then print the results.
+pub async fn run(service: Option<String>, wait: Duration, output: OutputFormat) -> Result<()> {
+    let query_name = service
+        .as_deref()
+        .map(normalize_service)
+        .unwrap_or_else(|| SERVICE_TYPES.to_string());
+    let sock = UdpSocket::bind("0.0.0.0:0")
+        .await
+        .context("Cannot open UDP socket")?;
+    let query = build_query(&query_name);
+
+    let pb = spinner(&format!("Browsing {query_name}..."));
+    let deadline = Instant::now() + wait;
+    let mut sent = 0;
+    let mut next_send = Instant::now();
+    let mut records = Vec::new();
+    let mut buf = vec![0u8; 9000];
+    loop {
+        // Send immediately and once more after a second, in case the first packet was lost.
+        if sent < 2 && Instant::now() >= next_send {
+            sock.send_to(&query, MDNS_ADDR)
+                .await
+                .context("Cannot send mDNS query (no multicast route?)")?;
+            sent += 1;
+            next_send = Instant::now() + Duration::from_secs(1);
+        }
+        let until = if sent < 2 {
+            next_send.min(deadline)
+        } else {
+            deadline
+        };
+        match tokio::time::timeout_at(until, sock.recv_from(&mut buf)).await {
+            Ok(Ok((n, from))) => match parse_message(&buf[..n]) {
+                Ok(mut r) => records.append(&mut r),
+                Err(e) => log::debug!("Ignoring malformed reply from {from}: {e}"),
+            },
+            Ok(Err(e)) => return Err(e).context("Error receiving mDNS replies"),
+            Err(_) if Instant::now() >= deadline => break,
+            Err(_) => {}
+        }
+    }
+    pb.finish_and_clear();
+
+    if service.is_none() {
+        let mut types: Vec<String> = records
+            .iter()
+            .filter(|r| r.name.eq_ignore_ascii_case(SERVICE_TYPES))
+            .filter_map(|r| match &r.data {
+                RData::Ptr(t) => Some(t.trim_end_matches(".local").to_string()),
+                _ => None,
+            })
+            .collect();
+        types.sort();
+        types.dedup();
+        match output {
+            OutputFormat::Json => {
+                println!("{}", colored_json::to_colored_json_auto(&json!(types))?)
+            }
+            OutputFormat::Table => {
+                if types.is_empty() {
+                    eprintln!("No services advertised.");
+                }
+                for t in types {
+                    println!("{t}");
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let services = collect(&records, &query_name);
+    match output {
+        OutputFormat::Json => {
+            let items: Vec<Value> = services
+                .iter()
+                .map(|s| {
+                    json!({
+                        "instance": short_instance(&s.instance, &query_name),
+                        "host": s.host,
+                        "port": s.port,
+                        "addresses": s.addrs.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                        "txt": s.txt,
+                    })
+                })
+                .collect();
+            println!(
+                "{}",
+                colored_json::to_colored_json_auto(&Value::Array(items))?
+            );
+        }
+        OutputFormat::Table => {
+            if services.is_empty() {
+                eprintln!("No {query_name} services found.");
+                return Ok(());
+            }
+            let rows: Vec<Vec<String>> = services
+                .iter()
+                .map(|s| {
+                    vec![
+                        short_instance(&s.instance, &query_name).to_string(),
+                        s.host.clone(),
+                        s.port.to_string(),
+                        s.addrs
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        s.txt.join(" "),
+                    ]
+                })
+                .collect();
+            table::print(&["INSTANCE", "HOST", "PORT", "ADDRESSES", "TXT"], &rows);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(out: &mut Vec<u8>, n: &str) {
+        for l in n.split('.') {
+            out.push(l.len() as u8);
+            out.extend_from_slice(l.as_bytes());
+        }
+        out.push(0);
+    }
+
+    fn record(out: &mut Vec<u8>, owner: &[u8], rtype: u16, rdata: &[u8]) {
+        out.extend_from_slice(owner);
+        out.extend_from_slice(&rtype.to_be_bytes());
+        out.extend_from_slice(&[0x80, 0x01, 0, 0, 0, 120]); // cache-flush IN, ttl 120
+        out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+        out.extend_from_slice(rdata);
+    }
+
+    /// A responder's answer: PTR plus SRV/TXT/A, using name compression.
+    fn sample_response() -> Vec<u8> {
+        let mut m = vec![0, 0, 0x84, 0, 0, 0, 0, 1, 0, 0, 0, 3];
+        let svc_off = m.len() as u16;
+        name(&mut m, "_http._tcp.local");
+        m.extend_from_slice(&[0, 12, 0, 1, 0, 0, 0, 120]);
+        let inst_off = m.len() as u16 + 2;
+        let mut ptr = vec![5];
+        ptr.extend_from_slice(b"board");
+        ptr.extend_from_slice(&(0xC000 | svc_off).to_be_bytes());
+        m.extend_from_slice(&(ptr.len() as u16).to_be_bytes());
+        m.extend_from_slice(&ptr);
+
+        let inst = (0xC000 | inst_off).to_be_bytes();
+        let mut srv = vec![0, 0, 0, 0, 0x1F, 0x90];
+        name(&mut srv, "esp32.local");
+        record(&mut m, &inst, TYPE_SRV, &srv);
+        record(&mut m, &inst, TYPE_TXT, b"\x07path=/a\x05v=1.2");
+        let mut host = Vec::new();
+        name(&mut host, "esp32.local");
+        record(&mut m, &host, TYPE_A, &[192, 168, 1, 40]);
+        m
+    }
+
+    #[test]
+    fn test_build_query() {
+        let q = build_query("_http._tcp.local");
+        assert_eq!(&q[4..6], &[0, 1]);
+        assert_eq!(read_name(&q, 12).unwrap().0, "_http._tcp.local");
+        assert_eq!(&q[q.len() - 4..], &[0, 12, 0, 1]);
+    }
+
+    #[test]
+    fn test_parse_and_collect() {
+        let records = parse_message(&sample_response()).unwrap();
+        assert_eq!(records.len(), 4);
+        let services = collect(&records, "_http._tcp.local");
+        assert_eq!(
+            services,
+            vec![Service {
+                instance: "board._http._tcp.local".into(),
+                host: "esp32.local".into(),
+                port: 8080,
+                addrs: vec!["192.168.1.40".parse().unwrap()],
+                txt: vec!["path=/a".into(), "v=1.2".into()],
+            }]
+        );
+        assert_eq!(
+            short_instance(&services[0].instance, "_http._tcp.local"),
+            "board"
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(parse_message(&[0, 0, 0]).is_err());
+        let mut loop_msg = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+        loop_msg.extend_from_slice(&[0xC0, 12]);
+        assert!(parse_message(&loop_msg).is_err());
+        assert_eq!(normalize_service("_ipp._tcp"), "_ipp._tcp.local");
+    }
+}
@@ -0,0 +1,201 @@
+//! `net mac`: normalize a MAC address into the common notations and resolve its
+//! vendor from the OUI table. A small table is bundled; `--update-oui` downloads
+//! the full IEEE registry into the user cache, which is preferred once present.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use reqwest::Client;
+use std::time::Duration;
+
+use crate::helpers::retry::{self, RetryPolicy};
+use crate::helpers::{cache, spinner::spinner, style};
+
+/// Bundled subset of the registry, in the IEEE `oui.txt` layout.
+const BUNDLED: &str = include_str!("../data/oui.txt");
+
+/// Where `--update-oui` fetches the full MA-L registry.
+const OUI_URL: &str = "https://standards-oui.ieee.org/oui/oui.txt";
+
+/// Cache file name for the downloaded registry.
+const CACHE_NAME: &str = "oui.txt";
+
+/// Parse a MAC (or bare 24-bit OUI) in colon, hyphen, Cisco-dot, or plain hex form.
+fn parse_mac(s: &str) -> Result<Vec<u8>> {
+    let hex: String = s
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ':' | '-' | '.' | ' '))
+        .collect();
+    if !(hex.len() == 12 || hex.len() == 6) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Not a MAC address or OUI: {s}");
+    }
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0))
+        .collect())
+}
+
+fn join_hex(bytes: &[u8], sep: &str, upper: bool) -> String {
+    bytes
+        .iter()
+        .map(|b| {
+            if upper {
+                format!("{b:02X}")
+            } else {
+                format!("{b:02x}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+/// Cisco-style `3c22.fbaa.bbcc`.
+fn dotted(bytes: &[u8]) -> String {
+    bytes
+        .chunks(2)
+        .map(|c| join_hex(c, "", false))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Vendor for a 24-bit prefix from an `oui.txt`-style table (`3C-22-FB   (hex)\t\tVendor`).
+fn lookup<'a>(table: &'a str, oui: &[u8]) -> Option<&'a str> {
+    let key = join_hex(&oui[..3], "-", true);
+    table.lines().find_map(|line| {
+        let (prefix, vendor) = line.split_once("(hex)")?;
+        (prefix.trim() == key).then(|| vendor.trim())
+    })
+}
+
+/// Number of `(hex)` entries, used to sanity-check a download.
+fn entry_count(table: &str) -> usize {
+    table.lines().filter(|l| l.contains("(hex)")).count()
+}
+
+/// Download the IEEE registry into the cache.
+async fn update_oui(timeout: Option<u64>) -> Result<()> {
+    let client = Client::builder()
+        .user_agent(concat!("swiftline/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(timeout.unwrap_or(120)))
+        .build()?;
+    let policy = RetryPolicy {
+        retries: 2,
+        base: Duration::from_secs(2),
+        statuses: vec![429, 500, 502, 503, 504],
+    };
+    let pb = spinner("Downloading OUI registry...");
+    let resp = retry::send(client.get(OUI_URL), &policy, &pb).await?;
+    let status = resp.status();
+    if !status.is_success() {
+        pb.finish_and_clear();
+        bail!("GET {OUI_URL} failed ({status})");
+    }
+    let text = resp.text().await.context("Error reading OUI registry")?;
+    pb.finish_and_clear();
+
+    let count = entry_count(&text);
+    if count < 1000 {
+        bail!("Download from {OUI_URL} does not look like the OUI registry ({count} entries)");
+    }
+    let path = cache::write(CACHE_NAME, text.as_bytes())?;
+    style::ok(&format!("Saved {count} vendors to: {}", path.display()));
+    Ok(())
+}
+
+/// Look up `mac`, or refresh the cached registry with `update`.
+pub async fn run(mac: Option<String>, update: bool, timeout: Option<u64>) -> Result<()> {
+    if update {
+        update_oui(timeout).await?;
+    }
+    let Some(mac) = mac else {
+        return Ok(());
+    };
+    let bytes = parse_mac(&mac)?;
+
+    let cached = cache::path(CACHE_NAME)
+        .ok()
+        .and_then(|p| std::fs::read_to_string(p).ok());
+    let (table, source) = match &cached {
+        Some(t) => (t.as_str(), "cached IEEE registry"),
+        None => (BUNDLED, "bundled table"),
+    };
+
+    let first = bytes[0];
+    let local = first & 0b10 != 0;
+    let vendor = match lookup(table, &bytes) {
+        Some(v) => format!("{v} {}", format!("({source})").dimmed()),
+        None if local => "none (locally administered, often randomized)".to_string(),
+        None if cached.is_none() => {
+            "unknown (run `net mac --update-oui` for the full registry)".to_string()
+        }
+        None => "unknown".to_string(),
+    };
+    let kind = format!(
+        "{}, {}",
+        if first & 1 != 0 {
+            "multicast"
+        } else {
+            "unicast"
+        },
+        if local {
+            "locally administered"
+        } else {
+            "globally unique"
+        }
+    );
+
+    let mut rows = vec![
+        ("OUI", join_hex(&bytes[..3], ":", true)),
+        ("Vendor", vendor),
+        ("Type", kind),
+    ];
+    if bytes.len() == 6 {
+        rows.splice(
+            0..0,
+            [
+                ("MAC", join_hex(&bytes, ":", false)),
+                ("Hyphen", join_hex(&bytes, "-", true)),
+                ("Cisco", dotted(&bytes)),
+                ("Bare", join_hex(&bytes, "", false)),
+            ],
+        );
+    }
+    for (k, v) in rows {
+        println!("{} {v}", format!("{k}:").bold());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        let want = vec![0x3c, 0x22, 0xfb, 0xaa, 0xbb, 0xcc];
+        for s in [
+            "3c:22:fb:aa:bb:cc",
+            "3C-22-FB-AA-BB-CC",
+            "3c22.fbaa.bbcc",
+            "3C22FBAABBCC",
+        ] {
+            assert_eq!(parse_mac(s).unwrap(), want, "{s}");
+        }
+        assert_eq!(dotted(&want), "3c22.fbaa.bbcc");
+        assert_eq!(join_hex(&want, "-", true), "3C-22-FB-AA-BB-CC");
+        assert_eq!(parse_mac("3c:22:fb").unwrap().len(), 3);
+        assert!(parse_mac("3c:22:fb:aa:bb").is_err());
+        assert!(parse_mac("zz:22:fb:aa:bb:cc").is_err());
+    }
+
+    #[test]
+    fn test_bundled_lookup() {
+        assert_eq!(lookup(BUNDLED, &[0x3c, 0x22, 0xfb]), Some("Apple, Inc."));
+        assert_eq!(
+            lookup(BUNDLED, &[0xb8, 0x27, 0xeb, 1, 2, 3]),
+            Some("Raspberry Pi Foundation")
+        );
+        assert_eq!(lookup(BUNDLED, &[0x02, 0x00, 0x00]), None);
+        assert!(entry_count(BUNDLED) > 50);
+    }
+}
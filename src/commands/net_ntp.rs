@@ -0,0 +1,208 @@
+//! `net ntp`: one SNTPv4 exchange (RFC 4330) to measure the local clock's offset
+//! and the round-trip delay, failing past `--max-offset` so scripts can catch the
+//! drift that breaks token validation.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::{lookup_host, UdpSocket};
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970).
+const NTP_UNIX_DELTA: f64 = 2_208_988_800.0;
+
+/// Current time as Unix seconds with sub-second precision.
+fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
+/// 64-bit NTP timestamp (32.32 fixed point) from Unix seconds.
+fn to_ntp(unix: f64) -> [u8; 8] {
+    let t = unix + NTP_UNIX_DELTA;
+    let secs = t.trunc() as u32;
+    let frac = (t.fract() * 4_294_967_296.0) as u32;
+    let mut out = [0u8; 8];
+    out[..4].copy_from_slice(&secs.to_be_bytes());
+    out[4..].copy_from_slice(&frac.to_be_bytes());
+    out
+}
+
+fn from_ntp(b: &[u8]) -> f64 {
+    let secs = u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64;
+    let frac = u32::from_be_bytes([b[4], b[5], b[6], b[7]]) as f64 / 4_294_967_296.0;
+    secs + frac - NTP_UNIX_DELTA
+}
+
+/// Client request: LI 0, version 4, mode 3 (client), our transmit time.
+fn build_request(t1: f64) -> [u8; 48] {
+    let mut pkt = [0u8; 48];
+    pkt[0] = 0x23;
+    pkt[40..48].copy_from_slice(&to_ntp(t1));
+    pkt
+}
+
+/// What one exchange measured.
+#[derive(Debug, PartialEq)]
+struct Sample {
+    stratum: u8,
+    reference: String,
+    /// Server clock minus local clock, seconds.
+    offset: f64,
+    /// Round-trip network delay, seconds.
+    delay: f64,
+}
+
+/// Evaluate a reply given our send (`t1`) and receive (`t4`) times.
+fn parse_reply(pkt: &[u8], request: &[u8; 48], t1: f64, t4: f64) -> Result<Sample> {
+    if pkt.len() < 48 {
+        bail!("Short NTP reply ({} bytes)", pkt.len());
+    }
+    let mode = pkt[0] & 0x7;
+    if mode != 4 {
+        bail!("Unexpected NTP mode {mode} in reply");
+    }
+    // The server echoes our transmit timestamp; anything else is a stray packet.
+    if pkt[24..32] != request[40..48] {
+        bail!("NTP reply does not match our request");
+    }
+    let stratum = pkt[1];
+    let refid = &pkt[12..16];
+    let reference = if stratum <= 1 {
+        String::from_utf8_lossy(refid)
+            .trim_end_matches('\0')
+            .to_string()
+    } else {
+        format!("{}.{}.{}.{}", refid[0], refid[1], refid[2], refid[3])
+    };
+    if stratum == 0 {
+        bail!("Server sent kiss-of-death code {reference:?} (rate limited or denied)");
+    }
+    let t2 = from_ntp(&pkt[32..40]);
+    let t3 = from_ntp(&pkt[40..48]);
+    Ok(Sample {
+        stratum,
+        reference,
+        offset: ((t2 - t1) + (t3 - t4)) / 2.0,
+        delay: ((t4 - t1) - (t3 - t2)).max(0.0),
+    })
+}
+
+/// Append `port` unless `server` already names one (`host:port`, `[v6]:port`).
+fn with_port(server: &str, port: u16) -> String {
+    if server.parse::<std::net::SocketAddr>().is_ok() {
+        return server.to_string();
+    }
+    if server.parse::<std::net::Ipv6Addr>().is_ok() {
+        return format!("[{server}]:{port}");
+    }
+    match server.rsplit_once(':') {
+        Some((host, p)) if !host.contains(':') && p.parse::<u16>().is_ok() => server.to_string(),
+        _ => format!("{server}:{port}"),
+    }
+}
+
+/// Query `server` (host or host:port) and print offset and delay.
+pub async fn run(server: &str, max_offset: Option<Duration>, timeout: Duration) -> Result<()> {
+    let target = with_port(server, 123);
+    let addr = lookup_host(&target)
+        .await
+        .with_context(|| format!("Cannot resolve NTP server {server}"))?
+        .next()
+        .with_context(|| format!("No address for {server}"))?;
+    let sock = UdpSocket::bind(if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    })
+    .await
+    .context("Cannot open UDP socket")?;
+
+    let t1 = now();
+    let request = build_request(t1);
+    sock.send_to(&request, addr).await?;
+    let mut buf = [0u8; 128];
+    let n = loop {
+        let (n, from) = tokio::time::timeout(timeout, sock.recv_from(&mut buf))
+            .await
+            .with_context(|| {
+                format!("No reply from {addr} within {:.1}s", timeout.as_secs_f64())
+            })??;
+        if from == addr {
+            break n;
+        }
+    };
+    let t4 = now();
+    let s = parse_reply(&buf[..n], &request, t1, t4)?;
+
+    println!("{} {server} ({addr})", "Server:".bold());
+    println!("{} {} (ref {})", "Stratum:".bold(), s.stratum, s.reference);
+    println!(
+        "{} {:+.6}s {}",
+        "Offset:".bold(),
+        s.offset,
+        if s.offset > 0.0 {
+            "(local clock is behind)"
+        } else {
+            "(local clock is ahead)"
+        }
+        .dimmed()
+    );
+    println!("{} {:.6}s", "Delay:".bold(), s.delay);
+
+    if let Some(max) = max_offset {
+        if s.offset.abs() > max.as_secs_f64() {
+            bail!(
+                "Clock offset {:+.3}s exceeds --max-offset {:.3}s",
+                s.offset,
+                max.as_secs_f64()
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let t = 1_700_000_000.25;
+        assert!((from_ntp(&to_ntp(t)) - t).abs() < 1e-6);
+        assert_eq!(build_request(t)[0], 0x23);
+    }
+
+    #[test]
+    fn test_with_port() {
+        assert_eq!(with_port("pool.ntp.org", 123), "pool.ntp.org:123");
+        assert_eq!(with_port("time.local:1123", 123), "time.local:1123");
+        assert_eq!(with_port("2001:db8::1", 123), "[2001:db8::1]:123");
+        assert_eq!(with_port("[2001:db8::1]:9", 123), "[2001:db8::1]:9");
+    }
+
+    #[test]
+    fn test_offset_and_delay() {
+        // Server is 5s ahead; 0.1s each way; 0.02s processing.
+        let t1 = 1_000.0;
+        let request = build_request(t1);
+        let mut reply = [0u8; 48];
+        reply[0] = 0x24; // v4, server
+        reply[1] = 2;
+        reply[12..16].copy_from_slice(&[192, 0, 2, 1]);
+        reply[24..32].copy_from_slice(&request[40..48]);
+        reply[32..40].copy_from_slice(&to_ntp(t1 + 0.1 + 5.0));
+        reply[40..48].copy_from_slice(&to_ntp(t1 + 0.12 + 5.0));
+        let s = parse_reply(&reply, &request, t1, t1 + 0.22).unwrap();
+        assert!((s.offset - 5.0).abs() < 1e-6, "{}", s.offset);
+        assert!((s.delay - 0.2).abs() < 1e-6, "{}", s.delay);
+        assert_eq!(s.reference, "192.0.2.1");
+
+        let mut kod = reply;
+        kod[1] = 0;
+        kod[12..16].copy_from_slice(b"RATE");
+        assert!(parse_reply(&kod, &request, t1, t1 + 0.22).is_err());
+        assert!(parse_reply(&reply, &build_request(2.0), t1, t1).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! `net udp send|listen`: fire test datagrams at syslog/statsd-style services and
+//! watch what arrives on a port, as text or a hexdump.
+
+use anyhow::{bail, Context, Result};
+use atty::Stream;
+use owo_colors::OwoColorize;
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tokio::net::{lookup_host, UdpSocket};
+
+use crate::helpers::{hex, style};
+
+/// Largest UDP payload over IPv4.
+const MAX_DATAGRAM: usize = 65_507;
+
+/// Parse hex digits, ignoring whitespace, `:` separators, and a `0x` prefix.
+fn parse_hex(s: &str) -> Result<Vec<u8>> {
+    let digits: String = s
+        .trim()
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect();
+    if !digits.len().is_multiple_of(2) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("--hex payload must be an even number of hex digits");
+    }
+    Ok((0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap_or(0))
+        .collect())
+}
+
+fn print_datagram(from: SocketAddr, data: &[u8], hex: bool) {
+    println!(
+        "{} {from} ({} byte{})",
+        "From".bold(),
+        data.len(),
+        if data.len() == 1 { "" } else { "s" }
+    );
+    if hex {
+        for line in hex::dump(data, 0, data.len(), |_| false) {
+            println!("{line}");
+        }
+    } else {
+        println!(
+            "{}",
+            String::from_utf8_lossy(data).trim_end_matches(['\r', '\n'])
+        );
+    }
+}
+
+/// Payload priority: --data > --data-file > piped stdin; `hex` decodes hex text.
+pub fn read_payload(data: Option<&str>, file: Option<&Path>, hex: bool) -> Result<Vec<u8>> {
+    let raw = if let Some(d) = data {
+        d.as_bytes().to_vec()
+    } else if let Some(p) = file {
+        std::fs::read(p).with_context(|| format!("Failed to read file: {}", p.display()))?
+    } else if !atty::is(Stream::Stdin) {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        buf
+    } else {
+        bail!("Nothing to send: pass --data, --data-file, or pipe stdin");
+    };
+    if hex {
+        parse_hex(&String::from_utf8_lossy(&raw))
+    } else {
+        Ok(raw)
+    }
+}
+
+/// Send one datagram to `target` (`host:port`), optionally waiting for a reply.
+pub async fn run_send(
+    target: &str,
+    payload: Vec<u8>,
+    wait: Option<Duration>,
+    hex: bool,
+) -> Result<()> {
+    if payload.len() > MAX_DATAGRAM {
+        bail!(
+            "Payload is {} bytes; a UDP datagram holds at most {MAX_DATAGRAM}",
+            payload.len()
+        );
+    }
+    let addr = lookup_host(target)
+        .await
+        .with_context(|| format!("Cannot resolve {target} (expected host:port)"))?
+        .next()
+        .with_context(|| format!("No address for {target}"))?;
+    let local = if addr.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    };
+    let sock = UdpSocket::bind(local)
+        .await
+        .context("Cannot open UDP socket")?;
+    let sent = sock
+        .send_to(&payload, addr)
+        .await
+        .with_context(|| format!("Send to {addr} failed"))?;
+    style::ok(&format!("Sent {sent} bytes to {addr}"));
+
+    if let Some(wait) = wait {
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        match tokio::time::timeout(wait, sock.recv_from(&mut buf)).await {
+            Ok(Ok((n, from))) => print_datagram(from, &buf[..n], hex),
+            Ok(Err(e)) => return Err(e).context("Error waiting for a reply"),
+            Err(_) => bail!("No reply within {:.1}s", wait.as_secs_f64()),
+        }
+    }
+    Ok(())
+}
+
+/// Print datagrams arriving on `bind:port` until Ctrl-C or `count` are received.
+pub async fn run_listen(port: u16, bind: &str, hex: bool, count: Option<usize>) -> Result<()> {
+    let sock = UdpSocket::bind((bind, port))
+        .await
+        .with_context(|| format!("Cannot listen on {bind}:{port}"))?;
+    eprintln!(
+        "{} {} (Ctrl-C to stop)",
+        "Listening on".dimmed(),
+        sock.local_addr()?
+    );
+    let mut buf = vec![0u8; MAX_DATAGRAM];
+    let mut seen = 0;
+    while count.is_none_or(|c| seen < c) {
+        let (n, from) = sock
+            .recv_from(&mut buf)
+            .await
+            .context("Error receiving datagram")?;
+        print_datagram(from, &buf[..n], hex);
+        seen += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(
+            parse_hex("0xdead BEEF").unwrap(),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(parse_hex("01:02").unwrap(), vec![1, 2]);
+        assert!(parse_hex("abc").is_err());
+        assert!(parse_hex("zz").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_and_receive_reply() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = server.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            let (n, from) = server.recv_from(&mut buf).await.unwrap();
+            server.send_to(&buf[..n], from).await.unwrap();
+        });
+        run_send(
+            &target,
+            b"ping".to_vec(),
+            Some(Duration::from_secs(2)),
+            false,
+        )
+        .await
+        .unwrap();
+    }
+}
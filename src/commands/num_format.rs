@@ -0,0 +1,418 @@
+//! `num format` / `num parse`: numbers and amounts in a locale's notation
+//! (`1.234.567,89 €` for de-DE) and back to plain `1234567.89`. Digits are
+//! handled as text, so no precision is lost to floating point. Space group
+//! separators are no-break spaces, so a formatted value stays one shell word.
+
+use anyhow::{bail, Context, Result};
+use atty::Stream;
+use std::io::{self, BufRead};
+
+/// How one locale writes numbers and where it puts the currency symbol.
+struct Locale {
+    tag: &'static str,
+    decimal: char,
+    group: char,
+    /// Indian grouping: the last three digits, then pairs (12,34,567).
+    indian: bool,
+    /// Currency symbol before the number (`$1.00`) rather than after (`1,00 €`).
+    prefix: bool,
+    /// A no-break space between the symbol and the number.
+    space: bool,
+}
+
+const NBSP: char = '\u{a0}';
+const NNBSP: char = '\u{202f}';
+
+const fn locale(
+    tag: &'static str,
+    decimal: char,
+    group: char,
+    prefix: bool,
+    space: bool,
+) -> Locale {
+    Locale {
+        tag,
+        decimal,
+        group,
+        indian: false,
+        prefix,
+        space,
+    }
+}
+
+const LOCALES: &[Locale] = &[
+    locale("en-US", '.', ',', true, false),
+    locale("en-GB", '.', ',', true, false),
+    Locale {
+        indian: true,
+        ..locale("en-IN", '.', ',', true, false)
+    },
+    locale("de-DE", ',', '.', false, true),
+    locale("de-CH", '.', '’', true, true),
+    locale("fr-FR", ',', NNBSP, false, true),
+    locale("es-ES", ',', '.', false, true),
+    locale("it-IT", ',', '.', false, true),
+    locale("nl-NL", ',', '.', true, true),
+    locale("pt-BR", ',', '.', true, true),
+    locale("sv-SE", ',', NBSP, false, true),
+    locale("nb-NO", ',', NBSP, false, true),
+    locale("da-DK", ',', '.', false, true),
+    locale("fi-FI", ',', NBSP, false, true),
+    locale("pl-PL", ',', NBSP, false, true),
+    locale("ru-RU", ',', NBSP, false, true),
+    locale("ja-JP", '.', ',', true, false),
+    locale("zh-CN", '.', ',', true, false),
+];
+
+/// Find a locale by tag (`de-DE`, `de_DE`, case-insensitive), falling back to
+/// the first one of the same language (`de-AT` → `de-DE`).
+fn find_locale(tag: &str) -> Result<&'static Locale> {
+    let tag = tag.trim().replace('_', "-");
+    let lang = tag.split('-').next().unwrap_or_default();
+    LOCALES
+        .iter()
+        .find(|l| l.tag.eq_ignore_ascii_case(&tag))
+        .or_else(|| {
+            LOCALES.iter().find(|l| {
+                l.tag
+                    .split('-')
+                    .next()
+                    .is_some_and(|x| x.eq_ignore_ascii_case(lang))
+            })
+        })
+        .with_context(|| {
+            let known: Vec<&str> = LOCALES.iter().map(|l| l.tag).collect();
+            format!("Unknown locale {tag} (known: {})", known.join(", "))
+        })
+}
+
+/// Symbol and minor-unit digits of an ISO 4217 code; other codes print as the
+/// code itself with two decimals.
+fn currency(code: &str) -> Result<(String, usize)> {
+    let code = code.trim().to_ascii_uppercase();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        bail!("Currency must be a three-letter ISO 4217 code, got: {code}");
+    }
+    let (symbol, digits) = match code.as_str() {
+        "USD" | "CAD" | "AUD" => ("$", 2),
+        "EUR" => ("€", 2),
+        "GBP" => ("£", 2),
+        "JPY" => ("¥", 0),
+        "CNY" => ("¥", 2),
+        "INR" => ("₹", 2),
+        "KRW" => ("₩", 0),
+        "BRL" => ("R$", 2),
+        "RUB" => ("₽", 2),
+        "PLN" => ("zł", 2),
+        "SEK" | "NOK" => ("kr", 2),
+        "DKK" => ("kr.", 2),
+        _ => return Ok((code, 2)),
+    };
+    Ok((symbol.to_string(), digits))
+}
+
+/// A number as text: sign, integer digits (no leading zeros), fraction digits.
+#[derive(Debug, PartialEq)]
+struct Decimal {
+    neg: bool,
+    int: String,
+    frac: String,
+}
+
+/// Parse plain notation: `-1234.5`, `+7`, `.25`, `1.5e6`.
+fn parse_plain(text: &str) -> Result<Decimal> {
+    let s = text.trim();
+    let invalid = || anyhow::anyhow!("Not a number: {text}");
+    let (neg, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (mantissa, exp) = match s.split_once(['e', 'E']) {
+        Some((m, e)) => (m, e.parse::<i64>().map_err(|_| invalid())?),
+        None => (s, 0),
+    };
+    // Far beyond any amount worth formatting, and keeps the digit strings small.
+    if exp.abs() > 100 {
+        bail!("Exponent out of range: {text}");
+    }
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{int}{frac}");
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let point = int.len() as i64 + exp;
+    let (int, frac) = if point <= 0 {
+        (String::new(), "0".repeat(-point as usize) + &digits)
+    } else if point as usize >= digits.len() {
+        (
+            digits.clone() + &"0".repeat(point as usize - digits.len()),
+            String::new(),
+        )
+    } else {
+        let (i, f) = digits.split_at(point as usize);
+        (i.to_string(), f.to_string())
+    };
+    Ok(Decimal::new(neg, &int, frac))
+}
+
+impl Decimal {
+    fn new(neg: bool, int: &str, frac: String) -> Self {
+        let int = int.trim_start_matches('0');
+        Decimal {
+            neg,
+            int: if int.is_empty() {
+                "0".into()
+            } else {
+                int.into()
+            },
+            frac,
+        }
+    }
+
+    /// Round half away from zero (or pad) to `places` fraction digits.
+    fn round(self, places: usize) -> Self {
+        if self.frac.len() <= places {
+            let frac = format!("{:0<places$}", self.frac);
+            return Decimal { frac, ..self };
+        }
+        let up = self.frac.as_bytes()[places] >= b'5';
+        let mut digits: Vec<u8> = format!("{}{}", self.int, &self.frac[..places]).into_bytes();
+        if up {
+            let mut i = digits.len();
+            loop {
+                if i == 0 {
+                    digits.insert(0, b'1');
+                    break;
+                }
+                i -= 1;
+                if digits[i] == b'9' {
+                    digits[i] = b'0';
+                } else {
+                    digits[i] += 1;
+                    break;
+                }
+            }
+        }
+        let digits = String::from_utf8(digits).unwrap_or_default();
+        let (int, frac) = digits.split_at(digits.len() - places);
+        Decimal::new(self.neg, int, frac.to_string())
+    }
+
+    fn is_zero(&self) -> bool {
+        self.int == "0" && self.frac.chars().all(|c| c == '0')
+    }
+}
+
+/// `int` with `sep` between groups of three, or in the Indian pattern.
+fn group(int: &str, sep: char, indian: bool) -> String {
+    let mut groups = Vec::new();
+    let mut rest = int;
+    let mut size = 3;
+    while rest.len() > size {
+        let (head, tail) = rest.split_at(rest.len() - size);
+        groups.push(tail);
+        rest = head;
+        if indian {
+            size = 2;
+        }
+    }
+    groups.push(rest);
+    groups.reverse();
+    groups.join(&sep.to_string())
+}
+
+/// `value` in `loc`'s notation. Amounts in a `currency` get its symbol and
+/// minor-unit digits unless `decimals` says otherwise.
+fn format(
+    value: &str,
+    loc: &Locale,
+    currency_code: Option<&str>,
+    decimals: Option<usize>,
+) -> Result<String> {
+    let mut number = parse_plain(value)?;
+    let symbol = currency_code.map(currency).transpose()?;
+    if let Some(places) = decimals.or(symbol.as_ref().map(|(_, d)| *d)) {
+        number = number.round(places);
+    }
+    let mut out = group(&number.int, loc.group, loc.indian);
+    if !number.frac.is_empty() {
+        out.push(loc.decimal);
+        out.push_str(&number.frac);
+    }
+    if let Some((symbol, _)) = symbol {
+        let space = if loc.space {
+            NBSP.to_string()
+        } else {
+            String::new()
+        };
+        out = if loc.prefix {
+            format!("{symbol}{space}{out}")
+        } else {
+            format!("{out}{space}{symbol}")
+        };
+    }
+    if number.neg && !number.is_zero() {
+        out.insert(0, '-');
+    }
+    Ok(out)
+}
+
+/// Whether `c` separates digit groups in `loc` (any space for space-grouped
+/// locales, either apostrophe for `’`).
+fn is_group(c: char, loc: &Locale) -> bool {
+    match loc.group {
+        NBSP | NNBSP => c.is_whitespace(),
+        '’' => matches!(c, '’' | '\''),
+        g => c == g,
+    }
+}
+
+/// A number written in `loc`'s notation, with or without a currency symbol or
+/// code, back in plain notation. `(1,234.00)` and `−5` count as negative.
+fn parse(text: &str, loc: &Locale) -> Result<String> {
+    let mut s = text.trim();
+    let mut neg = false;
+    if let Some(inner) = s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+        neg = true;
+        s = inner.trim();
+    }
+    // Drop a currency symbol or code on either side; a sign may sit outside
+    // the symbol (-$5) or inside it ($-5).
+    let trim = |s: &str| {
+        s.trim_matches(|c: char| {
+            !(c.is_ascii_digit() || matches!(c, '-' | '−' | '+') || c == loc.decimal)
+        })
+        .to_string()
+    };
+    let mut s = trim(s);
+    if let Some(rest) = s.strip_prefix(['-', '−']) {
+        neg = !neg;
+        s = trim(rest);
+    } else if let Some(rest) = s.strip_prefix('+') {
+        s = trim(rest);
+    }
+    let mut int = String::new();
+    let mut frac: Option<String> = None;
+    for c in s.chars() {
+        match (&mut frac, c) {
+            (Some(f), '0'..='9') => f.push(c),
+            (None, '0'..='9') => int.push(c),
+            (None, c) if c == loc.decimal => frac = Some(String::new()),
+            (None, c) if is_group(c, loc) => {}
+            _ => bail!("Cannot parse {text:?} as a {} number", loc.tag),
+        }
+    }
+    let frac = frac.unwrap_or_default();
+    if int.is_empty() && frac.is_empty() {
+        bail!("No digits in {text:?}");
+    }
+    let number = Decimal::new(neg, &int, frac);
+    let mut out = String::new();
+    if number.neg && !number.is_zero() {
+        out.push('-');
+    }
+    out.push_str(&number.int);
+    if !number.frac.is_empty() {
+        out.push('.');
+        out.push_str(&number.frac);
+    }
+    Ok(out)
+}
+
+/// The value argument, or each non-empty line of stdin.
+fn inputs(value: Option<String>) -> Result<Vec<String>> {
+    if let Some(v) = value {
+        return Ok(vec![v]);
+    }
+    if atty::is(Stream::Stdin) {
+        bail!("Provide a number, or pipe one per line on stdin");
+    }
+    let mut lines = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line.context("Failed to read stdin")?;
+        if !line.trim().is_empty() {
+            lines.push(line);
+        }
+    }
+    Ok(lines)
+}
+
+/// Print each number in `locale`'s notation, as an amount of `currency` if given.
+pub fn run_format(
+    value: Option<String>,
+    locale: &str,
+    currency: Option<&str>,
+    decimals: Option<usize>,
+) -> Result<()> {
+    let loc = find_locale(locale)?;
+    for v in inputs(value)? {
+        println!("{}", format(&v, loc, currency, decimals)?);
+    }
+    Ok(())
+}
+
+/// Print each number written in `locale`'s notation in plain notation.
+pub fn run_parse(value: Option<String>, locale: &str) -> Result<()> {
+    let loc = find_locale(locale)?;
+    for v in inputs(value)? {
+        println!("{}", parse(&v, loc)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(value: &str, tag: &str, cur: Option<&str>, dec: Option<usize>) -> String {
+        format(value, find_locale(tag).unwrap(), cur, dec).unwrap()
+    }
+
+    #[test]
+    fn test_format_locales_and_currencies() {
+        assert_eq!(
+            fmt("1234567.891", "de-DE", Some("EUR"), None),
+            "1.234.567,89\u{a0}€"
+        );
+        assert_eq!(
+            fmt("1234567.891", "en-US", Some("usd"), None),
+            "$1,234,567.89"
+        );
+        assert_eq!(fmt("1234567.891", "en_IN", None, None), "12,34,567.891");
+        assert_eq!(fmt("-1234.5", "fr-FR", None, Some(2)), "-1\u{202f}234,50");
+        assert_eq!(fmt("1234.5", "ja-JP", Some("JPY"), None), "¥1,235");
+        assert_eq!(fmt("1e3", "de-CH", Some("CHF"), None), "CHF\u{a0}1’000.00");
+        assert_eq!(fmt("12", "en-US", Some("XYZ"), None), "XYZ12.00");
+    }
+
+    #[test]
+    fn test_round_carries_and_drops_negative_zero() {
+        assert_eq!(fmt("999.995", "en-US", None, Some(2)), "1,000.00");
+        assert_eq!(fmt("-0.001", "en-US", None, Some(2)), "0.00");
+        assert_eq!(fmt(".5", "en-US", None, Some(0)), "1");
+        assert_eq!(fmt("1.25e-2", "en-US", None, None), "0.0125");
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        let de = find_locale("de").unwrap();
+        assert_eq!(parse("1.234.567,89 €", de).unwrap(), "1234567.89");
+        assert_eq!(parse("-1.234,5", de).unwrap(), "-1234.5");
+        let us = find_locale("en-US").unwrap();
+        assert_eq!(parse("-$1,234.56", us).unwrap(), "-1234.56");
+        assert_eq!(parse("(1,234.00)", us).unwrap(), "-1234.00");
+        let sv = find_locale("sv-SE").unwrap();
+        let formatted = fmt("-9876543.21", "sv-SE", Some("SEK"), None);
+        assert_eq!(parse(&formatted, sv).unwrap(), "-9876543.21");
+        assert!(parse("1,234.56", de).is_err());
+        assert!(parse("€", de).is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(find_locale("xx-YY").is_err());
+        assert!(currency("EURO").is_err());
+        assert!(parse_plain("12a").is_err());
+        assert!(parse_plain("1e999999").is_err());
+    }
+}
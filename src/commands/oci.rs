@@ -0,0 +1,368 @@
+//! `oci tags|manifest|pull-blob`: query container registries over the OCI
+//! distribution API. Handles the `WWW-Authenticate: Bearer` token dance
+//! (anonymous or with `--user`), Docker Hub naming, and tag pagination.
+
+use anyhow::{bail, Context, Result};
+use reqwest::header::{HeaderValue, ACCEPT, AUTHORIZATION, WWW_AUTHENTICATE};
+use reqwest::{Client, Response, StatusCode};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+use crate::helpers::download::download_to_file;
+use crate::helpers::spinner::spinner;
+use crate::helpers::{headers, hex, style};
+
+/// Manifest media types we accept, most specific first.
+const MANIFEST_TYPES: &str = "application/vnd.oci.image.index.v1+json, \
+application/vnd.oci.image.manifest.v1+json, \
+application/vnd.docker.distribution.manifest.list.v2+json, \
+application/vnd.docker.distribution.manifest.v2+json";
+
+/// A parsed image reference like `ghcr.io/org/app:1.2` or `alpine@sha256:...`.
+#[derive(Debug, PartialEq, Eq)]
+struct Reference {
+    registry: String,
+    repository: String,
+    /// Tag or digest; `None` when the reference names only a repository.
+    reference: Option<String>,
+}
+
+impl Reference {
+    /// Parse with Docker's defaults: no registry means Docker Hub, and single-name
+    /// Hub repositories live under `library/`.
+    fn parse(s: &str) -> Result<Self> {
+        let (name, reference) = match s.split_once('@') {
+            Some((n, d)) => (n, Some(d.to_string())),
+            None => match s.rsplit_once(':') {
+                // A colon after the last slash is a tag; before it, a registry port.
+                Some((n, t)) if !t.contains('/') => (n, Some(t.to_string())),
+                _ => (s, None),
+            },
+        };
+        if name.is_empty() {
+            bail!("Invalid image reference: {s}");
+        }
+        let (registry, repository) = match name.split_once('/') {
+            Some((first, rest)) if first.contains(['.', ':']) || first == "localhost" => {
+                (first.to_string(), rest.to_string())
+            }
+            _ => ("docker.io".to_string(), name.to_string()),
+        };
+        let registry = match registry.as_str() {
+            "docker.io" | "index.docker.io" => "registry-1.docker.io".to_string(),
+            _ => registry,
+        };
+        let repository = if registry == "registry-1.docker.io" && !repository.contains('/') {
+            format!("library/{repository}")
+        } else {
+            repository
+        };
+        Ok(Self {
+            registry,
+            repository,
+            reference,
+        })
+    }
+
+    fn base(&self) -> Result<Url> {
+        let local = self.registry.starts_with("localhost") || self.registry.starts_with("127.");
+        let scheme = if local { "http" } else { "https" };
+        Url::parse(&format!(
+            "{scheme}://{}/v2/{}/",
+            self.registry, self.repository
+        ))
+        .with_context(|| format!("Invalid registry: {}", self.registry))
+    }
+}
+
+/// Parse `Bearer realm="...",service="...",scope="..."` into its scheme and parameters.
+fn parse_challenge(value: &str) -> (String, Vec<(String, String)>) {
+    let (scheme, rest) = value.trim().split_once(' ').unwrap_or((value.trim(), ""));
+    let mut params = Vec::new();
+    let mut rest = rest.trim();
+    while let Some((key, after)) = rest.split_once('=') {
+        let key = key.trim().trim_start_matches(',').trim().to_string();
+        let (val, next) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or(""))
+            }
+            None => {
+                let end = after.find(',').unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        params.push((key, val.to_string()));
+        rest = next.trim_start_matches(',').trim();
+    }
+    (scheme.to_ascii_lowercase(), params)
+}
+
+/// Registry client that upgrades to a bearer token on the first 401.
+struct Registry {
+    client: Client,
+    user: Option<(String, String)>,
+    auth: Option<HeaderValue>,
+}
+
+impl Registry {
+    fn new(user: Option<String>, timeout_secs: Option<u64>) -> Result<Self> {
+        let user = match user {
+            Some(u) => {
+                let (name, pass) = u.split_once(':').context("--user must be name:password")?;
+                Some((name.to_string(), pass.to_string()))
+            }
+            None => None,
+        };
+        Ok(Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(timeout_secs.unwrap_or(300)))
+                .build()?,
+            user,
+            auth: None,
+        })
+    }
+
+    async fn send(&self, url: &Url, accept: Option<&str>) -> Result<Response> {
+        let mut req = self.client.get(url.clone());
+        if let Some(a) = accept {
+            req = req.header(ACCEPT, a);
+        }
+        if let Some(auth) = &self.auth {
+            req = req.header(AUTHORIZATION, auth.clone());
+        }
+        req.send()
+            .await
+            .context("Network error while sending request")
+    }
+
+    /// GET `url`, answering a Bearer/Basic challenge once if the registry asks for one.
+    async fn get(&mut self, url: &Url, accept: Option<&str>) -> Result<Response> {
+        let resp = self.send(url, accept).await?;
+        if resp.status() != StatusCode::UNAUTHORIZED || self.auth.is_some() {
+            return check(resp).await;
+        }
+        let challenge = resp
+            .headers()
+            .get(WWW_AUTHENTICATE)
+            .and_then(|v| v.to_str().ok())
+            .context("Registry returned 401 without a WWW-Authenticate challenge")?
+            .to_string();
+        let (scheme, params) = parse_challenge(&challenge);
+        self.auth = Some(match scheme.as_str() {
+            "bearer" => self.fetch_token(&params).await?,
+            "basic" => {
+                let Some((name, pass)) = &self.user else {
+                    bail!("Registry requires credentials; pass --user name:password");
+                };
+                basic(name, pass)?
+            }
+            other => bail!("Unsupported auth scheme: {other}"),
+        });
+        check(self.send(url, accept).await?).await
+    }
+
+    async fn fetch_token(&self, params: &[(String, String)]) -> Result<HeaderValue> {
+        let param = |k: &str| params.iter().find(|(n, _)| n == k).map(|(_, v)| v.as_str());
+        let realm = param("realm").context("Bearer challenge has no realm")?;
+        let mut url = Url::parse(realm).with_context(|| format!("Invalid token realm: {realm}"))?;
+        for key in ["service", "scope"] {
+            if let Some(v) = param(key) {
+                url.query_pairs_mut().append_pair(key, v);
+            }
+        }
+        let mut req = self.client.get(url);
+        if let Some((name, pass)) = &self.user {
+            req = req.basic_auth(name, Some(pass));
+        }
+        let resp = check(
+            req.send()
+                .await
+                .context("Network error while fetching token")?,
+        )
+        .await?;
+        let body: Value = resp.json().await.context("Invalid token response")?;
+        let token = body
+            .get("token")
+            .or_else(|| body.get("access_token"))
+            .and_then(Value::as_str)
+            .context("Token response has no token")?;
+        Ok(HeaderValue::from_str(&format!("Bearer {token}"))?)
+    }
+}
+
+fn basic(name: &str, pass: &str) -> Result<HeaderValue> {
+    use base64::Engine;
+    let raw = base64::engine::general_purpose::STANDARD.encode(format!("{name}:{pass}"));
+    Ok(HeaderValue::from_str(&format!("Basic {raw}"))?)
+}
+
+/// Turn registry error responses (`{"errors":[{"code","message"}]}`) into readable errors.
+async fn check(resp: Response) -> Result<Response> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let body: Value = resp.json().await.unwrap_or(Value::Null);
+    let detail = body["errors"]
+        .as_array()
+        .and_then(|e| e.first())
+        .map(|e| {
+            format!(
+                ": {} {}",
+                e["code"].as_str().unwrap_or(""),
+                e["message"].as_str().unwrap_or("")
+            )
+        })
+        .unwrap_or_default();
+    bail!("Registry request failed ({status}){detail}")
+}
+
+/// Next page URL from a `Link: </v2/...>; rel="next"` header (often relative).
+fn next_link(resp: &Response) -> Option<Url> {
+    let target = headers::link_rel(resp.headers(), "next")?;
+    resp.url().join(&target).ok()
+}
+
+/// List every tag of a repository, one per line.
+pub async fn run_tags(image: &str, user: Option<String>, timeout: Option<u64>) -> Result<()> {
+    let r = Reference::parse(image)?;
+    let mut reg = Registry::new(user, timeout)?;
+    let mut url = Some(r.base()?.join("tags/list?n=1000")?);
+    let pb = spinner("Listing tags...");
+    let mut tags: Vec<String> = Vec::new();
+    while let Some(u) = url {
+        let resp = reg.get(&u, None).await?;
+        url = next_link(&resp);
+        let body: Value = resp.json().await.context("Invalid tags response")?;
+        tags.extend(
+            body["tags"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|t| t.as_str().map(str::to_string)),
+        );
+    }
+    pb.finish_and_clear();
+    for t in &tags {
+        println!("{t}");
+    }
+    Ok(())
+}
+
+/// Print a manifest (index or image) as pretty JSON, or just its digest.
+pub async fn run_manifest(
+    image: &str,
+    user: Option<String>,
+    digest_only: bool,
+    timeout: Option<u64>,
+) -> Result<()> {
+    let r = Reference::parse(image)?;
+    let reference = r.reference.as_deref().unwrap_or("latest");
+    let mut reg = Registry::new(user, timeout)?;
+    let url = r.base()?.join(&format!("manifests/{reference}"))?;
+
+    let pb = spinner("Fetching manifest...");
+    let resp = reg.get(&url, Some(MANIFEST_TYPES)).await?;
+    let header_digest = resp
+        .headers()
+        .get("docker-content-digest")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let bytes = resp.bytes().await?;
+    pb.finish_and_clear();
+
+    let digest =
+        header_digest.unwrap_or_else(|| format!("sha256:{}", hex::encode(&Sha256::digest(&bytes))));
+    if digest_only {
+        println!("{digest}");
+        return Ok(());
+    }
+    let body: Value = serde_json::from_slice(&bytes).context("Manifest is not JSON")?;
+    eprintln!("Digest: {digest}");
+    println!("{}", colored_json::to_colored_json_auto(&body)?);
+    Ok(())
+}
+
+/// Download a blob by digest with a progress bar, verifying its SHA-256.
+pub async fn run_pull_blob(
+    image: &str,
+    output: Option<PathBuf>,
+    user: Option<String>,
+    timeout: Option<u64>,
+) -> Result<()> {
+    let r = Reference::parse(image)?;
+    let digest = match r.reference.as_deref() {
+        Some(d) if d.starts_with("sha256:") => d.to_string(),
+        _ => bail!("pull-blob needs a digest reference like <repo>@sha256:<hex>"),
+    };
+    let path = output.unwrap_or_else(|| PathBuf::from(digest.replace(':', "_")));
+    let mut reg = Registry::new(user, timeout)?;
+    let url = r.base()?.join(&format!("blobs/{digest}"))?;
+
+    let pb = spinner("Requesting...");
+    let resp = reg.get(&url, None).await?;
+    pb.finish_and_clear();
+
+    let mut hasher = Sha256::new();
+    let downloaded = download_to_file(resp, &path, |chunk| hasher.update(chunk)).await?;
+
+    let actual = format!("sha256:{}", hex::encode(&hasher.finalize()));
+    if actual != digest {
+        bail!(
+            "Digest mismatch for {}: expected {digest}, got {actual}",
+            path.display()
+        );
+    }
+    style::ok(&format!(
+        "Saved {downloaded} bytes to {} (digest verified)",
+        path.display()
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_docker_hub_defaults() {
+        let r = Reference::parse("alpine:3.20").unwrap();
+        assert_eq!(r.registry, "registry-1.docker.io");
+        assert_eq!(r.repository, "library/alpine");
+        assert_eq!(r.reference.as_deref(), Some("3.20"));
+
+        let r = Reference::parse("grafana/grafana").unwrap();
+        assert_eq!(r.repository, "grafana/grafana");
+        assert_eq!(r.reference, None);
+    }
+
+    #[test]
+    fn test_reference_with_registry_port_and_digest() {
+        let r = Reference::parse("localhost:5000/team/app@sha256:abc").unwrap();
+        assert_eq!(r.registry, "localhost:5000");
+        assert_eq!(r.repository, "team/app");
+        assert_eq!(r.reference.as_deref(), Some("sha256:abc"));
+        assert_eq!(
+            r.base().unwrap().as_str(),
+            "http://localhost:5000/v2/team/app/"
+        );
+
+        let r = Reference::parse("ghcr.io/org/app").unwrap();
+        assert_eq!(r.base().unwrap().as_str(), "https://ghcr.io/v2/org/app/");
+    }
+
+    #[test]
+    fn test_parse_challenge() {
+        let (scheme, params) = parse_challenge(
+            r#"Bearer realm="https://auth.docker.io/token",service="registry.docker.io",scope="repository:library/alpine:pull""#,
+        );
+        assert_eq!(scheme, "bearer");
+        assert_eq!(params.len(), 3);
+        assert_eq!(params[0].1, "https://auth.docker.io/token");
+        assert_eq!(params[2].1, "repository:library/alpine:pull");
+    }
+}
@@ -0,0 +1,679 @@
+//! `rand check-password`: estimate how guessable a password is, zxcvbn-style.
+//! The password is split into the cheapest-to-guess pieces (common passwords and
+//! words with capitalization and l33t variants, repeats, sequences, keyboard
+//! runs, years), their guess counts are multiplied, and the total maps to a 0-4
+//! score. `--breach` asks the Pwned Passwords range API about the first five hex
+//! digits of the SHA-1 only (k-anonymity), so the password never leaves the machine.
+
+use anyhow::{bail, Context, Result};
+use atty::Stream;
+use owo_colors::OwoColorize;
+use reqwest::Client;
+use serde_json::json;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use crate::cli::OutputFormat;
+use crate::helpers::hex;
+use crate::helpers::retry::{self, RetryPolicy};
+use crate::helpers::spinner::spinner;
+
+const HIBP_RANGE: &str = "https://api.pwnedpasswords.com/range/";
+
+/// Most common passwords and password words, most frequent first.
+const COMMON: &[&str] = &[
+    "123456",
+    "password",
+    "12345678",
+    "qwerty",
+    "123456789",
+    "12345",
+    "1234",
+    "111111",
+    "1234567",
+    "dragon",
+    "123123",
+    "baseball",
+    "abc123",
+    "football",
+    "monkey",
+    "letmein",
+    "696969",
+    "shadow",
+    "master",
+    "666666",
+    "qwertyuiop",
+    "123321",
+    "mustang",
+    "1234567890",
+    "michael",
+    "654321",
+    "superman",
+    "1qaz2wsx",
+    "7777777",
+    "121212",
+    "000000",
+    "qazwsx",
+    "123qwe",
+    "killer",
+    "trustno1",
+    "jordan",
+    "jennifer",
+    "zxcvbnm",
+    "asdfgh",
+    "hunter",
+    "buster",
+    "soccer",
+    "harley",
+    "batman",
+    "andrew",
+    "tigger",
+    "sunshine",
+    "iloveyou",
+    "charlie",
+    "robert",
+    "thomas",
+    "hockey",
+    "ranger",
+    "daniel",
+    "starwars",
+    "112233",
+    "george",
+    "computer",
+    "michelle",
+    "jessica",
+    "pepper",
+    "1111",
+    "zxcvbn",
+    "555555",
+    "11111111",
+    "131313",
+    "freedom",
+    "777777",
+    "pass",
+    "maggie",
+    "159753",
+    "aaaaaa",
+    "ginger",
+    "princess",
+    "joshua",
+    "cheese",
+    "amanda",
+    "summer",
+    "love",
+    "ashley",
+    "nicole",
+    "chelsea",
+    "biteme",
+    "matthew",
+    "access",
+    "yankees",
+    "987654321",
+    "dallas",
+    "austin",
+    "thunder",
+    "taylor",
+    "matrix",
+    "admin",
+    "welcome",
+    "login",
+    "hello",
+    "secret",
+    "changeme",
+    "test",
+    "guest",
+    "root",
+    "default",
+    "winter",
+    "spring",
+    "autumn",
+    "flower",
+    "orange",
+    "purple",
+    "apple",
+    "banana",
+    "house",
+    "money",
+    "family",
+    "pokemon",
+    "liverpool",
+    "arsenal",
+    "cookie",
+    "qwerty123",
+    "password1",
+    "master1",
+    "angel",
+    "lovely",
+    "samsung",
+    "google",
+    "secure",
+    "letmein1",
+    "welcome1",
+    "dragon1",
+    "monkey1",
+    "super",
+    "user",
+    "server",
+    "office",
+];
+
+/// Keyboard rows (US layout) for spotting runs like `qwerty` or `asdf`.
+const KEYBOARD_ROWS: [&str; 4] = [
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+];
+
+/// Guesses per character not covered by a pattern. Like zxcvbn this is far below
+/// the character-set size: unmatched text is often a word missing from `COMMON`.
+const BRUTEFORCE: f64 = 10.0;
+
+/// l33t substitutions undone before dictionary lookups.
+fn unleet(c: char) -> char {
+    match c {
+        '@' | '4' => 'a',
+        '8' => 'b',
+        '3' => 'e',
+        '6' | '9' => 'g',
+        '1' | '!' | '|' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' | '+' => 't',
+        '2' => 'z',
+        _ => c,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pattern {
+    /// Common password or word; `rank` is 1-based, `variant` when recased or l33t.
+    Dictionary {
+        rank: usize,
+        variant: bool,
+    },
+    Repeat,
+    Sequence,
+    Keyboard,
+    Year,
+    Bruteforce,
+}
+
+/// A piece of the password and how many guesses it costs.
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    start: usize,
+    end: usize,
+    guesses: f64,
+    pattern: Pattern,
+}
+
+/// Capitalization variants an attacker tries: none, first letter, all caps, else per letter.
+fn case_variants(token: &[char]) -> f64 {
+    let upper = token.iter().filter(|c| c.is_uppercase()).count();
+    let letters = token.iter().filter(|c| c.is_alphabetic()).count();
+    match upper {
+        0 => 1.0,
+        _ if upper == letters || (upper == 1 && token[0].is_uppercase()) => 2.0,
+        _ => 2f64.powi(upper.min(letters) as i32),
+    }
+}
+
+fn dictionary_matches(chars: &[char], ranks: &HashMap<&str, usize>, out: &mut Vec<Piece>) {
+    let lower: Vec<char> = chars.iter().flat_map(|c| c.to_lowercase()).collect();
+    if lower.len() != chars.len() {
+        return;
+    }
+    let unleeted: Vec<char> = lower.iter().map(|&c| unleet(c)).collect();
+    for i in 0..chars.len() {
+        for j in i + 3..=chars.len() {
+            for (text, leet) in [(&lower, false), (&unleeted, true)] {
+                let word: String = text[i..j].iter().collect();
+                let Some(&rank) = ranks.get(word.as_str()) else {
+                    continue;
+                };
+                let variant = leet && lower[i..j] != unleeted[i..j];
+                let leet_factor = if variant { 2.0 } else { 1.0 };
+                out.push(Piece {
+                    start: i,
+                    end: j,
+                    guesses: rank as f64 * case_variants(&chars[i..j]) * leet_factor,
+                    pattern: Pattern::Dictionary {
+                        rank,
+                        variant: variant || case_variants(&chars[i..j]) > 1.0,
+                    },
+                });
+            }
+        }
+    }
+}
+
+/// Repeated blocks: `aaa`, `abcabc`, `1212`.
+fn repeat_matches(chars: &[char], out: &mut Vec<Piece>) {
+    let n = chars.len();
+    for i in 0..n {
+        for size in 1..=(n - i) / 2 {
+            let block = &chars[i..i + size];
+            let mut count = 1;
+            while chars[i + count * size..].starts_with(block) {
+                count += 1;
+            }
+            if count >= 2 && count * size >= 3 {
+                out.push(Piece {
+                    start: i,
+                    end: i + count * size,
+                    guesses: estimate(block).guesses * count as f64,
+                    pattern: Pattern::Repeat,
+                });
+            }
+        }
+    }
+}
+
+/// Runs of consecutive letters or digits, either direction: `abcd`, `9876`.
+fn sequence_matches(chars: &[char], out: &mut Vec<Piece>) {
+    let class = |c: char| {
+        if c.is_ascii_lowercase() {
+            1
+        } else if c.is_ascii_uppercase() {
+            2
+        } else if c.is_ascii_digit() {
+            3
+        } else {
+            0
+        }
+    };
+    let mut i = 0;
+    while i + 2 < chars.len() {
+        let delta = chars[i + 1] as i32 - chars[i] as i32;
+        let mut j = i + 1;
+        while j < chars.len()
+            && delta.abs() == 1
+            && class(chars[j]) != 0
+            && class(chars[j]) == class(chars[i])
+            && chars[j] as i32 - chars[j - 1] as i32 == delta
+        {
+            j += 1;
+        }
+        if j - i >= 3 {
+            let base = if class(chars[i]) == 3 { 10.0 } else { 26.0 };
+            let direction = if delta < 0 { 2.0 } else { 1.0 };
+            out.push(Piece {
+                start: i,
+                end: j,
+                guesses: base * (j - i) as f64 * direction,
+                pattern: Pattern::Sequence,
+            });
+            i = j - 1;
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// Runs along a keyboard row, either direction: `qwerty`, `lkjh`.
+fn keyboard_matches(chars: &[char], out: &mut Vec<Piece>) {
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    for row in KEYBOARD_ROWS {
+        let reversed: String = row.chars().rev().collect();
+        for (dir, line) in [(1.0, row), (2.0, reversed.as_str())] {
+            for i in 0..chars.len() {
+                for j in i + 4..=chars.len() {
+                    if !line.contains(&lower[i..j].iter().collect::<String>()) {
+                        break;
+                    }
+                    out.push(Piece {
+                        start: i,
+                        end: j,
+                        guesses: 50.0 * (j - i) as f64 * dir * case_variants(&chars[i..j]),
+                        pattern: Pattern::Keyboard,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Four-digit years from 1900 to 2039.
+fn year_matches(chars: &[char], out: &mut Vec<Piece>) {
+    for i in 0..chars.len().saturating_sub(3) {
+        let s: String = chars[i..i + 4].iter().collect();
+        if s.parse::<u32>().is_ok_and(|y| (1900..=2039).contains(&y)) {
+            out.push(Piece {
+                start: i,
+                end: i + 4,
+                guesses: 140.0,
+                pattern: Pattern::Year,
+            });
+        }
+    }
+}
+
+/// Cheapest decomposition of the password.
+#[derive(Debug)]
+struct Estimate {
+    guesses: f64,
+    pieces: Vec<Piece>,
+}
+
+fn estimate(chars: &[char]) -> Estimate {
+    let ranks: HashMap<&str, usize> = COMMON
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, w)| (*w, i + 1))
+        .collect();
+    let mut matches = Vec::new();
+    dictionary_matches(chars, &ranks, &mut matches);
+    sequence_matches(chars, &mut matches);
+    keyboard_matches(chars, &mut matches);
+    year_matches(chars, &mut matches);
+    if chars.len() <= 64 {
+        repeat_matches(chars, &mut matches);
+    }
+
+    // best[k]: fewest guesses for chars[..k], and the piece that ends there.
+    let mut best: Vec<(f64, Option<Piece>)> = vec![(1.0, None); chars.len() + 1];
+    for k in 1..=chars.len() {
+        let brute = Piece {
+            start: k - 1,
+            end: k,
+            guesses: BRUTEFORCE,
+            pattern: Pattern::Bruteforce,
+        };
+        best[k] = (best[k - 1].0 * BRUTEFORCE, Some(brute));
+        for m in matches.iter().filter(|m| m.end == k) {
+            let total = best[m.start].0 * m.guesses.max(1.0);
+            if total < best[k].0 {
+                best[k] = (total, Some(*m));
+            }
+        }
+    }
+
+    let mut pieces = Vec::new();
+    let mut k = chars.len();
+    while let Some(piece) = best[k].1 {
+        pieces.push(piece);
+        k = piece.start;
+    }
+    pieces.reverse();
+    Estimate {
+        guesses: best[chars.len()].0,
+        pieces,
+    }
+}
+
+/// zxcvbn's score bands: 0 too guessable ... 4 very unguessable.
+fn score(guesses: f64) -> u8 {
+    match guesses {
+        g if g < 1e3 => 0,
+        g if g < 1e6 => 1,
+        g if g < 1e8 => 2,
+        g if g < 1e10 => 3,
+        _ => 4,
+    }
+}
+
+fn score_label(score: u8) -> &'static str {
+    ["very weak", "weak", "fair", "strong", "very strong"][score as usize]
+}
+
+/// `3 hours`, `2 years`, `centuries`.
+fn human_time(secs: f64) -> String {
+    const UNITS: [(&str, f64); 6] = [
+        ("second", 1.0),
+        ("minute", 60.0),
+        ("hour", 3600.0),
+        ("day", 86_400.0),
+        ("month", 2_629_800.0),
+        ("year", 31_557_600.0),
+    ];
+    if secs < 1.0 {
+        return "instant".to_string();
+    }
+    if secs >= 100.0 * 31_557_600.0 {
+        return "centuries".to_string();
+    }
+    let (unit, size) = UNITS
+        .iter()
+        .rev()
+        .find(|(_, size)| secs >= *size)
+        .copied()
+        .unwrap_or(UNITS[0]);
+    let n = (secs / size).round() as u64;
+    format!("{n} {unit}{}", if n == 1 { "" } else { "s" })
+}
+
+/// Attack scenarios and their guesses per second.
+const ATTACKS: [(&str, f64); 4] = [
+    ("online, throttled (100/hour)", 100.0 / 3600.0),
+    ("online (10/s)", 10.0),
+    ("offline, slow hash (10k/s)", 1e4),
+    ("offline, fast hash (10B/s)", 1e10),
+];
+
+/// Warning about the weakest piece, plus suggestions.
+fn feedback(est: &Estimate, len: usize) -> (Option<String>, Vec<&'static str>) {
+    let mut suggestions = Vec::new();
+    if score(est.guesses) >= 3 {
+        return (None, suggestions);
+    }
+    let warning = est.pieces.iter().find_map(|p| match p.pattern {
+        Pattern::Dictionary { rank, .. } if p.end - p.start == len => Some(if rank <= 10 {
+            "This is a top-10 common password".to_string()
+        } else {
+            format!("This is a very common password (#{rank})")
+        }),
+        Pattern::Dictionary { .. } => Some("Contains a common password or word".to_string()),
+        Pattern::Repeat => Some("Repeats like 'aaa' or 'abcabc' are easy to guess".to_string()),
+        Pattern::Sequence => Some("Sequences like 'abc' or '6543' are easy to guess".to_string()),
+        Pattern::Keyboard => Some("Keyboard runs like 'qwerty' are easy to guess".to_string()),
+        Pattern::Year => Some("Years are easy to guess".to_string()),
+        Pattern::Bruteforce => None,
+    });
+    suggestions.push("Use a few uncommon words together; length beats symbols");
+    if est
+        .pieces
+        .iter()
+        .any(|p| matches!(p.pattern, Pattern::Dictionary { variant: true, .. }))
+    {
+        suggestions.push("Capitals and l33t swaps like '@' for 'a' don't help much");
+    }
+    if est.pieces.iter().any(|p| p.pattern == Pattern::Year) {
+        suggestions.push("Avoid years and dates that are associated with you");
+    }
+    (warning, suggestions)
+}
+
+/// Breach count for the SHA-1 `suffix` in a range API response (`SUFFIX:COUNT` lines).
+fn range_count(body: &str, suffix: &str) -> u64 {
+    body.lines()
+        .filter_map(|l| l.trim().split_once(':'))
+        .find(|(s, _)| s.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, n)| n.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Times `password` appears in Pwned Passwords; only the hash prefix is sent.
+async fn breach_count(password: &str, timeout: Option<u64>) -> Result<u64> {
+    let hash = hex::encode(&Sha1::digest(password.as_bytes())).to_ascii_uppercase();
+    let (prefix, suffix) = hash.split_at(5);
+    let client = Client::builder()
+        .user_agent(concat!("swiftline/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(timeout.unwrap_or(30)))
+        .build()?;
+    let policy = RetryPolicy {
+        retries: 2,
+        base: Duration::from_secs(1),
+        statuses: vec![429, 500, 502, 503, 504],
+    };
+    // Padding hides the real response size from anyone watching the connection.
+    let req = client
+        .get(format!("{HIBP_RANGE}{prefix}"))
+        .header("Add-Padding", "true");
+    let pb = spinner("Checking Pwned Passwords...");
+    let resp = retry::send(req, &policy, &pb).await?;
+    let status = resp.status();
+    if !status.is_success() {
+        pb.finish_and_clear();
+        bail!("Pwned Passwords range API failed ({status})");
+    }
+    let body = resp
+        .text()
+        .await
+        .context("Error reading Pwned Passwords response")?;
+    pb.finish_and_clear();
+    Ok(range_count(&body, suffix))
+}
+
+/// The password from the argument, else the first line of stdin (prompting on a terminal).
+pub(crate) fn read_password(arg: Option<String>) -> Result<String> {
+    if let Some(p) = arg {
+        return Ok(p);
+    }
+    if atty::is(Stream::Stdin) {
+        eprint!("Password: ");
+        io::stderr().flush().ok();
+    }
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Score a password and optionally look it up in Pwned Passwords.
+pub async fn run_check_password(
+    password: Option<String>,
+    breach: bool,
+    timeout: Option<u64>,
+    output: OutputFormat,
+) -> Result<()> {
+    let password = read_password(password)?;
+    if password.is_empty() {
+        bail!("No password given");
+    }
+    let chars: Vec<char> = password.chars().collect();
+    let est = estimate(&chars);
+    let score = score(est.guesses);
+    let (warning, suggestions) = feedback(&est, chars.len());
+    let breaches = match breach {
+        true => Some(breach_count(&password, timeout).await?),
+        false => None,
+    };
+
+    if let OutputFormat::Json = output {
+        let times: serde_json::Map<String, serde_json::Value> = ATTACKS
+            .iter()
+            .map(|(name, rate)| (name.to_string(), json!(est.guesses / rate)))
+            .collect();
+        let report = json!({
+            "score": score,
+            "strength": score_label(score),
+            "guesses_log10": (est.guesses.log10() * 100.0).round() / 100.0,
+            "crack_seconds": times,
+            "warning": warning,
+            "suggestions": suggestions,
+            "breach_count": breaches,
+        });
+        println!("{}", colored_json::to_colored_json_auto(&report)?);
+        return Ok(());
+    }
+
+    let label = format!("{score}/4 ({})", score_label(score));
+    let label = match score {
+        0 | 1 => label.red().bold().to_string(),
+        2 => label.yellow().bold().to_string(),
+        _ => label.green().bold().to_string(),
+    };
+    println!("{} {label}", "Score:".bold());
+    println!("{} ~10^{:.1}", "Guesses:".bold(), est.guesses.log10());
+    println!("{}", "Time to crack:".bold());
+    for (name, rate) in ATTACKS {
+        println!("  {name:<30} {}", human_time(est.guesses / rate));
+    }
+    if let Some(w) = warning {
+        println!("{} {w}", "Warning:".yellow().bold());
+    }
+    for s in suggestions {
+        println!("{} {s}", "Tip:".bold());
+    }
+    match breaches {
+        Some(0) => println!("{} not found in Pwned Passwords", "Breach:".bold()),
+        Some(n) => println!(
+            "{} {}",
+            "Breach:".bold(),
+            format!("seen {n} times in Pwned Passwords; do not use it")
+                .red()
+                .bold()
+        ),
+        None => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(pw: &str) -> (u8, Vec<Pattern>) {
+        let chars: Vec<char> = pw.chars().collect();
+        let est = estimate(&chars);
+        (
+            score(est.guesses),
+            est.pieces.iter().map(|p| p.pattern).collect(),
+        )
+    }
+
+    #[test]
+    fn test_common_and_variants() {
+        assert_eq!(
+            check("password"),
+            (
+                0,
+                vec![Pattern::Dictionary {
+                    rank: 2,
+                    variant: false
+                }]
+            )
+        );
+        let (s, pieces) = check("P@ssw0rd");
+        assert_eq!(s, 0);
+        assert!(matches!(
+            pieces[..],
+            [Pattern::Dictionary { variant: true, .. }]
+        ));
+        assert_eq!(check("qwerty123").0, 0);
+    }
+
+    #[test]
+    fn test_patterns() {
+        assert!(check("aaaaaaaaaaaa").1.contains(&Pattern::Repeat));
+        assert!(check("zyxwvuts").1.contains(&Pattern::Sequence));
+        assert!(check("asdfghjk").1.contains(&Pattern::Keyboard));
+        assert!(check("mulberry1987").1.contains(&Pattern::Year));
+        assert!(check("aaaaaaaaaaaa").0 <= 1);
+    }
+
+    #[test]
+    fn test_strong_passwords() {
+        assert_eq!(check("correct horse battery staple").0, 4);
+        assert_eq!(check("x7#Qm!2vRp9&Lw").0, 4);
+        assert_eq!(score(999.0), 0);
+        assert_eq!(score(1e10), 4);
+    }
+
+    #[test]
+    fn test_human_time_and_range() {
+        assert_eq!(human_time(0.2), "instant");
+        assert_eq!(human_time(1.0), "1 second");
+        assert_eq!(human_time(7200.0), "2 hours");
+        assert_eq!(human_time(1e12), "centuries");
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:10\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493\r\nFFFFF0000000000000000000000000000000:0";
+        assert_eq!(
+            range_count(body, "1E4C9B93F3F0682250B6CF8331B7EE68FD8"),
+            3861493
+        );
+        assert_eq!(range_count(body, "ABC"), 0);
+    }
+}
@@ -0,0 +1,175 @@
+//! `registry get-schema`: fetch schemas from a Kafka schema registry speaking
+//! the Confluent REST API (Confluent, Redpanda, Karapace, Apicurio's ccompat
+//! endpoint), by subject and version or by the global id that framed Kafka
+//! messages carry. `avro decode --registry` uses the same client.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use reqwest::header::ACCEPT;
+use reqwest::Client;
+use serde_json::Value;
+use std::time::Duration;
+use url::Url;
+
+const ACCEPT_TYPES: &str = "application/vnd.schemaregistry.v1+json, application/json";
+
+/// A schema as the registry returns it.
+#[derive(Debug)]
+pub struct Registered {
+    pub id: Option<u64>,
+    pub subject: Option<String>,
+    pub version: Option<i64>,
+    /// AVRO (the default when the registry omits `schemaType`), JSON, or PROTOBUF.
+    pub kind: String,
+    pub schema: String,
+}
+
+impl Registered {
+    fn from_json(body: &Value) -> Result<Self> {
+        let schema = body["schema"]
+            .as_str()
+            .context("Registry response has no schema")?
+            .to_string();
+        Ok(Registered {
+            id: body["id"].as_u64(),
+            subject: body["subject"].as_str().map(str::to_string),
+            version: body["version"].as_i64(),
+            kind: body["schemaType"].as_str().unwrap_or("AVRO").to_string(),
+            schema,
+        })
+    }
+}
+
+pub struct Registry {
+    client: Client,
+    base: Url,
+    user: Option<String>,
+}
+
+impl Registry {
+    /// `url` is the registry root (any path prefix is kept); `user` is name:password.
+    pub fn new(url: &str, user: Option<&str>, timeout: Option<u64>) -> Result<Self> {
+        let base = Url::parse(url).with_context(|| format!("Invalid registry URL: {url}"))?;
+        if base.cannot_be_a_base() {
+            bail!("Invalid registry URL: {url}");
+        }
+        Ok(Registry {
+            client: Client::builder()
+                .timeout(Duration::from_secs(timeout.unwrap_or(30)))
+                .user_agent(concat!("swiftline/", env!("CARGO_PKG_VERSION")))
+                .build()?,
+            base,
+            user: user.map(str::to_string),
+        })
+    }
+
+    async fn get(&self, segments: &[&str]) -> Result<Value> {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .expect("checked in new")
+            .pop_if_empty()
+            .extend(segments);
+        let mut req = self.client.get(url.clone()).header(ACCEPT, ACCEPT_TYPES);
+        if let Some(user) = &self.user {
+            let (name, pass) = user.split_once(':').unwrap_or((user, ""));
+            req = req.basic_auth(name, Some(pass));
+        }
+        let resp = req
+            .send()
+            .await
+            .with_context(|| format!("Network error fetching {url}"))?;
+        let status = resp.status();
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        if !status.is_success() {
+            // Registry errors look like {"error_code": 40401, "message": "Subject not found."}
+            match body["message"].as_str() {
+                Some(m) => bail!("Registry request failed ({status}): {m}"),
+                None => bail!("Registry request failed ({status})"),
+            }
+        }
+        Ok(body)
+    }
+
+    /// A subject's schema at `version` (a number or "latest").
+    pub async fn by_subject(&self, subject: &str, version: &str) -> Result<Registered> {
+        let body = self
+            .get(&["subjects", subject, "versions", version])
+            .await?;
+        Registered::from_json(&body)
+    }
+
+    /// The schema with a global id.
+    pub async fn by_id(&self, id: u32) -> Result<Registered> {
+        let body = self.get(&["schemas", "ids", &id.to_string()]).await?;
+        let mut found = Registered::from_json(&body)?;
+        found.id = Some(id.into());
+        Ok(found)
+    }
+}
+
+/// Print a schema: JSON schemas (Avro, JSON Schema) pretty-printed, Protobuf as text.
+/// Subject, version, id, and type go to stderr.
+pub async fn run_get_schema(
+    url: &str,
+    subject: Option<&str>,
+    version: &str,
+    id: Option<u32>,
+    user: Option<&str>,
+    timeout: Option<u64>,
+) -> Result<()> {
+    let registry = Registry::new(url, user, timeout)?;
+    let found = match (subject, id) {
+        (_, Some(id)) => registry.by_id(id).await?,
+        (Some(subject), None) => registry.by_subject(subject, version).await?,
+        (None, None) => bail!("Pass --subject or --id"),
+    };
+
+    let mut meta = Vec::new();
+    if let Some(s) = &found.subject {
+        meta.push(s.clone());
+    }
+    if let Some(v) = found.version {
+        meta.push(format!("version {v}"));
+    }
+    if let Some(id) = found.id {
+        meta.push(format!("id {id}"));
+    }
+    meta.push(found.kind.clone());
+    eprintln!("{}", meta.join(", ").dimmed());
+
+    match serde_json::from_str::<Value>(&found.schema) {
+        Ok(json) => println!("{}", colored_json::to_colored_json_auto(&json)?),
+        Err(_) => println!("{}", found.schema.trim_end()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_registered_from_json() {
+        let avro = Registered::from_json(&json!({
+            "subject": "orders-value", "version": 3, "id": 42,
+            "schema": "{\"type\":\"string\"}"
+        }))
+        .unwrap();
+        assert_eq!(
+            (
+                avro.subject.as_deref(),
+                avro.version,
+                avro.id,
+                avro.kind.as_str()
+            ),
+            (Some("orders-value"), Some(3), Some(42), "AVRO")
+        );
+        let proto = Registered::from_json(
+            &json!({"schema": "syntax = \"proto3\";", "schemaType": "PROTOBUF"}),
+        )
+        .unwrap();
+        assert_eq!(proto.kind, "PROTOBUF");
+        assert!(Registered::from_json(&json!({"error_code": 40403})).is_err());
+    }
+}
@@ -0,0 +1,430 @@
+//! `s3 get|put|ls`: talk to S3-compatible object storage (AWS S3, MinIO, R2)
+//! with SigV4-signed path-style requests. Large uploads are streamed as
+//! multipart uploads, one part in memory at a time.
+
+use anyhow::{bail, Context, Result};
+use reqwest::header::{HeaderMap, ETAG};
+use reqwest::{Client, Method, Response};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+use url::Url;
+
+use crate::helpers::download::download_to_file;
+use crate::helpers::format::human_size;
+use crate::helpers::sigv4::{self, Credentials, Scope, EMPTY_SHA256};
+use crate::helpers::spinner::{self, spinner};
+use crate::helpers::{style, table};
+
+/// Uploads larger than this are sent as multipart uploads in parts of this size.
+const PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Connection settings shared by every `s3` subcommand.
+pub struct Target {
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub timeout: Option<u64>,
+}
+
+/// A signed-request client bound to one bucket.
+struct Bucket {
+    client: Client,
+    base: Url,
+    bucket: String,
+    region: String,
+    creds: Credentials,
+}
+
+impl Bucket {
+    fn new(target: Target) -> Result<Self> {
+        let base = Url::parse(&target.endpoint)
+            .with_context(|| format!("Invalid endpoint: {}", target.endpoint))?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(target.timeout.unwrap_or(300)))
+            .build()?;
+        Ok(Self {
+            client,
+            base,
+            bucket: target.bucket,
+            region: target.region,
+            creds: Credentials::from_env()?,
+        })
+    }
+
+    /// Path-style URL for an object key (empty key addresses the bucket itself).
+    fn url(&self, key: &str, query: &[(&str, &str)]) -> Result<Url> {
+        let path = format!(
+            "{}/{}/{}",
+            self.base.path().trim_end_matches('/'),
+            sigv4::uri_encode(&self.bucket, false),
+            sigv4::uri_encode(key, true)
+        );
+        let mut url = self.base.join(&path)?;
+        if !query.is_empty() {
+            let qs = query
+                .iter()
+                .map(|(k, v)| {
+                    if v.is_empty() {
+                        sigv4::uri_encode(k, false)
+                    } else {
+                        format!(
+                            "{}={}",
+                            sigv4::uri_encode(k, false),
+                            sigv4::uri_encode(v, false)
+                        )
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("&");
+            url.set_query(Some(&qs));
+        }
+        Ok(url)
+    }
+
+    /// Sign and send a request, turning S3 XML error bodies into readable errors.
+    async fn send(&self, method: Method, url: Url, body: Vec<u8>) -> Result<Response> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let payload_hash = if body.is_empty() {
+            EMPTY_SHA256.to_string()
+        } else {
+            sigv4::sha256_hex(&body)
+        };
+        let mut headers = HeaderMap::new();
+        let scope = Scope {
+            region: &self.region,
+            service: "s3",
+        };
+        sigv4::sign(
+            method.as_str(),
+            &url,
+            &mut headers,
+            &payload_hash,
+            &self.creds,
+            &scope,
+            now,
+        )?;
+
+        let resp = self
+            .client
+            .request(method.clone(), url.clone())
+            .headers(headers)
+            .body(body)
+            .send()
+            .await
+            .context("Network error while sending request")?;
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(resp);
+        }
+        let text = resp.text().await.unwrap_or_default();
+        match (xml_tag(&text, "Code"), xml_tag(&text, "Message")) {
+            (Some(code), Some(msg)) => bail!(
+                "S3 {method} {} failed ({status}): {code}: {msg}",
+                url.path()
+            ),
+            _ => bail!("S3 {method} {} failed ({status})", url.path()),
+        }
+    }
+}
+
+/// Text content of the first `<tag>...</tag>` in an XML document.
+fn xml_tag(xml: &str, tag: &str) -> Option<String> {
+    xml_tags(xml, tag).into_iter().next()
+}
+
+/// Text content of every `<tag>...</tag>` in document order, entities decoded.
+fn xml_tags(xml: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{tag}>"), format!("</{tag}>"));
+    let mut out = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else { break };
+        out.push(xml_unescape(&rest[..end]));
+        rest = &rest[end + close.len()..];
+    }
+    out
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Download an object to `output` (defaults to the key's file name).
+pub async fn run_get(target: Target, key: &str, output: Option<PathBuf>) -> Result<()> {
+    let bucket = Bucket::new(target)?;
+    let path = match output {
+        Some(p) => p,
+        None => PathBuf::from(
+            key.rsplit('/')
+                .next()
+                .filter(|n| !n.is_empty())
+                .with_context(|| format!("Cannot derive a file name from key: {key}"))?,
+        ),
+    };
+
+    let pb = spinner("Requesting...");
+    let resp = bucket
+        .send(Method::GET, bucket.url(key, &[])?, Vec::new())
+        .await?;
+    pb.finish_and_clear();
+
+    let downloaded = download_to_file(resp, &path, |_| {}).await?;
+
+    style::ok(&format!(
+        "Downloaded s3://{}/{key} ({}) to {}",
+        bucket.bucket,
+        human_size(downloaded),
+        path.display()
+    ));
+    Ok(())
+}
+
+/// Read up to `PART_SIZE` bytes (less only at end of file).
+async fn read_part(file: &mut File) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(PART_SIZE as usize);
+    file.take(PART_SIZE).read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Upload `file` as `key` (defaults to the file name); multipart above `PART_SIZE`.
+pub async fn run_put(target: Target, file: &Path, key: Option<String>) -> Result<()> {
+    let bucket = Bucket::new(target)?;
+    let key = match key {
+        Some(k) => k,
+        None => file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .with_context(|| format!("Cannot derive a key from: {}", file.display()))?
+            .to_string(),
+    };
+    let size = tokio::fs::metadata(file)
+        .await
+        .with_context(|| format!("Failed to read: {}", file.display()))?
+        .len();
+    let mut handle = File::open(file)
+        .await
+        .with_context(|| format!("Failed to open: {}", file.display()))?;
+    let pbar = spinner::bar(size);
+
+    if size <= PART_SIZE {
+        let body = read_part(&mut handle).await?;
+        bucket
+            .send(Method::PUT, bucket.url(&key, &[])?, body)
+            .await?;
+        pbar.finish_and_clear();
+    } else {
+        let result = multipart_upload(&bucket, &key, &mut handle, &pbar).await;
+        pbar.finish_and_clear();
+        result?;
+    }
+
+    style::ok(&format!(
+        "Uploaded {} ({}) to s3://{}/{key}",
+        file.display(),
+        human_size(size),
+        bucket.bucket
+    ));
+    Ok(())
+}
+
+async fn multipart_upload(
+    bucket: &Bucket,
+    key: &str,
+    file: &mut File,
+    pbar: &indicatif::ProgressBar,
+) -> Result<()> {
+    let resp = bucket
+        .send(
+            Method::POST,
+            bucket.url(key, &[("uploads", "")])?,
+            Vec::new(),
+        )
+        .await?;
+    let upload_id = xml_tag(&resp.text().await?, "UploadId")
+        .context("CreateMultipartUpload response has no UploadId")?;
+
+    let parts = async {
+        let mut etags: Vec<String> = Vec::new();
+        loop {
+            let body = read_part(file).await?;
+            if body.is_empty() {
+                break;
+            }
+            let len = body.len() as u64;
+            let number = (etags.len() + 1).to_string();
+            let url = bucket.url(key, &[("partNumber", &number), ("uploadId", &upload_id)])?;
+            let resp = bucket.send(Method::PUT, url, body).await?;
+            let etag = resp
+                .headers()
+                .get(ETAG)
+                .and_then(|v| v.to_str().ok())
+                .with_context(|| format!("Part {number} response has no ETag"))?;
+            etags.push(etag.to_string());
+            pbar.inc(len);
+        }
+        Ok::<_, anyhow::Error>(etags)
+    }
+    .await;
+
+    let etags = match parts {
+        Ok(etags) => etags,
+        Err(e) => {
+            // Best effort: don't leave orphaned parts billing in the bucket.
+            let url = bucket.url(key, &[("uploadId", &upload_id)])?;
+            let _ = bucket.send(Method::DELETE, url, Vec::new()).await;
+            return Err(e);
+        }
+    };
+
+    let mut xml = String::from("<CompleteMultipartUpload>");
+    for (i, etag) in etags.iter().enumerate() {
+        xml.push_str(&format!(
+            "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+            i + 1,
+            xml_escape(etag)
+        ));
+    }
+    xml.push_str("</CompleteMultipartUpload>");
+    let url = bucket.url(key, &[("uploadId", &upload_id)])?;
+    let resp = bucket.send(Method::POST, url, xml.into_bytes()).await?;
+    // S3 may report a failed completion with 200 OK and an <Error> body.
+    let text = resp.text().await?;
+    if let Some(code) = xml_tag(&text, "Code") {
+        bail!(
+            "CompleteMultipartUpload failed: {code}: {}",
+            xml_tag(&text, "Message").unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+/// One `<Contents>` entry of a ListObjectsV2 response.
+#[derive(Debug, PartialEq, Eq)]
+struct Object {
+    key: String,
+    size: u64,
+    modified: String,
+}
+
+fn parse_listing(xml: &str) -> (Vec<Object>, Option<String>) {
+    let objects = xml_tags(xml, "Contents")
+        .iter()
+        .map(|c| Object {
+            key: xml_tag(c, "Key").unwrap_or_default(),
+            size: xml_tag(c, "Size").and_then(|s| s.parse().ok()).unwrap_or(0),
+            modified: xml_tag(c, "LastModified").unwrap_or_default(),
+        })
+        .collect();
+    let next = match xml_tag(xml, "IsTruncated").as_deref() {
+        Some("true") => xml_tag(xml, "NextContinuationToken"),
+        _ => None,
+    };
+    (objects, next)
+}
+
+/// List objects under an optional prefix, following continuation tokens.
+pub async fn run_ls(target: Target, prefix: Option<String>) -> Result<()> {
+    let bucket = Bucket::new(target)?;
+    let prefix = prefix.unwrap_or_default();
+    let pb = spinner("Listing...");
+
+    let mut objects = Vec::new();
+    let mut token: Option<String> = None;
+    loop {
+        let mut query = vec![("list-type", "2")];
+        if !prefix.is_empty() {
+            query.push(("prefix", &prefix));
+        }
+        if let Some(t) = &token {
+            query.push(("continuation-token", t));
+        }
+        let resp = bucket
+            .send(Method::GET, bucket.url("", &query)?, Vec::new())
+            .await?;
+        let (page, next) = parse_listing(&resp.text().await?);
+        objects.extend(page);
+        match next {
+            Some(t) => token = Some(t),
+            None => break,
+        }
+    }
+    pb.finish_and_clear();
+
+    if objects.is_empty() {
+        style::warn_line("No objects found");
+        return Ok(());
+    }
+    let total: u64 = objects.iter().map(|o| o.size).sum();
+    let rows: Vec<Vec<String>> = objects
+        .iter()
+        .map(|o| vec![human_size(o.size), o.modified.clone(), o.key.clone()])
+        .collect();
+    table::print(&["Size", "Last modified", "Key"], &rows);
+    println!("{} object(s), {}", objects.len(), human_size(total));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> Bucket {
+        Bucket {
+            client: Client::new(),
+            base: Url::parse("http://localhost:9000").unwrap(),
+            bucket: "artifacts".into(),
+            region: "us-east-1".into(),
+            creds: Credentials {
+                access_key: "a".into(),
+                secret_key: "b".into(),
+                session_token: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_path_style_url_encodes_key() {
+        let b = target();
+        let url = b.url("builds/v1 final.tar.gz", &[]).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:9000/artifacts/builds/v1%20final.tar.gz"
+        );
+        let url = b.url("", &[("list-type", "2"), ("prefix", "a/b")]).unwrap();
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:9000/artifacts/?list-type=2&prefix=a%2Fb"
+        );
+        assert_eq!(
+            b.url("k", &[("uploads", "")]).unwrap().query(),
+            Some("uploads")
+        );
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let xml = r#"<ListBucketResult><IsTruncated>true</IsTruncated>
+            <Contents><Key>a&amp;b.txt</Key><LastModified>2024-01-01T00:00:00.000Z</LastModified><Size>12</Size></Contents>
+            <Contents><Key>c.bin</Key><Size>3</Size></Contents>
+            <NextContinuationToken>tok</NextContinuationToken></ListBucketResult>"#;
+        let (objects, next) = parse_listing(xml);
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].key, "a&b.txt");
+        assert_eq!(objects[0].size, 12);
+        assert_eq!(objects[1].modified, "");
+        assert_eq!(next.as_deref(), Some("tok"));
+    }
+}
@@ -0,0 +1,696 @@
+//! `sbom query`: list the components of a CycloneDX or SPDX JSON bill of
+//! materials with their licenses, group licenses into families (public domain,
+//! permissive, weak and strong copyleft), and match component versions against
+//! an offline advisory file in OSV format (as exported from osv.dev).
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use owo_colors::OwoColorize;
+use serde_json::{json, Value};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::cli::{LicenseFamily, OutputFormat};
+use crate::helpers::format::print_json;
+use crate::helpers::{style, table};
+
+/// One package from the SBOM.
+#[derive(Debug, Clone, PartialEq)]
+struct Component {
+    name: String,
+    version: String,
+    purl: Option<String>,
+    /// License ids or SPDX expressions, as listed.
+    licenses: Vec<String>,
+}
+
+impl Component {
+    /// Several listed licenses must all be honored, so they combine like AND.
+    fn family(&self) -> LicenseFamily {
+        self.licenses
+            .iter()
+            .map(|l| expression_family(l))
+            .max()
+            .unwrap_or(LicenseFamily::Unknown)
+    }
+
+    fn license(&self) -> String {
+        self.licenses.join(" AND ")
+    }
+}
+
+fn family_label(f: LicenseFamily) -> String {
+    f.to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+fn str_field(v: &Value, key: &str) -> String {
+    v[key].as_str().unwrap_or_default().to_string()
+}
+
+/// CycloneDX components, including nested ones.
+fn cyclonedx_components(list: &Value, out: &mut Vec<Component>) {
+    for c in list.as_array().into_iter().flatten() {
+        let licenses = c["licenses"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| {
+                l["expression"]
+                    .as_str()
+                    .or_else(|| l["license"]["id"].as_str())
+                    .or_else(|| l["license"]["name"].as_str())
+                    .map(str::to_string)
+            })
+            .collect();
+        out.push(Component {
+            name: str_field(c, "name"),
+            version: str_field(c, "version"),
+            purl: c["purl"].as_str().map(str::to_string),
+            licenses,
+        });
+        cyclonedx_components(&c["components"], out);
+    }
+}
+
+/// SPDX packages; the concluded license wins over the declared one unless it is NOASSERTION.
+fn spdx_components(doc: &Value) -> Vec<Component> {
+    let known = |v: &Value| {
+        v.as_str()
+            .filter(|s| !matches!(*s, "" | "NOASSERTION" | "NONE"))
+            .map(str::to_string)
+    };
+    doc["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|p| Component {
+            name: str_field(p, "name"),
+            version: str_field(p, "versionInfo"),
+            purl: p["externalRefs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|r| r["referenceType"] == "purl")
+                .and_then(|r| r["referenceLocator"].as_str())
+                .map(str::to_string),
+            licenses: known(&p["licenseConcluded"])
+                .or_else(|| known(&p["licenseDeclared"]))
+                .into_iter()
+                .collect(),
+        })
+        .collect()
+}
+
+/// Detect the document type and read its components.
+fn parse_sbom(doc: &Value) -> Result<(String, Vec<Component>)> {
+    if doc["bomFormat"] == "CycloneDX" {
+        let mut out = Vec::new();
+        cyclonedx_components(&doc["components"], &mut out);
+        let version = doc["specVersion"].as_str().unwrap_or("?");
+        return Ok((format!("CycloneDX {version}"), out));
+    }
+    if let Some(version) = doc["spdxVersion"].as_str() {
+        return Ok((version.to_string(), spdx_components(doc)));
+    }
+    bail!("Not a CycloneDX or SPDX JSON document (no bomFormat or spdxVersion)")
+}
+
+/// Family of one SPDX license id; `-only`, `-or-later`, and `+` variants share their base.
+fn license_family(id: &str) -> LicenseFamily {
+    let id = id.trim().to_ascii_lowercase();
+    let base = id
+        .trim_end_matches('+')
+        .trim_end_matches("-or-later")
+        .trim_end_matches("-only");
+    let starts = |prefixes: &[&str]| prefixes.iter().any(|p| base.starts_with(p));
+    if matches!(
+        base,
+        "cc0-1.0" | "unlicense" | "public domain" | "public-domain"
+    ) {
+        LicenseFamily::PublicDomain
+    } else if starts(&["agpl-", "gpl-", "eupl-", "osl-", "sspl-", "cc-by-sa-"]) {
+        LicenseFamily::Copyleft
+    } else if starts(&["lgpl-", "mpl-", "epl-", "cddl-", "cpl-", "ms-rl"]) {
+        LicenseFamily::WeakCopyleft
+    } else if starts(&[
+        "bsd-", "apache-", "unicode-", "python-", "psf-", "cc-by-", "blueoak-",
+    ]) || matches!(
+        base,
+        "mit"
+            | "mit-0"
+            | "0bsd"
+            | "isc"
+            | "zlib"
+            | "bsl-1.0"
+            | "x11"
+            | "openssl"
+            | "curl"
+            | "wtfpl"
+            | "ncsa"
+            | "postgresql"
+            | "artistic-2.0"
+    ) {
+        LicenseFamily::Permissive
+    } else {
+        LicenseFamily::Unknown
+    }
+}
+
+/// Family of an SPDX expression: OR picks the most permissive choice, AND the most restrictive.
+fn expression_family(expr: &str) -> LicenseFamily {
+    let spaced = expr.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut pos = 0;
+    parse_or(&tokens, &mut pos)
+}
+
+fn parse_or(tokens: &[&str], pos: &mut usize) -> LicenseFamily {
+    let mut family = parse_and(tokens, pos);
+    while tokens
+        .get(*pos)
+        .is_some_and(|t| t.eq_ignore_ascii_case("or"))
+    {
+        *pos += 1;
+        family = family.min(parse_and(tokens, pos));
+    }
+    family
+}
+
+fn parse_and(tokens: &[&str], pos: &mut usize) -> LicenseFamily {
+    let mut family = parse_atom(tokens, pos);
+    while tokens
+        .get(*pos)
+        .is_some_and(|t| t.eq_ignore_ascii_case("and"))
+    {
+        *pos += 1;
+        family = family.max(parse_atom(tokens, pos));
+    }
+    family
+}
+
+fn parse_atom(tokens: &[&str], pos: &mut usize) -> LicenseFamily {
+    let Some(token) = tokens.get(*pos) else {
+        return LicenseFamily::Unknown;
+    };
+    *pos += 1;
+    let family = if *token == "(" {
+        let inner = parse_or(tokens, pos);
+        if tokens.get(*pos) == Some(&")") {
+            *pos += 1;
+        }
+        inner
+    } else {
+        license_family(token)
+    };
+    // An exception (`GPL-2.0 WITH Classpath-exception-2.0`) keeps the base family.
+    if tokens
+        .get(*pos)
+        .is_some_and(|t| t.eq_ignore_ascii_case("with"))
+    {
+        *pos += 2;
+    }
+    family
+}
+
+/// Compare versions segment by segment (numbers numerically); a pre-release
+/// (`1.0.0-rc1`) sorts before its release, and build metadata is ignored.
+pub(crate) fn cmp_versions(a: &str, b: &str) -> Ordering {
+    let split = |v: &str| {
+        let v = v.trim().trim_start_matches('v');
+        let v = v.split('+').next().unwrap_or_default();
+        match v.split_once('-') {
+            Some((main, pre)) => (main.to_string(), Some(pre.to_string())),
+            None => (v.to_string(), None),
+        }
+    };
+    let cmp_segments = |x: &str, y: &str| {
+        let (xs, ys): (Vec<&str>, Vec<&str>) = (x.split('.').collect(), y.split('.').collect());
+        for i in 0..xs.len().max(ys.len()) {
+            let (p, q) = (
+                xs.get(i).copied().unwrap_or("0"),
+                ys.get(i).copied().unwrap_or("0"),
+            );
+            let ord = match (p.parse::<u64>(), q.parse::<u64>()) {
+                (Ok(m), Ok(n)) => m.cmp(&n),
+                _ => p.cmp(q),
+            };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    };
+    let ((am, ap), (bm, bp)) = (split(a), split(b));
+    cmp_segments(&am, &bm).then_with(|| match (ap, bp) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => cmp_segments(&x, &y),
+    })
+}
+
+/// OSV ecosystem for a purl type, with the package name as OSV spells it.
+fn purl_package(purl: &str) -> Option<(&'static str, String)> {
+    let rest = purl.strip_prefix("pkg:")?;
+    let rest = rest.split(['?', '#']).next()?;
+    let rest = rest.rsplit_once('@').map_or(rest, |(p, _)| p);
+    let (kind, path) = rest.split_once('/')?;
+    let path = path.replace("%40", "@");
+    let (namespace, name) = path.rsplit_once('/').unwrap_or(("", &path));
+    let ecosystem = match kind.to_ascii_lowercase().as_str() {
+        "cargo" => "crates.io",
+        "npm" => "npm",
+        "pypi" => "PyPI",
+        "maven" => "Maven",
+        "golang" => "Go",
+        "gem" => "RubyGems",
+        "nuget" => "NuGet",
+        "composer" => "Packagist",
+        "hex" => "Hex",
+        "pub" => "Pub",
+        "deb" => "Debian",
+        "apk" => "Alpine",
+        _ => return None,
+    };
+    let full = match (ecosystem, namespace) {
+        (_, "") => name.to_string(),
+        ("Maven", ns) => format!("{}:{name}", ns.replace('/', ".")),
+        ("Debian" | "Alpine", _) => name.to_string(),
+        (_, ns) => format!("{ns}/{name}"),
+    };
+    Some((ecosystem, full))
+}
+
+/// One `affected` entry of an OSV record.
+#[derive(Debug)]
+struct Affected {
+    id: String,
+    summary: String,
+    ecosystem: String,
+    name: String,
+    /// `(event, version)` pairs: introduced / fixed / last_affected.
+    events: Vec<Vec<(String, String)>>,
+    versions: Vec<String>,
+}
+
+impl Affected {
+    fn matches(&self, c: &Component) -> bool {
+        let same_package = match c.purl.as_deref().and_then(purl_package) {
+            // Debian:12 and Alpine:v3.19 carry a release suffix.
+            Some((eco, name)) => {
+                let osv_eco = self.ecosystem.split(':').next().unwrap_or_default();
+                (osv_eco.is_empty() || osv_eco.eq_ignore_ascii_case(eco))
+                    && (name.eq_ignore_ascii_case(&self.name)
+                        || c.name.eq_ignore_ascii_case(&self.name))
+            }
+            None => c.name.eq_ignore_ascii_case(&self.name),
+        };
+        same_package && !c.version.is_empty() && self.affects(&c.version)
+    }
+
+    /// The OSV range walk: each event, in order, switches the version in or out.
+    fn affects(&self, version: &str) -> bool {
+        if self.versions.iter().any(|v| v == version) {
+            return true;
+        }
+        self.events.iter().any(|range| {
+            let mut hit = false;
+            for (kind, at) in range {
+                match kind.as_str() {
+                    "introduced" if at == "0" || cmp_versions(version, at).is_ge() => hit = true,
+                    "fixed" if cmp_versions(version, at).is_ge() => hit = false,
+                    "last_affected" if cmp_versions(version, at).is_gt() => hit = false,
+                    _ => {}
+                }
+            }
+            hit
+        })
+    }
+
+    fn fixed(&self) -> String {
+        let fixed: Vec<&str> = self
+            .events
+            .iter()
+            .flatten()
+            .filter(|(k, _)| k == "fixed")
+            .map(|(_, v)| v.as_str())
+            .collect();
+        fixed.join(", ")
+    }
+}
+
+/// Read OSV records: one object, an array, or a `{"vulns": [...]}` wrapper.
+fn parse_advisories(doc: &Value) -> Result<Vec<Affected>> {
+    let records = match doc {
+        Value::Array(items) => items.as_slice(),
+        Value::Object(_) if doc["vulns"].is_array() => doc["vulns"].as_array().unwrap(),
+        Value::Object(_) if doc["id"].is_string() => std::slice::from_ref(doc),
+        _ => bail!(
+            "Advisory file must hold OSV records (an object, an array, or {{\"vulns\": [...]}})"
+        ),
+    };
+    let mut out = Vec::new();
+    for rec in records {
+        let id = str_field(rec, "id");
+        let summary = rec["summary"]
+            .as_str()
+            .or_else(|| rec["details"].as_str())
+            .unwrap_or_default()
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        for aff in rec["affected"].as_array().into_iter().flatten() {
+            let events = aff["ranges"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|r| r["type"] != "GIT")
+                .map(|r| {
+                    r["events"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|e| {
+                            let (k, v) = e.as_object()?.iter().next()?;
+                            Some((k.clone(), v.as_str()?.to_string()))
+                        })
+                        .collect()
+                })
+                .collect();
+            out.push(Affected {
+                id: id.clone(),
+                summary: summary.clone(),
+                ecosystem: str_field(&aff["package"], "ecosystem"),
+                name: str_field(&aff["package"], "name"),
+                events,
+                versions: aff["versions"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+            });
+        }
+    }
+    Ok(out)
+}
+
+fn read_json(path: &Path) -> Result<Value> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("Invalid JSON in {}", path.display()))
+}
+
+fn colored_family(f: LicenseFamily) -> String {
+    let label = family_label(f);
+    match f {
+        LicenseFamily::Copyleft => label.red().to_string(),
+        LicenseFamily::WeakCopyleft => label.yellow().to_string(),
+        LicenseFamily::Unknown => label.dimmed().to_string(),
+        _ => label.green().to_string(),
+    }
+}
+
+/// Options for `sbom query`.
+pub struct QueryOptions<'a> {
+    pub file: &'a Path,
+    pub licenses: bool,
+    pub component: Option<&'a str>,
+    pub families: &'a [LicenseFamily],
+    pub advisories: Option<&'a Path>,
+    pub output: OutputFormat,
+}
+
+/// List components, summarize licenses, or report known-vulnerable versions.
+pub fn run_query(opts: QueryOptions) -> Result<()> {
+    let (format, all) = parse_sbom(&read_json(opts.file)?)?;
+    let needle = opts.component.map(str::to_ascii_lowercase);
+    let components: Vec<Component> = all
+        .into_iter()
+        .filter(|c| {
+            needle
+                .as_deref()
+                .is_none_or(|n| c.name.to_ascii_lowercase().contains(n))
+        })
+        .filter(|c| opts.families.is_empty() || opts.families.contains(&c.family()))
+        .collect();
+    if matches!(opts.output, OutputFormat::Table) {
+        eprintln!(
+            "{}",
+            format!("{format}: {} component(s)", components.len()).dimmed()
+        );
+    }
+
+    if let Some(path) = opts.advisories {
+        return report_vulnerable(
+            &components,
+            &parse_advisories(&read_json(path)?)?,
+            opts.output,
+        );
+    }
+    if opts.licenses {
+        return report_licenses(&components, opts.output);
+    }
+    match opts.output {
+        OutputFormat::Json => print_json(&Value::Array(
+            components
+                .iter()
+                .map(|c| {
+                    json!({
+                        "name": c.name,
+                        "version": c.version,
+                        "purl": c.purl,
+                        "licenses": c.licenses,
+                        "family": family_label(c.family()),
+                    })
+                })
+                .collect(),
+        )),
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = components
+                .iter()
+                .map(|c| {
+                    vec![
+                        c.name.clone(),
+                        c.version.clone(),
+                        c.license(),
+                        colored_family(c.family()),
+                    ]
+                })
+                .collect();
+            table::print(&["NAME", "VERSION", "LICENSE", "FAMILY"], &rows);
+            Ok(())
+        }
+    }
+}
+
+/// Count components per license, most restrictive families first.
+fn report_licenses(components: &[Component], output: OutputFormat) -> Result<()> {
+    let mut by_license: BTreeMap<(LicenseFamily, String), Vec<&str>> = BTreeMap::new();
+    for c in components {
+        let license = if c.licenses.is_empty() {
+            "(none)".to_string()
+        } else {
+            c.license()
+        };
+        by_license
+            .entry((c.family(), license))
+            .or_default()
+            .push(&c.name);
+    }
+    let mut entries: Vec<_> = by_license.into_iter().collect();
+    entries.sort_by(|((fa, la), a), ((fb, lb), b)| {
+        fb.cmp(fa).then(b.len().cmp(&a.len())).then(la.cmp(lb))
+    });
+    match output {
+        OutputFormat::Json => print_json(&Value::Array(
+            entries
+                .iter()
+                .map(|((family, license), names)| {
+                    json!({
+                        "license": license,
+                        "family": family_label(*family),
+                        "count": names.len(),
+                        "components": names,
+                    })
+                })
+                .collect(),
+        )),
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = entries
+                .iter()
+                .map(|((family, license), names)| {
+                    vec![
+                        license.clone(),
+                        colored_family(*family),
+                        names.len().to_string(),
+                    ]
+                })
+                .collect();
+            table::print(&["LICENSE", "FAMILY", "COMPONENTS"], &rows);
+            Ok(())
+        }
+    }
+}
+
+/// List components hit by an advisory; any hit fails the command.
+fn report_vulnerable(
+    components: &[Component],
+    advisories: &[Affected],
+    output: OutputFormat,
+) -> Result<()> {
+    let hits: Vec<(&Component, &Affected)> = components
+        .iter()
+        .flat_map(|c| {
+            advisories
+                .iter()
+                .filter(|a| a.matches(c))
+                .map(move |a| (c, a))
+        })
+        .collect();
+    match output {
+        OutputFormat::Json => print_json(&Value::Array(
+            hits.iter()
+                .map(|(c, a)| {
+                    json!({
+                        "name": c.name,
+                        "version": c.version,
+                        "advisory": a.id,
+                        "fixed": a.fixed(),
+                        "summary": a.summary,
+                    })
+                })
+                .collect(),
+        ))?,
+        OutputFormat::Table if hits.is_empty() => style::ok(&format!(
+            "No known-vulnerable versions among {} component(s)",
+            components.len()
+        )),
+        OutputFormat::Table => {
+            let rows: Vec<Vec<String>> = hits
+                .iter()
+                .map(|(c, a)| {
+                    vec![
+                        c.name.clone(),
+                        c.version.red().to_string(),
+                        a.id.clone(),
+                        a.fixed(),
+                        a.summary.clone(),
+                    ]
+                })
+                .collect();
+            table::print(
+                &["NAME", "VERSION", "ADVISORY", "FIXED IN", "SUMMARY"],
+                &rows,
+            );
+        }
+    }
+    if !hits.is_empty() {
+        bail!("{} known-vulnerable component version(s)", hits.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_license_families() {
+        use LicenseFamily::*;
+        assert_eq!(expression_family("MIT OR Apache-2.0"), Permissive);
+        assert_eq!(expression_family("GPL-3.0-or-later"), Copyleft);
+        assert_eq!(expression_family("MPL-2.0 OR GPL-2.0+"), WeakCopyleft);
+        assert_eq!(
+            expression_family("(MIT OR Apache-2.0) AND LGPL-2.1-only"),
+            WeakCopyleft
+        );
+        assert_eq!(
+            expression_family("GPL-2.0-only WITH Classpath-exception-2.0"),
+            Copyleft
+        );
+        assert_eq!(expression_family("CC0-1.0"), PublicDomain);
+        assert_eq!(
+            expression_family("LicenseRef-Proprietary OR MIT"),
+            Permissive
+        );
+        assert_eq!(expression_family("LicenseRef-Proprietary"), Unknown);
+    }
+
+    #[test]
+    fn test_parse_cyclonedx_and_spdx() {
+        let cdx = json!({
+            "bomFormat": "CycloneDX", "specVersion": "1.5",
+            "components": [{
+                "name": "openssl", "version": "3.0.1", "purl": "pkg:generic/openssl@3.0.1",
+                "licenses": [{"license": {"id": "Apache-2.0"}}],
+                "components": [{"name": "zlib", "version": "1.2.13",
+                                "licenses": [{"expression": "Zlib"}]}]
+            }]
+        });
+        let (format, comps) = parse_sbom(&cdx).unwrap();
+        assert_eq!(format, "CycloneDX 1.5");
+        assert_eq!(comps.len(), 2);
+        assert_eq!(comps[1].name, "zlib");
+        assert_eq!(comps[0].family(), LicenseFamily::Permissive);
+
+        let spdx = json!({
+            "spdxVersion": "SPDX-2.3",
+            "packages": [{
+                "name": "serde", "versionInfo": "1.0.100",
+                "licenseConcluded": "NOASSERTION", "licenseDeclared": "MIT OR Apache-2.0",
+                "externalRefs": [{"referenceType": "purl", "referenceLocator": "pkg:cargo/serde@1.0.100"}]
+            }]
+        });
+        let (format, comps) = parse_sbom(&spdx).unwrap();
+        assert_eq!(format, "SPDX-2.3");
+        assert_eq!(comps[0].licenses, ["MIT OR Apache-2.0"]);
+        assert_eq!(comps[0].purl.as_deref(), Some("pkg:cargo/serde@1.0.100"));
+        assert!(parse_sbom(&json!({"x": 1})).is_err());
+    }
+
+    #[test]
+    fn test_versions_and_advisories() {
+        assert_eq!(cmp_versions("1.10.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(cmp_versions("v2.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(cmp_versions("1.0.0-rc.1", "1.0.0"), Ordering::Less);
+        assert_eq!(
+            purl_package("pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"),
+            Some(("Maven", "org.apache.logging.log4j:log4j-core".to_string()))
+        );
+        assert_eq!(
+            purl_package("pkg:npm/%40babel/core@7.0.0"),
+            Some(("npm", "@babel/core".to_string()))
+        );
+
+        let osv = json!({"vulns": [{
+            "id": "RUSTSEC-2099-0001", "summary": "Bad thing",
+            "affected": [{
+                "package": {"ecosystem": "crates.io", "name": "serde"},
+                "ranges": [{"type": "SEMVER", "events": [
+                    {"introduced": "0"}, {"fixed": "1.0.50"},
+                    {"introduced": "1.1.0"}, {"fixed": "1.1.3"}
+                ]}]
+            }]
+        }]});
+        let advisories = parse_advisories(&osv).unwrap();
+        let comp = |v: &str, purl: &str| Component {
+            name: "serde".into(),
+            version: v.into(),
+            purl: Some(purl.into()),
+            licenses: vec![],
+        };
+        let hit = |c: &Component| advisories.iter().any(|a| a.matches(c));
+        assert!(hit(&comp("1.0.10", "pkg:cargo/serde@1.0.10")));
+        assert!(!hit(&comp("1.0.50", "pkg:cargo/serde@1.0.50")));
+        assert!(hit(&comp("1.1.2", "pkg:cargo/serde@1.1.2")));
+        assert!(!hit(&comp("1.1.3", "pkg:cargo/serde@1.1.3")));
+        // Same name in another ecosystem is a different package.
+        assert!(!hit(&comp("1.0.10", "pkg:npm/serde@1.0.10")));
+        assert_eq!(advisories[0].fixed(), "1.0.50, 1.1.3");
+    }
+}
@@ -0,0 +1,486 @@
+//! `ssh fingerprint`: fetch a server's host keys the way `ssh-keyscan` does and
+//! print their SHA256 fingerprints, optionally checked against `known_hosts`.
+//!
+//! Only the unencrypted start of the SSH transport is spoken: version exchange,
+//! KEXINIT, and a curve25519 `KEX_ECDH_INIT` with a throwaway public value. The
+//! server's `KEX_ECDH_REPLY` carries its host key, after which we hang up. One
+//! connection is made per host key algorithm so every key type is collected.
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use owo_colors::OwoColorize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::helpers::table;
+
+const MSG_DISCONNECT: u8 = 1;
+const MSG_KEXINIT: u8 = 20;
+const MSG_KEX_ECDH_INIT: u8 = 30;
+const MSG_KEX_ECDH_REPLY: u8 = 31;
+
+const KEX_ALGS: &str = "curve25519-sha256,curve25519-sha256@libssh.org";
+const CIPHERS: &str = "chacha20-poly1305@openssh.com,aes128-ctr,aes192-ctr,aes256-ctr,aes128-gcm@openssh.com,aes256-gcm@openssh.com";
+const MACS: &str = "hmac-sha2-256-etm@openssh.com,hmac-sha2-512-etm@openssh.com,hmac-sha2-256,hmac-sha2-512,hmac-sha1";
+
+/// Host key algorithms to try, one connection each. RSA is listed three ways but
+/// all name the same key, so only the first one the server offers is used.
+const HOST_KEY_ALGS: [&[&str]; 5] = [
+    &["ssh-ed25519"],
+    &["ecdsa-sha2-nistp256"],
+    &["ecdsa-sha2-nistp384"],
+    &["ecdsa-sha2-nistp521"],
+    &["rsa-sha2-512", "rsa-sha2-256", "ssh-rsa"],
+];
+
+/// Cursor over SSH wire encoding.
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+        let out = self
+            .buf
+            .get(self.pos..self.pos + n)
+            .context("Truncated SSH packet")?;
+        self.pos += n;
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn string(&mut self) -> Result<&'a [u8]> {
+        let n = self.u32()? as usize;
+        self.bytes(n)
+    }
+
+    fn name_list(&mut self) -> Result<Vec<String>> {
+        Ok(String::from_utf8_lossy(self.string()?)
+            .split(',')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+fn put_string(out: &mut Vec<u8>, s: &[u8]) {
+    out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    out.extend_from_slice(s);
+}
+
+/// Wrap a payload in an unencrypted binary packet (8-byte blocks, >= 4 padding bytes).
+fn frame(payload: &[u8]) -> Vec<u8> {
+    let mut pad = 8 - (5 + payload.len()) % 8;
+    if pad < 4 {
+        pad += 8;
+    }
+    let mut out = Vec::with_capacity(5 + payload.len() + pad);
+    out.extend_from_slice(&((1 + payload.len() + pad) as u32).to_be_bytes());
+    out.push(pad as u8);
+    out.extend_from_slice(payload);
+    out.resize(out.len() + pad, 0);
+    out
+}
+
+/// Not secret, just unpredictable enough for a cookie and a throwaway ECDH value.
+fn nonce(label: &str) -> [u8; 32] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let mut h = Sha256::new();
+    h.update(nanos.to_be_bytes());
+    h.update(std::process::id().to_be_bytes());
+    h.update(label.as_bytes());
+    h.finalize().into()
+}
+
+fn kexinit(host_key_alg: &str) -> Vec<u8> {
+    let mut p = vec![MSG_KEXINIT];
+    p.extend_from_slice(&nonce("cookie")[..16]);
+    for list in [
+        KEX_ALGS,
+        host_key_alg,
+        CIPHERS,
+        CIPHERS,
+        MACS,
+        MACS,
+        "none",
+        "none",
+        "",
+        "",
+    ] {
+        put_string(&mut p, list.as_bytes());
+    }
+    p.push(0); // first_kex_packet_follows
+    p.extend_from_slice(&[0; 4]);
+    p
+}
+
+/// The server's KEXINIT name-lists we care about: (kex, host key algorithms).
+fn parse_kexinit(payload: &[u8]) -> Result<(Vec<String>, Vec<String>)> {
+    let mut r = Reader::new(payload);
+    if r.bytes(1)?[0] != MSG_KEXINIT {
+        bail!("Expected KEXINIT from server");
+    }
+    r.bytes(16)?;
+    Ok((r.name_list()?, r.name_list()?))
+}
+
+/// `K_S` from a `KEX_ECDH_REPLY`.
+fn parse_ecdh_reply(payload: &[u8]) -> Result<Vec<u8>> {
+    let mut r = Reader::new(payload);
+    r.bytes(1)?;
+    Ok(r.string()?.to_vec())
+}
+
+/// Key type name embedded at the start of a public key blob.
+fn key_type(blob: &[u8]) -> String {
+    Reader::new(blob)
+        .string()
+        .map(|t| String::from_utf8_lossy(t).into_owned())
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// OpenSSH-style `SHA256:<base64 without padding>`.
+fn fingerprint(blob: &[u8]) -> String {
+    format!(
+        "SHA256:{}",
+        base64::engine::general_purpose::STANDARD_NO_PAD.encode(Sha256::digest(blob))
+    )
+}
+
+struct Conn {
+    stream: BufReader<TcpStream>,
+}
+
+impl Conn {
+    async fn open(addr: &str) -> Result<Self> {
+        let tcp = TcpStream::connect(addr)
+            .await
+            .with_context(|| format!("Cannot connect to {addr}"))?;
+        let mut stream = BufReader::new(tcp);
+        stream
+            .get_mut()
+            .write_all(concat!("SSH-2.0-swiftline_", env!("CARGO_PKG_VERSION"), "\r\n").as_bytes())
+            .await?;
+        // Servers may send banner lines before their identification string.
+        for _ in 0..32 {
+            let mut line = String::new();
+            if stream.read_line(&mut line).await? == 0 {
+                bail!("{addr} closed the connection before identifying itself");
+            }
+            if line.starts_with("SSH-") {
+                return Ok(Self { stream });
+            }
+        }
+        bail!("{addr} does not look like an SSH server")
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<()> {
+        self.stream.get_mut().write_all(&frame(payload)).await?;
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Result<Vec<u8>> {
+        let len = self.stream.read_u32().await? as usize;
+        if !(5..=256 * 1024).contains(&len) {
+            bail!("Implausible SSH packet length {len}");
+        }
+        let mut buf = vec![0u8; len];
+        self.stream.read_exact(&mut buf).await?;
+        let pad = buf[0] as usize;
+        let payload = buf
+            .get(1..len.saturating_sub(pad))
+            .context("Bad SSH padding")?
+            .to_vec();
+        if payload.first() == Some(&MSG_DISCONNECT) {
+            let mut r = Reader::new(&payload[1..]);
+            r.u32()?;
+            let reason = String::from_utf8_lossy(r.string().unwrap_or_default()).into_owned();
+            bail!("Server disconnected: {reason}");
+        }
+        Ok(payload)
+    }
+
+    /// Next packet of type `msg`, skipping IGNORE/DEBUG and the like.
+    async fn expect(&mut self, msg: u8) -> Result<Vec<u8>> {
+        for _ in 0..16 {
+            let p = self.recv().await?;
+            if p.first() == Some(&msg) {
+                return Ok(p);
+            }
+        }
+        bail!("Server never sent SSH message {msg}")
+    }
+}
+
+/// Host key algorithms and kex methods the server advertises.
+async fn server_algorithms(addr: &str) -> Result<(Vec<String>, Vec<String>)> {
+    let mut conn = Conn::open(addr).await?;
+    parse_kexinit(&conn.expect(MSG_KEXINIT).await?)
+}
+
+/// Run key exchange far enough to receive the host key for `alg`.
+async fn fetch_host_key(addr: &str, alg: &str) -> Result<Vec<u8>> {
+    let mut conn = Conn::open(addr).await?;
+    conn.expect(MSG_KEXINIT).await?;
+    conn.send(&kexinit(alg)).await?;
+    let mut init = vec![MSG_KEX_ECDH_INIT];
+    put_string(&mut init, &nonce(alg));
+    conn.send(&init).await?;
+    parse_ecdh_reply(&conn.expect(MSG_KEX_ECDH_REPLY).await?)
+}
+
+/// Names `ssh` would look up in known_hosts: `host` on port 22, `[host]:port` otherwise.
+fn known_host_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{host}]:{port}")
+    }
+}
+
+/// ssh_config-style wildcard match: `*` is any run, `?` one character, nothing else is special.
+fn wildcard(pat: &[u8], s: &[u8]) -> bool {
+    match (pat.first(), s.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => wildcard(&pat[1..], s) || (!s.is_empty() && wildcard(pat, &s[1..])),
+        (Some(b'?'), Some(_)) => wildcard(&pat[1..], &s[1..]),
+        (Some(p), Some(c)) if p == c => wildcard(&pat[1..], &s[1..]),
+        _ => false,
+    }
+}
+
+/// Match one known_hosts host field (hashed `|1|salt|hash` or a pattern list).
+fn host_matches(field: &str, name: &str) -> bool {
+    if let Some(rest) = field.strip_prefix("|1|") {
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let Some((salt, hash)) = rest.split_once('|') else {
+            return false;
+        };
+        let (Ok(salt), Ok(hash)) = (b64.decode(salt), b64.decode(hash)) else {
+            return false;
+        };
+        let Ok(mut mac) = Hmac::<Sha1>::new_from_slice(&salt) else {
+            return false;
+        };
+        mac.update(name.as_bytes());
+        return mac.verify_slice(&hash).is_ok();
+    }
+    let name = name.to_ascii_lowercase();
+    let mut matched = false;
+    for pat in field.split(',') {
+        let (negated, pat) = match pat.strip_prefix('!') {
+            Some(p) => (true, p),
+            None => (false, pat),
+        };
+        let hit = wildcard(pat.to_ascii_lowercase().as_bytes(), name.as_bytes());
+        if hit && negated {
+            return false;
+        }
+        matched |= hit;
+    }
+    matched
+}
+
+/// Key blobs recorded for any of `names` (revoked and CA lines are skipped).
+fn known_keys(text: &str, names: &[String]) -> Vec<Vec<u8>> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('@') {
+                return None;
+            }
+            let mut f = line.split_whitespace();
+            let hosts = f.next()?;
+            let _type = f.next()?;
+            let key = f.next()?;
+            names
+                .iter()
+                .any(|n| host_matches(hosts, n))
+                .then(|| base64::engine::general_purpose::STANDARD.decode(key).ok())
+                .flatten()
+        })
+        .collect()
+}
+
+fn expand_home(p: &Path) -> PathBuf {
+    match (p.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => p.to_path_buf(),
+    }
+}
+
+/// Print fingerprints of every host key `target` offers; with `compare`, check known_hosts
+/// and fail on a mismatch.
+pub async fn run(target: &str, compare: Option<PathBuf>, timeout: Duration) -> Result<()> {
+    let (host, port) = match target.rsplit_once(':') {
+        Some((h, p)) if !h.contains(':') || h.ends_with(']') => (
+            h.trim_start_matches('[').trim_end_matches(']'),
+            p.parse::<u16>()
+                .with_context(|| format!("Invalid port in {target}"))?,
+        ),
+        _ => (target.trim_start_matches('[').trim_end_matches(']'), 22),
+    };
+    let addr = if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    };
+
+    let (kex, offered) = tokio::time::timeout(timeout, server_algorithms(&addr))
+        .await
+        .with_context(|| format!("Timed out talking to {addr}"))??;
+    if !kex.iter().any(|k| KEX_ALGS.split(',').any(|a| a == k)) {
+        bail!(
+            "{addr} does not offer curve25519 key exchange ({})",
+            kex.join(",")
+        );
+    }
+
+    let mut keys: Vec<Vec<u8>> = Vec::new();
+    for family in HOST_KEY_ALGS {
+        let Some(alg) = family.iter().find(|a| offered.iter().any(|o| o == *a)) else {
+            continue;
+        };
+        match tokio::time::timeout(timeout, fetch_host_key(&addr, alg)).await {
+            Ok(Ok(blob)) => keys.push(blob),
+            Ok(Err(e)) => log::info!("{alg}: {e:#}"),
+            Err(_) => log::info!("{alg}: timed out"),
+        }
+    }
+    if keys.is_empty() {
+        bail!("Could not obtain any host key from {addr}");
+    }
+
+    let known = match &compare {
+        Some(path) => {
+            let path = expand_home(path);
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Cannot read {}", path.display()))?;
+            let mut names = vec![known_host_name(host, port)];
+            if let Ok(Some(ip)) = tokio::net::lookup_host(&addr).await.map(|mut a| a.next()) {
+                names.push(known_host_name(&ip.ip().to_string(), port));
+            }
+            Some(known_keys(&text, &names))
+        }
+        None => None,
+    };
+
+    let mut mismatch = false;
+    let rows: Vec<Vec<String>> = keys
+        .iter()
+        .map(|blob| {
+            let kt = key_type(blob);
+            let mut row = vec![kt.clone(), fingerprint(blob)];
+            if let Some(known) = &known {
+                let status = if known.contains(blob) {
+                    "match".green().to_string()
+                } else if known.iter().any(|k| key_type(k) == kt) {
+                    mismatch = true;
+                    "MISMATCH".red().bold().to_string()
+                } else {
+                    "not recorded".yellow().to_string()
+                };
+                row.push(status);
+            }
+            row
+        })
+        .collect();
+    if known.is_some() {
+        table::print(&["TYPE", "FINGERPRINT", "KNOWN_HOSTS"], &rows);
+    } else {
+        table::print(&["TYPE", "FINGERPRINT"], &rows);
+    }
+
+    if mismatch {
+        bail!("Host key for {addr} does not match known_hosts");
+    }
+    if known.as_ref().is_some_and(Vec::is_empty) {
+        eprintln!("{}", format!("No known_hosts entry for {addr}").yellow());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A syntactically valid ed25519 public key blob.
+    fn ed25519_blob() -> Vec<u8> {
+        let mut b = Vec::new();
+        put_string(&mut b, b"ssh-ed25519");
+        put_string(&mut b, &[7u8; 32]);
+        b
+    }
+
+    #[test]
+    fn test_frame_padding() {
+        for n in 0..40 {
+            let f = frame(&vec![1u8; n]);
+            assert_eq!(f.len() % 8, 0);
+            let pad = f[4] as usize;
+            assert!(pad >= 4);
+            assert_eq!(
+                u32::from_be_bytes([f[0], f[1], f[2], f[3]]) as usize,
+                f.len() - 4
+            );
+        }
+    }
+
+    #[test]
+    fn test_kexinit_round_trip() {
+        let (kex, hk) = parse_kexinit(&kexinit("ssh-ed25519")).unwrap();
+        assert_eq!(kex, ["curve25519-sha256", "curve25519-sha256@libssh.org"]);
+        assert_eq!(hk, ["ssh-ed25519"]);
+    }
+
+    #[test]
+    fn test_fingerprint_and_type() {
+        let blob = ed25519_blob();
+        assert_eq!(key_type(&blob), "ssh-ed25519");
+        let fp = fingerprint(&blob);
+        assert!(fp.starts_with("SHA256:") && !fp.ends_with('='));
+        assert_eq!(fp.len(), "SHA256:".len() + 43);
+    }
+
+    #[test]
+    fn test_known_hosts_matching() {
+        let key = base64::engine::general_purpose::STANDARD.encode(ed25519_blob());
+        // Hashed entry for "example.com" with salt "salt" (ssh-keygen -H layout).
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let mut mac = Hmac::<Sha1>::new_from_slice(b"salt").unwrap();
+        mac.update(b"example.com");
+        let hashed = format!(
+            "|1|{}|{}",
+            b64.encode(b"salt"),
+            b64.encode(mac.finalize().into_bytes())
+        );
+        let text = format!(
+            "# comment\n{hashed} ssh-ed25519 {key}\n*.corp,!bad.corp ssh-ed25519 {key}\n[git.local]:2222 ssh-ed25519 {key}\n@revoked * ssh-ed25519 {key}\n"
+        );
+        assert_eq!(known_keys(&text, &["example.com".into()]).len(), 1);
+        assert_eq!(known_keys(&text, &["db.corp".into()]).len(), 1);
+        assert!(known_keys(&text, &["bad.corp".into()]).is_empty());
+        assert_eq!(
+            known_keys(&text, &[known_host_name("git.local", 2222)]),
+            vec![ed25519_blob()]
+        );
+        assert!(known_keys(&text, &["git.local".into()]).is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+//! `text convert`: re-encode text between character sets (e.g. windows-1252 → UTF-8),
+//! with BOM handling and `--guess` detection for files of unknown origin.
+
+use anyhow::{bail, Context, Result};
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use crate::helpers::style;
+
+/// Look up an encoding by WHATWG label (`latin1`, `cp1252`, `shift_jis`, `utf-16le`, ...).
+fn lookup(label: &str) -> Result<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+        .with_context(|| format!("Unknown encoding: {label}"))
+}
+
+/// Detect the encoding of raw bytes: BOM first, then UTF-8 validity, then a statistical guess.
+fn detect(data: &[u8]) -> (&'static Encoding, &'static str) {
+    if let Some((enc, _)) = Encoding::for_bom(data) {
+        return (enc, "byte order mark");
+    }
+    if std::str::from_utf8(data).is_ok() {
+        return (UTF_8, "valid UTF-8");
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(data, true);
+    (detector.guess(None, true), "statistical guess")
+}
+
+/// Decode bytes with a known encoding, stripping a matching BOM.
+fn decode<'a>(data: &'a [u8], enc: &'static Encoding, lossy: bool) -> Result<Cow<'a, str>> {
+    let (text, had_errors) = enc.decode_with_bom_removal(data);
+    if had_errors && !lossy {
+        bail!(
+            "Input is not valid {} (use --lossy to replace invalid bytes)",
+            enc.name()
+        );
+    }
+    Ok(text)
+}
+
+/// Encode text; UTF-16 is handled here because encoding_rs only decodes it.
+fn encode(text: &str, enc: &'static Encoding, bom: bool, lossy: bool) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    if enc == UTF_16LE || enc == UTF_16BE {
+        let le = enc == UTF_16LE;
+        if bom {
+            out.extend_from_slice(if le { &[0xFF, 0xFE] } else { &[0xFE, 0xFF] });
+        }
+        for unit in text.encode_utf16() {
+            out.extend_from_slice(&if le {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            });
+        }
+        return Ok(out);
+    }
+    if bom {
+        if enc != UTF_8 {
+            bail!("--bom is only meaningful for UTF-8 and UTF-16 output");
+        }
+        out.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+    }
+    let (bytes, _, unmappable) = enc.encode(text);
+    if unmappable && !lossy {
+        bail!(
+            "Text contains characters that {} cannot represent (use --lossy to substitute them)",
+            enc.name()
+        );
+    }
+    out.extend_from_slice(&bytes);
+    Ok(out)
+}
+
+/// Options for a conversion run.
+#[derive(Debug)]
+pub struct Options {
+    pub from: Option<String>,
+    pub to: String,
+    pub guess: bool,
+    pub bom: bool,
+    pub lossy: bool,
+    pub output: Option<PathBuf>,
+    pub in_place: bool,
+}
+
+/// Convert a file (or stdin) between encodings, or just report a guess.
+pub fn run(file: Option<PathBuf>, opts: Options) -> Result<()> {
+    let data = match &file {
+        Some(path) => {
+            fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?
+        }
+        None => {
+            let mut buf = Vec::new();
+            io::stdin().read_to_end(&mut buf)?;
+            buf
+        }
+    };
+
+    if opts.guess {
+        let (enc, how) = detect(&data);
+        println!("{} ({how})", enc.name());
+        return Ok(());
+    }
+
+    let source = match &opts.from {
+        Some(label) => lookup(label)?,
+        None => {
+            let (enc, how) = detect(&data);
+            log::info!("Detected input encoding {} ({how})", enc.name());
+            enc
+        }
+    };
+    let target = lookup(&opts.to)?;
+
+    let text = decode(&data, source, opts.lossy)?;
+    let bytes = encode(&text, target, opts.bom, opts.lossy)?;
+
+    let dest = match (&opts.output, opts.in_place, &file) {
+        (Some(path), _, _) => Some(path.clone()),
+        (None, true, Some(path)) => Some(path.clone()),
+        (None, true, None) => bail!("-i/--in-place needs a file argument"),
+        (None, false, _) => None,
+    };
+    match dest {
+        Some(path) => {
+            fs::write(&path, &bytes)
+                .with_context(|| format!("Failed to write file: {}", path.display()))?;
+            style::ok(&format!(
+                "Converted {} → {}: {}",
+                source.name(),
+                target.name(),
+                path.display()
+            ));
+        }
+        None => io::stdout().write_all(&bytes)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_1252_to_utf8() {
+        let enc = lookup("windows-1252").unwrap();
+        let text = decode(b"caf\xe9 \x80", enc, false).unwrap();
+        assert_eq!(text, "café €");
+        assert_eq!(
+            encode(&text, UTF_8, false, false).unwrap(),
+            "café €".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_utf16_roundtrip_with_bom() {
+        let bytes = encode("hi", UTF_16LE, true, false).unwrap();
+        assert_eq!(bytes, vec![0xFF, 0xFE, b'h', 0, b'i', 0]);
+        let (enc, how) = detect(&bytes);
+        assert_eq!(enc, UTF_16LE);
+        assert_eq!(how, "byte order mark");
+        assert_eq!(decode(&bytes, enc, false).unwrap(), "hi");
+    }
+
+    #[test]
+    fn test_unmappable_requires_lossy() {
+        let latin1 = lookup("iso-8859-1").unwrap();
+        assert!(encode("snow ☃", latin1, false, false).is_err());
+        assert!(encode("snow ☃", latin1, false, true).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_utf8_requires_lossy() {
+        assert!(decode(b"\xff\xfe\xfd", UTF_8, false).is_err());
+        assert!(decode(b"ok \xff", UTF_8, true).is_ok());
+    }
+}
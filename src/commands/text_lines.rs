@@ -0,0 +1,270 @@
+//! `text sort` / `text uniq` / `text freq`: line-oriented tools with byte-order
+//! (locale-independent) comparisons, so results are identical on every platform.
+
+use anyhow::{Context, Result};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read, Write};
+
+use crate::helpers::files;
+
+/// Read all lines from the given files (globs allowed) or stdin. CRLF is stripped.
+pub(crate) fn read_lines(inputs: &[String]) -> Result<Vec<String>> {
+    let mut text = String::new();
+    if inputs.is_empty() {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        text.push_str(&String::from_utf8_lossy(&buf));
+    } else {
+        for path in files::expand_globs(inputs)? {
+            let data =
+                fs::read(&path).with_context(|| format!("Failed to read: {}", path.display()))?;
+            text.push_str(&String::from_utf8_lossy(&data));
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+        }
+    }
+    Ok(split_lines(&text))
+}
+
+/// Split text into lines the way coreutils `sort` does: empty lines are kept,
+/// except the implicit one after a final newline. CRLF is stripped.
+fn split_lines(text: &str) -> Vec<String> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let body = text.strip_suffix('\n').unwrap_or(text);
+    body.split('\n')
+        .map(|l| l.strip_suffix('\r').unwrap_or(l).to_string())
+        .collect()
+}
+
+pub(crate) fn write_lines<I: IntoIterator<Item = String>>(lines: I) -> Result<()> {
+    let stdout = io::stdout();
+    let mut out = io::BufWriter::new(stdout.lock());
+    for line in lines {
+        // A closed pipe (e.g. `| head`) is not an error worth reporting.
+        if writeln!(out, "{line}").is_err() {
+            return Ok(());
+        }
+    }
+    out.flush().ok();
+    Ok(())
+}
+
+/// Leading number of a line (after whitespace), e.g. "  42 apples" → 42.
+fn numeric_key(line: &str) -> Option<f64> {
+    let s = line.trim_start();
+    let end = s
+        .char_indices()
+        .take_while(|(i, c)| {
+            c.is_ascii_digit() || *c == '.' || (*i == 0 && (*c == '-' || *c == '+'))
+        })
+        .map(|(i, c)| i + c.len_utf8())
+        .last()?;
+    s[..end].parse().ok()
+}
+
+/// Sort options for `text sort`.
+#[derive(Debug, Clone, Copy)]
+pub struct SortOptions {
+    pub numeric: bool,
+    pub reverse: bool,
+    pub unique: bool,
+    pub ignore_case: bool,
+}
+
+fn compare(a: &str, b: &str, opts: SortOptions) -> Ordering {
+    let ord = if opts.numeric {
+        // Lines without a number sort first, then by byte order among themselves.
+        match (numeric_key(a), numeric_key(b)) {
+            (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    } else {
+        Ordering::Equal
+    };
+    ord.then_with(|| {
+        if opts.ignore_case {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        } else {
+            a.cmp(b)
+        }
+    })
+}
+
+fn sort_lines(mut lines: Vec<String>, opts: SortOptions) -> Vec<String> {
+    // `sort_by` is stable, so equal keys keep input order.
+    lines.sort_by(|a, b| {
+        let ord = compare(a, b, opts);
+        if opts.reverse {
+            ord.reverse()
+        } else {
+            ord
+        }
+    });
+    if opts.unique {
+        lines.dedup_by(|a, b| compare(a, b, opts) == Ordering::Equal);
+    }
+    lines
+}
+
+/// Collapse runs of equal lines (or all repeats with `global`), with optional counts.
+fn uniq_lines(lines: Vec<String>, ignore_case: bool, global: bool) -> Vec<(usize, String)> {
+    let key = |s: &str| {
+        if ignore_case {
+            s.to_lowercase()
+        } else {
+            s.to_string()
+        }
+    };
+    let mut out: Vec<(usize, String)> = Vec::new();
+    if global {
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for line in lines {
+            match index.get(&key(&line)) {
+                Some(&i) => out[i].0 += 1,
+                None => {
+                    index.insert(key(&line), out.len());
+                    out.push((1, line));
+                }
+            }
+        }
+    } else {
+        for line in lines {
+            match out.last_mut() {
+                Some((n, last)) if key(last) == key(&line) => *n += 1,
+                _ => out.push((1, line)),
+            }
+        }
+    }
+    out
+}
+
+/// Count distinct lines; most frequent first, ties broken by byte order.
+fn frequencies(lines: Vec<String>, ignore_case: bool) -> Vec<(usize, String)> {
+    let mut counts = uniq_lines(lines, ignore_case, true);
+    counts.sort_by(|(na, a), (nb, b)| nb.cmp(na).then_with(|| a.cmp(b)));
+    counts
+}
+
+fn with_counts(rows: Vec<(usize, String)>) -> Vec<String> {
+    let width = rows
+        .iter()
+        .map(|(n, _)| n.to_string().len())
+        .max()
+        .unwrap_or(1);
+    rows.into_iter()
+        .map(|(n, line)| format!("{n:>width$} {line}"))
+        .collect()
+}
+
+/// `text sort`
+pub fn run_sort(inputs: Vec<String>, opts: SortOptions) -> Result<()> {
+    write_lines(sort_lines(read_lines(&inputs)?, opts))
+}
+
+/// `text uniq`
+pub fn run_uniq(inputs: Vec<String>, count: bool, ignore_case: bool, global: bool) -> Result<()> {
+    let rows = uniq_lines(read_lines(&inputs)?, ignore_case, global);
+    if count {
+        write_lines(with_counts(rows))
+    } else {
+        write_lines(rows.into_iter().map(|(_, l)| l))
+    }
+}
+
+/// `text freq`
+pub fn run_freq(inputs: Vec<String>, top: Option<usize>, ignore_case: bool) -> Result<()> {
+    let mut rows = frequencies(read_lines(&inputs)?, ignore_case);
+    if let Some(n) = top {
+        rows.truncate(n);
+    }
+    write_lines(with_counts(rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    const PLAIN: SortOptions = SortOptions {
+        numeric: false,
+        reverse: false,
+        unique: false,
+        ignore_case: false,
+    };
+
+    #[test]
+    fn test_split_lines_keeps_empty_lines() {
+        let lines = split_lines("b\n\na\n");
+        assert_eq!(lines, v(&["b", "", "a"]));
+        assert_eq!(sort_lines(lines, PLAIN), v(&["", "a", "b"]));
+        assert_eq!(split_lines("x\r\n\r\ny"), v(&["x", "", "y"]));
+        assert_eq!(split_lines("\n"), v(&[""]));
+        assert!(split_lines("").is_empty());
+    }
+
+    #[test]
+    fn test_sort_bytewise_and_numeric() {
+        assert_eq!(sort_lines(v(&["b", "B", "a"]), PLAIN), v(&["B", "a", "b"]));
+        let numeric = SortOptions {
+            numeric: true,
+            ..PLAIN
+        };
+        assert_eq!(
+            sort_lines(v(&["10 x", "9 y", "x", "-1"]), numeric),
+            v(&["x", "-1", "9 y", "10 x"])
+        );
+    }
+
+    #[test]
+    fn test_sort_reverse_unique() {
+        let opts = SortOptions {
+            reverse: true,
+            unique: true,
+            ..PLAIN
+        };
+        assert_eq!(
+            sort_lines(v(&["a", "c", "a", "b"]), opts),
+            v(&["c", "b", "a"])
+        );
+    }
+
+    #[test]
+    fn test_uniq_adjacent_vs_global() {
+        let lines = v(&["a", "a", "b", "a"]);
+        assert_eq!(
+            uniq_lines(lines.clone(), false, false),
+            vec![(2, "a".into()), (1, "b".into()), (1, "a".into())]
+        );
+        assert_eq!(
+            uniq_lines(lines, false, true),
+            vec![(3, "a".into()), (1, "b".into())]
+        );
+    }
+
+    #[test]
+    fn test_freq_ties_are_stable() {
+        let rows = frequencies(v(&["b", "a", "c", "a", "b"]), false);
+        assert_eq!(
+            rows,
+            vec![(2, "a".into()), (2, "b".into()), (1, "c".into())]
+        );
+        assert_eq!(with_counts(rows)[2], "1 c");
+    }
+
+    #[test]
+    fn test_numeric_key() {
+        assert_eq!(numeric_key("  42 apples"), Some(42.0));
+        assert_eq!(numeric_key("-3.5"), Some(-3.5));
+        assert_eq!(numeric_key("abc"), None);
+    }
+}
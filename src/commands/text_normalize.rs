@@ -0,0 +1,159 @@
+//! `text normalize`: line ending and whitespace cleanup across files.
+//! Works on raw bytes so any ASCII-compatible encoding is left intact.
+
+use anyhow::{bail, Context, Result};
+use owo_colors::OwoColorize;
+use std::fs;
+use std::io::{self, Read, Write};
+
+use crate::cli::Eol;
+use crate::helpers::{files, style};
+
+/// Normalization options shared by every input.
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub eol: Option<Eol>,
+    pub trim_trailing: bool,
+    pub final_newline: bool,
+}
+
+/// Normalize a byte buffer. Lines keep their original ending unless `eol` is set.
+fn normalize(input: &[u8], opts: Options) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len());
+    let mut last_ending: &[u8] = b"\n";
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (mut line, ending, next): (&[u8], &[u8], &[u8]) = match rest
+            .iter()
+            .position(|&b| b == b'\n')
+        {
+            Some(i) if i > 0 && rest[i - 1] == b'\r' => (&rest[..i - 1], b"\r\n", &rest[i + 1..]),
+            Some(i) => (&rest[..i], b"\n", &rest[i + 1..]),
+            None => (rest, b"", &[]),
+        };
+        if opts.trim_trailing {
+            while let Some((last, head)) = line.split_last() {
+                if *last == b' ' || *last == b'\t' {
+                    line = head;
+                } else {
+                    break;
+                }
+            }
+        }
+        out.extend_from_slice(line);
+        if !ending.is_empty() {
+            let ending = match opts.eol {
+                Some(Eol::Lf) => b"\n".as_slice(),
+                Some(Eol::Crlf) => b"\r\n".as_slice(),
+                None => ending,
+            };
+            out.extend_from_slice(ending);
+            last_ending = ending;
+        }
+        rest = next;
+    }
+
+    if opts.final_newline && !out.is_empty() && !out.ends_with(b"\n") {
+        let ending = match opts.eol {
+            Some(Eol::Lf) => b"\n".as_slice(),
+            Some(Eol::Crlf) => b"\r\n".as_slice(),
+            None => last_ending,
+        };
+        out.extend_from_slice(ending);
+    }
+    out
+}
+
+/// Normalize stdin to stdout, or each file (in place with -i, otherwise as a check).
+pub fn run(files_args: Vec<String>, opts: Options, in_place: bool) -> Result<()> {
+    if opts.eol.is_none() && !opts.trim_trailing && !opts.final_newline {
+        bail!("Nothing to do: pass --eol, --trim-trailing, and/or --final-newline");
+    }
+
+    if files_args.is_empty() {
+        if in_place {
+            bail!("-i/--in-place needs file arguments");
+        }
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        io::stdout().write_all(&normalize(&buf, opts))?;
+        return Ok(());
+    }
+
+    let paths = files::expand_globs(&files_args)?;
+    let mut changed = 0;
+    for path in &paths {
+        let data = fs::read(path).with_context(|| format!("Failed to read: {}", path.display()))?;
+        if data.contains(&0) {
+            println!("{} {} (binary)", "skipped".dimmed(), path.display());
+            continue;
+        }
+        let normalized = normalize(&data, opts);
+        if normalized == data {
+            continue;
+        }
+        changed += 1;
+        if in_place {
+            fs::write(path, &normalized)
+                .with_context(|| format!("Failed to write: {}", path.display()))?;
+            println!("{} {}", "fixed".green(), path.display());
+        } else {
+            println!("{} {}", "would fix".yellow(), path.display());
+        }
+    }
+
+    if changed == 0 {
+        style::ok(&format!("{} file(s) already normalized", paths.len()));
+    } else if in_place {
+        style::ok(&format!("Normalized {changed} of {} file(s)", paths.len()));
+    } else {
+        bail!(
+            "{changed} of {} file(s) need normalization (use -i to fix)",
+            paths.len()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opts(eol: Option<Eol>, trim: bool, final_nl: bool) -> Options {
+        Options {
+            eol,
+            trim_trailing: trim,
+            final_newline: final_nl,
+        }
+    }
+
+    #[test]
+    fn test_eol_conversion() {
+        assert_eq!(
+            normalize(b"a\r\nb\nc", opts(Some(Eol::Lf), false, false)),
+            b"a\nb\nc"
+        );
+        assert_eq!(
+            normalize(b"a\nb\r\n", opts(Some(Eol::Crlf), false, false)),
+            b"a\r\nb\r\n"
+        );
+    }
+
+    #[test]
+    fn test_trim_trailing_keeps_endings() {
+        assert_eq!(
+            normalize(b"a  \r\nb\t\n", opts(None, true, false)),
+            b"a\r\nb\n"
+        );
+    }
+
+    #[test]
+    fn test_final_newline_uses_existing_style() {
+        assert_eq!(normalize(b"a\r\nb", opts(None, false, true)), b"a\r\nb\r\n");
+        assert_eq!(
+            normalize(b"a", opts(Some(Eol::Crlf), false, true)),
+            b"a\r\n"
+        );
+        assert_eq!(normalize(b"", opts(None, false, true)), b"");
+    }
+}
@@ -0,0 +1,89 @@
+//! `text strip-ansi` / `text truncate`: clean colored output for logs and files,
+//! and clip lines to a terminal width counted in display columns (wide CJK and
+//! emoji take two, escape sequences take none).
+
+use anyhow::Result;
+use unicode_width::UnicodeWidthChar;
+
+use crate::commands::text_lines::{read_lines, write_lines};
+use crate::helpers::table::{ansi_len, display_width, strip_ansi};
+
+const ELLIPSIS: char = '…';
+
+/// Clip `line` to at most `width` columns, keeping escape sequences intact and
+/// resetting attributes if the cut happens inside colored text.
+fn truncate(line: &str, width: usize, ellipsis: bool) -> String {
+    if display_width(line) <= width {
+        return line.to_string();
+    }
+    let budget = if ellipsis {
+        width.saturating_sub(1)
+    } else {
+        width
+    };
+    let mut out = String::with_capacity(line.len());
+    let mut used = 0;
+    let mut styled = false;
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        if let Some(n) = ansi_len(rest) {
+            out.push_str(&rest[..n]);
+            styled = true;
+            rest = &rest[n..];
+            continue;
+        }
+        let w = c.width().unwrap_or(0);
+        if used + w > budget {
+            break;
+        }
+        out.push(c);
+        used += w;
+        rest = &rest[c.len_utf8()..];
+    }
+    if ellipsis && width > 0 {
+        out.push(ELLIPSIS);
+    }
+    if styled {
+        out.push_str("\u{1b}[0m");
+    }
+    out
+}
+
+/// `text strip-ansi`
+pub fn run_strip_ansi(inputs: Vec<String>) -> Result<()> {
+    write_lines(read_lines(&inputs)?.iter().map(|l| strip_ansi(l)))
+}
+
+/// `text truncate`
+pub fn run_truncate(inputs: Vec<String>, width: usize, ellipsis: bool) -> Result<()> {
+    write_lines(
+        read_lines(&inputs)?
+            .iter()
+            .map(|l| truncate(l, width, ellipsis)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_counts_columns() {
+        assert_eq!(truncate("hello world", 5, false), "hello");
+        assert_eq!(truncate("hello world", 6, true), "hello…");
+        assert_eq!(truncate("short", 10, true), "short");
+        // Wide characters are two columns and never split.
+        assert_eq!(truncate("日本語テキスト", 5, false), "日本");
+        assert_eq!(truncate("日本語テキスト", 5, true), "日本…");
+        assert_eq!(truncate("abc", 0, true), "");
+    }
+
+    #[test]
+    fn test_truncate_keeps_escapes() {
+        let red = "\u{1b}[31merror: disk full\u{1b}[0m";
+        let cut = truncate(red, 5, false);
+        assert_eq!(cut, "\u{1b}[31merror\u{1b}[0m");
+        assert_eq!(display_width(&cut), 5);
+        assert_eq!(truncate(red, 20, true), red);
+    }
+}
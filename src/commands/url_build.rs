@@ -0,0 +1,128 @@
+//! `url join` and `url template`: build URLs from parts with each piece
+//! percent-encoded for where it lands, instead of by string concatenation.
+
+use anyhow::{bail, Context, Result};
+use std::collections::BTreeMap;
+use url::Url;
+
+use crate::helpers::sigv4::uri_encode;
+
+/// Split repeated `name=value` arguments of `flag`.
+fn parse_pairs<'a>(items: &'a [String], flag: &str) -> Result<Vec<(&'a str, &'a str)>> {
+    items
+        .iter()
+        .map(|item| {
+            item.split_once('=')
+                .with_context(|| format!("{flag} must be name=value, got: {item}"))
+        })
+        .collect()
+}
+
+/// `base` with each of `segments` appended as one escaped path segment (a `/`
+/// inside a segment becomes `%2F`), then `query` pairs added.
+fn join(base: &str, segments: &[String], query: &[(&str, &str)]) -> Result<Url> {
+    let mut url = Url::parse(base).with_context(|| format!("Invalid base URL: {base}"))?;
+    if url.cannot_be_a_base() {
+        bail!("Cannot append path segments to {base}");
+    }
+    let mut path = url.path().trim_end_matches('/').to_string();
+    for segment in segments {
+        path.push('/');
+        path.push_str(&uri_encode(segment, false));
+    }
+    url.set_path(&path);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    Ok(url)
+}
+
+/// Fill `{name}` placeholders with escaped values (`{+name}` keeps `/`, for
+/// multi-segment paths). Every placeholder needs a value and every value a placeholder.
+fn fill(template: &str, values: &BTreeMap<&str, &str>) -> Result<Url> {
+    let mut out = String::with_capacity(template.len());
+    let mut used = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .with_context(|| format!("Unclosed {{ in template: {template}"))?;
+        let inner = &rest[start + 1..start + end];
+        let (name, keep_slash) = match inner.strip_prefix('+') {
+            Some(name) => (name, true),
+            None => (inner, false),
+        };
+        let value = values
+            .get(name)
+            .with_context(|| format!("No value for {{{inner}}}; pass --set {name}=..."))?;
+        out.push_str(&uri_encode(value, keep_slash));
+        used.push(name);
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+
+    if let Some(unused) = values.keys().find(|k| !used.contains(k)) {
+        bail!("--set {unused} does not match any placeholder in the template");
+    }
+    Url::parse(&out).with_context(|| format!("Template did not produce a valid URL: {out}"))
+}
+
+/// Print `base` joined with path segments and query pairs.
+pub fn run_join(base: &str, segments: &[String], query: &[String]) -> Result<()> {
+    println!("{}", join(base, segments, &parse_pairs(query, "--query")?)?);
+    Ok(())
+}
+
+/// Print `template` with its placeholders filled from `--set name=value`.
+pub fn run_template(template: &str, set: &[String]) -> Result<()> {
+    let values: BTreeMap<&str, &str> = parse_pairs(set, "--set")?.into_iter().collect();
+    println!("{}", fill(template, &values)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        let segments = |s: &[&str]| s.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            join(
+                "https://api.example.com/v1/",
+                &segments(&["users", "42"]),
+                &[]
+            )
+            .unwrap()
+            .as_str(),
+            "https://api.example.com/v1/users/42"
+        );
+        assert_eq!(
+            join(
+                "https://h/files?v=1",
+                &segments(&["a b/c", "ü"]),
+                &[("q", "x&y z")]
+            )
+            .unwrap()
+            .as_str(),
+            "https://h/files/a%20b%2Fc/%C3%BC?v=1&q=x%26y+z"
+        );
+        assert!(join("mailto:a@b.c", &segments(&["x"]), &[]).is_err());
+    }
+
+    #[test]
+    fn test_fill() {
+        let values: BTreeMap<&str, &str> =
+            [("org", "my org"), ("repo", "a/b"), ("path", "src/main.rs")].into();
+        assert_eq!(
+            fill("https://h/{org}/{repo}/blob/{+path}", &values)
+                .unwrap()
+                .as_str(),
+            "https://h/my%20org/a%2Fb/blob/src/main.rs"
+        );
+        assert!(fill("https://h/{org}/{missing}", &values).is_err());
+        assert!(fill("https://h/{org}", &values).is_err());
+        assert!(fill("https://h/{org", &values).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! Detached signatures on release artifacts: OpenPGP (`.asc` armored or `.sig`
+//! binary, as made by `gpg --detach-sign`) and minisign (`.minisig`). age only
+//! encrypts; projects that ship age keys sign their releases with minisign.
+//! Used by `verify file` and by `http get --save --verify-sig`.
+
+use anyhow::{bail, Context, Result};
+use log::debug;
+use minisign_verify as minisign;
+use owo_colors::OwoColorize;
+use pgp::composed::{Deserializable, DetachedSignature, SignedPublicKey};
+use pgp::types::KeyDetails;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use crate::commands::cert_inspect::utc;
+use crate::helpers::style;
+
+enum Signature {
+    OpenPgp(Box<DetachedSignature>),
+    Minisign(minisign::Signature),
+}
+
+enum PublicKey {
+    OpenPgp(Box<SignedPublicKey>),
+    Minisign(minisign::PublicKey),
+}
+
+/// A parsed signature and the public key it must verify against.
+pub struct Verifier {
+    sig: Signature,
+    key: PublicKey,
+}
+
+fn parse_signature(data: &[u8]) -> Result<Signature> {
+    let text = std::str::from_utf8(data).unwrap_or_default();
+    if text.trim_start().starts_with("untrusted comment:") {
+        let sig = minisign::Signature::decode(text)
+            .map_err(|e| anyhow::anyhow!("Invalid minisign signature: {e}"))?;
+        return Ok(Signature::Minisign(sig));
+    }
+    let (sig, _) = DetachedSignature::from_reader_single(Cursor::new(data))
+        .map_err(|e| anyhow::anyhow!("Not an OpenPGP or minisign signature: {e}"))?;
+    Ok(Signature::OpenPgp(Box::new(sig)))
+}
+
+fn parse_key(data: &[u8]) -> Result<PublicKey> {
+    let text = std::str::from_utf8(data).unwrap_or_default().trim();
+    if text.starts_with("untrusted comment:") {
+        let key = minisign::PublicKey::decode(text)
+            .map_err(|e| anyhow::anyhow!("Invalid minisign public key: {e}"))?;
+        return Ok(PublicKey::Minisign(key));
+    }
+    // A bare key as printed by `minisign -G` and published in READMEs.
+    if text.starts_with("RW") && !text.contains(char::is_whitespace) {
+        let key = minisign::PublicKey::from_base64(text)
+            .map_err(|e| anyhow::anyhow!("Invalid minisign public key: {e}"))?;
+        return Ok(PublicKey::Minisign(key));
+    }
+    let (key, _) = SignedPublicKey::from_reader_single(Cursor::new(data))
+        .map_err(|e| anyhow::anyhow!("Not an OpenPGP or minisign public key: {e}"))?;
+    key.verify_bindings()
+        .map_err(|e| anyhow::anyhow!("OpenPGP key has invalid self-signatures: {e}"))?;
+    Ok(PublicKey::OpenPgp(Box::new(key)))
+}
+
+fn open(path: &Path) -> Result<BufReader<File>> {
+    let file =
+        File::open(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+    Ok(BufReader::new(file))
+}
+
+/// Check a detached OpenPGP signature with whichever primary or subkey issued it.
+fn verify_openpgp(sig: &DetachedSignature, key: &SignedPublicKey, path: &Path) -> Result<String> {
+    let sig = &sig.signature;
+    let fingerprints = sig.issuer_fingerprint();
+    let key_ids = sig.issuer_key_id();
+    let issued = |k: &dyn KeyDetails| {
+        fingerprints.iter().any(|f| **f == k.fingerprint())
+            || key_ids.iter().any(|id| **id == k.legacy_key_id())
+    };
+    let result = if issued(&key.primary_key) {
+        sig.verify(&key.primary_key, open(path)?)
+    } else if let Some(sub) = key.public_subkeys.iter().find(|s| issued(*s)) {
+        sig.verify(sub, open(path)?)
+    } else {
+        let by = fingerprints
+            .first()
+            .map(|f| format!("{f:X}"))
+            .or_else(|| key_ids.first().map(|id| format!("{id:?}")))
+            .unwrap_or_else(|| "an unknown key".into());
+        bail!(
+            "Signature was made by {by}, not by key {:X}",
+            key.primary_key.fingerprint()
+        );
+    };
+    if let Err(e) = result {
+        debug!("OpenPGP verification: {e}");
+        bail!(
+            "BAD OpenPGP signature: {} does not match the signature",
+            path.display()
+        );
+    }
+
+    let signer = key
+        .details
+        .users
+        .first()
+        .and_then(|u| u.id.as_str())
+        .unwrap_or("unnamed key");
+    let mut msg = format!(
+        "Good OpenPGP signature from {signer} (key {:X})",
+        key.primary_key.fingerprint()
+    );
+    if let Some(created) = sig.created() {
+        msg.push_str(&format!(", signed {}", utc(i64::from(created.as_secs()))));
+    }
+    Ok(msg)
+}
+
+/// Check a minisign signature, streaming prehashed (default since minisign 0.8) files.
+fn verify_minisign(
+    sig: &minisign::Signature,
+    key: &minisign::PublicKey,
+    path: &Path,
+) -> Result<()> {
+    let bad = |e| anyhow::anyhow!("BAD minisign signature for {}: {e}", path.display());
+    match key.verify_stream(sig) {
+        Ok(mut stream) => {
+            let mut file = open(path)?;
+            let mut buf = vec![0u8; 64 * 1024];
+            loop {
+                match file.read(&mut buf)? {
+                    0 => break,
+                    n => stream.update(&buf[..n]),
+                }
+            }
+            stream.finalize().map_err(bad)
+        }
+        Err(minisign::Error::UnsupportedLegacyMode) => {
+            let data = std::fs::read(path)
+                .with_context(|| format!("Failed to read file: {}", path.display()))?;
+            key.verify(&data, sig, true).map_err(bad)
+        }
+        Err(minisign::Error::UnexpectedKeyId) => {
+            bail!("Signature was made by a different minisign key")
+        }
+        Err(e) => Err(bad(e)),
+    }
+}
+
+impl Verifier {
+    /// Parse signature and key bytes; the formats must match.
+    pub fn new(sig: &[u8], key: &[u8]) -> Result<Self> {
+        let (sig, key) = (parse_signature(sig)?, parse_key(key)?);
+        match (&sig, &key) {
+            (Signature::OpenPgp(_), PublicKey::Minisign(_)) => {
+                bail!("OpenPGP signature needs an OpenPGP public key, not a minisign key")
+            }
+            (Signature::Minisign(_), PublicKey::OpenPgp(_)) => {
+                bail!("minisign signature needs a minisign public key, not an OpenPGP key")
+            }
+            _ => Ok(Verifier { sig, key }),
+        }
+    }
+
+    /// Read the signature and key from files.
+    pub fn from_files(sig: &Path, key: &Path) -> Result<Self> {
+        let sig_bytes = std::fs::read(sig)
+            .with_context(|| format!("Failed to read signature: {}", sig.display()))?;
+        let key_bytes = std::fs::read(key)
+            .with_context(|| format!("Failed to read public key: {}", key.display()))?;
+        Self::new(&sig_bytes, &key_bytes)
+    }
+
+    /// Verify `path`, returning a one-line description of the good signature.
+    pub fn verify_file(&self, path: &Path) -> Result<String> {
+        match (&self.sig, &self.key) {
+            (Signature::OpenPgp(sig), PublicKey::OpenPgp(key)) => verify_openpgp(sig, key, path),
+            (Signature::Minisign(sig), PublicKey::Minisign(key)) => {
+                verify_minisign(sig, key, path)?;
+                Ok(format!(
+                    "Good minisign signature ({})",
+                    sig.trusted_comment().replace('\t', " ")
+                ))
+            }
+            _ => unreachable!("formats checked in Verifier::new"),
+        }
+    }
+}
+
+/// FILE.asc, FILE.sig, or FILE.minisig, whichever exists first.
+fn default_signature(file: &Path) -> Result<PathBuf> {
+    for ext in ["asc", "sig", "minisig"] {
+        let mut name = file.as_os_str().to_owned();
+        name.push(format!(".{ext}"));
+        let candidate = PathBuf::from(name);
+        if candidate.exists() {
+            return Ok(candidate);
+        }
+    }
+    bail!(
+        "No signature found next to {} (tried .asc, .sig, .minisig); pass --sig",
+        file.display()
+    )
+}
+
+/// Verify `file` against its detached signature; a bad signature is an error.
+pub fn run(file: &Path, sig: Option<&Path>, key: &Path) -> Result<()> {
+    let sig = match sig {
+        Some(p) => p.to_path_buf(),
+        None => default_signature(file)?,
+    };
+    let verifier = Verifier::from_files(&sig, key)?;
+    let msg = verifier.verify_file(file)?;
+    style::ok(&msg);
+    println!("{} {}", "Signature:".bold(), sig.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PGP_KEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----
+
+mDMEatF8QBYJKwYBBAHaRw8BAQdAqJ5+Rg7aWGEs7MbI8eqvm5/jw3J5rfr1Vngz
+aHsS3dW0JVN3aWZ0bGluZSBUZXN0IDxyZWxlYXNlQGV4YW1wbGUudGVzdD6IkAQT
+FggAOBYhBBeeMpsCW1x0vu6d6cYt6p8D9pO7BQJq0XxAAhsBBQsJCAcCBhUKCQgL
+AgQWAgMBAh4BAheAAAoJEMYt6p8D9pO7fFkA/jnrJNnYNwvUFv8F5uNj31yo0V4Z
+5aRvbmvv9bQolYTUAP97hG5thv7wkCoOSCljFJs9ztsMJWDee7b/GgDMonFuB7gz
+BGrRfEAWCSsGAQQB2kcPAQEHQC7luZbHnvI8yARSJhG9hKm2uyqpAPsinTXElduq
+U5OpiO8EGBYIACAWIQQXnjKbAltcdL7unenGLeqfA/aTuwUCatF8QAIbAgCBCRDG
+LeqfA/aTu3YgBBkWCAAdFiEE/cAfEwFiXSxEXnqSXgduTyVU4jQFAmrRfEAACgkQ
+XgduTyVU4jSZ6gD8DzbZ6NANYlQLWE3qAF7CYbfBNJMmCaoyqvXD1i408dcBAMPI
+dnykzBPtTAlpg+0tJtxCjR0234wjVmoBrOKKhs4G2yoA/3SV8i+wCbinFlI7Np2Y
+/rFfvFZOrtJpSGswOCDhf/WzAP4r6jIejryYdDwUfEhPurCact+ZlHT45c0SjmCo
+K6iMDg==
+=bXR/
+-----END PGP PUBLIC KEY BLOCK-----
+";
+
+    /// `gpg --armor --detach-sign` of "release artifact\n" by the signing subkey.
+    const PGP_SIG: &str = "-----BEGIN PGP SIGNATURE-----
+
+iHUEABYIAB0WIQT9wB8TAWJdLEReepJeB25PJVTiNAUCatF8QAAKCRBeB25PJVTi
+NPf2AP93i65paXyt6epbqY+6V0Q7bNwHBxkQTHT5MfZCI8sfPgD/UNVWx0U9Vt/N
+mtq1j04Unpl7wPyQCt3Gj0BFTPgJqQQ=
+=T3J2
+-----END PGP SIGNATURE-----
+";
+
+    // Legacy (non-prehashed) signature of "test" from the minisign-verify test suite.
+    const MINISIGN_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    const MINISIGN_SIG: &str = "untrusted comment: signature from minisign secret key
+RWQf6LRCGA9i59SLOFxz6NxvASXDJeRtuZykwQepbDEGt87ig1BNpWaVWuNrm73YiIiJbq71Wi+dP9eKL8OC351vwIasSSbXxwA=
+trusted comment: timestamp:1555779966\tfile:test
+QtKMXWyYcwdpZAlPF7tE2ENJkRd1ujvKjlj1m9RtHTBnZPa5WKU5uWRs5GoP5M/VqE81QFuMKI5k/SfNQUaOAA==
+";
+
+    fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("swiftline-sig-{}-{name}", std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_openpgp_subkey_signature() {
+        let v = Verifier::new(PGP_SIG.as_bytes(), PGP_KEY.as_bytes()).unwrap();
+        let good = temp_file("pgp-good", b"release artifact\n");
+        let msg = v.verify_file(&good).unwrap();
+        assert!(
+            msg.starts_with("Good OpenPGP signature from Swiftline Test <release@example.test>")
+        );
+        assert!(msg.contains("179E329B025B5C74BEEE9DE9C62DEA9F03F693BB"));
+
+        let bad = temp_file("pgp-bad", b"release artifact, tampered\n");
+        assert!(v.verify_file(&bad).is_err());
+        std::fs::remove_file(good).ok();
+        std::fs::remove_file(bad).ok();
+    }
+
+    #[test]
+    fn test_minisign_signature() {
+        let v = Verifier::new(MINISIGN_SIG.as_bytes(), MINISIGN_KEY.as_bytes()).unwrap();
+        let good = temp_file("mini-good", b"test");
+        assert_eq!(
+            v.verify_file(&good).unwrap(),
+            "Good minisign signature (timestamp:1555779966 file:test)"
+        );
+        let bad = temp_file("mini-bad", b"Test");
+        assert!(v.verify_file(&bad).is_err());
+        std::fs::remove_file(good).ok();
+        std::fs::remove_file(bad).ok();
+    }
+
+    #[test]
+    fn test_mismatched_formats() {
+        assert!(Verifier::new(PGP_SIG.as_bytes(), MINISIGN_KEY.as_bytes()).is_err());
+        assert!(Verifier::new(MINISIGN_SIG.as_bytes(), PGP_KEY.as_bytes()).is_err());
+        assert!(Verifier::new(b"not a signature", PGP_KEY.as_bytes()).is_err());
+    }
+}
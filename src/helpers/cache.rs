@@ -0,0 +1,39 @@
+//! Per-user cache directory for downloaded data such as the OUI vendor table:
+//! `$XDG_CACHE_HOME/swiftline`, falling back to `~/.cache/swiftline`
+//! (`%LOCALAPPDATA%\swiftline` on Windows).
+
+use anyhow::{Context, Result};
+use std::env;
+use std::path::PathBuf;
+
+/// Cache directory (not created).
+pub fn dir() -> Result<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+        .context("Cannot locate a cache directory (set XDG_CACHE_HOME or HOME)")?;
+    Ok(base.join("swiftline"))
+}
+
+/// Path of a cached file by name.
+pub fn path(name: &str) -> Result<PathBuf> {
+    Ok(dir()?.join(name))
+}
+
+/// Write `data` to the cache via a temp file + rename, so readers never see a partial file.
+/// `name` may include a subdirectory (`http/abc.body`).
+pub fn write(name: &str, data: &[u8]) -> Result<PathBuf> {
+    let dest = dir()?.join(name);
+    let dir = dest
+        .parent()
+        .context("Cache entry has no parent directory")?;
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Cannot create cache dir: {}", dir.display()))?;
+    let file = dest.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = dir.join(format!(".{file}.tmp"));
+    std::fs::write(&tmp, data).with_context(|| format!("Cannot write: {}", tmp.display()))?;
+    std::fs::rename(&tmp, &dest).with_context(|| format!("Cannot write: {}", dest.display()))?;
+    Ok(dest)
+}
@@ -0,0 +1,60 @@
+//! `Transfer-Encoding: chunked` decoding, shared by the HTTP listener (which
+//! decodes as bytes arrive) and the Unix-socket client (which has the whole body).
+
+use anyhow::{bail, Context, Result};
+
+/// Decode a chunked body; `None` while more data is needed. Chunk extensions
+/// and trailers are ignored. Fails once the decoded size exceeds `limit`.
+pub fn decode_partial(mut data: &[u8], limit: usize) -> Result<Option<Vec<u8>>> {
+    let mut out = Vec::new();
+    loop {
+        let Some(eol) = data.windows(2).position(|w| w == b"\r\n") else {
+            return Ok(None);
+        };
+        let size_line = String::from_utf8_lossy(&data[..eol]);
+        // Chunk extensions (`;name=value`) are allowed and ignored.
+        let size_hex = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_hex, 16)
+            .with_context(|| format!("Bad chunk size {size_hex:?}"))?;
+        data = &data[eol + 2..];
+        if size == 0 {
+            // Trailers (ignored) end with a blank line.
+            let done = data.starts_with(b"\r\n") || data.windows(4).any(|w| w == b"\r\n\r\n");
+            return Ok(done.then_some(out));
+        }
+        if data.len() < size + 2 {
+            return Ok(None);
+        }
+        out.extend_from_slice(&data[..size]);
+        if out.len() > limit {
+            bail!("Chunked body larger than {limit} bytes");
+        }
+        data = &data[size + 2..];
+    }
+}
+
+/// Decode a chunked body that was read in full.
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    decode_partial(data, usize::MAX)?.context("Truncated chunked body")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_partial() {
+        let body = b"4\r\nWiki\r\n5;ext=1\r\npedia\r\n0\r\n\r\n";
+        assert_eq!(
+            decode_partial(body, 64).unwrap(),
+            Some(b"Wikipedia".to_vec())
+        );
+        assert_eq!(decode_partial(b"4\r\nWi", 64).unwrap(), None);
+        assert_eq!(decode_partial(b"4\r\nWiki\r\n0\r\n", 64).unwrap(), None);
+        assert!(decode_partial(b"zz\r\n", 64).is_err());
+        assert!(decode_partial(body, 8).is_err());
+
+        assert_eq!(decode(body).unwrap(), b"Wikipedia");
+        assert!(decode(b"ff\r\nab").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! `--copy`: put a command's output on the system clipboard by piping it to
+//! the platform's copy tool — pbcopy on macOS, clip on Windows (and WSL), and
+//! wl-copy, xclip, or xsel on Linux, whichever is installed.
+
+use anyhow::{bail, Context, Result};
+use log::debug;
+use owo_colors::OwoColorize;
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+
+/// Copy tools to try, in order, with their arguments.
+fn candidates() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbcopy", &[])];
+    }
+    if cfg!(windows) {
+        return vec![("clip", &[])];
+    }
+    let mut tools: Vec<(&str, &[&str])> = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(("wl-copy", &[]));
+    }
+    tools.push(("xclip", &["-selection", "clipboard"]));
+    tools.push(("xsel", &["--clipboard", "--input"]));
+    tools.push(("clip.exe", &[]));
+    tools
+}
+
+/// Place `text` on the clipboard and note it on stderr, keeping stdout clean
+/// for pipes. Fails when no copy tool is installed.
+pub fn copy(text: &str) -> Result<()> {
+    for (program, args) in candidates() {
+        // xclip and xsel stay behind to serve the selection; they must not
+        // hold our stdout open, or a pipeline reading it would never finish.
+        let spawned = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                debug!("Clipboard tool {program} not found");
+                continue;
+            }
+            Err(e) => return Err(e).with_context(|| format!("Cannot start {program}")),
+        };
+        child
+            .stdin
+            .take()
+            .context("Clipboard tool has no stdin")?
+            .write_all(text.as_bytes())
+            .with_context(|| format!("Cannot write to {program}"))?;
+        let status = child.wait()?;
+        if !status.success() {
+            bail!("{program} failed to copy ({status})");
+        }
+        eprintln!("{}", "Copied to clipboard".green().bold());
+        return Ok(());
+    }
+    let tried: Vec<&str> = candidates().iter().map(|(p, _)| *p).collect();
+    bail!(
+        "No clipboard tool found (tried {}); install one or drop --copy",
+        tried.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_for_platform() {
+        let tools: Vec<&str> = candidates().iter().map(|(p, _)| *p).collect();
+        if cfg!(target_os = "macos") {
+            assert_eq!(tools, ["pbcopy"]);
+        } else if cfg!(windows) {
+            assert_eq!(tools, ["clip"]);
+        } else {
+            assert!(tools.ends_with(&["xclip", "xsel", "clip.exe"]));
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Content-Encoding for the HTTP commands. Responses are decoded here rather
+//! than inside reqwest so the bytes on the wire can still be counted (for
+//! progress bars and the `-v` size report) and `--no-decompress` can keep them.
+
+use anyhow::{Context, Result};
+use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder, ZstdDecoder};
+use futures::TryStreamExt;
+use log::info;
+use reqwest::header::{HeaderMap, CONTENT_ENCODING};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncRead, AsyncReadExt, BufReader};
+use tokio_util::io::StreamReader;
+
+use crate::helpers::format::human_size;
+
+/// Accept-Encoding sent with `--compressed`.
+pub const ACCEPT: &str = "gzip, deflate, br, zstd";
+
+/// A Content-Encoding we can decode.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coding {
+    Gzip,
+    Deflate,
+    Brotli,
+    Zstd,
+}
+
+impl Coding {
+    /// The response's coding; `None` for identity, absent, stacked, or unknown codings
+    /// (those bodies are passed through as they are).
+    pub fn of(hdrs: &HeaderMap) -> Option<Coding> {
+        let value = hdrs.get(CONTENT_ENCODING)?.to_str().ok()?.trim();
+        match value.to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Coding::Gzip),
+            "deflate" => Some(Coding::Deflate),
+            "br" => Some(Coding::Brotli),
+            "zstd" => Some(Coding::Zstd),
+            "" | "identity" => None,
+            other => {
+                info!("Content-Encoding {other} is not decoded; showing the raw body");
+                None
+            }
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Coding::Gzip => "gzip",
+            Coding::Deflate => "deflate",
+            Coding::Brotli => "br",
+            Coding::Zstd => "zstd",
+        }
+    }
+
+    fn decoder<'a, R: AsyncBufRead + Send + Unpin + 'a>(
+        self,
+        r: R,
+    ) -> Pin<Box<dyn AsyncRead + Send + 'a>> {
+        match self {
+            Coding::Gzip => Box::pin(GzipDecoder::new(r)),
+            Coding::Deflate => Box::pin(ZlibDecoder::new(r)),
+            Coding::Brotli => Box::pin(BrotliDecoder::new(r)),
+            Coding::Zstd => Box::pin(ZstdDecoder::new(r)),
+        }
+    }
+}
+
+/// A response body as a reader, decoded per `coding` (`None` reads it raw).
+/// `wire` counts the bytes as received, before decoding.
+pub fn body_reader(
+    resp: reqwest::Response,
+    coding: Option<Coding>,
+    wire: Arc<AtomicU64>,
+) -> Pin<Box<dyn AsyncRead + Send>> {
+    let stream = resp
+        .bytes_stream()
+        .inspect_ok(move |chunk| {
+            wire.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        })
+        .map_err(std::io::Error::other);
+    let reader = StreamReader::new(stream);
+    match coding {
+        Some(c) => c.decoder(reader),
+        None => Box::pin(reader),
+    }
+}
+
+/// Decode a body that was read in full.
+pub async fn decode(coding: Coding, body: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    coding
+        .decoder(BufReader::new(body))
+        .read_to_end(&mut out)
+        .await
+        .with_context(|| format!("Failed to decode {} response body", coding.name()))?;
+    Ok(out)
+}
+
+/// Log the transfer and decoded sizes (shown with -v).
+pub fn log_sizes(coding: Coding, wire: u64, decoded: u64) {
+    let ratio = if wire > 0 {
+        format!(" ({:.1}x)", decoded as f64 / wire as f64)
+    } else {
+        String::new()
+    };
+    info!(
+        "{}: {} transferred, {} decoded{ratio}",
+        coding.name(),
+        human_size(wire),
+        human_size(decoded)
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_compression::tokio::write::{GzipEncoder, ZstdEncoder};
+    use reqwest::header::HeaderValue;
+    use tokio::io::AsyncWriteExt;
+
+    #[test]
+    fn test_coding_of() {
+        let mut h = HeaderMap::new();
+        assert_eq!(Coding::of(&h), None);
+        for (value, want) in [
+            ("gzip", Some(Coding::Gzip)),
+            ("BR", Some(Coding::Brotli)),
+            ("zstd", Some(Coding::Zstd)),
+            ("identity", None),
+            ("gzip, br", None),
+        ] {
+            h.insert(CONTENT_ENCODING, HeaderValue::from_static(value));
+            assert_eq!(Coding::of(&h), want, "{value}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decode_round_trip() {
+        let text = b"swiftline ".repeat(100);
+        let mut gz = GzipEncoder::new(Vec::new());
+        gz.write_all(&text).await.unwrap();
+        gz.shutdown().await.unwrap();
+        let gz = gz.into_inner();
+        assert!(gz.len() < text.len());
+        assert_eq!(decode(Coding::Gzip, &gz).await.unwrap(), text);
+
+        let mut zs = ZstdEncoder::new(Vec::new());
+        zs.write_all(&text).await.unwrap();
+        zs.shutdown().await.unwrap();
+        assert_eq!(decode(Coding::Zstd, &zs.into_inner()).await.unwrap(), text);
+        assert!(decode(Coding::Gzip, b"not gzip").await.is_err());
+    }
+}
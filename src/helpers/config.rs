@@ -0,0 +1,34 @@
+//! User configuration file: `$SWIFTLINE_CONFIG`, else
+//! `$XDG_CONFIG_HOME/swiftline/config.toml`, falling back to
+//! `~/.config/swiftline/config.toml` (`%APPDATA%\swiftline\config.toml` on Windows).
+//! A missing file is an empty configuration.
+
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Config file location (may not exist).
+pub fn path() -> Result<PathBuf> {
+    if let Some(p) = env::var_os("SWIFTLINE_CONFIG").filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(p));
+    }
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+        .context("Cannot locate a config directory (set XDG_CONFIG_HOME or HOME)")?;
+    Ok(base.join("swiftline").join("config.toml"))
+}
+
+/// The parsed config file, empty when there is none.
+pub fn load() -> Result<toml::Table> {
+    let path = path()?;
+    match fs::read_to_string(&path) {
+        Ok(text) => toml::from_str(&text)
+            .with_context(|| format!("Invalid config file: {}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(toml::Table::new()),
+        Err(e) => Err(e).with_context(|| format!("Cannot read config file: {}", path.display())),
+    }
+}
@@ -0,0 +1,403 @@
+//! Cookie jar for the HTTP commands, persisted between invocations with
+//! `--cookie-jar`. Plugged into reqwest as its cookie store, so `Set-Cookie` on
+//! redirects is honored too. The file is JSON unless it already is (or is named)
+//! a Netscape `cookies.txt`, which curl and browsers' exporters read and write.
+
+use anyhow::{Context, Result};
+use reqwest::cookie::CookieStore;
+use reqwest::header::HeaderValue;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use url::Url;
+
+use crate::helpers::pace;
+
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+
+/// One stored cookie (RFC 6265 storage model, minus SameSite which is browser-only).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    /// Lowercase, without a leading dot.
+    pub domain: String,
+    /// Only sent to exactly `domain` (no Domain attribute was given).
+    pub host_only: bool,
+    pub path: String,
+    /// Epoch seconds; `None` for session cookies.
+    pub expires: Option<u64>,
+    pub secure: bool,
+    pub http_only: bool,
+}
+
+fn domain_match(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host[..host.len() - domain.len()].ends_with('.')
+            && host.parse::<std::net::IpAddr>().is_err())
+}
+
+fn path_match(req: &str, cookie: &str) -> bool {
+    req == cookie
+        || (req.starts_with(cookie)
+            && (cookie.ends_with('/') || req[cookie.len()..].starts_with('/')))
+}
+
+/// Directory of the request path, used when Set-Cookie has no Path.
+fn default_path(url: &Url) -> String {
+    let p = url.path();
+    match p.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(i) => p[..i].to_string(),
+    }
+}
+
+impl Cookie {
+    /// Parse a `Set-Cookie` value received from `url` at time `now`.
+    pub fn parse(header: &str, url: &Url, now: u64) -> Option<Cookie> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url),
+            expires: None,
+            secure: false,
+            http_only: false,
+        };
+        let mut max_age = None;
+        for attr in parts {
+            let (k, v) = attr.split_once('=').unwrap_or((attr, ""));
+            let v = v.trim();
+            match k.trim().to_ascii_lowercase().as_str() {
+                "domain" if !v.is_empty() => {
+                    let d = v.trim_start_matches('.').to_ascii_lowercase();
+                    // Reject cookies for domains the server does not belong to.
+                    if !domain_match(&host, &d) {
+                        return None;
+                    }
+                    cookie.domain = d;
+                    cookie.host_only = false;
+                }
+                "path" if v.starts_with('/') => cookie.path = v.to_string(),
+                "expires" => {
+                    if let Some(t) = pace::parse_http_date(&v.replace('-', " ")) {
+                        cookie.expires = Some(t);
+                    }
+                }
+                "max-age" => max_age = v.parse::<i64>().ok(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                _ => {}
+            }
+        }
+        // Max-Age wins over Expires; zero or negative deletes the cookie.
+        if let Some(age) = max_age {
+            cookie.expires = Some(if age <= 0 { 0 } else { now + age as u64 });
+        }
+        Some(cookie)
+    }
+
+    pub fn expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|t| t <= now)
+    }
+
+    /// Whether this cookie should be sent to `url`.
+    pub fn matches(&self, url: &Url, now: u64) -> bool {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return false;
+        };
+        let host_ok = if self.host_only {
+            host == self.domain
+        } else {
+            domain_match(&host, &self.domain)
+        };
+        host_ok
+            && path_match(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.expired(now)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "value": self.value,
+            "domain": self.domain,
+            "host_only": self.host_only,
+            "path": self.path,
+            "expires": self.expires,
+            "secure": self.secure,
+            "http_only": self.http_only,
+        })
+    }
+
+    fn from_json(v: &Value) -> Option<Cookie> {
+        Some(Cookie {
+            name: v["name"].as_str()?.to_string(),
+            value: v["value"].as_str().unwrap_or("").to_string(),
+            domain: v["domain"].as_str()?.to_string(),
+            host_only: v["host_only"].as_bool().unwrap_or(false),
+            path: v["path"].as_str().unwrap_or("/").to_string(),
+            expires: v["expires"].as_u64(),
+            secure: v["secure"].as_bool().unwrap_or(false),
+            http_only: v["http_only"].as_bool().unwrap_or(false),
+        })
+    }
+
+    /// `domain  include_subdomains  path  secure  expires  name  value`
+    fn to_netscape(&self) -> String {
+        let flag = |b: bool| if b { "TRUE" } else { "FALSE" };
+        let domain = if self.host_only {
+            self.domain.clone()
+        } else {
+            format!(".{}", self.domain)
+        };
+        format!(
+            "{}{domain}\t{}\t{}\t{}\t{}\t{}\t{}",
+            if self.http_only { "#HttpOnly_" } else { "" },
+            flag(!self.host_only),
+            self.path,
+            flag(self.secure),
+            self.expires.unwrap_or(0),
+            self.name,
+            self.value
+        )
+    }
+
+    fn from_netscape(line: &str) -> Option<Cookie> {
+        let (http_only, line) = match line.strip_prefix("#HttpOnly_") {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let f: Vec<&str> = line.split('\t').collect();
+        if f.len() != 7 {
+            return None;
+        }
+        let expires: u64 = f[4].parse().ok()?;
+        Some(Cookie {
+            name: f[5].to_string(),
+            value: f[6].to_string(),
+            domain: f[0].trim_start_matches('.').to_ascii_lowercase(),
+            host_only: !f[1].eq_ignore_ascii_case("TRUE"),
+            path: f[2].to_string(),
+            expires: (expires > 0).then_some(expires),
+            secure: f[3].eq_ignore_ascii_case("TRUE"),
+            http_only,
+        })
+    }
+}
+
+/// Cookies shared by all requests of one invocation, optionally backed by a file.
+#[derive(Debug, Default)]
+pub struct Jar {
+    cookies: Mutex<Vec<Cookie>>,
+    path: Option<PathBuf>,
+    netscape: bool,
+}
+
+impl Jar {
+    /// Load `path` if it exists (a missing file starts an empty jar that `save` creates).
+    pub fn load(path: Option<&Path>) -> Result<Jar> {
+        let Some(path) = path else {
+            return Ok(Jar::default());
+        };
+        let text = match fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Cannot read jar: {}", path.display()))
+            }
+        };
+        let netscape = text.starts_with(NETSCAPE_HEADER)
+            || (text.trim().is_empty() && path.extension().is_some_and(|e| e == "txt"));
+        let cookies = if netscape {
+            text.lines().filter_map(Cookie::from_netscape).collect()
+        } else if text.trim().is_empty() {
+            Vec::new()
+        } else {
+            let v: Value = serde_json::from_str(&text)
+                .with_context(|| format!("Cookie jar is not valid JSON: {}", path.display()))?;
+            v.as_array()
+                .map(|a| a.iter().filter_map(Cookie::from_json).collect())
+                .unwrap_or_default()
+        };
+        Ok(Jar {
+            cookies: Mutex::new(cookies),
+            path: Some(path.to_path_buf()),
+            netscape,
+        })
+    }
+
+    /// Store a cookie, replacing any with the same name/domain/path; expired ones delete it.
+    pub fn insert(&self, cookie: Cookie, now: u64) {
+        let mut cookies = self.cookies.lock().unwrap_or_else(|e| e.into_inner());
+        cookies.retain(|c| {
+            !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path)
+        });
+        if !cookie.expired(now) {
+            cookies.push(cookie);
+        }
+    }
+
+    /// Add a `--cookie name=value` for `url`'s host, as a session cookie.
+    pub fn add_pair(&self, pair: &str, url: &Url) -> Result<()> {
+        let (name, value) = pair
+            .split_once('=')
+            .with_context(|| format!("Cookie must be name=value, got: {pair}"))?;
+        let host = url.host_str().context("URL has no host for --cookie")?;
+        self.insert(
+            Cookie {
+                name: name.trim().to_string(),
+                value: value.trim().to_string(),
+                domain: host.to_ascii_lowercase(),
+                host_only: true,
+                path: "/".to_string(),
+                expires: None,
+                secure: false,
+                http_only: false,
+            },
+            pace::now_epoch(),
+        );
+        Ok(())
+    }
+
+    /// `Cookie` header value for `url` at `now`; longer paths first, as RFC 6265 asks.
+    pub fn header_for(&self, url: &Url, now: u64) -> Option<String> {
+        let cookies = self.cookies.lock().unwrap_or_else(|e| e.into_inner());
+        let mut matching: Vec<&Cookie> = cookies.iter().filter(|c| c.matches(url, now)).collect();
+        matching.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        (!matching.is_empty()).then(|| {
+            matching
+                .iter()
+                .map(|c| format!("{}={}", c.name, c.value))
+                .collect::<Vec<_>>()
+                .join("; ")
+        })
+    }
+
+    /// Write unexpired cookies back to the jar file, if there is one.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let now = pace::now_epoch();
+        let cookies = self.cookies.lock().unwrap_or_else(|e| e.into_inner());
+        let live = cookies.iter().filter(|c| !c.expired(now));
+        let text = if self.netscape {
+            let mut out = format!("{NETSCAPE_HEADER}\n# Written by swiftline\n\n");
+            for c in live {
+                out.push_str(&c.to_netscape());
+                out.push('\n');
+            }
+            out
+        } else {
+            let items: Vec<Value> = live.map(Cookie::to_json).collect();
+            serde_json::to_string_pretty(&items)? + "\n"
+        };
+        fs::write(path, text).with_context(|| format!("Cannot write jar: {}", path.display()))
+    }
+}
+
+impl CookieStore for Jar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let now = pace::now_epoch();
+        for h in cookie_headers {
+            if let Some(c) = h.to_str().ok().and_then(|s| Cookie::parse(s, url, now)) {
+                self.insert(c, now);
+            }
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.header_for(url, pace::now_epoch())
+            .and_then(|s| HeaderValue::from_str(&s).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_attributes() {
+        let u = url("https://app.example.com/login/form");
+        let c = Cookie::parse(
+            "sid=abc; Domain=.example.com; Path=/; Secure; HttpOnly; Max-Age=60",
+            &u,
+            1000,
+        )
+        .unwrap();
+        assert_eq!(c.domain, "example.com");
+        assert!(!c.host_only && c.secure && c.http_only);
+        assert_eq!(c.expires, Some(1060));
+
+        let c = Cookie::parse("a=1; Expires=Wed, 21-Oct-2015 07:28:00 GMT", &u, 0).unwrap();
+        assert_eq!(c.expires, Some(1_445_412_480));
+        assert_eq!(c.path, "/login");
+        assert!(c.host_only);
+
+        assert!(Cookie::parse("x=1; Domain=other.com", &u, 0).is_none());
+        assert!(Cookie::parse("novalue", &u, 0).is_none());
+    }
+
+    #[test]
+    fn test_matching_and_replacement() {
+        let jar = Jar::default();
+        let u = url("https://app.example.com/");
+        for h in [
+            "sid=1; Domain=example.com",
+            "pref=dark; Path=/settings",
+            "tok=s; Secure",
+        ] {
+            jar.insert(Cookie::parse(h, &u, 0).unwrap(), 0);
+        }
+        assert_eq!(
+            jar.header_for(&url("https://app.example.com/settings/x"), 0)
+                .as_deref(),
+            Some("pref=dark; sid=1; tok=s")
+        );
+        assert_eq!(
+            jar.header_for(&url("http://api.example.com/"), 0)
+                .as_deref(),
+            Some("sid=1")
+        );
+        assert_eq!(jar.header_for(&url("https://example.org/"), 0), None);
+
+        jar.insert(
+            Cookie::parse("sid=2; Domain=example.com", &u, 0).unwrap(),
+            0,
+        );
+        jar.insert(Cookie::parse("tok=; Max-Age=0", &u, 0).unwrap(), 0);
+        assert_eq!(jar.header_for(&u, 0).as_deref(), Some("sid=2"));
+    }
+
+    #[test]
+    fn test_netscape_round_trip() {
+        let c = Cookie::parse(
+            "sid=abc; Domain=example.com; HttpOnly; Max-Age=100",
+            &url("https://example.com/"),
+            1000,
+        )
+        .unwrap();
+        let line = c.to_netscape();
+        assert_eq!(
+            line,
+            "#HttpOnly_.example.com\tTRUE\t/\tFALSE\t1100\tsid\tabc"
+        );
+        assert_eq!(Cookie::from_netscape(&line), Some(c.clone()));
+        assert_eq!(Cookie::from_json(&c.to_json()), Some(c));
+    }
+}
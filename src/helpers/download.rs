@@ -0,0 +1,40 @@
+//! Streaming a response body to a file with a progress bar, for the commands
+//! that save a single object (`s3 get`, `oci pull-blob`).
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use reqwest::Response;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+use crate::helpers::spinner::{self, spinner};
+
+/// Write the body of `resp` to `path` (created or truncated), showing a byte bar
+/// when the length is known and a spinner otherwise. Every chunk is also passed
+/// to `on_chunk` (e.g. to hash it). Returns the number of bytes written.
+pub async fn download_to_file(
+    resp: Response,
+    path: &Path,
+    mut on_chunk: impl FnMut(&[u8]),
+) -> Result<u64> {
+    let mut file = File::create(path)
+        .await
+        .with_context(|| format!("Cannot create file: {}", path.display()))?;
+    let pbar = match resp.content_length() {
+        Some(t) => spinner::bar(t),
+        None => spinner("Downloading..."),
+    };
+    let mut downloaded: u64 = 0;
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("Error reading response stream")?;
+        on_chunk(&chunk);
+        file.write_all(&chunk).await?;
+        downloaded += chunk.len() as u64;
+        pbar.set_position(downloaded);
+    }
+    file.flush().await?;
+    pbar.finish_and_clear();
+    Ok(downloaded)
+}
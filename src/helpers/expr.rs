@@ -0,0 +1,678 @@
+//! A tiny expression language over JSON values, used by `json eval`.
+//!
+//! Grammar (lowest to highest precedence):
+//! - `cond ? a : b`            ternary
+//! - `a ?? b`                  `b` when `a` is null/missing
+//! - `||`, `&&`, `!`           logic (null, false, 0 and "" are falsy)
+//! - `==`, `!=`, `<`, `<=`, `>`, `>=`
+//! - `+`, `-`                  `+` adds numbers, joins strings/arrays, deep-merges objects
+//! - `*`, `/`, `%`
+//! - `a.b`, `a[0]`, `a["k"]`   access (missing keys and indexes yield null)
+//! - literals: numbers, "str" / 'str', true, false, null, `[..]`, `{key: expr, "k": expr}`
+//! - functions: num, str, bool, len, keys, json, upper, lower
+//!
+//! Identifiers resolve to bindings supplied by the caller (e.g. `--import base=base.json`),
+//! or to a record's top-level fields when evaluated with [`Compiled::eval_record`].
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+use crate::helpers::format::type_name;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Num(Number),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+const OPS: [&str; 25] = [
+    "??", "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "?", ":",
+    ".", ",", "(", ")", "[", "]", "{", "}",
+];
+
+fn lex(src: &str) -> Result<Vec<(Tok, usize)>> {
+    let mut out = Vec::new();
+    let bytes = src.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i] as char;
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        if c.is_ascii_digit() {
+            while i < bytes.len()
+                && (bytes[i].is_ascii_digit()
+                    || matches!(bytes[i], b'.' | b'e' | b'E')
+                    || (matches!(bytes[i], b'+' | b'-') && matches!(bytes[i - 1], b'e' | b'E')))
+            {
+                i += 1;
+            }
+            let lit = &src[start..i];
+            let num: Number = serde_json::from_str(lit)
+                .map_err(|_| anyhow!("Invalid number '{lit}' at offset {start}"))?;
+            out.push((Tok::Num(num), start));
+        } else if c == '"' || c == '\'' {
+            let mut s = String::new();
+            i += 1;
+            let mut chars = src[i..].char_indices();
+            loop {
+                let (off, ch) = chars
+                    .next()
+                    .with_context(|| format!("Unterminated string at offset {start}"))?;
+                if ch == c {
+                    i += off + 1;
+                    break;
+                }
+                if ch == '\\' {
+                    let (_, esc) = chars
+                        .next()
+                        .with_context(|| format!("Unterminated string at offset {start}"))?;
+                    s.push(match esc {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        other => other,
+                    });
+                } else {
+                    s.push(ch);
+                }
+            }
+            out.push((Tok::Str(s), start));
+        } else if c.is_ascii_alphabetic() || c == '_' || c == '$' {
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || matches!(bytes[i], b'_' | b'$'))
+            {
+                i += 1;
+            }
+            out.push((Tok::Ident(src[start..i].to_string()), start));
+        } else {
+            let op = OPS
+                .iter()
+                .find(|op| src[i..].starts_with(**op))
+                .with_context(|| format!("Unexpected character '{c}' at offset {start}"))?;
+            i += op.len();
+            out.push((Tok::Op(op), start));
+        }
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Lit(Value),
+    Ident(String),
+    Array(Vec<Expr>),
+    Object(Vec<(String, Expr)>),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+struct Parser {
+    toks: Vec<(Tok, usize)>,
+    pos: usize,
+    len: usize,
+    depth: usize,
+}
+
+/// Deepest nesting of brackets, ternaries, and prefix operators accepted (as in
+/// serde_json), so hostile input fails cleanly instead of overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Binary operator precedence levels, loosest first (ternary is handled separately).
+const LEVELS: [&[&str]; 7] = [
+    &["??"],
+    &["||"],
+    &["&&"],
+    &["==", "!="],
+    &["<", "<=", ">", ">="],
+    &["+", "-"],
+    &["*", "/", "%"],
+];
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.toks.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn offset(&self) -> usize {
+        self.toks.get(self.pos).map_or(self.len, |(_, o)| *o)
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Tok::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<()> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            bail!("Expected '{op}' at offset {}", self.offset())
+        }
+    }
+
+    /// Run `f` one nesting level deeper, failing past `MAX_DEPTH`.
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth == MAX_DEPTH {
+            bail!("Expression nesting too deep at offset {}", self.offset());
+        }
+        self.depth += 1;
+        let out = f(self);
+        self.depth -= 1;
+        out
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.nested(Self::ternary)
+    }
+
+    fn ternary(&mut self) -> Result<Expr> {
+        let cond = self.binary(0)?;
+        if self.eat("?") {
+            let then = self.expr()?;
+            self.expect(":")?;
+            let other = self.expr()?;
+            return Ok(Expr::Cond(Box::new(cond), Box::new(then), Box::new(other)));
+        }
+        Ok(cond)
+    }
+
+    /// Operators binding at `level` or tighter, by precedence climbing (one stack
+    /// frame per operand rather than one per precedence level).
+    fn binary(&mut self, level: usize) -> Result<Expr> {
+        let mut lhs = self.unary()?;
+        loop {
+            let (op, op_level) = match self.peek() {
+                Some(Tok::Op(o)) => match LEVELS.iter().position(|ops| ops.contains(o)) {
+                    Some(l) if l >= level => (*o, l),
+                    _ => return Ok(lhs),
+                },
+                _ => return Ok(lhs),
+            };
+            self.pos += 1;
+            let rhs = self.binary(op_level + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        for op in ["!", "-"] {
+            if self.eat(op) {
+                return Ok(Expr::Unary(op, Box::new(self.nested(Self::unary)?)));
+            }
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let mut e = self.primary()?;
+        loop {
+            if self.eat(".") {
+                match self.toks.get(self.pos) {
+                    Some((Tok::Ident(name), _)) => {
+                        e = Expr::Field(Box::new(e), name.clone());
+                        self.pos += 1;
+                    }
+                    _ => bail!("Expected field name after '.' at offset {}", self.offset()),
+                }
+            } else if self.eat("[") {
+                let idx = self.expr()?;
+                self.expect("]")?;
+                e = Expr::Index(Box::new(e), Box::new(idx));
+            } else {
+                return Ok(e);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let offset = self.offset();
+        let (tok, _) = self
+            .toks
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| anyhow!("Unexpected end of expression"))?;
+        self.pos += 1;
+        match tok {
+            Tok::Num(n) => Ok(Expr::Lit(Value::Number(n))),
+            Tok::Str(s) => Ok(Expr::Lit(Value::String(s))),
+            Tok::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Lit(Value::Bool(true))),
+                "false" => Ok(Expr::Lit(Value::Bool(false))),
+                "null" => Ok(Expr::Lit(Value::Null)),
+                _ if self.eat("(") => {
+                    let args = self.list(")")?;
+                    Ok(Expr::Call(name, args))
+                }
+                _ => Ok(Expr::Ident(name)),
+            },
+            Tok::Op("(") => {
+                let e = self.expr()?;
+                self.expect(")")?;
+                Ok(e)
+            }
+            Tok::Op("[") => Ok(Expr::Array(self.list("]")?)),
+            Tok::Op("{") => {
+                let mut fields = Vec::new();
+                while !self.eat("}") {
+                    let key = match self.toks.get(self.pos) {
+                        Some((Tok::Ident(k) | Tok::Str(k), _)) => k.clone(),
+                        _ => bail!("Expected object key at offset {}", self.offset()),
+                    };
+                    self.pos += 1;
+                    self.expect(":")?;
+                    fields.push((key, self.expr()?));
+                    if !self.eat(",") {
+                        self.expect("}")?;
+                        break;
+                    }
+                }
+                Ok(Expr::Object(fields))
+            }
+            Tok::Op(op) => bail!("Unexpected '{op}' at offset {offset}"),
+        }
+    }
+
+    /// Comma-separated expressions up to `close` (trailing comma allowed).
+    fn list(&mut self, close: &str) -> Result<Vec<Expr>> {
+        let mut items = Vec::new();
+        while !self.eat(close) {
+            items.push(self.expr()?);
+            if !self.eat(",") {
+                self.expect(close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+}
+
+fn parse(src: &str) -> Result<Expr> {
+    let toks = lex(src)?;
+    let mut p = Parser {
+        toks,
+        pos: 0,
+        len: src.len(),
+        depth: 0,
+    };
+    let e = p.expr()?;
+    if p.pos != p.toks.len() {
+        bail!("Unexpected trailing input at offset {}", p.offset());
+    }
+    Ok(e)
+}
+
+pub fn truthy(v: &Value) -> bool {
+    match v {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+/// Deep-merge `b` into `a`: nested objects merge, everything else is replaced.
+pub fn deep_merge(a: &mut Value, b: Value) {
+    match (a, b) {
+        (Value::Object(am), Value::Object(bm)) => {
+            for (k, bv) in bm {
+                match am.get_mut(&k) {
+                    Some(av) => deep_merge(av, bv),
+                    None => {
+                        am.insert(k, bv);
+                    }
+                }
+            }
+        }
+        (a, b) => *a = b,
+    }
+}
+
+fn arith(op: &str, a: &Number, b: &Number) -> Result<Value> {
+    if let (Some(x), Some(y)) = (a.as_i64(), b.as_i64()) {
+        let exact = match op {
+            "+" => x.checked_add(y),
+            "-" => x.checked_sub(y),
+            "*" => x.checked_mul(y),
+            "/" if y != 0 && x % y == 0 => Some(x / y),
+            "%" if y != 0 => Some(x % y),
+            _ => None,
+        };
+        if let Some(n) = exact {
+            return Ok(Value::from(n));
+        }
+    }
+    let (x, y) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+    let r = match op {
+        "+" => x + y,
+        "-" => x - y,
+        "*" => x * y,
+        "/" => x / y,
+        _ => x % y,
+    };
+    Number::from_f64(r)
+        .map(Value::Number)
+        .ok_or_else(|| anyhow!("Arithmetic result is not a finite number: {x} {op} {y}"))
+}
+
+fn binary(op: &str, a: Value, b: Value) -> Result<Value> {
+    match (op, a, b) {
+        ("==", a, b) => Ok(Value::Bool(a == b)),
+        ("!=", a, b) => Ok(Value::Bool(a != b)),
+        ("+", Value::String(x), Value::String(y)) => Ok(Value::String(x + &y)),
+        ("+", Value::String(x), y) => Ok(Value::String(x + &to_text(&y))),
+        ("+", Value::Array(mut x), Value::Array(y)) => {
+            x.extend(y);
+            Ok(Value::Array(x))
+        }
+        ("+", mut x @ Value::Object(_), y @ Value::Object(_)) => {
+            deep_merge(&mut x, y);
+            Ok(x)
+        }
+        ("+" | "-" | "*" | "/" | "%", Value::Number(x), Value::Number(y)) => arith(op, &x, &y),
+        ("<" | "<=" | ">" | ">=", a, b) => {
+            let ord = match (&a, &b) {
+                (Value::Number(x), Value::Number(y)) => x
+                    .as_f64()
+                    .unwrap_or(0.0)
+                    .partial_cmp(&y.as_f64().unwrap_or(0.0)),
+                (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+                _ => None,
+            }
+            .ok_or_else(|| anyhow!("Cannot compare {} with {}", type_name(&a), type_name(&b)))?;
+            Ok(Value::Bool(match op {
+                "<" => ord.is_lt(),
+                "<=" => ord.is_le(),
+                ">" => ord.is_gt(),
+                _ => ord.is_ge(),
+            }))
+        }
+        (op, a, b) => bail!(
+            "Operator '{op}' is not defined for {} and {}",
+            type_name(&a),
+            type_name(&b)
+        ),
+    }
+}
+
+/// Text form of a value: strings as-is, everything else as compact JSON.
+fn to_text(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn call(name: &str, mut args: Vec<Value>) -> Result<Value> {
+    if args.len() != 1 {
+        bail!("{name}() takes exactly one argument, got {}", args.len());
+    }
+    let arg = args.remove(0);
+    let v = match (name, arg) {
+        ("num", v @ Value::Number(_)) => v,
+        ("num", Value::String(s)) => {
+            let n: Number = serde_json::from_str(s.trim())
+                .map_err(|_| anyhow!("num(): '{s}' is not a number"))?;
+            Value::Number(n)
+        }
+        ("num", Value::Bool(b)) => Value::from(b as i64),
+        ("str", v) => Value::String(to_text(&v)),
+        ("bool", Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "1" | "on" => Value::Bool(true),
+            "false" | "no" | "0" | "off" | "" => Value::Bool(false),
+            _ => bail!("bool(): '{s}' is not a boolean"),
+        },
+        ("bool", v) => Value::Bool(truthy(&v)),
+        ("len", Value::String(s)) => Value::from(s.chars().count()),
+        ("len", Value::Array(a)) => Value::from(a.len()),
+        ("len", Value::Object(o)) => Value::from(o.len()),
+        ("keys", Value::Object(o)) => Value::Array(o.keys().cloned().map(Value::String).collect()),
+        ("json", Value::String(s)) => {
+            serde_json::from_str(&s).with_context(|| format!("json(): invalid JSON: {s}"))?
+        }
+        ("upper", Value::String(s)) => Value::String(s.to_uppercase()),
+        ("lower", Value::String(s)) => Value::String(s.to_lowercase()),
+        ("num" | "len" | "keys" | "json" | "upper" | "lower", v) => {
+            bail!("{name}() is not defined for {}", type_name(&v))
+        }
+        _ => bail!("Unknown function: {name}()"),
+    };
+    Ok(v)
+}
+
+fn eval_expr(e: &Expr, env: &HashMap<String, Value>) -> Result<Value> {
+    Ok(match e {
+        Expr::Lit(v) => v.clone(),
+        Expr::Ident(name) => env
+            .get(name)
+            .cloned()
+            .with_context(|| format!("Unknown name: {name}"))?,
+        Expr::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|i| eval_expr(i, env))
+                .collect::<Result<_>>()?,
+        ),
+        Expr::Object(fields) => {
+            let mut map = Map::new();
+            for (k, v) in fields {
+                map.insert(k.clone(), eval_expr(v, env)?);
+            }
+            Value::Object(map)
+        }
+        Expr::Field(base, name) => match eval_expr(base, env)? {
+            Value::Object(mut m) => m.remove(name).unwrap_or(Value::Null),
+            Value::Null => Value::Null,
+            other => bail!("Cannot read field '{name}' of {}", type_name(&other)),
+        },
+        Expr::Index(base, idx) => match (eval_expr(base, env)?, eval_expr(idx, env)?) {
+            (Value::Array(mut a), Value::Number(n)) => {
+                let i = n.as_i64().context("Array index must be an integer")?;
+                let i = if i < 0 { a.len() as i64 + i } else { i };
+                if i >= 0 && (i as usize) < a.len() {
+                    a.swap_remove(i as usize)
+                } else {
+                    Value::Null
+                }
+            }
+            (Value::Object(mut m), Value::String(k)) => m.remove(&k).unwrap_or(Value::Null),
+            (Value::Null, _) => Value::Null,
+            (b, i) => bail!("Cannot index {} with {}", type_name(&b), type_name(&i)),
+        },
+        Expr::Unary(op, inner) => {
+            let v = eval_expr(inner, env)?;
+            match (*op, v) {
+                ("!", v) => Value::Bool(!truthy(&v)),
+                (_, Value::Number(n)) => arith("-", &Number::from(0), &n)?,
+                (_, v) => bail!("Cannot negate {}", type_name(&v)),
+            }
+        }
+        Expr::Binary("&&", a, b) => {
+            Value::Bool(truthy(&eval_expr(a, env)?) && truthy(&eval_expr(b, env)?))
+        }
+        Expr::Binary("||", a, b) => {
+            Value::Bool(truthy(&eval_expr(a, env)?) || truthy(&eval_expr(b, env)?))
+        }
+        Expr::Binary("??", a, b) => match eval_expr(a, env)? {
+            Value::Null => eval_expr(b, env)?,
+            v => v,
+        },
+        Expr::Binary(op, a, b) => binary(op, eval_expr(a, env)?, eval_expr(b, env)?)?,
+        Expr::Cond(c, a, b) => {
+            if truthy(&eval_expr(c, env)?) {
+                eval_expr(a, env)?
+            } else {
+                eval_expr(b, env)?
+            }
+        }
+        Expr::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|a| eval_expr(a, env))
+                .collect::<Result<_>>()?;
+            call(name, args)?
+        }
+    })
+}
+
+/// Parse and evaluate `src` with the given name bindings.
+pub fn eval(src: &str, env: &HashMap<String, Value>) -> Result<Value> {
+    let expr = parse(src).with_context(|| format!("Invalid expression: {src}"))?;
+    eval_expr(&expr, env)
+}
+
+/// Collect the free names an expression refers to.
+fn idents<'a>(e: &'a Expr, out: &mut Vec<&'a str>) {
+    match e {
+        Expr::Lit(_) => {}
+        Expr::Ident(name) => out.push(name),
+        Expr::Array(items) | Expr::Call(_, items) => items.iter().for_each(|i| idents(i, out)),
+        Expr::Object(fields) => fields.iter().for_each(|(_, v)| idents(v, out)),
+        Expr::Field(a, _) | Expr::Unary(_, a) => idents(a, out),
+        Expr::Index(a, b) | Expr::Binary(_, a, b) => {
+            idents(a, out);
+            idents(b, out);
+        }
+        Expr::Cond(c, a, b) => {
+            idents(c, out);
+            idents(a, out);
+            idents(b, out);
+        }
+    }
+}
+
+/// An expression parsed once and evaluated against many inputs (e.g. log lines).
+pub struct Compiled(Expr);
+
+impl Compiled {
+    pub fn new(src: &str) -> Result<Self> {
+        parse(src)
+            .map(Compiled)
+            .with_context(|| format!("Invalid expression: {src}"))
+    }
+
+    /// Evaluate with `record`'s top-level fields as names and `_` as the whole
+    /// record. Names the record lacks are null, so `level == "error"` is simply
+    /// false for lines without a level.
+    pub fn eval_record(&self, record: &Value) -> Result<Value> {
+        let mut env: HashMap<String, Value> = match record {
+            Value::Object(map) => map.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            _ => HashMap::new(),
+        };
+        env.insert("_".to_string(), record.clone());
+        let mut names = Vec::new();
+        idents(&self.0, &mut names);
+        for name in names {
+            env.entry(name.to_string()).or_insert(Value::Null);
+        }
+        eval_expr(&self.0, &env)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn env() -> HashMap<String, Value> {
+        let mut env = HashMap::new();
+        env.insert(
+            "base".to_string(),
+            json!({"name": "api", "spec": {"replicas": 1, "image": "app:1"}, "ports": [80]}),
+        );
+        env.insert("env".to_string(), json!({"REPLICAS": "3"}));
+        env
+    }
+
+    #[test]
+    fn test_object_merge_and_env() {
+        let v = eval("base + {spec: {replicas: num(env.REPLICAS)}}", &env()).unwrap();
+        assert_eq!(v["spec"], json!({"replicas": 3, "image": "app:1"}));
+        assert_eq!(v["name"], json!("api"));
+    }
+
+    #[test]
+    fn test_arithmetic_and_precedence() {
+        assert_eq!(eval("1 + 2 * 3", &env()).unwrap(), json!(7));
+        assert_eq!(eval("(1 + 2) * 3", &env()).unwrap(), json!(9));
+        assert_eq!(eval("7 / 2", &env()).unwrap(), json!(3.5));
+        assert_eq!(eval("-base.spec.replicas", &env()).unwrap(), json!(-1));
+    }
+
+    #[test]
+    fn test_access_and_defaults() {
+        assert_eq!(eval("base.ports[0]", &env()).unwrap(), json!(80));
+        assert_eq!(eval("base.ports[-1]", &env()).unwrap(), json!(80));
+        assert_eq!(eval("base[\"name\"]", &env()).unwrap(), json!("api"));
+        assert_eq!(eval("env.MISSING ?? 'dev'", &env()).unwrap(), json!("dev"));
+    }
+
+    #[test]
+    fn test_logic_and_ternary() {
+        let v = eval(
+            "env.REPLICAS == '3' && len(base.ports) > 0 ? 'ok' : 'no'",
+            &env(),
+        )
+        .unwrap();
+        assert_eq!(v, json!("ok"));
+        assert_eq!(eval("!null", &env()).unwrap(), json!(true));
+    }
+
+    #[test]
+    fn test_concat_and_functions() {
+        assert_eq!(eval("'v' + 2", &env()).unwrap(), json!("v2"));
+        assert_eq!(
+            eval("base.ports + [443]", &env()).unwrap(),
+            json!([80, 443])
+        );
+        assert_eq!(
+            eval("keys(base.spec)", &env()).unwrap(),
+            json!(["replicas", "image"])
+        );
+        assert_eq!(eval("upper(base.name)", &env()).unwrap(), json!("API"));
+    }
+
+    #[test]
+    fn test_compiled_record() {
+        let expr = Compiled::new("level == 'error' && status >= 500").unwrap();
+        let hit = json!({"level": "error", "status": 502});
+        assert!(truthy(&expr.eval_record(&hit).unwrap()));
+        assert!(!truthy(&expr.eval_record(&json!({"msg": "hi"})).unwrap()));
+        let whole = Compiled::new("len(_)").unwrap();
+        assert_eq!(whole.eval_record(&hit).unwrap(), json!(2));
+        assert!(Compiled::new("level ==").is_err());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(eval("1 +", &env()).is_err());
+        assert!(eval("unknown", &env()).is_err());
+        assert!(eval("1 + 'a' - 1", &env()).is_err());
+        assert!(eval("num('abc')", &env()).is_err());
+        assert!(eval("{a: 1", &env()).is_err());
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let deep = "[".repeat(50_000);
+        let err = format!("{:#}", eval(&deep, &env()).unwrap_err());
+        assert!(err.contains("nesting too deep"), "{err}");
+        assert!(eval(&"!".repeat(50_000), &env()).is_err());
+        let ok = format!("{}1{}", "(".repeat(100), ")".repeat(100));
+        assert_eq!(eval(&ok, &env()).unwrap(), json!(1));
+    }
+}
@@ -0,0 +1,32 @@
+//! File argument helpers: expand glob patterns ourselves so `*.json` works
+//! the same in PowerShell/CMD (which do not expand globs) as in Unix shells.
+
+use anyhow::{bail, Context, Result};
+use std::path::PathBuf;
+
+/// Expand each argument as a glob pattern when it contains wildcard characters;
+/// plain paths are passed through untouched. Results keep argument order and are deduplicated.
+pub fn expand_globs(args: &[String]) -> Result<Vec<PathBuf>> {
+    let mut out: Vec<PathBuf> = Vec::new();
+    for arg in args {
+        if !arg.contains(['*', '?', '[']) {
+            let path = PathBuf::from(arg);
+            if !out.contains(&path) {
+                out.push(path);
+            }
+            continue;
+        }
+        let mut matched = false;
+        for entry in glob::glob(arg).with_context(|| format!("Invalid glob pattern: {arg}"))? {
+            let path = entry?;
+            if path.is_file() && !out.contains(&path) {
+                out.push(path);
+            }
+            matched = true;
+        }
+        if !matched {
+            bail!("No files match: {arg}");
+        }
+    }
+    Ok(out)
+}
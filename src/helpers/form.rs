@@ -0,0 +1,136 @@
+//! Form bodies for the HTTP commands: `--form key=value` as
+//! `application/x-www-form-urlencoded`, or `multipart/form-data` as soon as a
+//! `--form-file field=@path` is present. File parts are streamed through a progress bar.
+
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+use reqwest::multipart::{Form, Part};
+use reqwest::Body;
+use std::path::PathBuf;
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
+
+/// One `field=@path[;type=mime]` upload.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FileField {
+    pub name: String,
+    pub path: PathBuf,
+    pub mime: Option<String>,
+}
+
+/// Split `key=value` form fields.
+pub fn parse_fields(items: &[String]) -> Result<Vec<(String, String)>> {
+    items
+        .iter()
+        .map(|item| {
+            item.split_once('=')
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .with_context(|| format!("Form field must be key=value, got: {item}"))
+        })
+        .collect()
+}
+
+/// Parse curl-style `field=@path` with an optional `;type=mime/type` suffix.
+pub fn parse_file_field(item: &str) -> Result<FileField> {
+    let (name, rest) = item
+        .split_once('=')
+        .with_context(|| format!("Form file must be field=@path, got: {item}"))?;
+    let rest = rest.strip_prefix('@').unwrap_or(rest);
+    let (path, mime) = match rest.rsplit_once(";type=") {
+        Some((p, m)) => (p, Some(m.to_string())),
+        None => (rest, None),
+    };
+    Ok(FileField {
+        name: name.to_string(),
+        path: PathBuf::from(path),
+        mime,
+    })
+}
+
+/// Encode fields as `application/x-www-form-urlencoded`.
+pub fn urlencoded(fields: &[(String, String)]) -> String {
+    url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(fields)
+        .finish()
+}
+
+/// Stream a file in 64 KiB chunks, advancing `bar` as bytes are handed to the connection.
+fn file_body(file: File, bar: ProgressBar) -> Body {
+    let stream = futures::stream::unfold((file, bar), |(mut file, bar)| async move {
+        let mut buf = vec![0u8; 64 * 1024];
+        match file.read(&mut buf).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                bar.inc(n as u64);
+                Some((Ok(buf), (file, bar)))
+            }
+            Err(e) => Some((Err(e), (file, bar))),
+        }
+    });
+    Body::wrap_stream(stream)
+}
+
+/// Total size of all files, used to size the upload progress bar.
+pub async fn total_size(files: &[FileField]) -> Result<u64> {
+    let mut total = 0;
+    for f in files {
+        total += tokio::fs::metadata(&f.path)
+            .await
+            .with_context(|| format!("Failed to read: {}", f.path.display()))?
+            .len();
+    }
+    Ok(total)
+}
+
+/// Build a multipart form: text fields first, then streamed file parts.
+pub async fn multipart(
+    fields: Vec<(String, String)>,
+    files: Vec<FileField>,
+    bar: &ProgressBar,
+) -> Result<Form> {
+    let mut form = Form::new();
+    for (k, v) in fields {
+        form = form.text(k, v);
+    }
+    for f in files {
+        let file = File::open(&f.path)
+            .await
+            .with_context(|| format!("Failed to open: {}", f.path.display()))?;
+        let len = file.metadata().await?.len();
+        let file_name = f
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| f.name.clone());
+        let mut part =
+            Part::stream_with_length(file_body(file, bar.clone()), len).file_name(file_name);
+        part = part
+            .mime_str(f.mime.as_deref().unwrap_or("application/octet-stream"))
+            .with_context(|| format!("Invalid content type for {}", f.name))?;
+        form = form.part(f.name, part);
+    }
+    Ok(form)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_field() {
+        let f = parse_file_field("avatar=@img/me.png;type=image/png").unwrap();
+        assert_eq!(f.name, "avatar");
+        assert_eq!(f.path, PathBuf::from("img/me.png"));
+        assert_eq!(f.mime.as_deref(), Some("image/png"));
+        assert_eq!(parse_file_field("doc=@a.txt").unwrap().mime, None);
+        assert!(parse_file_field("nofield").is_err());
+    }
+
+    #[test]
+    fn test_urlencoded() {
+        let fields = parse_fields(&["q=a b&c".to_string(), "empty=".to_string()]).unwrap();
+        assert_eq!(urlencoded(&fields), "q=a+b%26c&empty=");
+        assert!(parse_fields(&["novalue".to_string()]).is_err());
+    }
+}
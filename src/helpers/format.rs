@@ -0,0 +1,51 @@
+//! Small value formatters shared by commands.
+
+use anyhow::Result;
+use serde_json::Value;
+
+/// JSON type name of a value; integers and other numbers are told apart.
+pub(crate) fn type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Human-readable byte count (binary units).
+pub fn human_size(n: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = n as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{n} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Print `value` as pretty JSON, colored on a terminal.
+pub fn print_json(value: &Value) -> Result<()> {
+    println!("{}", colored_json::to_colored_json_auto(value)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(8 * 1024 * 1024), "8.0 MiB");
+        assert_eq!(human_size(3 << 40), "3.0 TiB");
+    }
+}
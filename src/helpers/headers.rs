@@ -0,0 +1,491 @@
+//! Response header knowledge: grouping, one-line explanations, security checks,
+//! and `Link` parsing. Used by `http get --headers-table`, `http audit`, and paginating commands.
+
+use owo_colors::OwoColorize;
+use reqwest::header::HeaderMap;
+use std::fmt;
+
+use crate::helpers::table;
+
+/// Header categories, in display order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Group {
+    Security,
+    Cors,
+    Cache,
+    RateLimit,
+    Other,
+}
+
+impl Group {
+    fn title(self) -> &'static str {
+        match self {
+            Group::Security => "Security",
+            Group::Cors => "CORS",
+            Group::Cache => "Caching",
+            Group::RateLimit => "Rate limiting",
+            Group::Other => "Other",
+        }
+    }
+}
+
+/// Classify a (lowercase) header name.
+pub fn group_of(name: &str) -> Group {
+    match name {
+        "strict-transport-security"
+        | "content-security-policy"
+        | "content-security-policy-report-only"
+        | "x-content-type-options"
+        | "x-frame-options"
+        | "referrer-policy"
+        | "permissions-policy"
+        | "cross-origin-opener-policy"
+        | "cross-origin-embedder-policy"
+        | "cross-origin-resource-policy"
+        | "x-xss-protection"
+        | "set-cookie" => Group::Security,
+        n if n.starts_with("access-control-") => Group::Cors,
+        "cache-control" | "expires" | "etag" | "last-modified" | "age" | "vary" | "pragma" => {
+            Group::Cache
+        }
+        "retry-after" | "ratelimit" | "ratelimit-policy" => Group::RateLimit,
+        n if n.starts_with("x-ratelimit-") || n.starts_with("ratelimit-") => Group::RateLimit,
+        _ => Group::Other,
+    }
+}
+
+/// Short explanation of well-known headers.
+pub fn describe(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "strict-transport-security" => "Forces HTTPS for future visits (HSTS)",
+        "content-security-policy" => "Restricts where scripts, styles, and frames may load from",
+        "content-security-policy-report-only" => "CSP in report-only mode (not enforced)",
+        "x-content-type-options" => "nosniff stops browsers guessing content types",
+        "x-frame-options" => "Controls whether the page may be framed (clickjacking)",
+        "referrer-policy" => "How much of the URL is sent as Referer",
+        "permissions-policy" => "Enables/disables browser features (camera, geolocation, ...)",
+        "cross-origin-opener-policy" => "Isolates the browsing context from cross-origin windows",
+        "cross-origin-embedder-policy" => "Requires embedded resources to opt in (COEP)",
+        "cross-origin-resource-policy" => "Who may embed this resource (CORP)",
+        "x-xss-protection" => "Legacy XSS filter; obsolete in modern browsers",
+        "set-cookie" => "Sets a cookie; check Secure, HttpOnly, SameSite",
+        "access-control-allow-origin" => "Origins allowed to read the response",
+        "access-control-allow-credentials" => "Whether cookies/auth may be sent cross-origin",
+        "access-control-allow-methods" => "Methods allowed for cross-origin requests",
+        "access-control-allow-headers" => "Request headers allowed cross-origin",
+        "access-control-expose-headers" => "Response headers readable by cross-origin scripts",
+        "access-control-max-age" => "Seconds a preflight result may be cached",
+        "cache-control" => "Caching directives for browsers and proxies",
+        "expires" => "Absolute expiry date (superseded by max-age)",
+        "etag" => "Validator for conditional requests (If-None-Match)",
+        "last-modified" => "Validator for conditional requests (If-Modified-Since)",
+        "age" => "Seconds the response has been in a shared cache",
+        "vary" => "Request headers that change the cached response",
+        "pragma" => "HTTP/1.0 cache directive",
+        "retry-after" => "When to retry after 429/503",
+        "x-ratelimit-limit" | "ratelimit-limit" => "Requests allowed in the current window",
+        "x-ratelimit-remaining" | "ratelimit-remaining" => "Requests left in the current window",
+        "x-ratelimit-reset" | "ratelimit-reset" => "When the window resets",
+        "ratelimit" => "Structured rate-limit state (RFC 9239 draft)",
+        "ratelimit-policy" => "Rate-limit quota policy",
+        "server" => "Server software identification",
+        "x-powered-by" => "Framework identification (often best removed)",
+        "content-type" => "Media type of the body",
+        "content-length" => "Body size in bytes",
+        "content-encoding" => "Compression applied to the body",
+        "location" => "Redirect target",
+        _ => return None,
+    })
+}
+
+/// How serious a finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+        })
+    }
+}
+
+impl Severity {
+    /// Colored label for terminal output.
+    pub fn colored(self) -> String {
+        let label = self.to_string().to_uppercase();
+        match self {
+            Severity::Info => label.dimmed().to_string(),
+            Severity::Low => label.cyan().to_string(),
+            Severity::Medium => label.yellow().bold().to_string(),
+            Severity::High => label.red().bold().to_string(),
+        }
+    }
+}
+
+/// One observation about a response's headers.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub header: String,
+    pub message: String,
+}
+
+fn finding(severity: Severity, header: &str, message: impl Into<String>) -> Finding {
+    Finding {
+        severity,
+        header: header.to_string(),
+        message: message.into(),
+    }
+}
+
+fn get<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Check security-relevant headers. `https` enables HSTS expectations.
+pub fn check_security(headers: &HeaderMap, https: bool) -> Vec<Finding> {
+    let mut out = Vec::new();
+
+    match get(headers, "strict-transport-security") {
+        None if https => out.push(finding(
+            Severity::High,
+            "strict-transport-security",
+            "Missing: browsers may be downgraded to plain HTTP",
+        )),
+        None => {}
+        Some(v) => {
+            let max_age = v
+                .split(';')
+                .filter_map(|d| d.trim().strip_prefix("max-age="))
+                .find_map(|n| n.trim_matches('"').parse::<u64>().ok());
+            match max_age {
+                None => out.push(finding(
+                    Severity::Medium,
+                    "strict-transport-security",
+                    "No max-age directive",
+                )),
+                Some(n) if n < 15_552_000 => out.push(finding(
+                    Severity::Low,
+                    "strict-transport-security",
+                    format!("max-age={n} is under 180 days"),
+                )),
+                Some(_) => {}
+            }
+        }
+    }
+
+    let csp = get(headers, "content-security-policy");
+    match csp {
+        None => out.push(finding(
+            Severity::Medium,
+            "content-security-policy",
+            "Missing: no restriction on script sources",
+        )),
+        Some(v) => {
+            if v.contains("'unsafe-inline'") {
+                out.push(finding(
+                    Severity::Medium,
+                    "content-security-policy",
+                    "Allows 'unsafe-inline' scripts/styles",
+                ));
+            }
+            if v.contains("'unsafe-eval'") {
+                out.push(finding(
+                    Severity::Medium,
+                    "content-security-policy",
+                    "Allows 'unsafe-eval'",
+                ));
+            }
+            let wildcard_default = v.split(';').any(|d| {
+                let mut parts = d.split_whitespace();
+                parts.next() == Some("default-src") && parts.any(|p| p == "*")
+            });
+            if wildcard_default {
+                out.push(finding(
+                    Severity::Medium,
+                    "content-security-policy",
+                    "default-src allows any origin (*)",
+                ));
+            }
+        }
+    }
+
+    match get(headers, "x-content-type-options") {
+        Some(v) if v.trim().eq_ignore_ascii_case("nosniff") => {}
+        Some(v) => out.push(finding(
+            Severity::Low,
+            "x-content-type-options",
+            format!("Unexpected value '{v}' (use nosniff)"),
+        )),
+        None => out.push(finding(
+            Severity::Low,
+            "x-content-type-options",
+            "Missing: set to nosniff",
+        )),
+    }
+
+    let frame_ancestors = csp.is_some_and(|v| v.contains("frame-ancestors"));
+    match get(headers, "x-frame-options") {
+        None if !frame_ancestors => out.push(finding(
+            Severity::Medium,
+            "x-frame-options",
+            "Missing (and no CSP frame-ancestors): page can be framed",
+        )),
+        Some(v)
+            if !matches!(
+                v.trim().to_ascii_uppercase().as_str(),
+                "DENY" | "SAMEORIGIN"
+            ) =>
+        {
+            out.push(finding(
+                Severity::Low,
+                "x-frame-options",
+                format!("Unrecognized value '{v}'"),
+            ))
+        }
+        _ => {}
+    }
+
+    match get(headers, "referrer-policy") {
+        None => out.push(finding(
+            Severity::Info,
+            "referrer-policy",
+            "Missing: browser default applies",
+        )),
+        Some(v) if v.contains("unsafe-url") => out.push(finding(
+            Severity::Medium,
+            "referrer-policy",
+            "unsafe-url leaks full URLs to other sites",
+        )),
+        _ => {}
+    }
+
+    if get(headers, "permissions-policy").is_none() {
+        out.push(finding(
+            Severity::Info,
+            "permissions-policy",
+            "Missing: browser features are not restricted",
+        ));
+    }
+
+    if get(headers, "access-control-allow-origin") == Some("*") {
+        if get(headers, "access-control-allow-credentials") == Some("true") {
+            out.push(finding(
+                Severity::High,
+                "access-control-allow-origin",
+                "Wildcard origin combined with credentials",
+            ));
+        } else {
+            out.push(finding(
+                Severity::Info,
+                "access-control-allow-origin",
+                "Any origin may read responses",
+            ));
+        }
+    }
+
+    for cookie in headers.get_all("set-cookie").iter() {
+        let Ok(c) = cookie.to_str() else { continue };
+        let name = c.split('=').next().unwrap_or("").trim();
+        let attrs = c.to_ascii_lowercase();
+        let mut missing = Vec::new();
+        if https && !attrs.contains("secure") {
+            missing.push("Secure");
+        }
+        if !attrs.contains("httponly") {
+            missing.push("HttpOnly");
+        }
+        if !attrs.contains("samesite") {
+            missing.push("SameSite");
+        }
+        if !missing.is_empty() {
+            out.push(finding(
+                Severity::Low,
+                "set-cookie",
+                format!("Cookie '{name}' lacks {}", missing.join(", ")),
+            ));
+        }
+    }
+
+    for name in ["server", "x-powered-by"] {
+        if let Some(v) = get(headers, name) {
+            if v.chars().any(|c| c.is_ascii_digit()) {
+                out.push(finding(
+                    Severity::Info,
+                    name,
+                    format!("Discloses software version: {v}"),
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+/// Print headers grouped by category with explanations, followed by warnings.
+pub fn print_table(headers: &HeaderMap, https: bool) {
+    let mut names: Vec<&str> = headers.keys().map(|k| k.as_str()).collect();
+    names.sort_by_key(|n| (group_of(n), *n));
+
+    let mut current: Option<Group> = None;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let flush = |group: Option<Group>, rows: &mut Vec<Vec<String>>| {
+        if let Some(g) = group {
+            println!();
+            println!("{}", g.title().bold().underline());
+            table::print(&["Header", "Value", "Meaning"], rows);
+            rows.clear();
+        }
+    };
+    for name in names {
+        let group = group_of(name);
+        if current != Some(group) {
+            flush(current, &mut rows);
+            current = Some(group);
+        }
+        for value in headers.get_all(name).iter() {
+            let mut v = String::from_utf8_lossy(value.as_bytes()).to_string();
+            if v.chars().count() > 60 {
+                v = format!("{}…", v.chars().take(59).collect::<String>());
+            }
+            rows.push(vec![
+                name.cyan().to_string(),
+                v,
+                describe(name).unwrap_or("").dimmed().to_string(),
+            ]);
+        }
+    }
+    flush(current, &mut rows);
+
+    let findings: Vec<Finding> = check_security(headers, https)
+        .into_iter()
+        .filter(|f| f.severity > Severity::Info)
+        .collect();
+    if !findings.is_empty() {
+        println!();
+        println!("{}", "Warnings".bold().underline());
+        for f in findings {
+            println!("  ⚠ {} {}: {}", f.severity.colored(), f.header, f.message);
+        }
+    }
+    println!();
+}
+
+/// Target of the `Link` entry with relation `rel`, e.g. `<https://...?page=2>; rel="next"`.
+pub fn link_rel(headers: &HeaderMap, rel: &str) -> Option<String> {
+    headers
+        .get_all(reqwest::header::LINK)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .find_map(|entry| {
+            let mut parts = entry.split(';');
+            let target = parts.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+            parts
+                .filter_map(|p| p.trim().strip_prefix("rel="))
+                .any(|r| r.trim_matches('"').split_whitespace().any(|r| r == rel))
+                .then(|| target.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn map(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        for (k, v) in pairs {
+            h.append(*k, HeaderValue::from_static(v));
+        }
+        h
+    }
+
+    #[test]
+    fn test_group_of() {
+        assert_eq!(group_of("strict-transport-security"), Group::Security);
+        assert_eq!(group_of("access-control-allow-origin"), Group::Cors);
+        assert_eq!(group_of("etag"), Group::Cache);
+        assert_eq!(group_of("x-ratelimit-remaining"), Group::RateLimit);
+        assert_eq!(group_of("content-type"), Group::Other);
+    }
+
+    #[test]
+    fn test_missing_headers_flagged_on_https() {
+        let findings = check_security(&HeaderMap::new(), true);
+        let hsts = findings
+            .iter()
+            .find(|f| f.header == "strict-transport-security")
+            .unwrap();
+        assert_eq!(hsts.severity, Severity::High);
+        assert!(findings.iter().any(|f| f.header == "x-frame-options"));
+    }
+
+    #[test]
+    fn test_good_headers_have_no_warnings() {
+        let h = map(&[
+            (
+                "strict-transport-security",
+                "max-age=31536000; includeSubDomains",
+            ),
+            (
+                "content-security-policy",
+                "default-src 'self'; frame-ancestors 'none'",
+            ),
+            ("x-content-type-options", "nosniff"),
+            ("referrer-policy", "no-referrer"),
+            ("permissions-policy", "camera=()"),
+        ]);
+        assert!(check_security(&h, true).is_empty());
+    }
+
+    #[test]
+    fn test_weak_values() {
+        let h = map(&[
+            ("strict-transport-security", "max-age=300"),
+            (
+                "content-security-policy",
+                "default-src *; script-src 'unsafe-inline'",
+            ),
+            ("access-control-allow-origin", "*"),
+            ("access-control-allow-credentials", "true"),
+            ("set-cookie", "sid=1; Path=/"),
+        ]);
+        let f = check_security(&h, true);
+        assert!(f.iter().any(|f| f.message.contains("under 180 days")));
+        assert!(f.iter().any(|f| f.message.contains("unsafe-inline")));
+        assert!(f.iter().any(|f| f.message.contains("any origin (*)")));
+        assert!(f
+            .iter()
+            .any(|f| f.severity == Severity::High && f.header == "access-control-allow-origin"));
+        assert!(f
+            .iter()
+            .any(|f| f.message.contains("lacks Secure, HttpOnly")));
+    }
+
+    #[test]
+    fn test_link_rel() {
+        let h = map(&[(
+            "link",
+            r#"<https://api.github.com/x?page=2>; rel="next", <https://api.github.com/x?page=5>; rel="last""#,
+        )]);
+        assert_eq!(
+            link_rel(&h, "next").as_deref(),
+            Some("https://api.github.com/x?page=2")
+        );
+        assert_eq!(
+            link_rel(&h, "last").as_deref(),
+            Some("https://api.github.com/x?page=5")
+        );
+        assert_eq!(link_rel(&h, "prev"), None);
+    }
+}
@@ -0,0 +1,82 @@
+//! Hex encoding for digests and signatures, and hexdumps of binary data.
+
+use owo_colors::OwoColorize;
+
+/// Lowercase hex encoding.
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Decode an even-length hex string (either case); `None` if it is not one.
+pub fn decode(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Classic hexdump rows covering bytes `from..to` of `data`, 16 per row from a
+/// 16-byte boundary: offset, hex bytes, and an ASCII column. Bytes at offsets
+/// where `highlight` is true are shown in bold red.
+pub fn dump(data: &[u8], from: usize, to: usize, highlight: impl Fn(usize) -> bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut off = from - from % 16;
+    while off < to {
+        let end = (off + 16).min(data.len());
+        let mut hex_col = String::new();
+        let mut ascii = String::new();
+        for (i, byte) in data[off..end].iter().enumerate() {
+            let h = format!("{byte:02x}");
+            let ch = if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            };
+            if highlight(off + i) {
+                hex_col.push_str(&h.red().bold().to_string());
+                ascii.push_str(&ch.red().bold().to_string());
+            } else {
+                hex_col.push_str(&h);
+                ascii.push(ch);
+            }
+            hex_col.push(' ');
+        }
+        let pad = "   ".repeat(16 - (end - off));
+        lines.push(format!("{off:08x}  {hex_col}{pad} |{ascii}|"));
+        off += 16;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(encode(&[0x00, 0xab, 0xff]), "00abff");
+        assert_eq!(decode("00ABff"), Some(vec![0x00, 0xab, 0xff]));
+        assert_eq!(decode(""), Some(vec![]));
+        assert!(decode("abc").is_none());
+        assert!(decode("zz").is_none());
+        assert!(decode("éé").is_none());
+    }
+
+    #[test]
+    fn test_dump() {
+        let data = b"statsd.counter:1|c\n";
+        let lines = dump(data, 0, data.len(), |_| false);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("00000000  73 74 61 74"));
+        assert!(lines[0].ends_with("  |statsd.counter:1|"));
+        assert_eq!(
+            lines[1],
+            format!("00000010  7c 63 0a {} ||c.|", "   ".repeat(13))
+        );
+        // Rows start on a 16-byte boundary and stop once `to` is covered.
+        assert_eq!(dump(data, 17, 18, |_| false), &lines[1..]);
+    }
+}
@@ -0,0 +1,427 @@
+//! Syntax highlighting for response bodies on a terminal: XML, HTML, YAML, and
+//! JavaScript (JSON goes through colored_json). The language comes from the
+//! Content-Type, or from sniffing the body when the type is missing or generic.
+//! These are lexical highlighters: they color tokens, never reformat.
+
+use owo_colors::OwoColorize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lang {
+    Xml,
+    Html,
+    Yaml,
+    JavaScript,
+}
+
+const JS_KEYWORDS: &[&str] = &[
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "export",
+    "extends",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "let",
+    "new",
+    "of",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// The language of a body, by Content-Type first and sniffing second (only when
+/// the type says nothing, e.g. text/plain or application/octet-stream).
+pub fn detect(content_type: &str, body: &str) -> Option<Lang> {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    match mime.as_str() {
+        "text/html" | "application/xhtml+xml" => return Some(Lang::Html),
+        "application/xml" | "text/xml" => return Some(Lang::Xml),
+        m if m.ends_with("+xml") => return Some(Lang::Xml),
+        "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => {
+            return Some(Lang::Yaml)
+        }
+        "application/javascript"
+        | "text/javascript"
+        | "application/x-javascript"
+        | "application/ecmascript"
+        | "text/ecmascript" => return Some(Lang::JavaScript),
+        "" | "text/plain" | "application/octet-stream" => {}
+        _ => return None,
+    }
+
+    let head = body.trim_start();
+    let lower: String = head
+        .chars()
+        .take(15)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if lower.starts_with("<!doctype html") || lower.starts_with("<html") {
+        Some(Lang::Html)
+    } else if lower.starts_with("<?xml")
+        || (head.starts_with('<') && head.trim_end().ends_with('>'))
+    {
+        Some(Lang::Xml)
+    } else if head.starts_with("---\n") || head.starts_with("---\r\n") || head.starts_with("%YAML")
+    {
+        Some(Lang::Yaml)
+    } else {
+        None
+    }
+}
+
+/// `text` with ANSI colors for `lang`.
+pub fn highlight(lang: Lang, text: &str) -> String {
+    match lang {
+        Lang::Xml | Lang::Html => markup(text),
+        Lang::Yaml => text.split_inclusive('\n').map(yaml_line).collect(),
+        Lang::JavaScript => javascript(text),
+    }
+}
+
+/// Tags blue, attribute names cyan, values green, comments and declarations dimmed,
+/// entities yellow; text content as is.
+fn markup(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut rest = text;
+    while !rest.is_empty() {
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(rest.len(), |i| i + 3);
+            out.push_str(&(&rest[..end]).dimmed().to_string());
+            rest = &rest[end..];
+        } else if rest.starts_with("<![CDATA[") {
+            let end = rest.find("]]>").map_or(rest.len(), |i| i + 3);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            out.push_str(&(&rest[..end]).dimmed().to_string());
+            rest = &rest[end..];
+        } else if rest.starts_with('<')
+            && rest[1..].starts_with(|c: char| c.is_alphabetic() || c == '/')
+        {
+            let end = tag_end(rest);
+            out.push_str(&tag(&rest[..end]));
+            rest = &rest[end..];
+        } else if rest.starts_with('&') {
+            let end = rest
+                .find(|c: char| c == ';' || c.is_whitespace() || c == '<')
+                .filter(|&i| rest.as_bytes()[i] == b';')
+                .map_or(1, |i| i + 1);
+            if end > 1 {
+                out.push_str(&(&rest[..end]).yellow().to_string());
+            } else {
+                out.push('&');
+            }
+            rest = &rest[end..];
+        } else {
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first..]
+                .find(['<', '&'])
+                .map_or(rest.len(), |i| i + first);
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    out
+}
+
+/// Length of the tag at the start of `s`, skipping `>` inside quoted values.
+fn tag_end(s: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+fn tag(t: &str) -> String {
+    let close = if t.ends_with("/>") {
+        "/>"
+    } else if t.ends_with('>') {
+        ">"
+    } else {
+        ""
+    };
+    let open = if t.starts_with("</") { "</" } else { "<" };
+    let inner = &t[open.len()..t.len() - close.len()];
+    let name_end = inner
+        .find(|c: char| c.is_whitespace() || c == '/')
+        .unwrap_or(inner.len());
+    let mut out = format!("{}{}", open.dimmed(), (&inner[..name_end]).blue().bold());
+
+    let mut rest = &inner[name_end..];
+    while !rest.is_empty() {
+        let c = rest.chars().next().unwrap_or_default();
+        let len = if c == '"' || c == '\'' {
+            let len = rest[1..].find(c).map_or(rest.len(), |i| i + 2);
+            out.push_str(&(&rest[..len]).green().to_string());
+            len
+        } else if c.is_whitespace() || c == '=' || c == '/' {
+            out.push(c);
+            c.len_utf8()
+        } else {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || c == '=' || c == '/')
+                .unwrap_or(rest.len());
+            out.push_str(&(&rest[..len]).cyan().to_string());
+            len
+        };
+        rest = &rest[len..];
+    }
+    out.push_str(&close.dimmed().to_string());
+    out
+}
+
+/// Where a `#` comment starts on a YAML line (outside quotes, after a space).
+fn yaml_comment(line: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if prev.is_whitespace() => return Some(i),
+            _ => {}
+        }
+        prev = c;
+    }
+    None
+}
+
+/// Keys cyan, strings green, numbers and booleans yellow, anchors and aliases
+/// magenta, comments and document markers dimmed.
+fn yaml_line(line: &str) -> String {
+    let (body, newline) = match line.strip_suffix('\n') {
+        Some(b) => (b, "\n"),
+        None => (line, ""),
+    };
+    let (code, comment) = match yaml_comment(body) {
+        Some(i) => (&body[..i], &body[i..]),
+        None => (body, ""),
+    };
+    let trimmed = code.trim_end();
+    if matches!(trimmed, "---" | "...") || trimmed.starts_with('%') {
+        return format!("{}{}{newline}", code.dimmed(), comment.dimmed());
+    }
+
+    let indent_len = code.len() - code.trim_start().len();
+    let mut out = code[..indent_len].to_string();
+    let mut rest = &code[indent_len..];
+    while let Some(r) = rest.strip_prefix("- ") {
+        out.push_str(&"- ".dimmed().to_string());
+        rest = r;
+    }
+    if let Some(i) = yaml_key_end(rest) {
+        out.push_str(&(&rest[..i]).cyan().to_string());
+        out.push(':');
+        rest = &rest[i + 1..];
+    }
+    let value = rest.trim();
+    let lead = &rest[..rest.len() - rest.trim_start().len()];
+    let trail = &rest[lead.len() + value.len()..];
+    out.push_str(lead);
+    out.push_str(&yaml_scalar(value));
+    out.push_str(trail);
+    out.push_str(&comment.dimmed().to_string());
+    out.push_str(newline);
+    out
+}
+
+/// Byte index of the `:` ending a mapping key at the start of `s`.
+fn yaml_key_end(s: &str) -> Option<usize> {
+    if let Some(q @ ('"' | '\'')) = s.chars().next() {
+        let close = s[1..].find(q)? + 1;
+        return s[close + 1..].starts_with(':').then_some(close + 1);
+    }
+    let i = s
+        .find(": ")
+        .or_else(|| s.strip_suffix(':').map(|k| k.len()))?;
+    let key = &s[..i];
+    (!key.is_empty() && !key.contains(['{', '[', '"', '\''])).then_some(i)
+}
+
+fn yaml_scalar(v: &str) -> String {
+    if v.is_empty() {
+        return String::new();
+    }
+    if v.starts_with(['"', '\'']) {
+        return v.green().to_string();
+    }
+    if v.starts_with(['&', '*', '!']) || matches!(v, "|" | ">" | "|-" | ">-") {
+        return v.magenta().to_string();
+    }
+    let lower = v.to_ascii_lowercase();
+    if matches!(
+        lower.as_str(),
+        "true" | "false" | "yes" | "no" | "null" | "~" | "on" | "off"
+    ) || v.parse::<f64>().is_ok()
+    {
+        return v.yellow().to_string();
+    }
+    v.to_string()
+}
+
+/// Keywords magenta, strings green, numbers and literals yellow, comments dimmed.
+fn javascript(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() * 2);
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = if rest.starts_with("//") {
+            let len = rest.find('\n').unwrap_or(rest.len());
+            out.push_str(&(&rest[..len]).dimmed().to_string());
+            len
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            let len = body.find("*/").map_or(rest.len(), |i| i + 4);
+            out.push_str(&(&rest[..len]).dimmed().to_string());
+            len
+        } else if matches!(c, '"' | '\'' | '`') {
+            let len = string_len(rest, c);
+            out.push_str(&(&rest[..len]).green().to_string());
+            len
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '.' || ch == '_'))
+                .unwrap_or(rest.len());
+            out.push_str(&(&rest[..len]).yellow().to_string());
+            len
+        } else if c.is_alphabetic() || c == '_' || c == '$' {
+            let len = rest
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || ch == '$'))
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            if JS_KEYWORDS.contains(&word) {
+                out.push_str(&word.magenta().to_string());
+            } else if matches!(word, "true" | "false" | "null" | "undefined" | "NaN") {
+                out.push_str(&word.yellow().to_string());
+            } else {
+                out.push_str(word);
+            }
+            len
+        } else {
+            out.push(c);
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    out
+}
+
+/// Length of the string literal opened by `quote` at the start of `s`.
+fn string_len(s: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '\n' if quote != '`' => return i,
+            c if c == quote => return i + 1,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Drop ANSI escapes so output can be compared with the input.
+    fn plain(s: &str) -> String {
+        let mut out = String::new();
+        let mut rest = s;
+        while !rest.is_empty() {
+            match crate::helpers::table::ansi_len(rest) {
+                Some(n) => rest = &rest[n..],
+                None => {
+                    let c = rest.chars().next().unwrap();
+                    out.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect("text/html; charset=utf-8", ""), Some(Lang::Html));
+        assert_eq!(detect("application/atom+xml", ""), Some(Lang::Xml));
+        assert_eq!(detect("application/x-yaml", ""), Some(Lang::Yaml));
+        assert_eq!(detect("text/javascript", ""), Some(Lang::JavaScript));
+        assert_eq!(
+            detect("text/plain", "  <?xml version=\"1.0\"?><a/>"),
+            Some(Lang::Xml)
+        );
+        assert_eq!(detect("", "<!DOCTYPE html><html></html>"), Some(Lang::Html));
+        assert_eq!(detect("", "---\nkey: value\n"), Some(Lang::Yaml));
+        assert_eq!(detect("text/plain", "hello: world"), None);
+        assert_eq!(detect("text/csv", "<a>"), None);
+    }
+
+    #[test]
+    fn test_highlight_keeps_text() {
+        let samples = [
+            (
+                Lang::Html,
+                "<!-- c --><p class=\"x > y\" hidden>é < 5 &amp; b</p><br/>",
+            ),
+            (
+                Lang::Xml,
+                "<?xml version=\"1.0\"?>\n<feed xmlns='urn:x'><![CDATA[<raw>]]></feed>",
+            ),
+            (
+                Lang::Yaml,
+                "---\nname: \"a # b\"  # note\nitems:\n  - &ref 1\n  - key: *ref\nflag: true\n",
+            ),
+            (
+                Lang::JavaScript,
+                "const s = 'it\\'s'; // done\n/* x */ if (n > 1.5) return `t${s}`;",
+            ),
+        ];
+        for (lang, text) in samples {
+            let colored = highlight(lang, text);
+            assert_ne!(colored, text, "{lang:?} not colored");
+            assert_eq!(plain(&colored), text, "{lang:?}");
+        }
+        let yaml = highlight(Lang::Yaml, "name: x # c\n");
+        assert!(yaml.contains(&"name".cyan().to_string()));
+        assert!(yaml.contains(&"# c".dimmed().to_string()));
+        let js = highlight(Lang::JavaScript, "return x");
+        assert!(js.starts_with(&"return".magenta().to_string()));
+    }
+}
@@ -0,0 +1,214 @@
+//! Conditional-request cache for `http get --cache`. Each URL gets its raw body
+//! and a JSON metadata file (status, response headers, when it was stored) under
+//! `<cache dir>/http/`, named by the SHA-256 of the URL. A cached entry is
+//! revalidated with If-None-Match / If-Modified-Since and served again on 304,
+//! or served without any request while younger than `--cache-ttl`.
+
+use anyhow::{Context, Result};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_LENGTH, ETAG, IF_MODIFIED_SINCE,
+    IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::fs;
+use url::Url;
+
+use crate::helpers::{cache, hex};
+
+/// How `http get --cache` may reuse a stored response.
+#[derive(Debug, Clone, Copy)]
+pub struct Policy {
+    /// Serve a stored response without asking the server while it is younger than this (seconds).
+    pub ttl: Option<u64>,
+}
+
+/// A stored response.
+#[derive(Debug)]
+pub struct Entry {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    /// Epoch seconds of the last store or successful revalidation.
+    pub stored_at: u64,
+    pub body: Vec<u8>,
+}
+
+/// Cache file name stem for a URL.
+fn key(url: &Url) -> String {
+    hex::encode(&Sha256::digest(url.as_str().as_bytes()))
+}
+
+impl Entry {
+    pub fn age(&self, now: u64) -> u64 {
+        now.saturating_sub(self.stored_at)
+    }
+
+    /// Young enough to serve without a request.
+    pub fn is_fresh(&self, policy: &Policy, now: u64) -> bool {
+        policy.ttl.is_some_and(|ttl| self.age(now) < ttl)
+    }
+
+    /// Add If-None-Match / If-Modified-Since from the stored validators,
+    /// unless the caller already set them with -H.
+    pub fn add_validators(&self, req: &mut HeaderMap) {
+        for (from, to) in [(ETAG, IF_NONE_MATCH), (LAST_MODIFIED, IF_MODIFIED_SINCE)] {
+            if let Some(v) = self.headers.get(&from) {
+                if !req.contains_key(&to) {
+                    req.insert(to, v.clone());
+                }
+            }
+        }
+    }
+
+    /// Apply a 304: its headers replace the stored ones (except Content-Length,
+    /// which describes the empty 304 body) and the entry counts as new again.
+    pub fn revalidate(&mut self, hdrs: &HeaderMap, now: u64) {
+        for name in hdrs.keys().filter(|n| **n != CONTENT_LENGTH) {
+            self.headers.remove(name);
+            for value in hdrs.get_all(name) {
+                self.headers.append(name.clone(), value.clone());
+            }
+        }
+        self.stored_at = now;
+    }
+
+    fn to_json(&self) -> Value {
+        let headers: Vec<Value> = self
+            .headers
+            .iter()
+            .filter_map(|(k, v)| Some(json!([k.as_str(), v.to_str().ok()?])))
+            .collect();
+        json!({
+            "status": self.status.as_u16(),
+            "stored_at": self.stored_at,
+            "headers": headers,
+        })
+    }
+
+    fn from_json(meta: &Value, body: Vec<u8>) -> Option<Entry> {
+        let mut headers = HeaderMap::new();
+        for pair in meta["headers"].as_array()? {
+            let (Some(k), Some(v)) = (pair[0].as_str(), pair[1].as_str()) else {
+                continue;
+            };
+            if let (Ok(k), Ok(v)) = (HeaderName::try_from(k), HeaderValue::from_str(v)) {
+                headers.append(k, v);
+            }
+        }
+        Some(Entry {
+            status: StatusCode::from_u16(u16::try_from(meta["status"].as_u64()?).ok()?).ok()?,
+            headers,
+            stored_at: meta["stored_at"].as_u64()?,
+            body,
+        })
+    }
+
+    /// Worth keeping: a 200 not marked `no-store`, with a validator to revalidate
+    /// it by or a TTL to serve it under.
+    pub fn is_storable(&self, policy: &Policy) -> bool {
+        let no_store = self
+            .headers
+            .get_all(CACHE_CONTROL)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .any(|d| d.trim().eq_ignore_ascii_case("no-store"));
+        let validator = self.headers.contains_key(ETAG) || self.headers.contains_key(LAST_MODIFIED);
+        self.status == StatusCode::OK && !no_store && (validator || policy.ttl.is_some())
+    }
+}
+
+/// Stored response for `url`; a missing or unreadable entry is a miss.
+pub fn load(url: &Url) -> Result<Option<Entry>> {
+    let key = key(url);
+    let meta_path = cache::path(&format!("http/{key}.json"))?;
+    let Ok(meta) = fs::read_to_string(&meta_path) else {
+        return Ok(None);
+    };
+    let Ok(meta) = serde_json::from_str::<Value>(&meta) else {
+        return Ok(None);
+    };
+    let Ok(body) = fs::read(cache::path(&format!("http/{key}.body"))?) else {
+        return Ok(None);
+    };
+    Ok(Entry::from_json(&meta, body))
+}
+
+/// Store `entry` for `url`. The body goes first, so metadata never points at a missing body.
+pub fn save(url: &Url, entry: &Entry) -> Result<()> {
+    let key = key(url);
+    cache::write(&format!("http/{key}.body"), &entry.body)?;
+    let meta = serde_json::to_vec_pretty(&entry.to_json()).context("Cannot encode cache entry")?;
+    cache::write(&format!("http/{key}.json"), &meta)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(headers: &[(&str, &str)]) -> Entry {
+        let mut map = HeaderMap::new();
+        for (k, v) in headers {
+            map.append(
+                HeaderName::try_from(*k).unwrap(),
+                HeaderValue::from_str(v).unwrap(),
+            );
+        }
+        Entry {
+            status: StatusCode::OK,
+            headers: map,
+            stored_at: 1_000,
+            body: b"{\"ok\":true}".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_validators_and_freshness() {
+        let e = entry(&[
+            ("etag", "\"v1\""),
+            ("last-modified", "Tue, 01 Oct 2024 00:00:00 GMT"),
+        ]);
+        let mut req = HeaderMap::new();
+        req.insert(IF_NONE_MATCH, HeaderValue::from_static("\"mine\""));
+        e.add_validators(&mut req);
+        assert_eq!(req[IF_NONE_MATCH], "\"mine\"");
+        assert_eq!(req[IF_MODIFIED_SINCE], "Tue, 01 Oct 2024 00:00:00 GMT");
+
+        let ttl = Policy { ttl: Some(60) };
+        assert!(e.is_fresh(&ttl, 1_059));
+        assert!(!e.is_fresh(&ttl, 1_060));
+        assert!(!e.is_fresh(&Policy { ttl: None }, 1_000));
+    }
+
+    #[test]
+    fn test_storable() {
+        let none = Policy { ttl: None };
+        assert!(entry(&[("etag", "\"v1\"")]).is_storable(&none));
+        assert!(!entry(&[]).is_storable(&none));
+        assert!(entry(&[]).is_storable(&Policy { ttl: Some(10) }));
+        assert!(
+            !entry(&[("etag", "\"v1\""), ("cache-control", "private, no-store")])
+                .is_storable(&none)
+        );
+    }
+
+    #[test]
+    fn test_revalidate_and_round_trip() {
+        let mut e = entry(&[("etag", "\"v1\""), ("content-length", "11")]);
+        let mut not_modified = HeaderMap::new();
+        not_modified.insert(ETAG, HeaderValue::from_static("\"v2\""));
+        not_modified.insert(CONTENT_LENGTH, HeaderValue::from_static("0"));
+        e.revalidate(&not_modified, 2_000);
+        assert_eq!(e.headers[ETAG], "\"v2\"");
+        assert_eq!(e.headers[CONTENT_LENGTH], "11");
+        assert_eq!(e.stored_at, 2_000);
+
+        let back = Entry::from_json(&e.to_json(), e.body.clone()).unwrap();
+        assert_eq!(back.status, StatusCode::OK);
+        assert_eq!(back.headers, e.headers);
+        assert_eq!(back.stored_at, 2_000);
+        assert!(Entry::from_json(&json!({"status": 200}), vec![]).is_none());
+    }
+}
@@ -0,0 +1,696 @@
+//! JSONPath queries (RFC 9535 style) for `json select --jsonpath`.
+//!
+//! - `$` is the root; `.name` or `['name']` selects a member, `.*` or `[*]` every child
+//! - `..name`, `..*`, `..[0]` search all descendants (recursive descent)
+//! - `[0]`, `[-1]`, `[1:5:2]` index and slice arrays; `[0,2]` and `['a','b']` are unions
+//! - `[?(@.price > 10 && @.isbn)]` filters children: `@` is the child, `$` the root;
+//!   `== != < <= > >=`, `=~ /regex/i`, `&& || !`, parentheses, and existence tests
+//!
+//! A query yields the list of matched nodes, in document order.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+#[derive(Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Expr),
+}
+
+#[derive(Debug)]
+enum Segment {
+    /// `.x` or `[...]`: selectors applied to the node itself.
+    Child(Vec<Selector>),
+    /// `..x`: selectors applied to the node and every descendant.
+    Descendant(Vec<Selector>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug)]
+enum Expr {
+    /// `@...` (relative to the filtered child) or `$...` (from the root).
+    Path {
+        root: bool,
+        segments: Vec<Segment>,
+    },
+    Literal(Value),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Op, Box<Expr>),
+    Match(Box<Expr>, Regex),
+}
+
+/// A compiled JSONPath query.
+#[derive(Debug)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    /// Parse a query. The leading `$` may be omitted (`store.book[*].author`).
+    pub fn compile(path: &str) -> Result<JsonPath> {
+        let mut p = Parser {
+            src: path,
+            chars: path.chars().collect(),
+            i: 0,
+        };
+        p.skip_ws();
+        let bare = !p.eat('$');
+        let segments = p.segments(bare)?;
+        p.skip_ws();
+        if p.i < p.chars.len() {
+            return Err(p.error("unexpected character"));
+        }
+        Ok(JsonPath { segments })
+    }
+
+    /// The nodes of `root` the query matches.
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        run_segments(&self.segments, vec![root], root)
+    }
+}
+
+struct Parser<'s> {
+    src: &'s str,
+    chars: Vec<char>,
+    i: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.i).copied()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.chars.get(self.i + n).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.i += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let n = s.chars().count();
+        if self.chars[self.i..].iter().take(n).copied().eq(s.chars()) {
+            self.i += n;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.i += 1;
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        self.skip_ws();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{c}'")))
+        }
+    }
+
+    fn error(&self, msg: &str) -> anyhow::Error {
+        anyhow::anyhow!(
+            "Invalid JSONPath {:?}: {msg} at position {}",
+            self.src,
+            self.i
+        )
+    }
+
+    /// Segments until something that cannot continue a path. With `bare`, a
+    /// leading member name needs no dot.
+    fn segments(&mut self, bare: bool) -> Result<Vec<Segment>> {
+        let mut out = Vec::new();
+        if bare && self.peek().is_some_and(|c| is_name_char(c) || c == '*') {
+            out.push(Segment::Child(vec![self.dot_selector()?]));
+        }
+        loop {
+            match self.peek() {
+                Some('.') if self.peek_at(1) == Some('.') => {
+                    self.i += 2;
+                    let sels = if self.peek() == Some('[') {
+                        self.bracket()?
+                    } else {
+                        vec![self.dot_selector()?]
+                    };
+                    out.push(Segment::Descendant(sels));
+                }
+                Some('.') => {
+                    self.i += 1;
+                    out.push(Segment::Child(vec![self.dot_selector()?]));
+                }
+                Some('[') => out.push(Segment::Child(self.bracket()?)),
+                _ => return Ok(out),
+            }
+        }
+    }
+
+    /// `*` or a member name after a dot.
+    fn dot_selector(&mut self) -> Result<Selector> {
+        if self.eat('*') {
+            return Ok(Selector::Wildcard);
+        }
+        let start = self.i;
+        while self.peek().is_some_and(is_name_char) {
+            self.i += 1;
+        }
+        if self.i == start {
+            return Err(self.error("expected a member name or '*'"));
+        }
+        Ok(Selector::Name(self.chars[start..self.i].iter().collect()))
+    }
+
+    /// `[sel, sel, ...]`
+    fn bracket(&mut self) -> Result<Vec<Selector>> {
+        self.i += 1;
+        let mut sels = Vec::new();
+        loop {
+            self.skip_ws();
+            sels.push(self.bracket_selector()?);
+            self.skip_ws();
+            if self.eat(']') {
+                return Ok(sels);
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected ',' or ']'"));
+            }
+        }
+    }
+
+    fn bracket_selector(&mut self) -> Result<Selector> {
+        match self.peek() {
+            Some('*') => {
+                self.i += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('\'' | '"') => Ok(Selector::Name(self.string()?)),
+            Some('?') => {
+                self.i += 1;
+                Ok(Selector::Filter(self.or()?))
+            }
+            _ => {
+                let start = self.int()?;
+                self.skip_ws();
+                if !self.eat(':') {
+                    return start.map(Selector::Index).ok_or_else(|| {
+                        self.error("expected an index, slice, name, '*', or filter")
+                    });
+                }
+                let end = self.int()?;
+                self.skip_ws();
+                let step = if self.eat(':') { self.int()? } else { None };
+                Ok(Selector::Slice(start, end, step))
+            }
+        }
+    }
+
+    /// An optional signed integer.
+    fn int(&mut self) -> Result<Option<i64>> {
+        self.skip_ws();
+        let start = self.i;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.i += 1;
+        }
+        let text: String = self.chars[start..self.i].iter().collect();
+        match text.as_str() {
+            "" => Ok(None),
+            t => t.parse().map(Some).map_err(|_| self.error("bad integer")),
+        }
+    }
+
+    /// A '...' or "..." string with backslash escapes.
+    fn string(&mut self) -> Result<String> {
+        let quote = self.peek().context("expected a string")?;
+        self.i += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(c) if c == quote => {
+                    self.i += 1;
+                    return Ok(out);
+                }
+                Some('\\') => {
+                    self.i += 1;
+                    let c = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    out.push(match c {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        c => c,
+                    });
+                    self.i += 1;
+                }
+                Some(c) => {
+                    out.push(c);
+                    self.i += 1;
+                }
+            }
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut left = self.and()?;
+        loop {
+            self.skip_ws();
+            if !self.eat_str("||") {
+                return Ok(left);
+            }
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut left = self.unary()?;
+        loop {
+            self.skip_ws();
+            if !self.eat_str("&&") {
+                return Ok(left);
+            }
+            left = Expr::And(Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        self.skip_ws();
+        if self.peek() == Some('!') && self.peek_at(1) != Some('=') {
+            self.i += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.operand()?;
+        self.skip_ws();
+        if self.eat_str("=~") {
+            self.skip_ws();
+            return Ok(Expr::Match(Box::new(left), self.regex()?));
+        }
+        let op = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ]
+        .into_iter()
+        .find(|(s, _)| self.eat_str(s));
+        match op {
+            Some((_, op)) => Ok(Expr::Compare(Box::new(left), op, Box::new(self.operand()?))),
+            None => Ok(left),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Expr> {
+        self.skip_ws();
+        match self.peek() {
+            Some('(') => {
+                self.i += 1;
+                let e = self.or()?;
+                self.expect(')')?;
+                Ok(e)
+            }
+            Some(c @ ('@' | '$')) => {
+                self.i += 1;
+                Ok(Expr::Path {
+                    root: c == '$',
+                    segments: self.segments(false)?,
+                })
+            }
+            Some('\'' | '"') => Ok(Expr::Literal(Value::String(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.i;
+                self.i += 1;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'))
+                {
+                    self.i += 1;
+                }
+                let text: String = self.chars[start..self.i].iter().collect();
+                let n: serde_json::Number = text.parse().map_err(|_| self.error("bad number"))?;
+                Ok(Expr::Literal(Value::Number(n)))
+            }
+            _ => {
+                for (word, value) in [
+                    ("true", Value::Bool(true)),
+                    ("false", Value::Bool(false)),
+                    ("null", Value::Null),
+                ] {
+                    if self.eat_str(word) {
+                        return Ok(Expr::Literal(value));
+                    }
+                }
+                Err(self.error("expected a value, '@', '$', or '('"))
+            }
+        }
+    }
+
+    /// `/pattern/flags` (flags: i, m, s, x) or a quoted pattern.
+    fn regex(&mut self) -> Result<Regex> {
+        let (pattern, flags) = if self.eat('/') {
+            let mut pat = String::new();
+            loop {
+                match self.peek() {
+                    None => return Err(self.error("unterminated regex")),
+                    Some('/') => {
+                        self.i += 1;
+                        break;
+                    }
+                    Some('\\') if self.peek_at(1) == Some('/') => {
+                        pat.push('/');
+                        self.i += 2;
+                    }
+                    Some(c) => {
+                        pat.push(c);
+                        self.i += 1;
+                    }
+                }
+            }
+            let start = self.i;
+            while self.peek().is_some_and(|c| "imsx".contains(c)) {
+                self.i += 1;
+            }
+            (pat, self.chars[start..self.i].iter().collect::<String>())
+        } else {
+            (self.string()?, String::new())
+        };
+        let pattern = if flags.is_empty() {
+            pattern
+        } else {
+            format!("(?{flags}){pattern}")
+        };
+        Regex::new(&pattern).map_err(|e| self.error(&format!("bad regex: {e}")))
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || !c.is_ascii()
+}
+
+fn run_segments<'a>(
+    segments: &[Segment],
+    mut nodes: Vec<&'a Value>,
+    root: &'a Value,
+) -> Vec<&'a Value> {
+    for seg in segments {
+        let mut next = Vec::new();
+        match seg {
+            Segment::Child(sels) => {
+                for node in &nodes {
+                    for sel in sels {
+                        apply(sel, node, root, &mut next);
+                    }
+                }
+            }
+            Segment::Descendant(sels) => {
+                let mut all = Vec::new();
+                for node in &nodes {
+                    descendants(node, &mut all);
+                }
+                for node in all {
+                    for sel in sels {
+                        apply(sel, node, root, &mut next);
+                    }
+                }
+            }
+        }
+        nodes = next;
+    }
+    nodes
+}
+
+/// `v` and everything below it, parents before children.
+fn descendants<'a>(v: &'a Value, out: &mut Vec<&'a Value>) {
+    out.push(v);
+    match v {
+        Value::Array(items) => items.iter().for_each(|c| descendants(c, out)),
+        Value::Object(map) => map.values().for_each(|c| descendants(c, out)),
+        _ => {}
+    }
+}
+
+fn children(v: &Value) -> Vec<&Value> {
+    match v {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => map.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn apply<'a>(sel: &Selector, v: &'a Value, root: &'a Value, out: &mut Vec<&'a Value>) {
+    match (sel, v) {
+        (Selector::Name(name), Value::Object(map)) => out.extend(map.get(name)),
+        (Selector::Wildcard, _) => out.extend(children(v)),
+        (Selector::Index(i), Value::Array(items)) => {
+            let len = items.len() as i64;
+            let idx = if *i < 0 { i + len } else { *i };
+            if (0..len).contains(&idx) {
+                out.push(&items[idx as usize]);
+            }
+        }
+        (Selector::Slice(start, end, step), Value::Array(items)) => {
+            out.extend(slice(items.len(), *start, *end, *step).map(|i| &items[i]))
+        }
+        (Selector::Filter(expr), _) => {
+            out.extend(children(v).into_iter().filter(|c| truthy(expr, c, root)))
+        }
+        _ => {}
+    }
+}
+
+/// Indices of an RFC 9535 slice over `len` elements (a step of 0 selects nothing).
+fn slice(
+    len: usize,
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+) -> impl Iterator<Item = usize> {
+    let len = len as i64;
+    let step = step.unwrap_or(1);
+    let norm = |i: i64| if i < 0 { i + len } else { i };
+    let (lo, hi) = if step >= 0 {
+        (
+            start.map_or(0, norm).clamp(0, len),
+            end.map_or(len, norm).clamp(0, len),
+        )
+    } else {
+        (
+            end.map_or(-1, norm).clamp(-1, len - 1),
+            start.map_or(len - 1, norm).clamp(-1, len - 1),
+        )
+    };
+    let mut indices = Vec::new();
+    if step > 0 {
+        let mut i = lo;
+        while i < hi {
+            indices.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        let mut i = hi;
+        while i > lo {
+            indices.push(i as usize);
+            i += step;
+        }
+    }
+    indices.into_iter()
+}
+
+/// Whether a filter expression holds for the child `cur`.
+fn truthy(e: &Expr, cur: &Value, root: &Value) -> bool {
+    match e {
+        Expr::Path { .. } => !nodes(e, cur, root).is_empty(),
+        Expr::Literal(v) => !matches!(v, Value::Null | Value::Bool(false)),
+        Expr::Not(e) => !truthy(e, cur, root),
+        Expr::And(a, b) => truthy(a, cur, root) && truthy(b, cur, root),
+        Expr::Or(a, b) => truthy(a, cur, root) || truthy(b, cur, root),
+        Expr::Compare(a, op, b) => compare(singular(a, cur, root), *op, singular(b, cur, root)),
+        Expr::Match(a, re) => {
+            matches!(singular(a, cur, root), Some(Value::String(s)) if re.is_match(&s))
+        }
+    }
+}
+
+fn nodes<'a>(e: &Expr, cur: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+    match e {
+        Expr::Path {
+            root: true,
+            segments,
+        } => run_segments(segments, vec![root], root),
+        Expr::Path { segments, .. } => run_segments(segments, vec![cur], root),
+        _ => Vec::new(),
+    }
+}
+
+/// A comparison operand: a literal, the single node a path selects, a logical
+/// expression's boolean, or `None` when a path selects zero or several nodes.
+fn singular(e: &Expr, cur: &Value, root: &Value) -> Option<Value> {
+    match e {
+        Expr::Literal(v) => Some(v.clone()),
+        Expr::Path { .. } => match nodes(e, cur, root).as_slice() {
+            [one] => Some((*one).clone()),
+            _ => None,
+        },
+        _ => Some(Value::Bool(truthy(e, cur, root))),
+    }
+}
+
+fn compare(a: Option<Value>, op: Op, b: Option<Value>) -> bool {
+    let ord = match (&a, &b) {
+        (Some(Value::Number(x)), Some(Value::Number(y))) => x.as_f64().partial_cmp(&y.as_f64()),
+        (Some(Value::String(x)), Some(Value::String(y))) => Some(x.cmp(y)),
+        (x, y) if x == y => Some(Ordering::Equal),
+        _ => None,
+    };
+    match op {
+        Op::Eq => ord == Some(Ordering::Equal),
+        Op::Ne => ord != Some(Ordering::Equal),
+        Op::Lt => ord == Some(Ordering::Less) && comparable(&a),
+        Op::Le => matches!(ord, Some(Ordering::Less | Ordering::Equal)) && comparable(&a),
+        Op::Gt => ord == Some(Ordering::Greater) && comparable(&a),
+        Op::Ge => matches!(ord, Some(Ordering::Greater | Ordering::Equal)) && comparable(&a),
+    }
+}
+
+/// Only numbers and strings order; anything else is just equal or not.
+fn comparable(v: &Option<Value>) -> bool {
+    matches!(v, Some(Value::Number(_) | Value::String(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn store() -> Value {
+        json!({"store": {
+            "book": [
+                {"category": "reference", "author": "Nigel Rees", "title": "Sayings of the Century", "price": 8.95},
+                {"category": "fiction", "author": "Evelyn Waugh", "title": "Sword of Honour", "price": 12.99},
+                {"category": "fiction", "author": "Herman Melville", "title": "Moby Dick", "isbn": "0-553-21311-3", "price": 8.99},
+                {"category": "fiction", "author": "J. R. R. Tolkien", "title": "The Lord of the Rings", "isbn": "0-395-19395-8", "price": 22.99}
+            ],
+            "bicycle": {"color": "red", "price": 399}
+        }, "limit": 10})
+    }
+
+    fn query<'a>(doc: &'a Value, path: &str) -> Result<Vec<&'a Value>> {
+        Ok(JsonPath::compile(path)?.select(doc))
+    }
+
+    fn q(path: &str) -> Value {
+        let doc = store();
+        Value::Array(query(&doc, path).unwrap().into_iter().cloned().collect())
+    }
+
+    #[test]
+    fn test_members_indexes_and_unions() {
+        assert_eq!(q("$.store.bicycle.color"), json!(["red"]));
+        assert_eq!(q("store['bicycle']['color']"), json!(["red"]));
+        assert_eq!(q("$.store.book[-1].author"), json!(["J. R. R. Tolkien"]));
+        assert_eq!(
+            q("$.store.book[0,2].title"),
+            json!(["Sayings of the Century", "Moby Dick"])
+        );
+        assert_eq!(q("$.store.bicycle['color','price']"), json!(["red", 399]));
+        assert_eq!(q("$.store.nope"), json!([]));
+        assert_eq!(q("$"), json!([store()]));
+    }
+
+    #[test]
+    fn test_slices() {
+        let doc = json!([0, 1, 2, 3, 4, 5]);
+        let s = |p| Value::Array(query(&doc, p).unwrap().into_iter().cloned().collect());
+        assert_eq!(s("$[1:3]"), json!([1, 2]));
+        assert_eq!(s("$[:2]"), json!([0, 1]));
+        assert_eq!(s("$[-2:]"), json!([4, 5]));
+        assert_eq!(s("$[::2]"), json!([0, 2, 4]));
+        assert_eq!(s("$[::-1]"), json!([5, 4, 3, 2, 1, 0]));
+        assert_eq!(s("$[4:1:-2]"), json!([4, 2]));
+        assert_eq!(s("$[0:5:0]"), json!([]));
+    }
+
+    #[test]
+    fn test_recursive_descent_and_wildcards() {
+        assert_eq!(
+            q("$..author"),
+            json!([
+                "Nigel Rees",
+                "Evelyn Waugh",
+                "Herman Melville",
+                "J. R. R. Tolkien"
+            ])
+        );
+        assert_eq!(q("$.store.*").as_array().unwrap().len(), 2);
+        assert_eq!(q("$..price").as_array().unwrap().len(), 5);
+        assert_eq!(q("$..book[2].title"), json!(["Moby Dick"]));
+        assert_eq!(q("$..[?(@.isbn)]").as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(
+            q("$.store.book[?(@.price > 10)].title"),
+            json!(["Sword of Honour", "The Lord of the Rings"])
+        );
+        assert_eq!(
+            q("$.store.book[?@.price < $.limit && @.category == 'fiction'].title"),
+            json!(["Moby Dick"])
+        );
+        assert_eq!(q("$.store.book[?(!@.isbn)].price"), json!([8.95, 12.99]));
+        assert_eq!(
+            q("$.store.book[?(@.author =~ /tolkien/i || @.price == 8.95)].title"),
+            json!(["Sayings of the Century", "The Lord of the Rings"])
+        );
+        assert_eq!(q("$.store.book[?(@.price >= 399)]"), json!([]));
+        assert_eq!(q("$.store[?(@.color != 'blue')].price"), json!([399]));
+        // Ordering never holds between mismatched types or missing members.
+        assert_eq!(q("$.store.book[?(@.isbn > 5)]"), json!([]));
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        for bad in [
+            "$.store[",
+            "$.store.book[?(@.price >)]",
+            "$['unterminated]",
+            "$.a[1:x]",
+            "$.a b",
+            "$..",
+            "$.a[?(@.b =~ /(/)]",
+        ] {
+            assert!(JsonPath::compile(bad).is_err(), "{bad}");
+        }
+    }
+}
@@ -1,4 +1,33 @@
-//! Small helper modules for styling and progress.
+//! Small helper modules shared by commands.
 
+pub mod cache;
+pub mod chunked;
+pub mod clipboard;
+pub mod compression;
+pub mod config;
+pub mod cookies;
+pub mod download;
+pub mod duration;
+pub mod expr;
+pub mod files;
+pub mod form;
+pub mod format;
+pub mod headers;
+pub mod hex;
+pub mod highlight;
+pub mod http_cache;
+pub mod jsonpath;
+pub mod pace;
+pub mod percent;
+pub mod preview;
+pub mod ratelimit;
+pub mod resolve;
+pub mod retry;
+pub mod sigv4;
 pub mod spinner;
 pub mod style;
+pub mod table;
+pub mod template;
+pub mod tunnel;
+pub mod unix_http;
+pub mod write_out;
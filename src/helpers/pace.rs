@@ -0,0 +1,215 @@
+//! Rate-limit pacing: read rate-limit response headers and work out how long to
+//! wait before the next request so we stay under the server's limit.
+//!
+//! Understands `X-RateLimit-Remaining`/`X-RateLimit-Reset` (delta seconds or epoch),
+//! `RateLimit-Remaining`/`RateLimit-Reset`, the structured `RateLimit` field
+//! (`limit=100, remaining=5, reset=30` or `"default";r=5;t=30`), and `Retry-After`.
+
+use log::info;
+use owo_colors::OwoColorize;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Rate-limit state advertised by a response.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RateLimit {
+    pub remaining: Option<u64>,
+    pub reset: Option<Duration>,
+    pub retry_after: Option<Duration>,
+}
+
+fn header<'a>(h: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    h.get(name).and_then(|v| v.to_str().ok()).map(str::trim)
+}
+
+/// Current time in epoch seconds.
+pub fn now_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Reset values above this are epoch timestamps rather than delta seconds.
+const EPOCH_THRESHOLD: u64 = 1_000_000_000;
+
+fn reset_from(value: &str, now: u64) -> Option<Duration> {
+    let n: f64 = value.parse().ok()?;
+    if n < 0.0 {
+        return None;
+    }
+    let secs = if n as u64 > EPOCH_THRESHOLD {
+        (n as u64).saturating_sub(now) as f64
+    } else {
+        n
+    };
+    Some(Duration::from_secs_f64(secs))
+}
+
+/// Parse an IMF-fixdate such as `Sun, 06 Nov 1994 08:49:37 GMT` into epoch seconds.
+pub fn parse_http_date(s: &str) -> Option<u64> {
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    if parts.len() != 6 || parts[5] != "GMT" {
+        return None;
+    }
+    let day: u64 = parts[1].parse().ok()?;
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|m| *m == parts[2])? as u64
+        + 1;
+    let year: u64 = parts[3].parse().ok()?;
+    let hms: Vec<u64> = parts[4]
+        .split(':')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    if hms.len() != 3 || year < 1970 {
+        return None;
+    }
+    // Days from civil date (Howard Hinnant's algorithm).
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let yoe = y - era * 400;
+    let doy = (153 * m + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(days * 86_400 + hms[0] * 3600 + hms[1] * 60 + hms[2])
+}
+
+/// Read `key=value` parameters out of a structured `RateLimit` header.
+fn structured_param(value: &str, keys: &[&str]) -> Option<String> {
+    value
+        .split([',', ';'])
+        .filter_map(|p| p.trim().split_once('='))
+        .find(|(k, _)| keys.contains(&k.trim()))
+        .map(|(_, v)| v.trim().trim_matches('"').to_string())
+}
+
+/// Extract rate-limit state from response headers at time `now` (epoch seconds).
+pub fn parse(h: &HeaderMap, now: u64) -> RateLimit {
+    let mut rl = RateLimit::default();
+
+    for prefix in ["x-ratelimit-", "ratelimit-"] {
+        if rl.remaining.is_none() {
+            rl.remaining = header(h, &format!("{prefix}remaining")).and_then(|v| v.parse().ok());
+        }
+        if rl.reset.is_none() {
+            rl.reset = header(h, &format!("{prefix}reset-after"))
+                .or_else(|| header(h, &format!("{prefix}reset")))
+                .and_then(|v| reset_from(v, now));
+        }
+    }
+
+    if let Some(v) = header(h, "ratelimit") {
+        if rl.remaining.is_none() {
+            rl.remaining = structured_param(v, &["remaining", "r"]).and_then(|n| n.parse().ok());
+        }
+        if rl.reset.is_none() {
+            rl.reset = structured_param(v, &["reset", "t"]).and_then(|n| reset_from(&n, now));
+        }
+    }
+
+    rl.retry_after = header(h, "retry-after").and_then(|v| match v.parse::<u64>() {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(_) => parse_http_date(v).map(|t| Duration::from_secs(t.saturating_sub(now))),
+    });
+    rl
+}
+
+/// How long to wait before the next request. When the quota is exhausted we wait
+/// for the reset; otherwise the remaining requests are spread evenly over the window.
+pub fn delay(rl: &RateLimit, status: StatusCode) -> Option<Duration> {
+    if status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE {
+        if let Some(wait) = rl.retry_after.or(rl.reset) {
+            return Some(wait);
+        }
+    }
+    match (rl.remaining, rl.reset) {
+        (Some(0), Some(reset)) => Some(reset),
+        (Some(n), Some(reset)) if !reset.is_zero() => Some(reset / (n as u32).max(1)),
+        _ => None,
+    }
+}
+
+/// Sleep as long as the response's rate-limit headers require, explaining why on stderr.
+pub async fn pace(h: &HeaderMap, status: StatusCode) {
+    let rl = parse(h, now_epoch());
+    info!("Rate limit state: {rl:?}");
+    let Some(wait) = delay(&rl, status) else {
+        return;
+    };
+    if wait < Duration::from_millis(10) {
+        return;
+    }
+    let reason = match rl.remaining {
+        Some(0) => "quota exhausted".to_string(),
+        Some(n) => format!("{n} request(s) left"),
+        None => format!("server asked to retry ({status})"),
+    };
+    eprintln!(
+        "{} {reason}, sleeping {:.1}s",
+        "Pacing:".yellow().bold(),
+        wait.as_secs_f64()
+    );
+    tokio::time::sleep(wait).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn map(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        for (k, v) in pairs {
+            h.insert(*k, HeaderValue::from_static(v));
+        }
+        h
+    }
+
+    #[test]
+    fn test_x_ratelimit_epoch_reset() {
+        let h = map(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1700000060"),
+        ]);
+        let rl = parse(&h, 1_700_000_000);
+        assert_eq!(rl.remaining, Some(0));
+        assert_eq!(rl.reset, Some(Duration::from_secs(60)));
+        assert_eq!(delay(&rl, StatusCode::OK), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_structured_field_spreads_requests() {
+        let h = map(&[("ratelimit", "limit=100, remaining=10, reset=30")]);
+        let rl = parse(&h, 0);
+        assert_eq!(delay(&rl, StatusCode::OK), Some(Duration::from_secs(3)));
+
+        let h = map(&[("ratelimit", "\"default\";r=5;t=10")]);
+        assert_eq!(parse(&h, 0).remaining, Some(5));
+    }
+
+    #[test]
+    fn test_retry_after_on_429() {
+        let h = map(&[("retry-after", "Sun, 06 Nov 1994 08:49:37 GMT")]);
+        let rl = parse(&h, 784_111_770);
+        assert_eq!(rl.retry_after, Some(Duration::from_secs(7)));
+        assert_eq!(
+            delay(&rl, StatusCode::TOO_MANY_REQUESTS),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(delay(&rl, StatusCode::OK), None);
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_http_date("garbage"), None);
+    }
+}
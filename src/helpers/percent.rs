@@ -0,0 +1,50 @@
+//! `%XX` escapes in URL paths, for the built-in servers and for file names
+//! taken from URLs.
+
+/// Decode `%XX` escapes; `None` for bad escapes or non-UTF-8 results. `+` is
+/// left alone, as it is in a path.
+pub fn decode(s: &str) -> Option<String> {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'%' {
+            let hex = std::str::from_utf8(b.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(b[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Escape a path segment for an `href`: everything but unreserved characters.
+pub fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_and_encode() {
+        assert_eq!(decode("a%2Fb+c%20d").as_deref(), Some("a/b+c d"));
+        assert_eq!(decode("%C3%A9").as_deref(), Some("é"));
+        assert!(decode("bad%zz").is_none());
+        assert!(decode("cut%2").is_none());
+        assert!(decode("%FF").is_none());
+        assert_eq!(encode("a b&c.txt"), "a%20b%26c.txt");
+        assert_eq!(decode(&encode("ü/ x")).as_deref(), Some("ü/ x"));
+    }
+}
@@ -0,0 +1,95 @@
+//! Truncated JSON previews: keep the first N elements of every array and the
+//! first N keys of every object, marking what was cut with `…(+1234 more)`.
+
+use owo_colors::OwoColorize;
+use serde_json::{Map, Value};
+
+use crate::helpers::{style, table};
+
+/// Text of the marker standing in for `n` dropped entries.
+fn marker(n: usize) -> String {
+    format!("…(+{n} more)")
+}
+
+/// `value` with every array and object cut to its first `limit` entries. Cut
+/// arrays end with a marker string; cut objects with a marker key set to null.
+pub fn truncate(value: &Value, limit: usize) -> Value {
+    match value {
+        Value::Array(items) => {
+            let mut out: Vec<Value> = items
+                .iter()
+                .take(limit)
+                .map(|v| truncate(v, limit))
+                .collect();
+            if items.len() > limit {
+                out.push(Value::String(marker(items.len() - limit)));
+            }
+            Value::Array(out)
+        }
+        Value::Object(map) => {
+            let mut out: Map<String, Value> = map
+                .iter()
+                .take(limit)
+                .map(|(k, v)| (k.clone(), truncate(v, limit)))
+                .collect();
+            if map.len() > limit {
+                out.insert(marker(map.len() - limit), Value::Null);
+            }
+            Value::Object(out)
+        }
+        other => other.clone(),
+    }
+}
+
+/// The dropped count if `line` (colors removed) is a marker entry.
+fn marker_count(line: &str) -> Option<&str> {
+    let entry = line.trim();
+    let entry = entry.strip_suffix(": null").unwrap_or(entry);
+    entry.strip_prefix("\"…(+")?.strip_suffix(" more)\"")
+}
+
+/// Pretty JSON of `value` cut to `limit` entries per level, colored on a
+/// terminal, with the markers printed bare rather than as JSON strings.
+pub fn render(value: &Value, limit: usize) -> Result<String, serde_json::Error> {
+    let pretty = colored_json::to_colored_json_auto(&truncate(value, limit))?;
+    let lines: Vec<String> = pretty
+        .lines()
+        .map(|line| {
+            let plain = table::strip_ansi(line);
+            let Some(count) = marker_count(&plain) else {
+                return line.to_string();
+            };
+            let indent = &plain[..plain.len() - plain.trim_start().len()];
+            let text = format!("…(+{count} more)");
+            if style::is_tty() {
+                format!("{indent}{}", text.dimmed())
+            } else {
+                format!("{indent}{text}")
+            }
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_truncate_levels() {
+        let v = json!({"a": [1, 2, 3, 4], "b": {"x": 1, "y": 2, "z": 3}, "c": 0});
+        assert_eq!(
+            truncate(&v, 2),
+            json!({"a": [1, 2, "…(+2 more)"], "b": {"x": 1, "y": 2, "…(+1 more)": null}, "…(+1 more)": null})
+        );
+        assert_eq!(truncate(&json!([1]), 1), json!([1]));
+    }
+
+    #[test]
+    fn test_render_bare_markers() {
+        let out = render(&json!({"items": [1, 2, 3]}), 1).unwrap();
+        let plain = table::strip_ansi(&out);
+        assert_eq!(plain, "{\n  \"items\": [\n    1,\n    …(+2 more)\n  ]\n}");
+    }
+}
@@ -0,0 +1,107 @@
+//! Client-side request rate limiting (`--rate`): a token bucket shared by every
+//! task of a batch or bench run. Callers reserve a token and sleep outside the
+//! lock, so concurrent waiters are released in turn, `1/rate` seconds apart.
+
+use anyhow::{bail, Result};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+#[derive(Debug)]
+struct Bucket {
+    /// Tokens available; negative while requests are queued behind the limit.
+    tokens: f64,
+    last: Instant,
+}
+
+/// Token bucket refilled at `rate` tokens per second, holding at most `burst`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// A limiter for `rate` requests per second without bursts.
+    pub fn new(rate: f64) -> Result<Self> {
+        Self::with_burst(rate, 1.0)
+    }
+
+    pub fn with_burst(rate: f64, burst: f64) -> Result<Self> {
+        if !(rate.is_finite() && rate > 0.0) {
+            bail!("--rate must be a positive number of requests per second");
+        }
+        Ok(RateLimiter {
+            rate,
+            burst: burst.max(1.0),
+            bucket: Mutex::new(Bucket {
+                tokens: burst.max(1.0),
+                last: Instant::now(),
+            }),
+        })
+    }
+
+    /// Reserve one token at `now` and return how long the caller must wait for it.
+    fn reserve(&self, bucket: &mut Bucket, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.last = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+
+    /// Wait until the next request may be sent.
+    pub async fn acquire(&self) {
+        let wait = {
+            let mut bucket = self.bucket.lock().await;
+            self.reserve(&mut bucket, Instant::now())
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(tokens: f64, last: Instant) -> Bucket {
+        Bucket { tokens, last }
+    }
+
+    #[test]
+    fn test_queued_requests_are_spaced() {
+        let limiter = RateLimiter::new(4.0).unwrap();
+        let t0 = Instant::now();
+        let mut b = bucket(1.0, t0);
+        let waits: Vec<Duration> = (0..4).map(|_| limiter.reserve(&mut b, t0)).collect();
+        let ms: Vec<u128> = waits.iter().map(Duration::as_millis).collect();
+        assert_eq!(ms, [0, 250, 500, 750]);
+        // Half a second later two of the queued slots have been paid off.
+        assert_eq!(
+            limiter
+                .reserve(&mut b, t0 + Duration::from_millis(500))
+                .as_millis(),
+            500
+        );
+    }
+
+    #[test]
+    fn test_idle_refill_is_capped() {
+        let limiter = RateLimiter::with_burst(10.0, 3.0).unwrap();
+        let t0 = Instant::now();
+        let mut b = bucket(0.0, t0);
+        let later = t0 + Duration::from_secs(60);
+        let waits: Vec<bool> = (0..4)
+            .map(|_| limiter.reserve(&mut b, later).is_zero())
+            .collect();
+        assert_eq!(waits, [true, true, true, false]);
+        assert!(RateLimiter::new(0.0).is_err());
+        assert!(RateLimiter::new(f64::NAN).is_err());
+    }
+}
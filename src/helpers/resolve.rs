@@ -0,0 +1,144 @@
+//! curl-style `--resolve host:port:addr` DNS overrides, wired into reqwest as a
+//! custom resolver. Names without an override fall back to the system resolver,
+//! and TLS still verifies against the hostname in the URL.
+
+use anyhow::{bail, Context, Result};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::{IpAddr, SocketAddr};
+use url::Url;
+
+/// One `--resolve` entry; `host` is lowercase, or `*` for every host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub host: String,
+    pub port: u16,
+    pub addrs: Vec<IpAddr>,
+}
+
+impl Override {
+    /// Parse `host:port:addr[,addr...]`; IPv6 addresses may be bracketed.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let usage = || format!("Invalid --resolve {spec:?}; expected host:port:addr");
+        let (host, rest) = spec.split_once(':').with_context(usage)?;
+        let (port, addrs) = rest.split_once(':').with_context(usage)?;
+        let port: u16 = port.parse().with_context(usage)?;
+        if host.is_empty() || addrs.is_empty() {
+            bail!(usage());
+        }
+        let addrs = addrs
+            .split(',')
+            .map(|a| {
+                let a = a.trim();
+                let bare = a.strip_prefix('[').and_then(|a| a.strip_suffix(']'));
+                bare.unwrap_or(a)
+                    .parse::<IpAddr>()
+                    .with_context(|| format!("Invalid address {a:?} in --resolve {spec:?}"))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Override {
+            host: host.to_ascii_lowercase(),
+            port,
+            addrs,
+        })
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        self.host == "*" || self.host.eq_ignore_ascii_case(host)
+    }
+
+    /// Whether the entry applies to `url`: same host and the same (explicit or default) port.
+    pub fn applies_to(&self, url: &Url) -> bool {
+        url.host_str().is_some_and(|h| self.matches(h))
+            && url.port_or_known_default() == Some(self.port)
+    }
+}
+
+/// Resolver answering overridden names from the table and the rest from the system.
+pub struct StaticResolver {
+    overrides: Vec<Override>,
+}
+
+impl StaticResolver {
+    pub fn new(overrides: Vec<Override>) -> Self {
+        StaticResolver { overrides }
+    }
+
+    /// Addresses for `host`; an exact host entry wins over `*`.
+    fn lookup(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        let found = self
+            .overrides
+            .iter()
+            .find(|o| o.host != "*" && o.matches(host))
+            .or_else(|| self.overrides.iter().find(|o| o.host == "*"))?;
+        // Port 0 lets reqwest use the port of the URL.
+        Some(
+            found
+                .addrs
+                .iter()
+                .map(|ip| SocketAddr::new(*ip, 0))
+                .collect(),
+        )
+    }
+}
+
+impl Resolve for StaticResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        let found = self.lookup(&host);
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = match found {
+                Some(addrs) => addrs,
+                None => tokio::net::lookup_host((host.as_str(), 0)).await?.collect(),
+            };
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let o = Override::parse("API.example.com:443:10.0.0.5,[::1]").unwrap();
+        assert_eq!(o.host, "api.example.com");
+        assert_eq!(o.port, 443);
+        assert_eq!(
+            o.addrs,
+            [
+                "10.0.0.5".parse::<IpAddr>().unwrap(),
+                "::1".parse().unwrap()
+            ]
+        );
+        assert_eq!(
+            Override::parse("a:80:::1").unwrap().addrs,
+            ["::1".parse::<IpAddr>().unwrap()]
+        );
+        for bad in [
+            "a:80",
+            "a:x:1.2.3.4",
+            ":80:1.2.3.4",
+            "a:80:",
+            "a:80:example.com",
+        ] {
+            assert!(Override::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_applies_and_lookup() {
+        let o = Override::parse("api.example.com:443:10.0.0.5").unwrap();
+        assert!(o.applies_to(&Url::parse("https://api.example.com/v1").unwrap()));
+        assert!(o.applies_to(&Url::parse("http://API.example.com:443/").unwrap()));
+        assert!(!o.applies_to(&Url::parse("http://api.example.com/").unwrap()));
+
+        let r = StaticResolver::new(vec![o, Override::parse("*:443:10.0.0.9").unwrap()]);
+        let ip = |h| r.lookup(h).map(|a| a[0].ip().to_string());
+        assert_eq!(ip("api.example.com").as_deref(), Some("10.0.0.5"));
+        assert_eq!(ip("other.test").as_deref(), Some("10.0.0.9"));
+        assert!(StaticResolver::new(vec![])
+            .lookup("api.example.com")
+            .is_none());
+    }
+}
@@ -0,0 +1,124 @@
+//! Retries with jittered exponential backoff for the HTTP commands. Transient
+//! network errors (connect failures, timeouts) and selected status codes are
+//! retried; a longer `Retry-After` from the server wins over the computed delay.
+
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+use log::info;
+use reqwest::{RequestBuilder, Response};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::helpers::pace;
+
+/// Longest single wait between attempts.
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// How many times to retry, how long to wait, and which statuses count as transient.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub base: Duration,
+    pub statuses: Vec<u16>,
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (0-based): `base * 2^attempt`, capped, with
+    /// "equal jitter" so the wait lands between half and all of that value.
+    /// `jitter` is a fraction in `[0, 1)`.
+    pub fn backoff(&self, attempt: u32, jitter: f64) -> Duration {
+        let full = self
+            .base
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_DELAY);
+        full / 2 + full.mul_f64(jitter.clamp(0.0, 1.0) / 2.0)
+    }
+
+    fn retry_status(&self, status: u16) -> bool {
+        self.statuses.contains(&status)
+    }
+}
+
+/// Cheap jitter source; good enough to spread out clients, not for anything secret.
+fn jitter() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    f64::from(nanos % 1_000_000) / 1_000_000.0
+}
+
+fn transient(err: &reqwest::Error) -> bool {
+    err.is_connect() || err.is_timeout()
+}
+
+/// Send `req`, retrying per `policy` and reporting each retry on the spinner.
+/// Requests with streaming bodies cannot be cloned and are sent only once.
+pub async fn send(req: RequestBuilder, policy: &RetryPolicy, pb: &ProgressBar) -> Result<Response> {
+    let mut attempt = 0;
+    loop {
+        let retry_req = if attempt < policy.retries {
+            req.try_clone()
+        } else {
+            None
+        };
+        let Some(this) = retry_req else {
+            return req
+                .send()
+                .await
+                .context("Network error while sending request");
+        };
+
+        let (reason, server_wait) = match this.send().await {
+            Ok(resp) if policy.retry_status(resp.status().as_u16()) => {
+                let rl = pace::parse(resp.headers(), pace::now_epoch());
+                (format!("status {}", resp.status()), rl.retry_after)
+            }
+            Ok(resp) => return Ok(resp),
+            Err(e) if transient(&e) => (e.to_string(), None),
+            Err(e) => return Err(e).context("Network error while sending request"),
+        };
+
+        let delay = policy
+            .backoff(attempt, jitter())
+            .max(server_wait.unwrap_or_default().min(MAX_DELAY));
+        attempt += 1;
+        let msg = format!(
+            "Retry {attempt}/{} in {:.1}s ({reason})",
+            policy.retries,
+            delay.as_secs_f64()
+        );
+        info!("{msg}");
+        pb.set_message(msg);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            retries: 5,
+            base: Duration::from_secs(1),
+            statuses: vec![500, 503],
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_with_jitter_bounds() {
+        let p = policy();
+        assert_eq!(p.backoff(0, 0.0), Duration::from_millis(500));
+        let d = p.backoff(0, 0.99);
+        assert!(d > Duration::from_millis(990) && d < Duration::from_secs(1));
+        assert_eq!(p.backoff(3, 0.0), Duration::from_secs(4));
+        assert_eq!(p.backoff(20, 1.0), MAX_DELAY);
+    }
+
+    #[test]
+    fn test_retry_status() {
+        let p = policy();
+        assert!(p.retry_status(503));
+        assert!(!p.retry_status(404));
+    }
+}
@@ -1,4 +1,4 @@
-//! Spinner helper: a minimal, readable spinner for async tasks.
+//! Spinner helpers: a minimal, readable spinner for async tasks and byte/count progress bars.
 
 use indicatif::{ProgressBar, ProgressStyle};
 use std::time::Duration;
@@ -15,3 +15,23 @@ pub fn spinner(msg: &str) -> ProgressBar {
     pb.set_message(msg.to_string());
     pb
 }
+
+/// Create a byte progress bar for transfers whose total size is known.
+pub fn bar(total: u64) -> ProgressBar {
+    let bar = ProgressBar::new(total);
+    bar.set_style(
+        ProgressStyle::with_template("{bar:40.cyan/blue} {bytes}/{total_bytes} ({eta})")
+            .expect("valid progress template"),
+    );
+    bar
+}
+
+/// Create a progress bar counting completed items (e.g. requests).
+pub fn counter(total: u64) -> ProgressBar {
+    let bar = ProgressBar::new(total);
+    bar.set_style(
+        ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} ({per_sec})")
+            .expect("valid progress template"),
+    );
+    bar
+}
@@ -1,6 +1,7 @@
 //! Tiny styling helpers: enable ANSI on Windows and provide a few colored lines.
 
 use atty::Stream;
+#[cfg(windows)]
 use nu_ansi_term; // used just to enable ANSI on Windows
 use owo_colors::OwoColorize;
 
@@ -12,7 +13,6 @@ pub fn init_colors() {
 
 /// Check if stdout is a TTY (used by colored_json to auto-disable colors).
 #[inline]
-#[allow(dead_code)] // Available for future use
 pub fn is_tty() -> bool {
     atty::is(Stream::Stdout)
 }
@@ -28,13 +28,27 @@ pub fn ok(msg: &str) {
 }
 
 /// Print a yellow warning line.
-#[allow(dead_code)] // Available for future use
 pub fn warn_line(msg: &str) {
     println!("{}", msg.yellow().bold());
 }
 
+/// Print a `name: value` header line with the name highlighted.
+pub fn header(name: &str, value: &str) {
+    println!("{}: {value}", name.cyan());
+}
+
 /// Print a red error line to stderr.
 #[allow(dead_code)] // Available for future use
 pub fn err_line(msg: &str) {
     eprintln!("{}", msg.red().bold());
 }
+
+/// An HTTP status code colored by class: 2xx green, 3xx cyan, 4xx yellow, else red.
+pub fn status_colored(code: u16) -> String {
+    match code {
+        200..=299 => code.green().to_string(),
+        300..=399 => code.cyan().to_string(),
+        400..=499 => code.yellow().to_string(),
+        _ => code.red().to_string(),
+    }
+}
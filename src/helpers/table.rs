@@ -0,0 +1,133 @@
+//! Table helper: left-aligned columns sized by display width, ignoring ANSI color codes.
+
+use owo_colors::OwoColorize;
+use unicode_width::UnicodeWidthStr;
+
+/// Byte length of the ANSI escape sequence at the start of `s`, if there is one:
+/// CSI (`ESC [ ... final`), OSC (`ESC ] ... BEL` or `ESC \`, e.g. hyperlinks),
+/// a charset designation (`ESC ( B`), or any other two-byte escape.
+pub fn ansi_len(s: &str) -> Option<usize> {
+    let b = s.as_bytes();
+    if b.first() != Some(&0x1b) {
+        return None;
+    }
+    match b.get(1)? {
+        b'[' => {
+            // Parameters and intermediates, then one final byte in @..~.
+            let end = b[2..].iter().position(|c| (0x40..=0x7e).contains(c))?;
+            Some(end + 3)
+        }
+        b']' => {
+            let mut i = 2;
+            while i < b.len() {
+                match b[i] {
+                    0x07 => return Some(i + 1),
+                    0x1b if b.get(i + 1) == Some(&b'\\') => return Some(i + 2),
+                    _ => i += 1,
+                }
+            }
+            Some(b.len())
+        }
+        b'(' | b')' => Some(if b.get(2).is_some_and(u8::is_ascii) {
+            3
+        } else {
+            2
+        }),
+        c if c.is_ascii() => Some(2),
+        _ => Some(1),
+    }
+}
+
+/// Remove ANSI escape sequences (colors, cursor moves, OSC hyperlinks) from a string.
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        match ansi_len(rest) {
+            Some(n) => rest = &rest[n..],
+            None => {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    out
+}
+
+/// Display width of a string as it appears in a terminal.
+pub fn display_width(s: &str) -> usize {
+    strip_ansi(s).width()
+}
+
+/// Render a table with a bold header row. Returns lines without trailing newlines.
+pub fn render(header: &[&str], rows: &[Vec<String>]) -> Vec<String> {
+    let cols = header.len();
+    let mut widths: Vec<usize> = header.iter().map(|h| h.width()).collect();
+    for row in rows {
+        for (i, cell) in row.iter().enumerate().take(cols) {
+            widths[i] = widths[i].max(display_width(cell));
+        }
+    }
+
+    let line = |cells: Vec<String>| -> String {
+        let mut out = String::new();
+        for (i, cell) in cells.iter().enumerate() {
+            out.push_str(cell);
+            if i + 1 < cells.len() {
+                out.push_str(&" ".repeat(widths[i] - display_width(cell) + 2));
+            }
+        }
+        out.trim_end().to_string()
+    };
+
+    let mut lines = Vec::with_capacity(rows.len() + 1);
+    lines.push(line(
+        header
+            .iter()
+            .map(|h| h.bold().to_string())
+            .collect::<Vec<_>>(),
+    ));
+    for row in rows {
+        let mut cells = row.clone();
+        cells.resize(cols, String::new());
+        lines.push(line(cells));
+    }
+    lines
+}
+
+/// Print a table to stdout.
+pub fn print(header: &[&str], rows: &[Vec<String>]) {
+    for line in render(header, rows) {
+        println!("{line}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi("\u{1b}[1m\u{1b}[31mhi\u{1b}[0m"), "hi");
+        assert_eq!(display_width("\u{1b}[32mok\u{1b}[39m ✓"), 4);
+        assert_eq!(strip_ansi("\u{1b}[2K\u{1b}[1~x"), "x");
+        assert_eq!(
+            strip_ansi("\u{1b}]8;;https://ex.com\u{7}link\u{1b}]8;;\u{1b}\\"),
+            "link"
+        );
+        assert_eq!(strip_ansi("a\u{1b}(Bb"), "ab");
+    }
+
+    #[test]
+    fn test_render_aligns_columns() {
+        let rows = vec![
+            vec!["a".to_string(), "1".to_string()],
+            vec!["long".to_string(), "2".to_string()],
+        ];
+        let lines: Vec<String> = render(&["k", "v"], &rows)
+            .iter()
+            .map(|l| strip_ansi(l))
+            .collect();
+        assert_eq!(lines, vec!["k     v", "a     1", "long  2"]);
+    }
+}
@@ -0,0 +1,107 @@
+//! `{{NAME}}` request templating, shared by the HTTP verb commands (`--var`)
+//! and `.http` request files. Only identifier-like names are placeholders, so
+//! JSON bodies and other text with braces pass through untouched.
+
+use anyhow::{bail, Context, Result};
+
+/// Parse `--var NAME=VALUE` flags.
+pub fn parse_vars(flags: &[String]) -> Result<Vec<(String, String)>> {
+    flags
+        .iter()
+        .map(|f| {
+            let (k, v) = f
+                .split_once('=')
+                .with_context(|| format!("--var must be NAME=VALUE, got: {f}"))?;
+            Ok((k.trim().to_string(), v.to_string()))
+        })
+        .collect()
+}
+
+/// `{{ name }}` or `{{$processEnv NAME}}` with the braces removed and trimmed,
+/// if `inner` is a placeholder at all.
+fn placeholder(inner: &str) -> Option<&str> {
+    let key = inner.trim();
+    let name = key.strip_prefix("$processEnv").map_or(key, str::trim);
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    valid.then_some(key)
+}
+
+/// Replace `{{name}}` from `vars` (later entries win), falling back to the
+/// environment variable `name`; `{{$processEnv NAME}}` reads only the environment.
+/// Variable values may themselves refer to variables.
+pub fn substitute(text: &str, vars: &[(String, String)]) -> Result<String> {
+    expand(text, vars, 0)
+}
+
+fn expand(text: &str, vars: &[(String, String)], depth: usize) -> Result<String> {
+    if depth > 10 {
+        bail!("Variables refer to each other in a loop");
+    }
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let Some(key) = placeholder(&rest[start + 2..start + end]) else {
+            out.push_str(&rest[start..start + end + 2]);
+            rest = &rest[start + end + 2..];
+            continue;
+        };
+        let value = if let Some(env) = key.strip_prefix("$processEnv") {
+            let env = env.trim();
+            std::env::var(env).with_context(|| format!("Environment variable {env} is not set"))?
+        } else if let Some((_, raw)) = vars.iter().rev().find(|(n, _)| n == key) {
+            expand(raw, vars, depth + 1)?
+        } else {
+            std::env::var(key).with_context(|| {
+                format!("Undefined variable {{{{{key}}}}} (set it with --var {key}=... or in the environment)")
+            })?
+        };
+        out.push_str(&value);
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        let vars = parse_vars(&[
+            "host=https://{{domain}}".into(),
+            "domain=example.com".into(),
+            "domain=api.example.com".into(),
+        ])
+        .unwrap();
+        assert_eq!(
+            substitute("{{ host }}/x", &vars).unwrap(),
+            "https://api.example.com/x"
+        );
+        assert!(substitute("{{missing_swiftline_var}}", &vars).is_err());
+        let looped = vec![("a".to_string(), "{{a}}".to_string())];
+        assert!(substitute("{{a}}", &looped).is_err());
+        assert!(parse_vars(&["novalue".into()]).is_err());
+    }
+
+    #[test]
+    fn test_non_placeholders_pass_through() {
+        let vars = parse_vars(&["id=7".into()]).unwrap();
+        assert_eq!(
+            substitute(r#"{"q": "{{ a b }}", "id": {{id}}, "t": "{{"}"#, &vars).unwrap(),
+            r#"{"q": "{{ a b }}", "id": 7, "t": "{{"}"#
+        );
+        let path = std::env::var("PATH").unwrap();
+        assert_eq!(
+            substitute("{{PATH}} {{$processEnv PATH}}", &[]).unwrap(),
+            format!("{path} {path}")
+        );
+    }
+}
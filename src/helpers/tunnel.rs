@@ -0,0 +1,274 @@
+//! Public URLs for local listeners. A tunnel provider is a command that exposes
+//! `localhost:{port}` and prints its public URL; the URL is picked out of the
+//! command's output with a regex. `cloudflared`, `ngrok`, and `bore` are built in,
+//! and more (such as a self-hosted relay) are added in the config file:
+//!
+//! ```toml
+//! [tunnel]
+//! provider = "relay"            # used by a bare --tunnel
+//!
+//! [tunnels.relay]
+//! command = "bore local {port} --to relay.example.com"
+//! pattern = 'listening at (\S+)'   # first capture group (or the whole match)
+//! public_url = "http://{match}"    # optional; defaults to the match itself
+//! ```
+
+use anyhow::{bail, Context, Result};
+use log::debug;
+use regex::Regex;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+
+use crate::commands::http_curl::split_command;
+
+/// How long a provider may take to print its URL.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Matches the first http(s) URL in a line.
+const ANY_URL: &str = r#"https?://[^\s"'<>|]+"#;
+
+/// Built-in providers: name, command, pattern, public URL template.
+const BUILT_IN: &[(&str, &str, &str, Option<&str>)] = &[
+    (
+        "cloudflared",
+        "cloudflared tunnel --no-autoupdate --url http://localhost:{port}",
+        r"https://[a-z0-9-]+\.trycloudflare\.com",
+        None,
+    ),
+    (
+        "ngrok",
+        "ngrok http {port} --log stdout --log-format logfmt",
+        r"url=(https://\S+)",
+        None,
+    ),
+    (
+        "bore",
+        "bore local {port} --to bore.pub",
+        r"listening at (\S+)",
+        Some("http://{match}"),
+    ),
+];
+
+/// A resolved tunnel provider.
+#[derive(Debug)]
+pub struct Provider {
+    pub name: String,
+    command: String,
+    pattern: Regex,
+    public_url: Option<String>,
+}
+
+/// A running tunnel; the provider process is stopped when this is dropped.
+pub struct Tunnel {
+    pub url: String,
+    _child: Child,
+}
+
+impl Provider {
+    /// The provider called `name`, or the default one when `name` is empty:
+    /// `[tunnel] provider`, else the only `[tunnels.*]` entry. Config entries
+    /// override built-ins of the same name.
+    pub fn resolve(name: &str, config: &toml::Table) -> Result<Provider> {
+        let tables = config.get("tunnels").and_then(|t| t.as_table());
+        let name = match name {
+            "" => match config
+                .get("tunnel")
+                .and_then(|t| t.get("provider"))
+                .and_then(|p| p.as_str())
+            {
+                Some(p) => p.to_string(),
+                None => match tables.map(|t| t.keys().collect::<Vec<_>>()) {
+                    Some(keys) if keys.len() == 1 => keys[0].clone(),
+                    _ => bail!(
+                        "No default tunnel provider; name one (--tunnel {}) or set [tunnel] provider in {}",
+                        Self::names(config).join("|"),
+                        super::config::path()?.display()
+                    ),
+                },
+            },
+            n => n.to_string(),
+        };
+
+        if let Some(entry) = tables.and_then(|t| t.get(&name)) {
+            let field = |key: &str| entry.get(key).and_then(|v| v.as_str());
+            let command =
+                field("command").with_context(|| format!("[tunnels.{name}] needs a command"))?;
+            let pattern = field("pattern").unwrap_or(ANY_URL);
+            return Ok(Provider {
+                pattern: Regex::new(pattern)
+                    .with_context(|| format!("Invalid pattern in [tunnels.{name}]"))?,
+                command: command.to_string(),
+                public_url: field("public_url").map(str::to_string),
+                name,
+            });
+        }
+        match BUILT_IN.iter().find(|(n, ..)| *n == name) {
+            Some((_, command, pattern, public_url)) => Ok(Provider {
+                name,
+                command: command.to_string(),
+                pattern: Regex::new(pattern)?,
+                public_url: public_url.map(str::to_string),
+            }),
+            None => bail!(
+                "Unknown tunnel provider {name:?} (known: {})",
+                Self::names(config).join(", ")
+            ),
+        }
+    }
+
+    /// Built-in and configured provider names.
+    fn names(config: &toml::Table) -> Vec<String> {
+        let mut names: Vec<String> = BUILT_IN.iter().map(|(n, ..)| n.to_string()).collect();
+        if let Some(t) = config.get("tunnels").and_then(|t| t.as_table()) {
+            names.extend(
+                t.keys()
+                    .filter(|k| !names.contains(k))
+                    .cloned()
+                    .collect::<Vec<_>>(),
+            );
+        }
+        names
+    }
+
+    /// The public URL announced in one line of provider output, if any.
+    fn extract(&self, line: &str) -> Option<String> {
+        let caps = self.pattern.captures(line)?;
+        let found = caps.get(1).or_else(|| caps.get(0))?.as_str();
+        Some(match &self.public_url {
+            Some(template) => template.replace("{match}", found),
+            None => found.to_string(),
+        })
+    }
+
+    /// Start the provider for `localhost:port` and wait for its public URL.
+    pub async fn open(&self, port: u16) -> Result<Tunnel> {
+        let argv = split_command(&self.command.replace("{port}", &port.to_string()))?;
+        let (program, args) = argv.split_first().context("Empty tunnel command")?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Cannot start tunnel provider {}: {program}", self.name))?;
+
+        // Providers log to either stream; keep draining both so they never block.
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        if let Some(out) = child.stdout.take() {
+            drain(BufReader::new(out), tx.clone());
+        }
+        if let Some(err) = child.stderr.take() {
+            drain(BufReader::new(err), tx);
+        }
+
+        let mut last = Vec::new();
+        let wait = async {
+            while let Some(line) = rx.recv().await {
+                if let Some(url) = self.extract(&line) {
+                    return Some(url);
+                }
+                last.push(line);
+            }
+            None
+        };
+        let url = match tokio::time::timeout(STARTUP_TIMEOUT, wait).await {
+            Ok(Some(url)) => url,
+            Ok(None) => bail!(
+                "Tunnel provider {} exited without a public URL:\n{}",
+                self.name,
+                last.iter()
+                    .rev()
+                    .take(5)
+                    .rev()
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            Err(_) => bail!(
+                "Tunnel provider {} printed no public URL within {}s",
+                self.name,
+                STARTUP_TIMEOUT.as_secs()
+            ),
+        };
+        // Keep logging what the provider says for the rest of the session.
+        let name = self.name.clone();
+        tokio::spawn(async move {
+            while let Some(line) = rx.recv().await {
+                debug!("{name}: {line}");
+            }
+        });
+        Ok(Tunnel { url, _child: child })
+    }
+}
+
+/// Forward each line of `reader` to `tx` until it closes.
+fn drain<R: AsyncBufRead + Unpin + Send + 'static>(reader: R, tx: mpsc::UnboundedSender<String>) {
+    tokio::spawn(async move {
+        let mut lines = reader.lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_extract() {
+        let config: toml::Table = toml::from_str(
+            r#"
+            [tunnels.relay]
+            command = "bore local {port} --to relay.example.com"
+            pattern = 'listening at (\S+)'
+            public_url = "https://{match}"
+            "#,
+        )
+        .unwrap();
+        let relay = Provider::resolve("", &config).unwrap();
+        assert_eq!(relay.name, "relay");
+        assert_eq!(
+            relay.extract("2026-10-16 INFO listening at relay.example.com:41235"),
+            Some("https://relay.example.com:41235".into())
+        );
+        assert_eq!(relay.extract("connected to server"), None);
+
+        let cf = Provider::resolve("cloudflared", &config).unwrap();
+        assert_eq!(
+            cf.extract("INF |  https://quiet-lake-1234.trycloudflare.com  |"),
+            Some("https://quiet-lake-1234.trycloudflare.com".into())
+        );
+        let ngrok = Provider::resolve("ngrok", &toml::Table::new()).unwrap();
+        assert_eq!(
+            ngrok.extract("lvl=info msg=\"started tunnel\" url=https://ab12.ngrok-free.app"),
+            Some("https://ab12.ngrok-free.app".into())
+        );
+        assert!(Provider::resolve("", &toml::Table::new()).is_err());
+        assert!(Provider::resolve("nope", &config).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_open_reads_url_from_output() {
+        let config: toml::Table = toml::from_str(
+            r#"
+            [tunnels.echo]
+            command = "sh -c 'echo starting >&2; echo ready at https://t.example/{port}; sleep 5'"
+            "#,
+        )
+        .unwrap();
+        let tunnel = Provider::resolve("echo", &config)
+            .unwrap()
+            .open(9000)
+            .await
+            .unwrap();
+        assert_eq!(tunnel.url, "https://t.example/9000");
+    }
+}
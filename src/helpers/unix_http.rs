@@ -0,0 +1,135 @@
+//! Minimal HTTP/1.1 GET over a Unix domain socket, for local daemon APIs such as
+//! the Docker Engine that reqwest cannot reach. One request per connection.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::helpers::chunked;
+
+/// Status code and (de-chunked) body of a response.
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Split a raw response into status and body, honoring chunked and Content-Length framing.
+fn parse_response(raw: &[u8]) -> Result<Response> {
+    let end = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .context("Incomplete HTTP response")?;
+    let head = String::from_utf8_lossy(&raw[..end]);
+    let body = &raw[end + 4..];
+    let mut lines = head.split("\r\n");
+    let status_line = lines.next().unwrap_or_default();
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .with_context(|| format!("Malformed status line: {status_line}"))?;
+
+    let mut chunked = false;
+    let mut length = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.eq_ignore_ascii_case("chunked");
+        } else if name.eq_ignore_ascii_case("content-length") {
+            length = value.parse::<usize>().ok();
+        }
+    }
+    let body = if chunked {
+        chunked::decode(body)?
+    } else if let Some(n) = length {
+        body.get(..n).context("Truncated HTTP body")?.to_vec()
+    } else {
+        body.to_vec()
+    };
+    Ok(Response { status, body })
+}
+
+/// GET `path` (with query) from the HTTP server listening on `socket`.
+#[cfg(unix)]
+pub async fn get(socket: &Path, path: &str, timeout: Duration) -> Result<Response> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let exchange = async {
+        let mut stream = tokio::net::UnixStream::connect(socket)
+            .await
+            .with_context(|| format!("Cannot connect to {}", socket.display()))?;
+        let request =
+            format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nUser-Agent: swiftline\r\nConnection: close\r\n\r\n");
+        stream.write_all(request.as_bytes()).await?;
+        let mut raw = Vec::new();
+        stream.read_to_end(&mut raw).await?;
+        parse_response(&raw)
+    };
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .with_context(|| {
+            format!(
+                "No response from {} within {:.1}s",
+                socket.display(),
+                timeout.as_secs_f64()
+            )
+        })?
+}
+
+#[cfg(not(unix))]
+pub async fn get(socket: &Path, _path: &str, _timeout: Duration) -> Result<Response> {
+    anyhow::bail!(
+        "Unix sockets are not supported on this platform ({})",
+        socket.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response_framing() {
+        let plain =
+            parse_response(b"HTTP/1.1 404 Not Found\r\nContent-Length: 2\r\n\r\n{}extra").unwrap();
+        assert_eq!((plain.status, plain.body.as_slice()), (404, &b"{}"[..]));
+
+        let chunked = parse_response(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n[1,2\r\n2;x=y\r\n,3\r\n1\r\n]\r\n0\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(chunked.body, b"[1,2,3]");
+
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_err());
+        assert!(
+            parse_response(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nff\r\nab")
+                .is_err()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_get_over_socket() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let path = std::env::temp_dir().join(format!("swiftline-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 512];
+            let n = conn.read(&mut buf).await.unwrap();
+            assert!(buf[..n].starts_with(b"GET /_ping HTTP/1.1\r\n"));
+            conn.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK")
+                .await
+                .unwrap();
+        });
+        let resp = get(&path, "/_ping", Duration::from_secs(5)).await.unwrap();
+        assert_eq!((resp.status, resp.body.as_slice()), (200, &b"OK"[..]));
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,186 @@
+//! `--format` templates for HTTP commands, like curl's `-w` but with JSON
+//! paths: `{status} {url} {time_ms}ms {json:data.id}` prints one summary
+//! line instead of the response. `{{` and `}}` are literal braces.
+
+use anyhow::{bail, Result};
+use reqwest::header::{HeaderMap, CONTENT_TYPE};
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+use std::time::Duration;
+use url::Url;
+
+use crate::commands::json_select::get_by_path;
+
+/// Placeholders a template may use.
+#[derive(Debug)]
+enum Field {
+    Status,
+    Method,
+    Url,
+    TimeMs,
+    Size,
+    ContentType,
+    Header(String),
+    Json(String),
+}
+
+#[derive(Debug)]
+enum Part {
+    Text(String),
+    Field(Field),
+}
+
+/// A parsed `--format` string.
+#[derive(Debug)]
+pub struct Template(Vec<Part>);
+
+/// What a template can refer to, gathered once the body has been read.
+pub struct Response<'a> {
+    pub method: &'a Method,
+    pub url: &'a Url,
+    pub status: StatusCode,
+    pub headers: &'a HeaderMap,
+    pub body: &'a [u8],
+    pub elapsed: Duration,
+}
+
+fn field(name: &str) -> Result<Field> {
+    Ok(match name.trim() {
+        "status" => Field::Status,
+        "method" => Field::Method,
+        "url" => Field::Url,
+        "time_ms" => Field::TimeMs,
+        "size" => Field::Size,
+        "content_type" => Field::ContentType,
+        other => match other.split_once(':') {
+            Some(("header", h)) if !h.trim().is_empty() => Field::Header(h.trim().to_string()),
+            Some(("json", p)) if !p.trim().is_empty() => Field::Json(p.trim().to_string()),
+            _ => bail!(
+                "Unknown --format placeholder {{{other}}} (expected status, method, url, \
+                 time_ms, size, content_type, header:NAME, or json:PATH)"
+            ),
+        },
+    })
+}
+
+impl Template {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => bail!("Unclosed {{ in --format: {text}"),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(field(&name)?));
+                }
+                '}' => bail!("Unmatched }} in --format (write }}}} for a literal brace): {text}"),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Text(literal));
+        }
+        Ok(Template(parts))
+    }
+
+    /// Fill in the placeholders; missing headers and JSON paths print as `-`.
+    pub fn render(&self, resp: &Response) -> String {
+        let needs_json = self
+            .0
+            .iter()
+            .any(|p| matches!(p, Part::Field(Field::Json(_))));
+        let json: Option<Value> = needs_json
+            .then(|| serde_json::from_slice(resp.body).ok())
+            .flatten();
+        let mut out = String::new();
+        for part in &self.0 {
+            match part {
+                Part::Text(t) => out.push_str(t),
+                Part::Field(f) => out.push_str(&value(f, resp, json.as_ref())),
+            }
+        }
+        out
+    }
+}
+
+fn value(field: &Field, resp: &Response, json: Option<&Value>) -> String {
+    let header = |name: &str| {
+        resp.headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map_or_else(|| "-".to_string(), str::to_string)
+    };
+    match field {
+        Field::Status => resp.status.as_u16().to_string(),
+        Field::Method => resp.method.to_string(),
+        Field::Url => resp.url.to_string(),
+        Field::TimeMs => resp.elapsed.as_millis().to_string(),
+        Field::Size => resp.body.len().to_string(),
+        Field::ContentType => header(CONTENT_TYPE.as_str()),
+        Field::Header(name) => header(name),
+        Field::Json(path) => match json.and_then(|j| get_by_path(j, path)) {
+            Some(v) => match v.as_ref() {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            },
+            None => "-".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_parse_and_render() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("abc"));
+        let url = Url::parse("https://api.example.com/items/7").unwrap();
+        let resp = Response {
+            method: &Method::GET,
+            url: &url,
+            status: StatusCode::OK,
+            headers: &headers,
+            body: br#"{"data": {"id": 7, "name": "x", "tags": ["a"]}}"#,
+            elapsed: Duration::from_millis(42),
+        };
+        let t = Template::parse(
+            "{status} {method} {url} {time_ms}ms {json:data.id} {json:data.name} \
+             {json:data.tags} {json:nope} {header:X-Request-Id} {content_type} {{ok}}",
+        )
+        .unwrap();
+        assert_eq!(
+            t.render(&resp),
+            r#"200 GET https://api.example.com/items/7 42ms 7 x ["a"] - abc - {ok}"#
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Template::parse("{bogus}").is_err());
+        assert!(Template::parse("{status").is_err());
+        assert!(Template::parse("status}").is_err());
+        assert!(Template::parse("{json:}").is_err());
+    }
+}
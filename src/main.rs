@@ -4,15 +4,36 @@ use anyhow::Result;
 use clap::{CommandFactory, Parser};
 use env_logger::Env;
 use log::debug;
+use std::process::ExitCode;
 
 mod cli;
 mod commands;
 mod helpers;
 
-use cli::{Cli, Commands, HttpCommands, JsonCommands};
+use cli::{
+    AvroCommands, BinCommands, CertCommands, Cli, CloudCommands, Commands, DbCommands,
+    DepsCommands, DockerCommands, ForgeApi, ForgeCommands, HtmlCommands, HttpCommands,
+    JsonCommands, KafkaCommands, LogsCommands, MdCommands, NetCommands, NumCommands, OciCommands,
+    OpenapiCommands, RandCommands, RegistryCommands, S3Commands, S3Target, SbomCommands,
+    SqliteCommands, SshCommands, TextCommands, UdpCommands, UrlCommands, VerifyCommands,
+};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            // Budget overruns get their own code so cron jobs can tell slow from broken.
+            match e.downcast_ref::<commands::http_request::OverBudget>() {
+                Some(_) => ExitCode::from(commands::http_request::EXIT_OVER_BUDGET),
+                None => ExitCode::FAILURE,
+            }
+        }
+    }
+}
+
+async fn run() -> Result<()> {
     // Enable ANSI colors on Windows and set small style helpers.
     helpers::style::init_colors();
 
@@ -38,21 +59,873 @@ async fn main() -> Result<()> {
             Ok(())
         }
 
-        // http get <...>
+        // http get <url> | --url-file <file> --save-dir <dir> [--parallel N]
         Some(Commands::Http(HttpCommands::Get {
             url,
+            url_file,
+            save_dir,
+            parallel,
+            rate,
+            cache,
+            cache_ttl,
+            request,
+        })) => match (url, url_file, save_dir) {
+            (_, Some(file), Some(dir)) => {
+                commands::http_batch::run(&file, parallel, rate, dir, request).await
+            }
+            (Some(url), _, _) => {
+                let cache = cache.then_some(helpers::http_cache::Policy { ttl: cache_ttl });
+                commands::http_request::run(reqwest::Method::GET, &url, None, request, cache).await
+            }
+            _ => Err(anyhow::anyhow!(
+                "Provide a URL, or --url-file with --save-dir"
+            )),
+        },
+
+        // http post <url> [--data <...> | --data-file <...> | stdin]
+        Some(Commands::Http(HttpCommands::Post { url, body, request })) => {
+            commands::http_request::run(reqwest::Method::POST, &url, Some(&body), request, None)
+                .await
+        }
+
+        // http graphql <url> --query '...' [--variables JSON]
+        Some(Commands::Http(HttpCommands::Graphql {
+            url,
+            query,
+            query_file,
+            variables,
+            operation,
+            request,
+        })) => {
+            let source = match (query, query_file) {
+                (Some(q), _) => commands::http_graphql::QuerySource::Inline(q),
+                (None, Some(path)) => commands::http_graphql::QuerySource::File(path),
+                (None, None) => commands::http_graphql::QuerySource::Stdin,
+            };
+            commands::http_graphql::run(
+                &url,
+                source,
+                variables.as_deref(),
+                operation.as_deref(),
+                request,
+            )
+            .await
+        }
+
+        // http request <METHOD> <url> [--data <...> | --data-file <...>]
+        Some(Commands::Http(HttpCommands::Request {
+            method,
+            url,
+            body,
+            request,
+        })) => {
+            let method = commands::http_request::parse_method(&method)?;
+            commands::http_request::run(method, &url, Some(&body), request, None).await
+        }
+
+        // http openapi <spec> list | call <operationId> [--param k=v]...
+        Some(Commands::Http(HttpCommands::Openapi { spec, action })) => {
+            let spec = commands::http_openapi::Spec::load(&spec).await?;
+            match action {
+                OpenapiCommands::List => commands::http_openapi::run_list(&spec),
+                OpenapiCommands::Call {
+                    operation,
+                    params,
+                    server,
+                    body,
+                    request,
+                } => {
+                    commands::http_openapi::run_call(
+                        &spec,
+                        &operation,
+                        &params,
+                        server.as_deref(),
+                        body,
+                        request,
+                    )
+                    .await
+                }
+            }
+        }
+
+        // http run <file> [name] [--var k=v]
+        Some(Commands::Http(HttpCommands::Run {
+            file,
+            name,
+            request,
+        })) => commands::http_file::run(&file, name.as_deref(), request).await,
+
+        // http new (interactive)
+        Some(Commands::Http(HttpCommands::New { request })) => {
+            commands::http_new::run(request).await
+        }
+
+        // http replay --har <file> [--entry N] [--set-header "K: V"] [--set-query k=v]...
+        Some(Commands::Http(HttpCommands::Replay {
+            har,
+            entry,
+            set_header,
+            drop_header,
+            set_query,
+            method,
+            base_url,
+            body,
+            request,
+        })) => {
+            let overrides = commands::http_replay::Overrides {
+                method,
+                base_url,
+                set_headers: set_header,
+                drop_headers: drop_header,
+                set_query,
+            };
+            commands::http_replay::run(&har, entry, overrides, body, request).await
+        }
+
+        // http import-curl "curl ..." [--print]
+        Some(Commands::Http(HttpCommands::ImportCurl { command, print })) => {
+            commands::http_curl::run_import(command, print).await
+        }
+
+        // http audit <url> [--json]
+        Some(Commands::Http(HttpCommands::Audit { url, timeout, json })) => {
+            commands::http_audit::run(&url, timeout, json).await
+        }
+
+        // http cors <url> --origin <...> [--method PUT] [--header X-Custom]...
+        Some(Commands::Http(HttpCommands::Cors {
+            url,
+            origin,
+            method,
             headers,
+            credentials,
             timeout,
-            save,
-            pretty,
-        })) => commands::http_get::run(&url, &headers, timeout, save, pretty).await,
+        })) => commands::http_cors::run(&url, origin, method, headers, credentials, timeout).await,
+
+        // http metrics <url> [--filter <selector>] [--agg sum --by label]
+        Some(Commands::Http(HttpCommands::Metrics {
+            url,
+            filter,
+            agg,
+            by,
+            output,
+            timeout,
+        })) => commands::http_metrics::run(&url, filter, agg, by, output, timeout).await,
+
+        // http bench <url> -n <requests> -c <concurrency>
+        Some(Commands::Http(HttpCommands::Bench {
+            url,
+            requests,
+            concurrency,
+            method,
+            headers,
+            data,
+            timeout,
+            rate,
+            output,
+        })) => {
+            let opts = commands::http_bench::BenchOptions {
+                method: commands::http_request::parse_method(&method)?,
+                headers,
+                data,
+                requests,
+                concurrency,
+                timeout,
+                rate,
+            };
+            commands::http_bench::run(&url, opts, output).await
+        }
+
+        // http snapshot <url> [--store dir] [--update]
+        Some(Commands::Http(HttpCommands::Snapshot {
+            url,
+            store,
+            update,
+            timeout,
+        })) => commands::http_snapshot::run(&url, store, update, timeout).await,
+
+        // http serve [dir] --port 8080 [--bind 0.0.0.0]
+        Some(Commands::Http(HttpCommands::Serve { dir, port, bind })) => {
+            commands::http_serve::run(dir, port, bind).await
+        }
+
+        // http listen [--port N] [--forward <url>] [--respond-status N --respond-body @f] [--tunnel]
+        Some(Commands::Http(HttpCommands::Listen {
+            port,
+            bind,
+            forward,
+            retry,
+            timeout,
+            respond_status,
+            respond_body,
+            respond_delay,
+            tunnel,
+        })) => {
+            let stub = commands::http_listen::Stub {
+                status: respond_status,
+                body: respond_body,
+                delay: respond_delay,
+            };
+            commands::http_listen::run(port, bind, forward, retry, timeout, stub, tunnel).await
+        }
+
+        // http ws <url> [-H ...] [--json] [--keep-open]
+        Some(Commands::Http(HttpCommands::Ws {
+            url,
+            headers,
+            json,
+            keep_open,
+            timeout,
+        })) => commands::http_ws::run(&url, headers, json, keep_open, timeout).await,
 
-        // json select --path <...> [--text <...>] [--file <...>] [--json5]
+        // json select --path <...> [--text <...>] [--file <...>] [--format F] [--json5]
         Some(Commands::Json(JsonCommands::Select {
             text,
             file,
+            format,
             json5,
             path,
-        })) => commands::json_select::run(text, file, json5, path),
+            as_array,
+            raw,
+            compact,
+            count,
+            coerce,
+            copy,
+            jsonpath,
+        })) => {
+            let output = commands::json_select::Output {
+                as_array,
+                raw,
+                compact,
+                count,
+                coerce,
+                copy,
+            };
+            commands::json_select::run(text, file, format, json5, path, jsonpath, output)
+        }
+
+        // json format [--text <...>] [--file <...>] [--indent N | --minify] [--sort-keys] [-i]
+        Some(Commands::Json(JsonCommands::Format {
+            text,
+            file,
+            json5,
+            indent,
+            minify,
+            sort_keys,
+            in_place,
+        })) => {
+            let opts = commands::json_format::Options {
+                indent,
+                minify,
+                sort_keys,
+            };
+            commands::json_format::run(text, file, json5, opts, in_place)
+        }
+
+        // json validate <files/globs>... | --file <...>  [--schema <...>] [--jobs N] [--json5]
+        Some(Commands::Json(JsonCommands::Validate {
+            files,
+            file,
+            schema,
+            jobs,
+            json5,
+        })) => commands::json_validate::run(files, file, schema, jobs, json5),
+
+        // json convert [--text <...>] [--file <...>] [--from F] --to F [--flatten [--separator S]]
+        Some(Commands::Json(JsonCommands::Convert {
+            text,
+            file,
+            from,
+            to,
+            flatten,
+            separator,
+        })) => commands::json_convert::run(text, file, from, to, flatten, &separator),
+
+        // json diff <a> <b> [--patch] [--json5]
+        Some(Commands::Json(JsonCommands::Diff { a, b, patch, json5 })) => {
+            commands::json_diff::run(&a, &b, json5, patch)
+        }
+
+        // json merge <files>... [--arrays replace|concat] [-i]
+        Some(Commands::Json(JsonCommands::Merge {
+            files,
+            arrays,
+            json5,
+            in_place,
+        })) => commands::json_merge::run(files, arrays, json5, in_place),
+
+        // json patch <file> <patch> [--merge-patch] [-i]
+        Some(Commands::Json(JsonCommands::Patch {
+            file,
+            patch,
+            merge_patch,
+            json5,
+            in_place,
+        })) => commands::json_patch::run(file, patch, merge_patch, json5, in_place),
+
+        // json replace <file> --path <...> [--from <...>] --to <...> [-i] [--dry-run]
+        Some(Commands::Json(JsonCommands::Replace {
+            file,
+            path,
+            from,
+            to,
+            json5,
+            in_place,
+            dry_run,
+        })) => {
+            commands::json_replace::run(file, &path, from.as_deref(), &to, json5, in_place, dry_run)
+        }
+
+        // json set --path <...> --value <...> [--text <...>] [--file <...> [-i]]
+        Some(Commands::Json(JsonCommands::Set {
+            text,
+            file,
+            json5,
+            path,
+            value,
+            in_place,
+        })) => commands::json_edit::run_set(text, file, json5, &path, &value, in_place),
+
+        // json delete --path <...>... [--missing-ok] [--text <...>] [--file <...> [-i]]
+        Some(Commands::Json(JsonCommands::Delete {
+            text,
+            file,
+            json5,
+            paths,
+            missing_ok,
+            in_place,
+        })) => commands::json_edit::run_delete(text, file, json5, &paths, missing_ok, in_place),
+
+        // json flatten [--text <...>] [--file <...>] [--output json|lines|env]
+        Some(Commands::Json(JsonCommands::Flatten {
+            text,
+            file,
+            json5,
+            output,
+        })) => commands::json_flatten::run_flatten(text, file, json5, output),
+
+        // json unflatten [--text <...>] [--file <...>]
+        Some(Commands::Json(JsonCommands::Unflatten { text, file, json5 })) => {
+            commands::json_flatten::run_unflatten(text, file, json5)
+        }
+
+        // json inspect [--text <...>] [--file <...>] [--path a.b]
+        Some(Commands::Json(JsonCommands::Inspect {
+            text,
+            file,
+            json5,
+            path,
+        })) => commands::json_inspect::run(text, file, json5, path),
+
+        // json eval --expr <...> [--import name=path]...
+        Some(Commands::Json(JsonCommands::Eval {
+            expr,
+            expr_file,
+            imports,
+            compact,
+        })) => commands::json_eval::run(expr, expr_file, imports, compact),
+
+        // md frontmatter <file> [--output json|yaml|toml] [--set k=v]... [-i]
+        Some(Commands::Md(MdCommands::Frontmatter {
+            file,
+            output,
+            set,
+            in_place,
+        })) => commands::md_frontmatter::run(file, output, set, in_place),
+
+        // html to-md|to-text [file] [--url <...>]
+        Some(Commands::Html(HtmlCommands::ToMd { file, url, timeout })) => {
+            commands::html_convert::run(commands::html_convert::Mode::Markdown, file, url, timeout)
+                .await
+        }
+        Some(Commands::Html(HtmlCommands::ToText { file, url, timeout })) => {
+            commands::html_convert::run(commands::html_convert::Mode::Text, file, url, timeout)
+                .await
+        }
+
+        // bin diff <a> <b> [--quick]
+        Some(Commands::Bin(BinCommands::Diff {
+            a,
+            b,
+            quick,
+            context,
+            max_ranges,
+        })) => commands::bin_diff::run(&a, &b, quick, context, max_ranges),
+
+        // text normalize [files...] [--eol lf|crlf] [--trim-trailing] [--final-newline] [-i]
+        Some(Commands::Text(TextCommands::Normalize {
+            files,
+            eol,
+            trim_trailing,
+            final_newline,
+            in_place,
+        })) => {
+            let opts = commands::text_normalize::Options {
+                eol,
+                trim_trailing,
+                final_newline,
+            };
+            commands::text_normalize::run(files, opts, in_place)
+        }
+
+        // text convert [file] [--from <enc>] [--to <enc>] [--guess] [--bom]
+        Some(Commands::Text(TextCommands::Convert {
+            file,
+            from,
+            to,
+            guess,
+            bom,
+            lossy,
+            output,
+            in_place,
+        })) => {
+            let opts = commands::text_convert::Options {
+                from,
+                to,
+                guess,
+                bom,
+                lossy,
+                output,
+                in_place,
+            };
+            commands::text_convert::run(file, opts)
+        }
+
+        // text sort|uniq|freq [files...]
+        Some(Commands::Text(TextCommands::Sort {
+            files,
+            numeric,
+            reverse,
+            unique,
+            ignore_case,
+        })) => {
+            let opts = commands::text_lines::SortOptions {
+                numeric,
+                reverse,
+                unique,
+                ignore_case,
+            };
+            commands::text_lines::run_sort(files, opts)
+        }
+        Some(Commands::Text(TextCommands::Uniq {
+            files,
+            count,
+            ignore_case,
+            global,
+        })) => commands::text_lines::run_uniq(files, count, ignore_case, global),
+        Some(Commands::Text(TextCommands::Freq {
+            files,
+            top,
+            ignore_case,
+        })) => commands::text_lines::run_freq(files, top, ignore_case),
+
+        // text strip-ansi|truncate [files...]
+        Some(Commands::Text(TextCommands::StripAnsi { files })) => {
+            commands::text_width::run_strip_ansi(files)
+        }
+        Some(Commands::Text(TextCommands::Truncate {
+            files,
+            width,
+            ellipsis,
+        })) => commands::text_width::run_truncate(files, width, ellipsis),
+
+        // net cidr <cidr> [contains <ip> | split <prefix> [--limit N]]
+        Some(Commands::Net(NetCommands::Cidr { cidr, action })) => {
+            commands::net_cidr::run(&cidr, action)
+        }
+
+        // net mac <mac> [--update-oui]
+        Some(Commands::Net(NetCommands::Mac {
+            mac,
+            update_oui,
+            timeout,
+        })) => commands::net_mac::run(mac, update_oui, timeout).await,
+
+        // net udp send <host:port> --data <payload> [--hex] [--wait 2s]
+        Some(Commands::Net(NetCommands::Udp(UdpCommands::Send {
+            target,
+            data,
+            data_file,
+            hex,
+            wait,
+        }))) => {
+            let payload =
+                commands::net_udp::read_payload(data.as_deref(), data_file.as_deref(), hex)?;
+            commands::net_udp::run_send(&target, payload, wait, hex).await
+        }
+
+        // net udp listen <port> [--hex] [--count N]
+        Some(Commands::Net(NetCommands::Udp(UdpCommands::Listen {
+            port,
+            bind,
+            hex,
+            count,
+        }))) => commands::net_udp::run_listen(port, &bind, hex, count).await,
+
+        // net ntp [server] [--max-offset 500ms]
+        Some(Commands::Net(NetCommands::Ntp {
+            server,
+            max_offset,
+            timeout,
+        })) => commands::net_ntp::run(&server, max_offset, timeout).await,
+
+        // net discover [--service _http._tcp] [--timeout 5s]
+        Some(Commands::Net(NetCommands::Discover {
+            service,
+            timeout,
+            output,
+        })) => commands::net_discover::run(service, timeout, output).await,
+
+        // ssh fingerprint <host[:port]> [--compare [known_hosts]]
+        Some(Commands::Ssh(SshCommands::Fingerprint {
+            target,
+            compare,
+            timeout,
+        })) => commands::ssh_fingerprint::run(&target, compare, timeout).await,
+
+        // logs tail --file <app.jsonl> [--path a,b] [--where expr] [--follow] | parse --format <fmt>
+        Some(Commands::Logs(LogsCommands::Tail {
+            file,
+            path,
+            filter,
+            follow,
+            lines,
+        })) => commands::logs_tail::run(&file, path, filter, follow, lines),
+        Some(Commands::Logs(LogsCommands::Parse {
+            files,
+            format,
+            output,
+        })) => commands::logs_parse::run(files, format, output),
+
+        // verify webhook --provider <...> --secret <...> --signature-header <...> --body @file
+        Some(Commands::Verify(VerifyCommands::Webhook {
+            provider,
+            secret,
+            signature_header,
+            body,
+            tolerance,
+        })) => {
+            commands::verify_webhook::run(provider, &secret, &signature_header, &body, tolerance)
+        }
+
+        // verify file <file> --key <pubkey> [--sig <sig>]
+        Some(Commands::Verify(VerifyCommands::File { file, sig, key })) => {
+            commands::verify_sig::run(&file, sig.as_deref(), &key)
+        }
+
+        // forge gh|gl api <path> [--paginate]
+        Some(Commands::Forge(cmd)) => {
+            let (
+                forge,
+                ForgeApi::Api {
+                    path,
+                    method,
+                    data,
+                    paginate,
+                    host,
+                    token,
+                    timeout,
+                },
+            ) = match cmd {
+                ForgeCommands::Gh(api) => (commands::forge::Forge::Github, api),
+                ForgeCommands::Gl(api) => (commands::forge::Forge::Gitlab, api),
+            };
+            let opts = commands::forge::ApiOptions {
+                path,
+                method,
+                data,
+                paginate,
+                host,
+                token,
+                timeout,
+            };
+            commands::forge::run(forge, opts).await
+        }
+
+        // oci tags|manifest|pull-blob <image>
+        Some(Commands::Oci(OciCommands::Tags {
+            image,
+            user,
+            timeout,
+        })) => commands::oci::run_tags(&image, user, timeout).await,
+        Some(Commands::Oci(OciCommands::Manifest {
+            image,
+            digest,
+            user,
+            timeout,
+        })) => commands::oci::run_manifest(&image, user, digest, timeout).await,
+        Some(Commands::Oci(OciCommands::PullBlob {
+            image,
+            output,
+            user,
+            timeout,
+        })) => commands::oci::run_pull_blob(&image, output, user, timeout).await,
+
+        // s3 get|put|ls --endpoint <...> --bucket <...>
+        Some(Commands::S3(S3Commands::Get {
+            key,
+            output,
+            target,
+        })) => commands::s3::run_get(s3_target(target), &key, output).await,
+        Some(Commands::S3(S3Commands::Put { file, key, target })) => {
+            commands::s3::run_put(s3_target(target), &file, key).await
+        }
+        Some(Commands::S3(S3Commands::Ls { prefix, target })) => {
+            commands::s3::run_ls(s3_target(target), prefix).await
+        }
+
+        // docker ps|images|inspect
+        Some(Commands::Docker(DockerCommands::Ps {
+            all,
+            socket,
+            output,
+        })) => commands::docker::run_ps(all, socket, output).await,
+        Some(Commands::Docker(DockerCommands::Images {
+            all,
+            socket,
+            output,
+        })) => commands::docker::run_images(all, socket, output).await,
+        Some(Commands::Docker(DockerCommands::Inspect { target, socket })) => {
+            commands::docker::run_inspect(&target, socket).await
+        }
+
+        // num format <number> [--locale de-DE] [--currency EUR] [--decimals N]
+        Some(Commands::Num(NumCommands::Format {
+            number,
+            locale,
+            currency,
+            decimals,
+        })) => commands::num_format::run_format(number, &locale, currency.as_deref(), decimals),
+
+        // num parse <text> [--locale de-DE]
+        Some(Commands::Num(NumCommands::Parse { text, locale })) => {
+            commands::num_format::run_parse(text, &locale)
+        }
+
+        // rand check-password [password] [--breach]
+        Some(Commands::Rand(RandCommands::CheckPassword {
+            password,
+            breach,
+            timeout,
+            output,
+        })) => commands::rand_password::run_check_password(password, breach, timeout, output).await,
+
+        // cert inspect <file>
+        Some(Commands::Cert(CertCommands::Inspect { file, output })) => {
+            commands::cert_inspect::run(&file, output)
+        }
+
+        // cert selfsigned [--cn NAME] [--san NAME]... [--days N]
+        Some(Commands::Cert(CertCommands::Selfsigned {
+            cn,
+            sans,
+            days,
+            out,
+            key_out,
+            force,
+        })) => commands::cert_gen::run_selfsigned(&cn, &sans, days, &out, &key_out, force),
+
+        // cert convert --in FILE --out FILE [--to pem|der|base64]
+        Some(Commands::Cert(CertCommands::Convert {
+            input,
+            out,
+            to,
+            key_out,
+            force,
+        })) => commands::cert_convert::run(&input, &out, to, key_out.as_deref(), force),
+
+        // cert keygen [--algo ed25519|p256|rsa2048|rsa4096]
+        Some(Commands::Cert(CertCommands::Keygen {
+            algo,
+            out,
+            pub_out,
+            force,
+        })) => commands::cert_gen::run_keygen(algo, &out, pub_out.as_deref(), force),
+
+        // cert csr --cn NAME [--san NAME]... [--key FILE]
+        Some(Commands::Cert(CertCommands::Csr {
+            cn,
+            sans,
+            org,
+            key,
+            algo,
+            out,
+            key_out,
+            force,
+        })) => commands::cert_gen::run_csr(commands::cert_gen::CsrOptions {
+            cn,
+            sans,
+            org,
+            key,
+            algo,
+            out,
+            key_out,
+            force,
+        }),
+
+        // sbom query <file> [--licenses] [--component NAME] [--family ...] [--advisories FILE]
+        Some(Commands::Sbom(SbomCommands::Query {
+            file,
+            licenses,
+            component,
+            family,
+            advisories,
+            output,
+        })) => commands::sbom::run_query(commands::sbom::QueryOptions {
+            file: &file,
+            licenses,
+            component: component.as_deref(),
+            families: &family,
+            advisories: advisories.as_deref(),
+            output,
+        }),
+
+        // deps list [--lockfile FILE] [--output json]
+        Some(Commands::Deps(DepsCommands::List { lockfile, output })) => {
+            commands::deps::run_list(lockfile.as_deref(), output)
+        }
+
+        // deps why <package>[@version] [--lockfile FILE]
+        Some(Commands::Deps(DepsCommands::Why { package, lockfile })) => {
+            commands::deps::run_why(&package, lockfile.as_deref())
+        }
+
+        // registry get-schema --url URL (--subject S [--version N] | --id ID)
+        Some(Commands::Registry(RegistryCommands::GetSchema {
+            url,
+            subject,
+            version,
+            id,
+            user,
+            timeout,
+        })) => {
+            commands::registry::run_get_schema(
+                &url,
+                subject.as_deref(),
+                &version,
+                id,
+                user.as_deref(),
+                timeout,
+            )
+            .await
+        }
+
+        // avro decode [file] (--schema S | --registry URL) [--pretty]
+        Some(Commands::Avro(AvroCommands::Decode {
+            file,
+            schema,
+            registry,
+            user,
+            pretty,
+        })) => {
+            commands::avro::run_decode(
+                file.as_deref(),
+                schema.as_deref(),
+                registry.as_deref(),
+                user.as_deref(),
+                pretty,
+            )
+            .await
+        }
+
+        // kafka peek --rest-proxy URL --topic T [--count N] [--format F]
+        Some(Commands::Kafka(KafkaCommands::Peek {
+            rest_proxy,
+            topic,
+            count,
+            format,
+            from,
+            group,
+            timeout,
+            user,
+            output,
+        })) => {
+            let opts = commands::kafka::PeekOptions {
+                topic,
+                count,
+                format,
+                from,
+                group,
+                timeout: std::time::Duration::from_secs(timeout),
+                user,
+                output,
+            };
+            commands::kafka::run_peek(&rest_proxy, opts).await
+        }
+
+        // db query <url> <sql> [-W] [--tls MODE] [--cacert PEM] [--output json]
+        Some(Commands::Db(DbCommands::Query {
+            url,
+            sql,
+            password,
+            tls,
+            cacert,
+            timeout,
+            output,
+        })) => {
+            let opts = commands::db_query::QueryOptions {
+                password,
+                tls,
+                cacert,
+                timeout,
+                output,
+            };
+            commands::db_query::run_query(&url, &sql, opts).await
+        }
+
+        // db redis [--url URL] ping|get|set|del|ttl|keys ...
+        Some(Commands::Db(DbCommands::Redis {
+            url,
+            timeout,
+            action,
+        })) => commands::db_redis::run(url, timeout, action).await,
+
+        // db sqlite query <file> <sql> [-p VALUE | -p NAME=VALUE]... [--output json]
+        Some(Commands::Db(DbCommands::Sqlite(SqliteCommands::Query {
+            database,
+            sql,
+            params,
+            output,
+        }))) => commands::db_sqlite::run_query(&database, &sql, &params, output),
+
+        // cloud metadata --provider aws|gcp|azure [path]
+        Some(Commands::Cloud(CloudCommands::Metadata {
+            provider,
+            path,
+            endpoint,
+            timeout,
+        })) => {
+            commands::cloud_metadata::run(provider, path.as_deref(), endpoint.as_deref(), timeout)
+                .await
+        }
+
+        // url join <base> <segments>... [--query k=v]...
+        Some(Commands::Url(UrlCommands::Join {
+            base,
+            segments,
+            query,
+        })) => commands::url_build::run_join(&base, &segments, &query),
+
+        // url template <template> [--set k=v]...
+        Some(Commands::Url(UrlCommands::Template { template, set })) => {
+            commands::url_build::run_template(&template, &set)
+        }
+
+        // convert <in> [out] [--from F] [--to F]
+        Some(Commands::Convert {
+            input,
+            output,
+            from,
+            to,
+        }) => commands::convert::run(&input, output.as_deref(), from, to),
+    }
+}
+
+fn s3_target(t: S3Target) -> commands::s3::Target {
+    commands::s3::Target {
+        endpoint: t.endpoint,
+        bucket: t.bucket,
+        region: t.region,
+        timeout: t.timeout,
     }
 }
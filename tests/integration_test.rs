@@ -1,3 +1,6 @@
+// The original tests pass `&[...]` to `args`; keep them as written.
+#![allow(clippy::needless_borrows_for_generic_args)]
+
 use assert_cmd::Command;
 use std::fs;
 
@@ -115,3 +118,17 @@ fn test_json_help_shows_new_flags() {
     assert!(stdout.contains("--json5"));
     assert!(stdout.contains("--file"));
 }
+
+#[test]
+fn test_text_freq_from_stdin() {
+    let mut cmd = Command::cargo_bin("swiftline").unwrap();
+    let output = cmd
+        .args(["text", "freq", "--top", "2"])
+        .write_stdin("b\r\na\nb\nc\nb\n")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "3 b\n1 a\n");
+}